@@ -1,7 +1,10 @@
 from __future__ import annotations
 
+import gc
+import os
+import threading
 import time
-from typing import List, Optional
+from typing import List, Literal, Optional
 
 from fastapi import FastAPI, HTTPException, Response
 from fastapi.middleware.cors import CORSMiddleware
@@ -26,37 +29,118 @@ class TranscribeRequest(BaseModel):
     width: Optional[int] = None
     height: Optional[int] = None
     video_data_url: Optional[str] = Field(default=None, alias="videoDataUrl")
+    # BCP 47 hint from the app's locale detection; the LRS3 model is English-only
+    # and ignores it.
+    language: Optional[str] = None
 
 
-@app.on_event("startup")
-def startup_event() -> None:
+class WarmupRequest(BaseModel):
+    variant: Literal["default", "battery"] = "default"
+
+
+_load_lock = threading.Lock()
+
+
+def _load_model_in_background(variant: str) -> None:
     load_start = time.perf_counter()
-    model = load_model()
+    try:
+        model = load_model(variant)
+    except Exception as exc:
+        app.state.model_stage = "error"
+        app.state.model_error = str(exc)
+        return
     app.state.model = model
-    app.state.model_ready = True
     app.state.model_backend = getattr(model, "name", "unknown")
     app.state.model_device = getattr(model, "runtime_device", "cpu")
     app.state.model_device_reason = getattr(model, "runtime_device_reason", None)
     app.state.model_load_ms = int((time.perf_counter() - load_start) * 1000)
+    app.state.model_variant = variant
     app.state.model_error = None
+    app.state.model_ready = True
+    app.state.model_stage = "ready"
 
 
-@app.get("/health")
-def health_check(response: Response) -> dict:
+def start_model_load(variant: str = "default") -> None:
+    """Starts loading the model once; later calls are no-ops unless they ask
+    for a different variant than the one loaded, which replaces it."""
+    with _load_lock:
+        stage = getattr(app.state, "model_stage", "idle")
+        loaded = getattr(app.state, "model_variant", None)
+        if stage == "ready" and loaded != variant:
+            app.state.model = None
+            app.state.model_ready = False
+        elif stage not in ("idle", "error"):
+            return
+        app.state.model_stage = "loading"
+        app.state.model_error = None
+    threading.Thread(
+        target=_load_model_in_background,
+        args=(variant,),
+        name="telepathy-model-load",
+        daemon=True,
+    ).start()
+
+
+@app.on_event("startup")
+def startup_event() -> None:
+    # Loading runs in the background so /health answers immediately. With
+    # TELEPATHY_MODEL_PRELOAD=0 it waits for the desktop app's POST /warmup.
+    app.state.model_stage = "idle"
+    app.state.model_ready = False
+    if os.getenv("TELEPATHY_MODEL_PRELOAD", "1") != "0":
+        start_model_load()
+
+
+def _health_payload() -> dict:
     ready = bool(getattr(app.state, "model_ready", False))
-    if not ready:
-        response.status_code = 503
     return {
         "status": "ok" if ready else "loading",
         "ready": ready,
+        "stage": getattr(app.state, "model_stage", "idle"),
         "backend": getattr(app.state, "model_backend", "unknown"),
         "device": getattr(app.state, "model_device", None),
         "deviceReason": getattr(app.state, "model_device_reason", None),
         "modelLoadMs": getattr(app.state, "model_load_ms", None),
+        "variant": getattr(app.state, "model_variant", None),
         "error": getattr(app.state, "model_error", None),
     }
 
 
+@app.get("/health")
+def health_check(response: Response) -> dict:
+    payload = _health_payload()
+    if not payload["ready"]:
+        response.status_code = 503
+    return payload
+
+
+@app.post("/warmup")
+def warmup(payload: Optional[WarmupRequest] = None) -> dict:
+    start_model_load(payload.variant if payload else "default")
+    return _health_payload()
+
+
+@app.post("/unload")
+def unload() -> dict:
+    """Drops the model so its memory can be reclaimed; /warmup reloads it."""
+    with _load_lock:
+        if getattr(app.state, "model_stage", "idle") == "ready":
+            app.state.model = None
+            app.state.model_ready = False
+            app.state.model_stage = "idle"
+    gc.collect()
+    try:
+        import torch
+
+        if torch.cuda.is_available():
+            torch.cuda.empty_cache()
+        if torch.backends.mps.is_available():
+            torch.mps.empty_cache()
+    except Exception:
+        pass
+    return _health_payload()
+
+
 @app.post("/transcribe")
 def transcribe_endpoint(payload: TranscribeRequest) -> dict:
     model = getattr(app.state, "model", None)
@@ -244,7 +244,16 @@ class AutoAvsrTranscriber(BaseTranscriber):
         return "cpu", "auto-cpu"
 
 
-def load_model() -> BaseTranscriber:
+def _config_filename(variant: str) -> str:
+    default = os.getenv("TELEPATHY_AUTOAVSR_CONFIG", "configs/LRS3_V_WER19.1.ini")
+    if variant == "battery":
+        return os.getenv("TELEPATHY_AUTOAVSR_BATTERY_CONFIG", default)
+    return default
+
+
+def load_model(variant: str = "default") -> BaseTranscriber:
+    """Loads the configured backend. The ``battery`` variant swaps in
+    TELEPATHY_AUTOAVSR_BATTERY_CONFIG (usually a smaller model) when set."""
     backend = os.getenv("TELEPATHY_MODEL_BACKEND", "autoavsr").lower()
     if backend == "stub":
         return StubTranscriber()
@@ -267,9 +276,7 @@ def load_model() -> BaseTranscriber:
 
         config = AutoAvsrConfig(
             repo_dir=Path(repo_path).expanduser().resolve(),
-            config_filename=os.getenv(
-                "TELEPATHY_AUTOAVSR_CONFIG", "configs/LRS3_V_WER19.1.ini"
-            ),
+            config_filename=_config_filename(variant),
             detector=os.getenv("TELEPATHY_AUTOAVSR_DETECTOR", "mediapipe"),
             device=os.getenv("TELEPATHY_AUTOAVSR_DEVICE", "mps").strip().lower(),
             gpu_idx=int(os.getenv("TELEPATHY_AUTOAVSR_GPU_IDX", "-1")),
@@ -0,0 +1,132 @@
+//! Audio handoff benchmark: `cargo bench --bench audio_buffer`.
+//!
+//! Simulates a 48 kHz mono device delivering `f32` callbacks smaller than a
+//! chunk and `i16` callbacks larger than one, converted into the pool as the
+//! recorder's cpal callback does, to a consumer that keeps a sliding window
+//! of chunks (as VAD does). Checks that the callback path never allocates
+//! and memory stays flat.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::VecDeque,
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use telepathy_lib::audio_buffer::{AudioChunk, BufferPool};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const SAMPLE_RATE: u32 = 48_000;
+/// Matches the recorder's chunk length.
+const CHUNK_LEN: usize = 1600;
+/// 300 ms of audio held by the consumer at any time.
+const WINDOW_MS: f64 = 300.0;
+/// 10 minutes of audio per scenario.
+const AUDIO_SECS: usize = 600;
+
+/// Feeds device buffers of `input`'s size through the pool the way the
+/// cpal callback does, converting each sample to `f32` on the way in, to a
+/// consumer holding a sliding window of chunks (as VAD does).
+fn run<T: Copy>(name: &str, input: &[T], convert: impl Fn(T) -> f32) {
+    let chunks_per_callback = input.len().div_ceil(CHUNK_LEN);
+    let chunk_ms = CHUNK_LEN.min(input.len()) as f64 * 1000.0 / f64::from(SAMPLE_RATE);
+    let window_chunks = (WINDOW_MS / chunk_ms).ceil() as usize;
+    let callbacks = AUDIO_SECS * SAMPLE_RATE as usize / input.len();
+    let mut pool = BufferPool::new(
+        window_chunks + chunks_per_callback + 1,
+        CHUNK_LEN,
+        SAMPLE_RATE,
+        1,
+    );
+    let mut window: VecDeque<AudioChunk> =
+        VecDeque::with_capacity(window_chunks + chunks_per_callback);
+
+    let mut callback_allocations = 0;
+    let mut dropped = 0;
+    let mut energy = 0.0f32;
+    let mut live_after_warmup = 0;
+    let started = Instant::now();
+
+    for callback in 0..callbacks {
+        if callback == window_chunks * 2 {
+            live_after_warmup = LIVE_BYTES.load(Ordering::Relaxed);
+        }
+
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let mut filled = [None, None, None, None];
+        let mut count = 0;
+        dropped += pool.write_converted(input, &convert, |chunk| {
+            filled[count % filled.len()] = Some(chunk);
+            count += 1;
+        });
+        callback_allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+        // Consumer side: keep a sliding window and read a zero-copy sub-slice.
+        for chunk in filled.into_iter().flatten() {
+            energy += chunk.slice(0..160).iter().map(|s| s * s).sum::<f32>();
+            window.push_back(chunk);
+            if window.len() > window_chunks {
+                window.pop_front();
+            }
+        }
+    }
+
+    let elapsed = started.elapsed();
+    let live_at_end = LIVE_BYTES.load(Ordering::Relaxed);
+    black_box(energy);
+
+    println!("{name}:");
+    println!(
+        "  {callbacks} callbacks of {} samples in {:.2?} ({:.0} ns/callback)",
+        input.len(),
+        elapsed,
+        elapsed.as_nanos() as f64 / callbacks as f64
+    );
+    println!("  allocations in callback path: {callback_allocations}");
+    println!(
+        "  samples dropped: {dropped}, overruns: {}",
+        pool.overruns()
+    );
+    println!("  live heap bytes after warm-up: {live_after_warmup}, at end: {live_at_end}");
+
+    assert_eq!(callback_allocations, 0, "{name}: audio callback allocated");
+    assert_eq!(dropped, 0, "{name}: pool too small for the consumer window");
+    assert_eq!(
+        live_after_warmup, live_at_end,
+        "{name}: memory grew during capture"
+    );
+}
+
+fn main() {
+    let wave = |i: usize| (i as f32 * 0.05).sin();
+    let small: Vec<f32> = (0..480).map(wave).collect();
+    run("f32, 10 ms callbacks", &small, |sample| sample);
+    // Larger than a chunk, as some devices deliver: split across slots.
+    let large: Vec<i16> = (0..4096)
+        .map(|i| (wave(i) * f32::from(i16::MAX)) as i16)
+        .collect();
+    run("i16, 85 ms callbacks", &large, |sample| {
+        f32::from(sample) / 32_768.0
+    });
+}
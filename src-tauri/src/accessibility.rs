@@ -0,0 +1,144 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::idle::Dormancy;
+
+/// TCC has no public change notification, so a slow poll catches grants made
+/// while the app stays in the background; activation covers the common case.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilityStatus {
+    pub granted: bool,
+    pub detail: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+fn probe() -> AccessibilityStatus {
+    AccessibilityStatus {
+        granted: crate::ax::is_process_trusted(false),
+        detail: None,
+    }
+}
+
+/// Shows the system dialog pointing at System Settings if access is
+/// missing; it doesn't wait for the answer.
+#[cfg(target_os = "macos")]
+fn prompt() {
+    crate::ax::is_process_trusted(true);
+}
+
+#[cfg(not(target_os = "macos"))]
+fn prompt() {}
+
+/// Windows needs no grant for synthesized input, but UIPI still drops it when
+/// the target app is elevated and we are not.
+#[cfg(target_os = "windows")]
+fn probe() -> AccessibilityStatus {
+    AccessibilityStatus {
+        granted: true,
+        detail: (!crate::win32::current_process_elevated()).then(|| {
+            "Apps running as administrator only accept pasted text when Telepathy also runs as administrator."
+                .to_string()
+        }),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn probe() -> AccessibilityStatus {
+    AccessibilityStatus {
+        granted: true,
+        detail: None,
+    }
+}
+
+/// Cached accessibility status. Reads are a lock and a clone; the probe only
+/// runs on startup, app activation, the slow timer, or an explicit refresh.
+#[derive(Default)]
+pub struct AccessibilityMonitor {
+    status: Mutex<Option<AccessibilityStatus>>,
+    refreshing: AtomicBool,
+}
+
+impl AccessibilityMonitor {
+    pub fn status(&self) -> AccessibilityStatus {
+        let mut status = self.status.lock().unwrap_or_else(|err| err.into_inner());
+        status.get_or_insert_with(probe).clone()
+    }
+
+    /// Re-probes and emits `telepathy://accessibility-changed` if the result
+    /// differs from the cached one.
+    pub fn refresh(&self, app: &AppHandle) -> AccessibilityStatus {
+        let fresh = probe();
+        let previous = self
+            .status
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .replace(fresh.clone());
+        if previous.as_ref() != Some(&fresh) {
+            tracing::info!(granted = fresh.granted, detail = ?fresh.detail, "accessibility changed");
+            let _ = app.emit("telepathy://accessibility-changed", &fresh);
+        }
+        fresh
+    }
+
+    /// Refreshes off the calling thread; overlapping requests collapse into
+    /// the one already running.
+    pub fn refresh_in_background(app: &AppHandle) {
+        let monitor = app.state::<AccessibilityMonitor>();
+        if monitor.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let app = app.clone();
+        thread::spawn(move || {
+            let monitor = app.state::<AccessibilityMonitor>();
+            monitor.refresh(&app);
+            monitor.refreshing.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Runs the first probe and starts the slow refresh timer.
+pub fn init(app: &AppHandle) {
+    app.state::<AccessibilityMonitor>().refresh(app);
+
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(REFRESH_INTERVAL);
+        if !Dormancy::active(&app) {
+            AccessibilityMonitor::refresh_in_background(&app);
+        }
+    });
+}
+
+/// Returns the cached status; pass `refresh` to re-probe first, or `prompt`
+/// to also show the macOS dialog when access is missing.
+#[tauri::command]
+pub async fn check_accessibility_permission(
+    app: AppHandle,
+    refresh: Option<bool>,
+    prompt: Option<bool>,
+) -> Result<AccessibilityStatus, String> {
+    crate::run_blocking(move || {
+        let monitor = app.state::<AccessibilityMonitor>();
+        let prompted = prompt.unwrap_or(false);
+        if prompted {
+            self::prompt();
+        }
+        Ok(if prompted || refresh.unwrap_or(false) {
+            monitor.refresh(&app)
+        } else {
+            monitor.status()
+        })
+    })
+    .await
+}
@@ -0,0 +1,438 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Instant,
+};
+
+use tauri::{AppHandle, Listener, Manager, State};
+
+use crate::{
+    dictation::{DictationEvent, DictationTransition, STATE_EVENT},
+    storage::Storage,
+    usage::UsageSample,
+};
+
+const METRICS_FILE: &str = "metrics.json";
+/// Where an unreadable `metrics.json` is moved before starting afresh.
+const METRICS_BACKUP: &str = "metrics.json.bak";
+/// Latency samples kept per metric; enough for stable percentiles without
+/// letting the file grow forever.
+const MAX_SAMPLES: usize = 500;
+const UNKNOWN_PROVIDER: &str = "unknown";
+
+/// Bounded window of recent latency samples, in milliseconds.
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+struct LatencySamples(VecDeque<u64>);
+
+impl LatencySamples {
+    fn push(&mut self, ms: u64) {
+        if self.0.len() == MAX_SAMPLES {
+            self.0.pop_front();
+        }
+        self.0.push_back(ms);
+    }
+
+    fn summary(&self) -> LatencySummary {
+        summarize(self.0.iter().copied())
+    }
+}
+
+/// Count, mean, and percentiles of latency samples in milliseconds.
+pub fn summarize(samples: impl IntoIterator<Item = u64>) -> LatencySummary {
+    let mut sorted: Vec<u64> = samples.into_iter().collect();
+    sorted.sort_unstable();
+    let percentile = |p: f64| -> Option<u64> {
+        let last = sorted.len().checked_sub(1)?;
+        Some(sorted[((last as f64) * p).round() as usize])
+    };
+    LatencySummary {
+        count: sorted.len(),
+        mean_ms: (!sorted.is_empty())
+            .then(|| sorted.iter().sum::<u64>() as f64 / sorted.len() as f64),
+        p50_ms: percentile(0.5),
+        p95_ms: percentile(0.95),
+        max_ms: sorted.last().copied(),
+    }
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ProviderMetrics {
+    dictations: u64,
+    empty_transcripts: u64,
+    delivery_failures: u64,
+    words: u64,
+    recording_ms: u64,
+    hotkey_to_first_partial: LatencySamples,
+    stop_to_paste: LatencySamples,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySummary {
+    pub count: usize,
+    pub mean_ms: Option<f64>,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderReport {
+    pub provider: String,
+    pub dictations: u64,
+    pub empty_transcript_rate: Option<f64>,
+    pub delivery_failure_rate: Option<f64>,
+    pub words_per_dictation: Option<f64>,
+    /// Accuracy proxy: implausibly low or high speaking rates hint at
+    /// hallucinated or dropped words.
+    pub words_per_minute: Option<f64>,
+    pub hotkey_to_first_partial: LatencySummary,
+    pub stop_to_paste: LatencySummary,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsReport {
+    pub generated_at: String,
+    pub providers: Vec<ProviderReport>,
+}
+
+/// Timestamps for the dictation currently in flight.
+#[derive(Default)]
+struct Session {
+    pressed_at: Option<Instant>,
+    recording_at: Option<Instant>,
+    released_at: Option<Instant>,
+    first_partial_at: Option<Instant>,
+    /// When the final transcript reached delivery.
+    transcript_at: Option<Instant>,
+    provider: Option<String>,
+}
+
+/// What `metrics.json` holds. Daily usage totals live in the history
+/// database instead (see `usage`).
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Metrics {
+    providers: BTreeMap<String, ProviderMetrics>,
+}
+
+impl Metrics {
+    /// Reads either layout: an object with `providers`, or, from earlier
+    /// versions, the bare provider map.
+    fn parse(raw: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(raw).map_err(|err| err.to_string())?;
+        if value.get("providers").is_some() {
+            serde_json::from_value(value)
+        } else {
+            serde_json::from_value(value).map(|providers| Self { providers })
+        }
+        .map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    session: Session,
+    metrics: Metrics,
+}
+
+/// What the finished dictation took, for its history entry.
+pub struct FinishedSession {
+    pub provider: Option<String>,
+    /// Hotkey press to release.
+    pub recording_ms: Option<u64>,
+    /// Hotkey release to delivery.
+    pub latency_ms: Option<u64>,
+    /// What the dictation adds to today's usage totals.
+    pub usage: UsageSample,
+}
+
+/// Local-only latency and quality aggregates. Nothing here leaves the machine
+/// and no transcript text is stored, only counts and timings.
+pub struct Analytics {
+    path: Option<PathBuf>,
+    inner: Mutex<Inner>,
+}
+
+/// Reads `metrics.json`. A file that doesn't parse is moved aside to
+/// `metrics.json.bak`, so the next dictation doesn't overwrite it, and
+/// counting starts afresh.
+fn read_metrics(path: &Path) -> Metrics {
+    let Ok(raw) = fs::read_to_string(path) else {
+        return Metrics::default();
+    };
+    Metrics::parse(&raw).unwrap_or_else(|err| {
+        let backup = path.with_file_name(METRICS_BACKUP);
+        match fs::rename(path, &backup) {
+            Ok(()) => tracing::warn!(%err, backup = %backup.display(), "metrics unreadable; backed up and reset"),
+            Err(rename) => tracing::warn!(%err, %rename, "metrics unreadable and not backed up; reset"),
+        }
+        Metrics::default()
+    })
+}
+
+fn ratio(part: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| part as f64 / total as f64)
+}
+
+fn millis_between(start: Option<Instant>, end: Instant) -> Option<u64> {
+    start.map(|start| end.saturating_duration_since(start).as_millis() as u64)
+}
+
+impl Analytics {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = Storage::data_dir(app).map(|dir| dir.join(METRICS_FILE));
+        let metrics = path.as_deref().map(read_metrics).unwrap_or_default();
+        Self {
+            path,
+            inner: Mutex::new(Inner {
+                session: Session::default(),
+                metrics,
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn hotkey_pressed(&self) {
+        self.lock().session = Session {
+            pressed_at: Some(Instant::now()),
+            ..Session::default()
+        };
+    }
+
+    /// The recorder is running; the first one to report counts.
+    pub fn recording_started(&self) {
+        let mut inner = self.lock();
+        if inner.session.recording_at.is_none() {
+            inner.session.recording_at = Some(Instant::now());
+        }
+    }
+
+    pub fn hotkey_released(&self) {
+        self.lock().session.released_at = Some(Instant::now());
+    }
+
+    /// Marks the first transcript text of the session. One-shot providers
+    /// report their final transcript here.
+    pub fn first_partial(&self, provider: Option<&str>) {
+        let mut inner = self.lock();
+        if inner.session.first_partial_at.is_none() {
+            inner.session.first_partial_at = Some(Instant::now());
+        }
+        if let Some(provider) = provider {
+            inner.session.provider = Some(provider.to_string());
+        }
+    }
+
+    /// Marks the final transcript arriving for delivery.
+    pub fn transcript_ready(&self) {
+        self.lock().session.transcript_at = Some(Instant::now());
+    }
+
+    /// Folds the finished session into the per-provider aggregates and
+    /// persists them. Today's totals are the caller's to record, from
+    /// [`FinishedSession::usage`].
+    pub fn dictation_finished(&self, text: &str, delivered: bool) -> FinishedSession {
+        let now = Instant::now();
+        let mut inner = self.lock();
+        let session = std::mem::take(&mut inner.session);
+        let recording_ms = session
+            .released_at
+            .and_then(|released| millis_between(session.pressed_at, released));
+        let words = text.split_whitespace().count() as u64;
+        let finished = FinishedSession {
+            provider: session.provider.clone(),
+            recording_ms,
+            latency_ms: millis_between(session.released_at, now),
+            usage: UsageSample {
+                words,
+                recording_ms,
+                record_start_ms: session
+                    .recording_at
+                    .and_then(|recording| millis_between(session.pressed_at, recording)),
+                transcript_ms: session
+                    .transcript_at
+                    .and_then(|transcript| millis_between(session.released_at, transcript)),
+                paste_ms: millis_between(session.transcript_at, now).filter(|_| delivered),
+            },
+        };
+        let provider = session
+            .provider
+            .unwrap_or_else(|| UNKNOWN_PROVIDER.to_string());
+        let metrics = inner.metrics.providers.entry(provider).or_default();
+
+        metrics.dictations += 1;
+        if words == 0 {
+            metrics.empty_transcripts += 1;
+        }
+        metrics.words += words;
+        if !delivered {
+            metrics.delivery_failures += 1;
+        }
+        if let (Some(pressed), Some(released)) = (session.pressed_at, session.released_at) {
+            metrics.recording_ms += millis_between(Some(pressed), released).unwrap_or(0);
+        }
+        if let Some(first_partial) = session.first_partial_at {
+            if let Some(ms) = millis_between(session.pressed_at, first_partial) {
+                metrics.hotkey_to_first_partial.push(ms);
+            }
+        }
+        if delivered {
+            if let Some(ms) = millis_between(session.released_at, now) {
+                metrics.stop_to_paste.push(ms);
+            }
+        }
+
+        // Metrics are best-effort; a failed write must never fail a dictation.
+        let _ = self.persist(&inner.metrics);
+        finished
+    }
+
+    fn persist(&self, metrics: &Metrics) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("App data directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Unable to create metrics directory: {err}"))?;
+        }
+        let raw = serde_json::to_string(metrics)
+            .map_err(|err| format!("Unable to serialize metrics: {err}"))?;
+        fs::write(path, raw).map_err(|err| format!("Unable to write metrics: {err}"))
+    }
+
+    fn report(&self) -> MetricsReport {
+        let inner = self.lock();
+        let providers = inner
+            .metrics
+            .providers
+            .iter()
+            .map(|(provider, metrics)| ProviderReport {
+                provider: provider.clone(),
+                dictations: metrics.dictations,
+                empty_transcript_rate: ratio(metrics.empty_transcripts, metrics.dictations),
+                delivery_failure_rate: ratio(metrics.delivery_failures, metrics.dictations),
+                words_per_dictation: ratio(metrics.words, metrics.dictations),
+                words_per_minute: ratio(metrics.words * 60_000, metrics.recording_ms),
+                hotkey_to_first_partial: metrics.hotkey_to_first_partial.summary(),
+                stop_to_paste: metrics.stop_to_paste.summary(),
+            })
+            .collect();
+        MetricsReport {
+            generated_at: chrono::Local::now().to_rfc3339(),
+            providers,
+        }
+    }
+}
+
+/// Notes when the recorder comes up, for the hotkey-to-recording latency.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(STATE_EVENT, move |event| {
+        let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) else {
+            return;
+        };
+        if transition.event == DictationEvent::RecorderStarted {
+            handle.state::<Analytics>().recording_started();
+        }
+    });
+}
+
+/// Returns the aggregated report and, when `path` is given, also writes it
+/// there as pretty-printed JSON.
+#[tauri::command]
+pub async fn export_metrics(
+    analytics: State<'_, Analytics>,
+    path: Option<PathBuf>,
+) -> Result<MetricsReport, String> {
+    let report = analytics.report();
+    let Some(path) = path else {
+        return Ok(report);
+    };
+    crate::run_blocking(move || {
+        let raw = serde_json::to_string_pretty(&report)
+            .map_err(|err| format!("Unable to serialize metrics: {err}"))?;
+        fs::write(&path, raw)
+            .map_err(|err| format!("Unable to write {}: {err}", path.display()))?;
+        Ok(report)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_of_no_samples_has_no_statistics() {
+        let summary = summarize([]);
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.mean_ms, None);
+        assert_eq!(summary.p50_ms, None);
+        assert_eq!(summary.p95_ms, None);
+        assert_eq!(summary.max_ms, None);
+    }
+
+    #[test]
+    fn summary_sorts_before_taking_percentiles() {
+        let summary = summarize((1..=100).rev());
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.mean_ms, Some(50.5));
+        assert_eq!(summary.p50_ms, Some(51));
+        assert_eq!(summary.p95_ms, Some(95));
+        assert_eq!(summary.max_ms, Some(100));
+
+        let single = summarize([42]);
+        assert_eq!(
+            (single.p50_ms, single.p95_ms, single.max_ms),
+            (Some(42), Some(42), Some(42))
+        );
+    }
+
+    #[test]
+    fn latency_samples_keep_the_most_recent_window() {
+        let mut samples = LatencySamples::default();
+        for ms in 0..MAX_SAMPLES as u64 + 10 {
+            samples.push(ms);
+        }
+        let summary = samples.summary();
+        assert_eq!(summary.count, MAX_SAMPLES);
+        assert_eq!(samples.0.front(), Some(&10));
+        assert_eq!(summary.max_ms, Some(MAX_SAMPLES as u64 + 9));
+    }
+
+    #[test]
+    fn metrics_read_the_bare_provider_map_of_earlier_versions() {
+        let metrics = Metrics::parse(r#"{"Deepgram":{"dictations":3,"words":12}}"#)
+            .expect("legacy metrics parse");
+        assert_eq!(metrics.providers["Deepgram"].dictations, 3);
+    }
+
+    #[test]
+    fn metrics_read_the_providers_object_and_ignore_other_keys() {
+        let metrics = Metrics::parse(
+            r#"{"providers":{"Deepgram":{"dictations":1}},"days":{"2026-01-02":{"words":4}}}"#,
+        )
+        .expect("metrics parse");
+        assert_eq!(metrics.providers.len(), 1);
+        assert_eq!(metrics.providers["Deepgram"].dictations, 1);
+    }
+
+    #[test]
+    fn a_damaged_providers_object_is_not_read_as_provider_names() {
+        // A bad entry must fail the file, not turn "providers" into a name.
+        assert!(Metrics::parse(r#"{"providers":{"Deepgram":{"dictations":"x"}}}"#).is_err());
+        assert!(Metrics::parse(r#"{"providers":[]}"#).is_err());
+        assert!(Metrics::parse("not json").is_err());
+    }
+}
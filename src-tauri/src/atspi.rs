@@ -0,0 +1,230 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use zbus::{
+    blocking::{connection, fdo::DBusProxy, Connection, MessageIterator, Proxy},
+    message::Type,
+    names::BusName,
+    zvariant::{OwnedObjectPath, OwnedValue},
+    MatchRule,
+};
+
+use crate::focus::{Caret, FrontmostApp, PasteTarget, Rect};
+
+const ACCESSIBLE: &str = "org.a11y.atspi.Accessible";
+const TEXT: &str = "org.a11y.atspi.Text";
+const FOCUS_EVENT: &str = "object:state-changed:focused";
+/// `ATSPI_STATE_EDITABLE`; states arrive as a 64-bit set split over two u32s.
+const STATE_EDITABLE: u32 = 7;
+/// `ATSPI_COORD_TYPE_SCREEN`.
+const COORD_SCREEN: u32 = 0;
+
+fn atspi_error(err: zbus::Error) -> String {
+    format!("AT-SPI request failed: {err}")
+}
+
+/// The accessibility bus is separate from the session bus; the session bus
+/// only hands out its address.
+fn connect() -> Result<Connection, String> {
+    let session =
+        Connection::session().map_err(|err| format!("Unable to reach the session bus: {err}"))?;
+    let address: String = Proxy::new(&session, "org.a11y.Bus", "/org/a11y/bus", "org.a11y.Bus")
+        .and_then(|proxy| proxy.call("GetAddress", &()))
+        .map_err(|err| format!("The accessibility bus is not running: {err}"))?;
+    connection::Builder::address(address.as_str())
+        .and_then(|builder| builder.build())
+        .map_err(|err| format!("Unable to connect to the accessibility bus: {err}"))
+}
+
+/// Toolkits only emit events someone has registered for. The registry took
+/// extra arguments in newer at-spi2-core releases, so both forms are tried.
+fn register_focus_events(conn: &Connection) -> Result<(), String> {
+    let registry = Proxy::new(
+        conn,
+        "org.a11y.atspi.Registry",
+        "/org/a11y/atspi/registry",
+        "org.a11y.atspi.Registry",
+    )
+    .map_err(atspi_error)?;
+    registry
+        .call_method("RegisterEvent", &(FOCUS_EVENT,))
+        .or_else(|_| registry.call_method("RegisterEvent", &(FOCUS_EVENT, Vec::<&str>::new(), "")))
+        .map(|_| ())
+        .map_err(atspi_error)
+}
+
+/// An accessible object, addressed by the unique bus name of the app that
+/// owns it and its object path.
+#[derive(Clone, PartialEq)]
+struct Object {
+    sender: String,
+    path: OwnedObjectPath,
+}
+
+/// `kind` and `detail1` of an `Object:StateChanged` signal. The trailing
+/// fields changed shape across at-spi2 versions, so both are accepted.
+fn state_change(message: &zbus::Message) -> Option<(String, i32)> {
+    let body = message.body();
+    if let Ok((kind, detail1, _, _, _)) = body.deserialize::<(
+        String,
+        i32,
+        i32,
+        OwnedValue,
+        std::collections::HashMap<String, OwnedValue>,
+    )>() {
+        return Some((kind, detail1));
+    }
+    body.deserialize::<(String, i32, i32, OwnedValue)>()
+        .ok()
+        .map(|(kind, detail1, _, _)| (kind, detail1))
+}
+
+/// Follows keyboard focus across every app that exposes AT-SPI (GTK, Qt,
+/// Chromium and Firefox with accessibility on), remembering the focused
+/// object so paste-target queries never have to walk the tree.
+pub struct FocusListener {
+    conn: Connection,
+    focused: Arc<Mutex<Option<Object>>>,
+    _thread: thread::JoinHandle<()>,
+}
+
+impl FocusListener {
+    pub fn spawn() -> Result<Self, String> {
+        let conn = connect()?;
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface("org.a11y.atspi.Event.Object")
+            .and_then(|builder| builder.member("StateChanged"))
+            .map_err(atspi_error)?
+            .build();
+        let events = MessageIterator::for_match_rule(rule, &conn, None).map_err(atspi_error)?;
+        register_focus_events(&conn)?;
+
+        let focused = Arc::new(Mutex::new(None));
+        let latest = Arc::clone(&focused);
+        let thread = thread::Builder::new()
+            .name("telepathy-atspi-focus".to_string())
+            .spawn(move || {
+                for message in events.flatten() {
+                    let Some((kind, detail1)) = state_change(&message) else {
+                        continue;
+                    };
+                    if kind != "focused" {
+                        continue;
+                    }
+                    let header = message.header();
+                    let (Some(sender), Some(path)) = (header.sender(), header.path()) else {
+                        continue;
+                    };
+                    let object = Object {
+                        sender: sender.to_string(),
+                        path: path.clone().into(),
+                    };
+                    let mut latest = latest.lock().unwrap_or_else(|err| err.into_inner());
+                    if detail1 != 0 {
+                        *latest = Some(object);
+                    } else if latest.as_ref() == Some(&object) {
+                        *latest = None;
+                    }
+                }
+            })
+            .map_err(|err| format!("Unable to start AT-SPI focus listener: {err}"))?;
+        Ok(Self {
+            conn,
+            focused,
+            _thread: thread,
+        })
+    }
+
+    fn proxy(&self, object: &Object, interface: &'static str) -> Result<Proxy<'static>, String> {
+        Proxy::new(
+            &self.conn,
+            object.sender.clone(),
+            object.path.clone(),
+            interface,
+        )
+        .map_err(atspi_error)
+    }
+
+    fn app(&self, object: &Object) -> FrontmostApp {
+        let name = self
+            .proxy(object, ACCESSIBLE)
+            .and_then(|proxy| {
+                proxy
+                    .call::<_, _, (String, OwnedObjectPath)>("GetApplication", &())
+                    .map_err(atspi_error)
+            })
+            .and_then(|(sender, path)| self.proxy(&Object { sender, path }, ACCESSIBLE))
+            .and_then(|proxy| proxy.get_property::<String>("Name").map_err(atspi_error))
+            .ok()
+            .filter(|name| !name.is_empty());
+        let pid = BusName::try_from(object.sender.as_str())
+            .ok()
+            .and_then(|sender| {
+                DBusProxy::new(&self.conn)
+                    .ok()?
+                    .get_connection_unix_process_id(sender)
+                    .ok()
+            });
+        // The executable name is the closest Linux has to a bundle id.
+        let id = pid.and_then(|pid| {
+            std::fs::read_link(format!("/proc/{pid}/exe"))
+                .ok()?
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        });
+        FrontmostApp { name, id, pid }
+    }
+
+    fn editable(&self, object: &Object) -> Option<bool> {
+        let states: Vec<u32> = self
+            .proxy(object, ACCESSIBLE)
+            .ok()?
+            .call("GetState", &())
+            .ok()?;
+        let low = states.first().copied().unwrap_or(0);
+        Some(low & (1 << STATE_EDITABLE) != 0)
+    }
+
+    /// Caret offset and on-screen box, for toolkits that implement `Text`.
+    fn caret(&self, object: &Object) -> Option<Caret> {
+        let text = self.proxy(object, TEXT).ok()?;
+        let offset = text.get_property::<i32>("CaretOffset").ok()?;
+        if offset < 0 {
+            return None;
+        }
+        let bounds = text
+            .call::<_, _, (i32, i32, i32, i32)>("GetCharacterExtents", &(offset, COORD_SCREEN))
+            .ok()
+            .filter(|&(_, _, width, height)| width > 0 || height > 0)
+            .map(|(x, y, width, height)| Rect {
+                x: f64::from(x),
+                y: f64::from(y),
+                width: f64::from(width),
+                height: f64::from(height),
+            });
+        Some(Caret {
+            offset: Some(offset as i64),
+            bounds,
+        })
+    }
+
+    pub fn paste_target(&self) -> PasteTarget {
+        let focused = self
+            .focused
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone();
+        let Some(object) = focused else {
+            return PasteTarget::new("atspi");
+        };
+        PasteTarget {
+            app: Some(self.app(&object)),
+            editable_text: self.editable(&object),
+            caret: self.caret(&object),
+            ..PasteTarget::new("atspi")
+        }
+    }
+}
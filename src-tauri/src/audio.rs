@@ -0,0 +1,1028 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SampleFormat, SizedSample,
+};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use crate::{
+    audio_buffer::{AudioChunk, BufferPool},
+    capture::CaptureDevice,
+    dictation::{self, DictationEvent, DictationTransition},
+    idle::Dormancy,
+    settings::SettingsStore,
+    vad::{VadSettings, VoiceDetector},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// How often device lists are compared to notice a microphone being plugged
+/// in or out.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// 100 ms at 16 kHz mono; devices usually run faster, so chunks are shorter.
+const CHUNK_LEN: usize = 1600;
+const POOL_SLOTS: usize = 16;
+/// One `telepathy://audio-level` per this much audio, fast enough for a
+/// smooth meter.
+const LEVEL_FRAME_MS: u32 = 50;
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioSettings {
+    /// Record the microphone from Rust while the hotkey dictation runs,
+    /// independent of the webview.
+    pub native_capture: bool,
+    /// Input device name; the system default when unset or not found.
+    pub input_device: Option<String>,
+    /// Also emit each chunk as `telepathy://audio-chunk`.
+    pub stream_chunks: bool,
+    /// Keeps the microphone open between dictations and starts each native
+    /// recording with this much of the audio from just before it; 0 opens
+    /// the microphone only while recording.
+    pub pre_roll_ms: u32,
+}
+
+/// Returned by `start_recording`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingFormat {
+    pub device: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Returned by `stop_recording`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSummary {
+    /// Audio recorded, not counting pauses.
+    pub duration_ms: u64,
+    /// Time spent paused, and the stretches of audio stitched together.
+    pub paused_ms: u64,
+    pub segments: u32,
+    /// Chunks dropped because the collector fell behind the device.
+    pub overruns: u64,
+}
+
+/// Payload of `telepathy://audio-chunk`: interleaved `f32` PCM.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChunkPayload<'a> {
+    sequence: u64,
+    sample_rate: u32,
+    channels: u16,
+    samples: &'a [f32],
+}
+
+/// Payload of `telepathy://audio-level`, for the overlay's input meter and
+/// waveform: linear levels from 0 to 1 over one frame.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioLevel {
+    rms: f32,
+    peak: f32,
+}
+
+/// Splits the sample stream into fixed frames regardless of how the device
+/// sizes its buffers, and measures each.
+struct LevelMeter {
+    frame_len: usize,
+    sum_squares: f32,
+    peak: f32,
+    len: usize,
+}
+
+impl LevelMeter {
+    fn new(format: &RecordingFormat) -> Self {
+        let frames = (format.sample_rate * LEVEL_FRAME_MS / 1000).max(1) as usize;
+        Self {
+            frame_len: frames * usize::from(format.channels.max(1)),
+            sum_squares: 0.0,
+            peak: 0.0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, samples: &[f32], mut on_frame: impl FnMut(AudioLevel)) {
+        for &sample in samples {
+            self.sum_squares += sample * sample;
+            self.peak = self.peak.max(sample.abs());
+            self.len += 1;
+            if self.len == self.frame_len {
+                on_frame(AudioLevel {
+                    rms: (self.sum_squares / self.len as f32).sqrt(),
+                    peak: self.peak.min(1.0),
+                });
+                self.sum_squares = 0.0;
+                self.peak = 0.0;
+                self.len = 0;
+            }
+        }
+    }
+}
+
+/// A finished recording as interleaved `f32` PCM, returned by
+/// `take_recording` and accepted by `transcribe_local`.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedAudio {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl RecordedAudio {
+    /// Averages channels to mono and linearly resamples to `sample_rate`.
+    pub fn to_mono(&self, sample_rate: u32) -> Vec<f32> {
+        let channels = usize::from(self.channels.max(1));
+        let mono: Vec<f32> = self
+            .samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+        if self.sample_rate == sample_rate || mono.is_empty() {
+            return mono;
+        }
+        let step = f64::from(self.sample_rate) / f64::from(sample_rate);
+        let len = (mono.len() as f64 / step) as usize;
+        (0..len)
+            .map(|index| {
+                let position = index as f64 * step;
+                let base = position as usize;
+                let next = mono.get(base + 1).copied().unwrap_or(mono[base]);
+                let fraction = (position - base as f64) as f32;
+                mono[base] + (next - mono[base]) * fraction
+            })
+            .collect()
+    }
+
+    /// Mono 16-bit little-endian PCM at `sample_rate`, the `linear16`
+    /// encoding streaming APIs take.
+    pub fn to_pcm16(&self, sample_rate: u32) -> Vec<u8> {
+        self.to_mono(sample_rate)
+            .into_iter()
+            .flat_map(|sample| {
+                ((sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16).to_le_bytes()
+            })
+            .collect()
+    }
+
+    /// A 16-bit mono PCM WAV file at `sample_rate`, the format every
+    /// transcription API accepts.
+    pub fn to_wav(&self, sample_rate: u32) -> Vec<u8> {
+        let pcm = self.to_pcm16(sample_rate);
+        let data_len = pcm.len() as u32;
+        let mut wav = Vec::with_capacity(44 + pcm.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, one channel.
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        // Block align and bits per sample.
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.extend_from_slice(&pcm);
+        wav
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        let frames = self.samples.len() as u64 / u64::from(self.channels.max(1));
+        frames * 1000 / u64::from(self.sample_rate.max(1))
+    }
+}
+
+/// Live copies of the running recording's chunks. Senders are dropped when
+/// the recording stops, which ends each subscriber's stream.
+type Subscribers = Arc<Mutex<Vec<tokio::sync::mpsc::UnboundedSender<RecordedAudio>>>>;
+
+struct ActiveRecording {
+    stop: Arc<AtomicBool>,
+    format: RecordingFormat,
+    thread: thread::JoinHandle<Result<(Vec<f32>, u64), String>>,
+    /// A copy of the audio is kept for saving to disk.
+    keep: bool,
+    /// Chunks are dropped while set, so resuming continues the same buffer.
+    paused: Arc<AtomicBool>,
+    paused_at: Option<Instant>,
+    paused_ms: u64,
+    segments: u32,
+}
+
+/// Where a recording's chunks come from.
+enum Source {
+    /// A stream opened for this recording alone.
+    Device,
+    /// The standby stream, handed over with the audio it held.
+    Standby {
+        chunks: mpsc::Receiver<AudioChunk>,
+        format: RecordingFormat,
+        pre_roll: Vec<f32>,
+        overruns: Arc<AtomicU64>,
+    },
+}
+
+/// Where the standby stream sends its chunks while a recording runs.
+struct Forward {
+    chunks: mpsc::SyncSender<AudioChunk>,
+    /// The recording's overrun count; chunks dropped on the way add to it.
+    overruns: Arc<AtomicU64>,
+}
+
+/// What the standby thread shares with recordings.
+struct PreRoll {
+    /// The most recent audio, `capacity` samples at most.
+    ring: VecDeque<f32>,
+    capacity: usize,
+    /// The running recording; chunks go there instead of the ring while it
+    /// is set.
+    forward: Option<Forward>,
+}
+
+/// The microphone held open between dictations for `audio.preRollMs`.
+struct Standby {
+    stop: Arc<AtomicBool>,
+    format: RecordingFormat,
+    /// The settings it was opened with, to notice when they change.
+    settings: AudioSettings,
+    pre_roll: Arc<Mutex<PreRoll>>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl Standby {
+    /// Hands the ring's audio and every chunk from now on to a new
+    /// recording, or `None` when the stream has died.
+    fn take_over(&self) -> Option<Source> {
+        if self.thread.is_finished() {
+            return None;
+        }
+        let (sender, chunks) = mpsc::sync_channel(POOL_SLOTS);
+        let overruns = Arc::new(AtomicU64::new(0));
+        let mut pre_roll = self.pre_roll.lock().unwrap_or_else(|err| err.into_inner());
+        pre_roll.forward = Some(Forward {
+            chunks: sender,
+            overruns: Arc::clone(&overruns),
+        });
+        Some(Source::Standby {
+            chunks,
+            format: self.format.clone(),
+            pre_roll: pre_roll.ring.drain(..).collect(),
+            overruns,
+        })
+    }
+
+    fn close(self) {
+        self.stop.store(true, Ordering::Release);
+        let _ = self.thread.join();
+        tracing::info!("microphone standby closed");
+    }
+}
+
+/// The microphone recording, if one is running, and the last one finished.
+#[derive(Default)]
+pub struct Recorder {
+    active: Mutex<Option<ActiveRecording>>,
+    last: Mutex<Option<RecordedAudio>>,
+    /// The last recording's copy for `recordings`, kept apart from `last`
+    /// because transcription takes that one.
+    kept: Mutex<Option<RecordedAudio>>,
+    subscribers: Subscribers,
+    standby: Mutex<Option<Standby>>,
+}
+
+impl Recorder {
+    pub fn is_running(&self) -> bool {
+        self.active
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .is_some()
+    }
+
+    /// Starts recording, from the standby stream with its pre-roll when
+    /// one is open, otherwise from the configured input device.
+    pub fn start(&self, app: &AppHandle) -> Result<RecordingFormat, String> {
+        let mut active = self.active.lock().unwrap_or_else(|err| err.into_inner());
+        if active.is_some() {
+            return Err("A recording is already running.".to_string());
+        }
+        let settings = app.state::<SettingsStore>().snapshot();
+        let source = self
+            .standby
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .and_then(Standby::take_over)
+            .unwrap_or(Source::Device);
+        let subscribers = Arc::clone(&self.subscribers);
+        let keep = settings.recordings.enabled;
+        let mut recording = spawn(
+            app.clone(),
+            settings.audio,
+            settings.vad,
+            subscribers,
+            source,
+        )
+        .inspect_err(|err| {
+            tracing::warn!(%err, "recording failed to start");
+        })?;
+        recording.keep = keep;
+        let format = recording.format.clone();
+        tracing::info!(
+            device = %format.device,
+            sample_rate = format.sample_rate,
+            channels = format.channels,
+            "recording started"
+        );
+        *active = Some(recording);
+        Ok(format)
+    }
+
+    /// Stops the running recording, releases the device, and keeps the
+    /// audio for `take_recording`.
+    pub fn stop(&self) -> Result<RecordingSummary, String> {
+        let recording = self
+            .active
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+            .ok_or("No recording is running.".to_string())?;
+        recording.stop.store(true, Ordering::Release);
+        let (samples, overruns) = recording
+            .thread
+            .join()
+            .map_err(|_| "Recording thread panicked.".to_string())??;
+        let format = recording.format;
+        let frames = samples.len() / usize::from(format.channels.max(1));
+        let duration_ms = (frames as u64 * 1000) / u64::from(format.sample_rate.max(1));
+        let paused_ms = recording.paused_ms
+            + recording
+                .paused_at
+                .map_or(0, |at| at.elapsed().as_millis() as u64);
+        let segments = recording.segments;
+        tracing::info!(
+            duration_ms,
+            paused_ms,
+            segments,
+            overruns,
+            "recording stopped"
+        );
+        let audio = RecordedAudio {
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            samples,
+        };
+        *self.kept.lock().unwrap_or_else(|err| err.into_inner()) =
+            recording.keep.then(|| audio.clone());
+        *self.last.lock().unwrap_or_else(|err| err.into_inner()) = Some(audio);
+        Ok(RecordingSummary {
+            duration_ms,
+            paused_ms,
+            segments,
+            overruns,
+        })
+    }
+
+    /// Chunks of the next (or current) recording as they are captured, for
+    /// streaming transcription. The stream ends when the recording stops.
+    pub fn subscribe(&self) -> tokio::sync::mpsc::UnboundedReceiver<RecordedAudio> {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(sender);
+        receiver
+    }
+
+    /// Hands over the last finished recording, leaving none behind.
+    pub fn take(&self) -> Option<RecordedAudio> {
+        self.last
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+    }
+
+    /// Pauses or resumes the running recording. Audio captured while paused
+    /// is dropped; after resuming it is appended to the same buffer.
+    pub fn set_paused(&self, paused: bool) -> Result<(), String> {
+        let mut active = self.active.lock().unwrap_or_else(|err| err.into_inner());
+        let recording = active
+            .as_mut()
+            .ok_or("No recording is running.".to_string())?;
+        if recording.paused.swap(paused, Ordering::AcqRel) == paused {
+            return Ok(());
+        }
+        if paused {
+            recording.paused_at = Some(Instant::now());
+        } else if let Some(at) = recording.paused_at.take() {
+            recording.paused_ms += at.elapsed().as_millis() as u64;
+            recording.segments += 1;
+        }
+        tracing::info!(paused, "recording pause changed");
+        Ok(())
+    }
+
+    /// Drops the last recording and its kept copy, for a cancelled
+    /// dictation that must not be transcribed.
+    pub fn discard(&self) {
+        let last = self
+            .last
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        self.take_kept();
+        if last.is_some() {
+            tracing::info!("cancelled recording discarded");
+        }
+    }
+
+    /// Hands over the copy of the last recording kept for saving.
+    pub fn take_kept(&self) -> Option<RecordedAudio> {
+        self.kept
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+    }
+
+    /// Opens, reopens, or closes the standby stream to match `settings`:
+    /// open while native capture and pre-roll are both on. `reopen` moves it
+    /// to the device that is now in effect even if the settings are the same.
+    pub fn sync_standby(&self, settings: &AudioSettings, reopen: bool) {
+        let wanted = settings.native_capture && settings.pre_roll_ms > 0;
+        let mut standby = self.standby.lock().unwrap_or_else(|err| err.into_inner());
+        let current = standby
+            .as_ref()
+            .filter(|standby| !standby.thread.is_finished());
+        let unchanged = current.is_some_and(|current| {
+            current.settings.input_device == settings.input_device
+                && current.settings.pre_roll_ms == settings.pre_roll_ms
+        });
+        if wanted && unchanged && !reopen {
+            return;
+        }
+        if let Some(previous) = standby.take() {
+            previous.close();
+        }
+        if wanted {
+            *standby = open_standby(settings.clone())
+                .inspect_err(|err| tracing::warn!(%err, "microphone standby not opened"))
+                .ok();
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevice {
+    /// The PipeWire node name on Linux, otherwise the device's name, which
+    /// is how cpal tells devices apart.
+    pub id: String,
+    /// What to show for the device.
+    pub name: String,
+    pub is_default: bool,
+    /// Chosen with `set_audio_device`.
+    pub selected: bool,
+}
+
+/// Payload of `telepathy://device-changed`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceChanged<'a> {
+    /// The microphone recordings now use; `None` when there is none.
+    device: Option<&'a str>,
+    /// The chosen device, when there is one.
+    preferred: Option<&'a str>,
+    /// The chosen device is missing, so the system default is used.
+    fallback: bool,
+}
+
+/// The input devices present, from PipeWire when its tools are installed
+/// and otherwise from cpal.
+fn input_devices() -> Vec<CaptureDevice> {
+    #[cfg(target_os = "linux")]
+    if crate::pipewire::available() {
+        match crate::pipewire::list_inputs() {
+            Ok(inputs) => return inputs,
+            Err(err) => tracing::warn!(%err, "PipeWire inputs not listed; using cpal"),
+        }
+    }
+    let host = cpal::default_host();
+    let default = host
+        .default_input_device()
+        .and_then(|device| device.name().ok());
+    host.input_devices()
+        .map(|devices| {
+            devices
+                .filter_map(|device| device.name().ok())
+                .map(|name| CaptureDevice {
+                    is_default: default.as_ref() == Some(&name),
+                    id: name.clone(),
+                    name,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Which microphone recordings open, so a chosen device coming or going can
+/// be reported. cpal has no hotplug notifications, so this polls.
+#[derive(Default)]
+pub struct InputDeviceMonitor {
+    /// `None` until the first check.
+    current: Mutex<Option<Option<String>>>,
+}
+
+impl InputDeviceMonitor {
+    /// Works out the device in effect and, when it differs from last time,
+    /// emits `telepathy://device-changed` and moves the standby stream.
+    pub fn check(app: &AppHandle) {
+        let audio = app.state::<SettingsStore>().snapshot().audio;
+        let available = input_devices();
+        let preferred = audio.input_device.as_deref();
+        let fallback =
+            preferred.is_some_and(|preferred| !available.iter().any(|input| input.id == preferred));
+        let device = match preferred {
+            Some(preferred) if !fallback => Some(preferred.to_string()),
+            _ => available
+                .into_iter()
+                .find(|input| input.is_default)
+                .map(|input| input.id),
+        };
+        let monitor = app.state::<InputDeviceMonitor>();
+        let previous = monitor
+            .current
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .replace(device.clone());
+        if previous.is_none() || previous.as_ref() == Some(&device) {
+            return;
+        }
+        tracing::info!(device = ?device, fallback, "input device changed");
+        let _ = app.emit(
+            "telepathy://device-changed",
+            DeviceChanged {
+                device: device.as_deref(),
+                preferred,
+                fallback,
+            },
+        );
+        app.state::<Recorder>().sync_standby(&audio, true);
+    }
+}
+
+fn input_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device, String> {
+    let named = name.and_then(|name| {
+        host.input_devices()
+            .ok()?
+            .find(|device| device.name().is_ok_and(|device| device == name))
+    });
+    if named.is_none() {
+        if let Some(name) = name {
+            tracing::warn!(%name, "input device not found; using the default");
+        }
+    }
+    named
+        .or_else(|| host.default_input_device())
+        .ok_or("No microphone is available.".to_string())
+}
+
+/// Builds a stream that converts samples to `f32` straight into the pool's
+/// slots, so the callback never allocates whatever buffer size the device
+/// delivers. Chunks that don't fit in the channel are dropped and counted.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    chunks: mpsc::SyncSender<AudioChunk>,
+    overruns: Arc<AtomicU64>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let mut pool = BufferPool::new(POOL_SLOTS, CHUNK_LEN, config.sample_rate.0, config.channels);
+    let mut dropped = 0;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            pool.write_converted(
+                data,
+                |sample| sample.to_sample::<f32>(),
+                |chunk| {
+                    if chunks.try_send(chunk).is_err() {
+                        dropped += 1;
+                    }
+                },
+            );
+            overruns.store(pool.overruns() + dropped, Ordering::Relaxed);
+        },
+        |err| tracing::warn!(%err, "microphone stream error"),
+        None,
+    )
+}
+
+/// An open microphone; dropping it closes the device. Held only for that,
+/// so the streams are never read.
+#[allow(dead_code)]
+enum InputStream {
+    Cpal(cpal::Stream),
+    #[cfg(target_os = "linux")]
+    PipeWire(crate::pipewire::Recording),
+}
+
+/// Opens the configured microphone, through PipeWire when its tools are
+/// installed (falling back to cpal if that fails) and cpal elsewhere.
+fn open(
+    settings: &AudioSettings,
+    chunks: mpsc::SyncSender<AudioChunk>,
+    overruns: Arc<AtomicU64>,
+) -> Result<(InputStream, RecordingFormat), String> {
+    #[cfg(target_os = "linux")]
+    if crate::pipewire::available() {
+        let preferred = settings.input_device.as_deref();
+        match crate::pipewire::open_input(preferred, chunks.clone(), Arc::clone(&overruns)) {
+            Ok((recording, device)) => {
+                let format = RecordingFormat {
+                    device,
+                    sample_rate: crate::pipewire::FORMAT.sample_rate,
+                    channels: crate::pipewire::FORMAT.channels,
+                };
+                return Ok((InputStream::PipeWire(recording), format));
+            }
+            Err(err) => tracing::warn!(%err, "PipeWire microphone not opened; using cpal"),
+        }
+    }
+    let host = cpal::default_host();
+    let device = input_device(&host, settings.input_device.as_deref())?;
+    let supported = device
+        .default_input_config()
+        .map_err(|err| format!("Unable to read the microphone format: {err}"))?;
+    let config = supported.config();
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, chunks, overruns),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, chunks, overruns),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, chunks, overruns),
+        SampleFormat::I32 => build_stream::<i32>(&device, &config, chunks, overruns),
+        format => return Err(format!("Unsupported microphone sample format: {format}")),
+    }
+    .map_err(|err| format!("Unable to open the microphone: {err}"))?;
+    stream
+        .play()
+        .map_err(|err| format!("Unable to start the microphone: {err}"))?;
+    let format = RecordingFormat {
+        device: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+        sample_rate: config.sample_rate.0,
+        channels: config.channels,
+    };
+    Ok((InputStream::Cpal(stream), format))
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Keeps the input device open on its own thread, holding the last
+/// `pre_roll_ms` of audio until a recording takes the stream over.
+fn open_standby(settings: AudioSettings) -> Result<Standby, String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let pre_roll = Arc::new(Mutex::new(PreRoll {
+        ring: VecDeque::new(),
+        capacity: 0,
+        forward: None,
+    }));
+    let (opened, ready) = mpsc::channel();
+    let thread = {
+        let stop = Arc::clone(&stop);
+        let pre_roll = Arc::clone(&pre_roll);
+        let settings = settings.clone();
+        thread::Builder::new()
+            .name("telepathy-standby".to_string())
+            .spawn(move || {
+                let (sender, chunks) = mpsc::sync_channel(POOL_SLOTS);
+                // Chunks the stream drops while forwarding count as the
+                // recording's overruns.
+                let stream_overruns = Arc::new(AtomicU64::new(0));
+                let mut counted = 0;
+                // Held until the thread ends, which closes the device.
+                let _stream = match open(&settings, sender, Arc::clone(&stream_overruns)) {
+                    Ok((stream, format)) => {
+                        let frames =
+                            format.sample_rate as usize * settings.pre_roll_ms as usize / 1000;
+                        pre_roll
+                            .lock()
+                            .unwrap_or_else(|err| err.into_inner())
+                            .capacity = frames * usize::from(format.channels.max(1));
+                        let _ = opened.send(Ok(format));
+                        stream
+                    }
+                    Err(err) => {
+                        let _ = opened.send(Err(err));
+                        return;
+                    }
+                };
+                while !stop.load(Ordering::Acquire) {
+                    let chunk = match chunks.recv_timeout(POLL_INTERVAL) {
+                        Ok(chunk) => chunk,
+                        Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    };
+                    let stream_dropped = stream_overruns.load(Ordering::Relaxed) - counted;
+                    counted += stream_dropped;
+                    let mut pre_roll = pre_roll.lock().unwrap_or_else(|err| err.into_inner());
+                    if let Some(forward) = pre_roll.forward.as_ref() {
+                        let dropped = match forward.chunks.try_send(chunk) {
+                            Ok(()) => 0,
+                            Err(mpsc::TrySendError::Full(_)) => 1,
+                            // The recording stopped; back to listening.
+                            Err(mpsc::TrySendError::Disconnected(_)) => {
+                                pre_roll.forward = None;
+                                continue;
+                            }
+                        };
+                        forward
+                            .overruns
+                            .fetch_add(stream_dropped + dropped, Ordering::Relaxed);
+                        continue;
+                    }
+                    pre_roll.ring.extend(chunk.iter());
+                    let excess = pre_roll.ring.len().saturating_sub(pre_roll.capacity);
+                    pre_roll.ring.drain(..excess);
+                }
+            })
+            .map_err(|err| format!("Unable to start the microphone standby: {err}"))?
+    };
+    let format = ready
+        .recv()
+        .map_err(|_| "The microphone standby stopped while starting.".to_string())??;
+    tracing::info!(device = %format.device, pre_roll_ms = settings.pre_roll_ms, "microphone standby opened");
+    Ok(Standby {
+        stop,
+        format,
+        settings,
+        pre_roll,
+        thread,
+    })
+}
+
+/// Runs the stream on its own thread (cpal streams aren't `Send` on every
+/// platform) and collects chunks until `stop` is set, watching them for
+/// speech as they arrive. A standby source's pre-roll is collected first.
+fn spawn(
+    app: AppHandle,
+    settings: AudioSettings,
+    vad: VadSettings,
+    subscribers: Subscribers,
+    source: Source,
+) -> Result<ActiveRecording, String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let paused = Arc::new(AtomicBool::new(false));
+    let (opened, ready) = mpsc::channel();
+    let thread = {
+        let stop = Arc::clone(&stop);
+        let paused = Arc::clone(&paused);
+        thread::Builder::new()
+            .name("telepathy-recorder".to_string())
+            .spawn(move || {
+                let (mut stream, format, chunks, pre_roll, overruns) = match source {
+                    Source::Device => {
+                        let (sender, chunks) = mpsc::sync_channel(POOL_SLOTS);
+                        let overruns = Arc::new(AtomicU64::new(0));
+                        match open(&settings, sender, Arc::clone(&overruns)) {
+                            Ok((stream, format)) => {
+                                (Some(stream), format, chunks, Vec::new(), overruns)
+                            }
+                            Err(err) => {
+                                let _ = opened.send(Err(err.clone()));
+                                subscribers
+                                    .lock()
+                                    .unwrap_or_else(|err| err.into_inner())
+                                    .clear();
+                                return Err(err);
+                            }
+                        }
+                    }
+                    Source::Standby {
+                        chunks,
+                        format,
+                        pre_roll,
+                        overruns,
+                    } => (None, format, chunks, pre_roll, overruns),
+                };
+                let _ = opened.send(Ok(format.clone()));
+                let mut recording = Vec::new();
+                let mut detector = VoiceDetector::new(app.clone(), vad);
+                let mut meter = LevelMeter::new(&format);
+                let mut collect = |chunk: AudioChunk| {
+                    if paused.load(Ordering::Acquire) {
+                        return;
+                    }
+                    if settings.stream_chunks {
+                        let _ = app.emit(
+                            "telepathy://audio-chunk",
+                            ChunkPayload {
+                                sequence: chunk.sequence,
+                                sample_rate: chunk.sample_rate,
+                                channels: chunk.channels,
+                                samples: &chunk,
+                            },
+                        );
+                    }
+                    meter.push(&chunk, |level| {
+                        let _ = app.emit("telepathy://audio-level", level);
+                    });
+                    detector.feed(&chunk, rms(&chunk), chunk.sample_rate, chunk.channels);
+                    subscribers
+                        .lock()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .retain(|subscriber| {
+                            subscriber
+                                .send(RecordedAudio {
+                                    sample_rate: chunk.sample_rate,
+                                    channels: chunk.channels,
+                                    samples: chunk.to_vec(),
+                                })
+                                .is_ok()
+                        });
+                    recording.extend_from_slice(&chunk);
+                };
+                if !pre_roll.is_empty() {
+                    collect(AudioChunk::from_samples(
+                        pre_roll,
+                        format.sample_rate,
+                        format.channels,
+                    ));
+                }
+                while !stop.load(Ordering::Acquire) {
+                    match chunks.recv_timeout(POLL_INTERVAL) {
+                        Ok(chunk) => collect(chunk),
+                        Err(mpsc::RecvTimeoutError::Timeout) => {}
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                // Closes the device, if this recording opened one.
+                stream.take();
+                chunks.try_iter().for_each(&mut collect);
+                detector.trim(&mut recording, format.sample_rate, format.channels);
+                subscribers
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .clear();
+                Ok((recording, overruns.load(Ordering::Relaxed)))
+            })
+            .map_err(|err| format!("Unable to start recording: {err}"))?
+    };
+    let format = ready
+        .recv()
+        .map_err(|_| "Recording stopped while starting.".to_string())??;
+    Ok(ActiveRecording {
+        stop,
+        format,
+        thread,
+        keep: false,
+        paused,
+        paused_at: None,
+        paused_ms: 0,
+        segments: 1,
+    })
+}
+
+/// Mirrors the hotkey dictation with a native recording when
+/// `audio.nativeCapture` is on: starts on arm, stops once recording ends.
+/// Also keeps the pre-roll standby in step with the settings.
+pub fn init(app: &AppHandle) {
+    let audio = app.state::<SettingsStore>().snapshot().audio;
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        handle.state::<Recorder>().sync_standby(&audio, false);
+    });
+    let handle = app.clone();
+    app.listen("telepathy://settings-changed", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let audio = handle.state::<SettingsStore>().snapshot().audio;
+            handle.state::<Recorder>().sync_standby(&audio, false);
+            InputDeviceMonitor::check(&handle);
+        });
+    });
+    let handle = app.clone();
+    thread::spawn(move || loop {
+        if !Dormancy::active(&handle) {
+            InputDeviceMonitor::check(&handle);
+        }
+        thread::sleep(DEVICE_POLL_INTERVAL);
+    });
+    let handle = app.clone();
+    app.listen(dictation::STATE_EVENT, move |event| {
+        let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) else {
+            return;
+        };
+        let start = transition.event == DictationEvent::Arm;
+        let cancel = transition.event == DictationEvent::Cancel;
+        let stop = transition.from.recording() && !transition.to.recording();
+        let pause = match transition.event {
+            DictationEvent::Pause => Some(true),
+            DictationEvent::Resume => Some(false),
+            _ => None,
+        };
+        if !start && !stop && pause.is_none() {
+            return;
+        }
+        let handle = handle.clone();
+        let span = dictation::span(transition.session);
+        // Opening and closing devices blocks; keep it off the emitting thread.
+        tauri::async_runtime::spawn_blocking(move || {
+            let _span = span.entered();
+            let recorder = handle.state::<Recorder>();
+            if let Some(paused) = pause {
+                if recorder.is_running() {
+                    if let Err(err) = recorder.set_paused(paused) {
+                        tracing::warn!(%err, "recording pause not applied");
+                    }
+                }
+            } else if start
+                && handle
+                    .state::<SettingsStore>()
+                    .snapshot()
+                    .audio
+                    .native_capture
+            {
+                let _ = recorder.start(&handle);
+            } else if stop && recorder.is_running() {
+                if let Err(err) = recorder.stop() {
+                    tracing::warn!(%err, "recording did not stop cleanly");
+                }
+                if cancel {
+                    recorder.discard();
+                }
+            }
+        });
+    });
+}
+
+#[tauri::command]
+pub async fn start_recording(app: AppHandle) -> Result<RecordingFormat, String> {
+    crate::run_blocking(move || app.state::<Recorder>().start(&app)).await
+}
+
+#[tauri::command]
+pub async fn stop_recording(app: AppHandle) -> Result<RecordingSummary, String> {
+    crate::run_blocking(move || app.state::<Recorder>().stop()).await
+}
+
+/// The last finished recording's PCM; `None` once taken.
+#[tauri::command]
+pub fn take_recording(app: AppHandle) -> Option<RecordedAudio> {
+    app.state::<Recorder>().take()
+}
+
+/// Microphones that can be recorded from, for `set_audio_device`.
+#[tauri::command]
+pub async fn list_audio_devices(app: AppHandle) -> Result<Vec<AudioDevice>, String> {
+    crate::run_blocking(move || {
+        let selected = app.state::<SettingsStore>().snapshot().audio.input_device;
+        Ok(input_devices()
+            .into_iter()
+            .map(|input| AudioDevice {
+                selected: selected.as_ref() == Some(&input.id),
+                is_default: input.is_default,
+                name: input.name,
+                id: input.id,
+            })
+            .collect())
+    })
+    .await
+}
+
+/// Records from the device `id` from now on, falling back to the system
+/// default whenever it is unplugged; `None` follows the system default.
+#[tauri::command]
+pub async fn set_audio_device(app: AppHandle, id: Option<String>) -> Result<(), String> {
+    crate::run_blocking(move || {
+        if let Some(id) = id.as_deref() {
+            if !input_devices().iter().any(|input| input.id == id) {
+                return Err(format!("No microphone named \"{id}\" is connected."));
+            }
+        }
+        let store = app.state::<SettingsStore>();
+        let mut settings = store.snapshot();
+        settings.audio.input_device = id.clone();
+        store.replace(settings)?;
+        tracing::info!(device = ?id, "input device selected");
+        Ok(())
+    })
+    .await
+}
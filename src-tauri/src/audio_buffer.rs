@@ -0,0 +1,214 @@
+use std::{ops::Deref, ops::Range, sync::Arc};
+
+/// A read-only view of interleaved `f32` samples, shared by capture, VAD and
+/// transcription. Capture copies device samples once into a [`BufferPool`]
+/// slot; cloning or slicing a chunk after that only bumps a refcount.
+#[derive(Clone)]
+pub struct AudioChunk {
+    data: Arc<[f32]>,
+    range: Range<usize>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Monotonic capture order, so consumers can detect dropped chunks.
+    pub sequence: u64,
+}
+
+impl AudioChunk {
+    /// Wraps an owned buffer, for audio that didn't come from a [`BufferPool`]
+    /// (decoded files, tests).
+    pub fn from_samples(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Self {
+        let len = samples.len();
+        Self {
+            data: samples.into(),
+            range: 0..len,
+            sample_rate,
+            channels,
+            sequence: 0,
+        }
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.data[self.range.clone()]
+    }
+
+    /// A sub-view sharing the same allocation. `range` is relative to this
+    /// chunk and clamped to its length.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        let end = range.end.min(self.range.len());
+        let start = range.start.min(end);
+        Self {
+            data: self.data.clone(),
+            range: self.range.start + start..self.range.start + end,
+            ..*self
+        }
+    }
+
+    pub fn frames(&self) -> usize {
+        self.range.len() / usize::from(self.channels.max(1))
+    }
+
+    pub fn duration_ms(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.frames() as f64 * 1000.0 / f64::from(self.sample_rate)
+    }
+}
+
+impl Deref for AudioChunk {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        self.samples()
+    }
+}
+
+/// Fixed set of slots filled from the audio callback without allocating.
+///
+/// Size it so `slot_count * chunk_len` covers the longest backlog a consumer
+/// may hold (e.g. the VAD window); when every slot is still referenced the
+/// incoming samples are dropped and counted as an overrun rather than
+/// growing the pool on the real-time thread.
+pub struct BufferPool {
+    slots: Vec<Arc<[f32]>>,
+    next: usize,
+    sample_rate: u32,
+    channels: u16,
+    sequence: u64,
+    overruns: u64,
+}
+
+impl BufferPool {
+    pub fn new(slot_count: usize, chunk_len: usize, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            slots: (0..slot_count.max(1))
+                .map(|_| vec![0.0; chunk_len.max(1)].into())
+                .collect(),
+            next: 0,
+            sample_rate,
+            channels,
+            sequence: 0,
+            overruns: 0,
+        }
+    }
+
+    pub fn chunk_len(&self) -> usize {
+        self.slots[0].len()
+    }
+
+    /// Number of chunks dropped because no slot was free.
+    pub fn overruns(&self) -> u64 {
+        self.overruns
+    }
+
+    /// Copies `samples` into free slots, handing each filled chunk to `sink`.
+    /// Returns the number of samples dropped for lack of a free slot.
+    /// Performs no allocation, so it is safe to call from the audio callback.
+    pub fn write(&mut self, samples: &[f32], sink: impl FnMut(AudioChunk)) -> usize {
+        self.write_converted(samples, |sample| sample, sink)
+    }
+
+    /// Like [`BufferPool::write`], converting device samples to `f32` as
+    /// they are copied into the slots, so buffers of any size need no
+    /// scratch space.
+    pub fn write_converted<T: Copy>(
+        &mut self,
+        mut samples: &[T],
+        convert: impl Fn(T) -> f32,
+        mut sink: impl FnMut(AudioChunk),
+    ) -> usize {
+        let chunk_len = self.chunk_len();
+        let mut dropped = 0;
+        while !samples.is_empty() {
+            let take = samples.len().min(chunk_len);
+            let (head, rest) = samples.split_at(take);
+            samples = rest;
+            match self.fill(head, &convert) {
+                Some(chunk) => sink(chunk),
+                None => dropped += take,
+            }
+        }
+        dropped
+    }
+
+    fn fill<T: Copy>(&mut self, samples: &[T], convert: impl Fn(T) -> f32) -> Option<AudioChunk> {
+        let count = self.slots.len();
+        for offset in 0..count {
+            let index = (self.next + offset) % count;
+            // Only slots no consumer still references are writable.
+            if let Some(slot) = Arc::get_mut(&mut self.slots[index]) {
+                for (to, &from) in slot.iter_mut().zip(samples) {
+                    *to = convert(from);
+                }
+                self.next = (index + 1) % count;
+                let sequence = self.sequence;
+                self.sequence += 1;
+                return Some(AudioChunk {
+                    data: self.slots[index].clone(),
+                    range: 0..samples.len(),
+                    sample_rate: self.sample_rate,
+                    channels: self.channels,
+                    sequence,
+                });
+            }
+        }
+        self.overruns += 1;
+        self.sequence += 1;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_splits_long_buffers_into_chunks() {
+        let mut pool = BufferPool::new(4, 3, 16_000, 1);
+        let mut chunks = Vec::new();
+        let dropped = pool.write(&[1.0, 2.0, 3.0, 4.0, 5.0], |chunk| chunks.push(chunk));
+        assert_eq!(dropped, 0);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].samples(), &[1.0, 2.0, 3.0]);
+        assert_eq!(chunks[1].samples(), &[4.0, 5.0]);
+        assert_eq!((chunks[0].sequence, chunks[1].sequence), (0, 1));
+    }
+
+    #[test]
+    fn write_converted_converts_in_place() {
+        let mut pool = BufferPool::new(2, 4, 16_000, 1);
+        let mut chunks = Vec::new();
+        pool.write_converted(
+            &[i16::MIN, 0, i16::MAX / 2],
+            |sample| f32::from(sample) / 32_768.0,
+            |chunk| chunks.push(chunk),
+        );
+        assert_eq!(chunks[0].samples(), &[-1.0, 0.0, 16_383.0 / 32_768.0]);
+    }
+
+    #[test]
+    fn write_drops_and_counts_when_every_slot_is_held() {
+        let mut pool = BufferPool::new(2, 2, 16_000, 1);
+        let mut held = Vec::new();
+        let dropped = pool.write(&[0.0; 6], |chunk| held.push(chunk));
+        assert_eq!(dropped, 2);
+        assert_eq!(pool.overruns(), 1);
+        assert_eq!(held.len(), 2);
+
+        // Releasing a chunk frees its slot again.
+        held.pop();
+        let dropped = pool.write(&[0.0; 2], |chunk| held.push(chunk));
+        assert_eq!(dropped, 0);
+        assert_eq!(held[1].sequence, 3);
+    }
+
+    #[test]
+    fn slice_shares_and_clamps() {
+        let chunk = AudioChunk::from_samples(vec![0.0, 1.0, 2.0, 3.0], 16_000, 2);
+        let slice = chunk.slice(1..10);
+        assert_eq!(slice.samples(), &[1.0, 2.0, 3.0]);
+        let (start, end) = (3, 1);
+        assert_eq!(chunk.slice(start..end).samples(), &[] as &[f32]);
+        assert_eq!(chunk.frames(), 2);
+    }
+}
@@ -0,0 +1,335 @@
+use std::{ffi::c_void, ptr::NonNull};
+
+use objc2_core_foundation::{CFRetained, CFString, CFType};
+use objc2_foundation::{NSDictionary, NSNumber, NSString};
+
+use crate::focus::Rect;
+
+type AXError = i32;
+
+const AX_SUCCESS: AXError = 0;
+/// `kAXValueCGRectType`.
+const AX_VALUE_CG_RECT: u32 = 3;
+/// `kAXValueCFRangeType`.
+const AX_VALUE_CF_RANGE: u32 = 4;
+
+const FOCUSED_ELEMENT: &str = "AXFocusedUIElement";
+const ROLE: &str = "AXRole";
+const SUBROLE: &str = "AXSubrole";
+const VALUE: &str = "AXValue";
+const SELECTED_TEXT: &str = "AXSelectedText";
+const SELECTED_TEXT_RANGE: &str = "AXSelectedTextRange";
+const SECURE_TEXT_FIELD: &str = "AXSecureTextField";
+const BOUNDS_FOR_RANGE: &str = "AXBoundsForRange";
+/// `kAXTrustedCheckOptionPrompt`.
+const TRUSTED_CHECK_OPTION_PROMPT: &str = "AXTrustedCheckOptionPrompt";
+
+/// `CFRange`, in UTF-16 code units like the strings it indexes.
+#[repr(C)]
+#[derive(Default)]
+struct CFRange {
+    location: isize,
+    length: isize,
+}
+
+/// `CGRect`, in points from the top-left of the main display.
+#[repr(C)]
+#[derive(Default)]
+struct CGRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrustedWithOptions(options: *const c_void) -> u8;
+    fn AXUIElementCreateSystemWide() -> *mut c_void;
+    fn AXUIElementCopyAttributeValue(
+        element: *mut c_void,
+        attribute: *const c_void,
+        value: *mut *mut c_void,
+    ) -> AXError;
+    fn AXUIElementCopyParameterizedAttributeValue(
+        element: *mut c_void,
+        attribute: *const c_void,
+        parameter: *const c_void,
+        value: *mut *mut c_void,
+    ) -> AXError;
+    fn AXUIElementSetAttributeValue(
+        element: *mut c_void,
+        attribute: *const c_void,
+        value: *const c_void,
+    ) -> AXError;
+    fn AXUIElementIsAttributeSettable(
+        element: *mut c_void,
+        attribute: *const c_void,
+        settable: *mut u8,
+    ) -> AXError;
+    fn AXValueCreate(kind: u32, value: *const c_void) -> *mut c_void;
+    fn AXValueGetValue(value: *const c_void, kind: u32, out: *mut c_void) -> u8;
+}
+
+fn cf_ptr(value: &CFType) -> *mut c_void {
+    (value as *const CFType).cast_mut().cast()
+}
+
+/// NSString is toll-free bridged to the CFString the AX calls take.
+fn ns_ptr(value: &NSString) -> *const c_void {
+    (value as *const NSString).cast()
+}
+
+/// Takes ownership of a +1 reference returned by a Create or Copy call.
+fn retained(raw: *mut c_void) -> Option<CFRetained<CFType>> {
+    // SAFETY: callers only pass pointers from Create/Copy functions, which
+    // hand over one reference for `CFRetained` to release.
+    NonNull::new(raw).map(|raw| unsafe { CFRetained::from_raw(raw.cast::<CFType>()) })
+}
+
+/// Whether Telepathy may drive other apps through the Accessibility API and
+/// post keystrokes into them. With `prompt`, macOS also shows its dialog
+/// pointing at System Settings when access is missing; the dialog doesn't
+/// block, so the result is the state from before the user answers.
+pub fn is_process_trusted(prompt: bool) -> bool {
+    let options = prompt.then(|| {
+        NSDictionary::<NSString, NSNumber>::from_slices(
+            &[&*NSString::from_str(TRUSTED_CHECK_OPTION_PROMPT)],
+            &[&*NSNumber::new_bool(true)],
+        )
+    });
+    let options = options.as_deref().map_or(std::ptr::null(), |options| {
+        (options as *const NSDictionary<NSString, NSNumber>).cast()
+    });
+    // SAFETY: `options` is null or a dictionary toll-free bridged to the
+    // CFDictionary the call reads; it outlives the call.
+    unsafe { AXIsProcessTrustedWithOptions(options) != 0 }
+}
+
+/// An `AXUIElementRef`.
+struct Element(CFRetained<CFType>);
+
+impl Element {
+    fn system_wide() -> Option<Self> {
+        // SAFETY: no arguments; the result is owned.
+        retained(unsafe { AXUIElementCreateSystemWide() }).map(Self)
+    }
+
+    fn copy(&self, attribute: &str) -> Option<CFRetained<CFType>> {
+        let name = NSString::from_str(attribute);
+        let mut value = std::ptr::null_mut();
+        // SAFETY: `self` is a live AXUIElement and `name` a CFString; the
+        // copied value is owned by the caller on success.
+        let err =
+            unsafe { AXUIElementCopyAttributeValue(cf_ptr(&self.0), ns_ptr(&name), &mut value) };
+        if err == AX_SUCCESS {
+            retained(value)
+        } else {
+            None
+        }
+    }
+
+    fn element(&self, attribute: &str) -> Option<Element> {
+        self.copy(attribute).map(Element)
+    }
+
+    fn string(&self, attribute: &str) -> Option<String> {
+        let value = self.copy(attribute)?;
+        value
+            .downcast_ref::<CFString>()
+            .map(|string| string.to_string())
+    }
+
+    fn range(&self, attribute: &str) -> Option<CFRange> {
+        let value = self.copy(attribute)?;
+        let mut range = CFRange::default();
+        // SAFETY: AXValueGetValue checks the value's type and only writes a
+        // CFRange into `range` when it matches.
+        let ok = unsafe {
+            AXValueGetValue(
+                cf_ptr(&value),
+                AX_VALUE_CF_RANGE,
+                (&mut range as *mut CFRange).cast(),
+            )
+        };
+        (ok != 0).then_some(range)
+    }
+
+    /// Screen box of the text in `range`, for elements that support
+    /// `AXBoundsForRange`.
+    fn bounds_for_range(&self, range: CFRange) -> Option<CGRect> {
+        let name = NSString::from_str(BOUNDS_FOR_RANGE);
+        // SAFETY: AXValueCreate copies the CFRange it is pointed at.
+        let parameter = retained(unsafe {
+            AXValueCreate(AX_VALUE_CF_RANGE, (&range as *const CFRange).cast())
+        })?;
+        let mut value = std::ptr::null_mut();
+        // SAFETY: `self` is a live AXUIElement, `name` a CFString, and
+        // `parameter` an AXValue; the copied value is owned on success.
+        let err = unsafe {
+            AXUIElementCopyParameterizedAttributeValue(
+                cf_ptr(&self.0),
+                ns_ptr(&name),
+                cf_ptr(&parameter),
+                &mut value,
+            )
+        };
+        if err != AX_SUCCESS {
+            return None;
+        }
+        let value = retained(value)?;
+        let mut rect = CGRect::default();
+        // SAFETY: AXValueGetValue checks the value's type and only writes a
+        // CGRect into `rect` when it matches.
+        let ok = unsafe {
+            AXValueGetValue(
+                cf_ptr(&value),
+                AX_VALUE_CG_RECT,
+                (&mut rect as *mut CGRect).cast(),
+            )
+        };
+        (ok != 0 && rect.height > 0.0).then_some(rect)
+    }
+
+    fn is_secure(&self) -> bool {
+        [ROLE, SUBROLE]
+            .iter()
+            .any(|attribute| self.string(attribute).as_deref() == Some(SECURE_TEXT_FIELD))
+    }
+
+    fn settable(&self, attribute: &str) -> bool {
+        let name = NSString::from_str(attribute);
+        let mut settable = 0u8;
+        // SAFETY: `self` is a live AXUIElement and `name` a CFString.
+        let err = unsafe {
+            AXUIElementIsAttributeSettable(cf_ptr(&self.0), ns_ptr(&name), &mut settable)
+        };
+        err == AX_SUCCESS && settable != 0
+    }
+
+    fn set(&self, attribute: &str, value: *const c_void) -> Result<(), String> {
+        let name = NSString::from_str(attribute);
+        // SAFETY: `self` is a live AXUIElement, `name` a CFString, and
+        // `value` a CF object of the type the attribute expects.
+        let err = unsafe { AXUIElementSetAttributeValue(cf_ptr(&self.0), ns_ptr(&name), value) };
+        if err == AX_SUCCESS {
+            Ok(())
+        } else {
+            Err(format!("Setting {attribute} failed (AXError {err})."))
+        }
+    }
+
+    fn set_text(&self, attribute: &str, text: &str) -> Result<(), String> {
+        self.set(attribute, ns_ptr(&NSString::from_str(text)))
+    }
+
+    fn set_range(&self, attribute: &str, range: CFRange) -> Result<(), String> {
+        // SAFETY: AXValueCreate copies the CFRange it is pointed at.
+        let value = retained(unsafe {
+            AXValueCreate(AX_VALUE_CF_RANGE, (&range as *const CFRange).cast())
+        })
+        .ok_or("Unable to create an AXValue.".to_string())?;
+        self.set(attribute, cf_ptr(&value))
+    }
+}
+
+/// Splices `text` over `range` of `value`, counting in UTF-16 units.
+fn splice(value: &str, range: &CFRange, text: &str) -> Option<String> {
+    let units: Vec<u16> = value.encode_utf16().collect();
+    let start = usize::try_from(range.location).ok()?;
+    let end = start.checked_add(usize::try_from(range.length).ok()?)?;
+    let before = String::from_utf16(units.get(..start)?).ok()?;
+    let after = String::from_utf16(units.get(end..)?).ok()?;
+    Some(format!("{before}{text}{after}"))
+}
+
+/// Whether the focused element is a password field. False when nothing has
+/// focus or Accessibility access is missing.
+pub fn focused_is_secure() -> bool {
+    Element::system_wide()
+        .and_then(|system| system.element(FOCUSED_ELEMENT))
+        .is_some_and(|focused| focused.is_secure())
+}
+
+/// Screen box of the focused element's caret, in points from the top-left
+/// of the main display. `None` when nothing has focus, Accessibility
+/// access is missing, or the element doesn't report text bounds.
+pub fn caret_bounds() -> Option<Rect> {
+    let focused = Element::system_wide()?.element(FOCUSED_ELEMENT)?;
+    let selection = focused.range(SELECTED_TEXT_RANGE)?;
+    let caret = CFRange {
+        location: selection.location,
+        length: 0,
+    };
+    if let Some(rect) = focused.bounds_for_range(caret) {
+        return Some(Rect {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        });
+    }
+    // Many apps give an empty range no box; measure the character before
+    // the caret and keep its trailing edge.
+    let previous = CFRange {
+        location: selection
+            .location
+            .checked_sub(1)
+            .filter(|&start| start >= 0)?,
+        length: 1,
+    };
+    let rect = focused.bounds_for_range(previous)?;
+    Some(Rect {
+        x: rect.x + rect.width,
+        y: rect.y,
+        width: 0.0,
+        height: rect.height,
+    })
+}
+
+/// Replaces the focused element's selection (or inserts at its caret) with
+/// `text` through the Accessibility API, leaving the clipboard alone.
+///
+/// Prefers `AXSelectedText`, which editors apply like typing. Elements that
+/// only expose a writable `AXValue` get the text spliced in at the selected
+/// range. Anything else, password fields, and elements that accept the
+/// write but ignore it (common in web views) are refused so the caller can
+/// fall back to a clipboard paste.
+pub fn insert_text(text: &str) -> Result<(), String> {
+    let focused = Element::system_wide()
+        .and_then(|system| system.element(FOCUSED_ELEMENT))
+        .ok_or("No element has the keyboard focus.".to_string())?;
+    if focused.is_secure() {
+        return Err("The focused field is a password field.".to_string());
+    }
+
+    let before = focused.string(VALUE);
+    if focused.settable(SELECTED_TEXT) {
+        focused.set_text(SELECTED_TEXT, text)?;
+        let after = focused.string(VALUE);
+        if before.is_some() && before == after && !text.is_empty() {
+            return Err("The focused element ignored the inserted text.".to_string());
+        }
+        return Ok(());
+    }
+
+    if !focused.settable(VALUE) {
+        return Err("The focused element is not writable.".to_string());
+    }
+    let current = before.ok_or("The focused element has no text value.".to_string())?;
+    let selection = focused
+        .range(SELECTED_TEXT_RANGE)
+        .ok_or("The focused element does not report its caret.".to_string())?;
+    let updated = splice(&current, &selection, text)
+        .ok_or("The focused element's selection is out of range.".to_string())?;
+    focused.set_text(VALUE, &updated)?;
+
+    // Setting the value usually drops the caret at the end; put it after
+    // the inserted text. Best effort: the text is already in place.
+    let caret = CFRange {
+        location: selection.location + text.encode_utf16().count() as isize,
+        length: 0,
+    };
+    let _ = focused.set_range(SELECTED_TEXT_RANGE, caret);
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Listener, Manager};
+
+use crate::{
+    analytics::{self, LatencySummary},
+    destinations::{DeliveryResult, DestinationKind},
+    dictation::{
+        DictationEvent, DictationMachine, DictationPhase, DictationTransition, STATE_EVENT,
+    },
+    injection::{Injector, PasteTimings},
+    model_warmup::ModelState,
+    settings::SettingsStore,
+};
+
+const DEFAULT_TRIALS: u32 = 5;
+const MAX_TRIALS: u32 = 20;
+/// How long each trial records before stopping, roughly one short phrase.
+const DEFAULT_CLIP_MS: u64 = 2_000;
+const RECORD_START_TIMEOUT: Duration = Duration::from_secs(10);
+const TRANSCRIPT_TIMEOUT: Duration = Duration::from_secs(90);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(20);
+/// Settle time between trials so the camera is fully released.
+const TRIAL_GAP: Duration = Duration::from_millis(750);
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrialFailure {
+    pub trial: u32,
+    pub stage: &'static str,
+    pub error: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub generated_at: String,
+    pub trials: u32,
+    pub completed: u32,
+    pub clip_ms: u64,
+    /// Transcription backend reported by the server when the run started.
+    pub backend: Option<String>,
+    pub hotkey_to_record_start: LatencySummary,
+    pub stop_to_final_transcript: LatencySummary,
+    pub transcript_to_paste_complete: LatencySummary,
+    pub failures: Vec<TrialFailure>,
+}
+
+/// Set while a benchmark runs; deliveries then skip real destinations,
+/// analytics, and the transcript stream.
+#[derive(Default)]
+pub struct LatencyBenchmark(AtomicBool);
+
+impl LatencyBenchmark {
+    pub fn is_running(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Clears the running flag however the run ends.
+struct RunningGuard<'a>(&'a LatencyBenchmark);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0 .0.store(false, Ordering::Release);
+    }
+}
+
+/// Stand-in for delivery during a benchmark: times the clipboard write but
+/// sends no keystroke, so nothing is typed into whatever app is frontmost.
+pub async fn deliver_dry_run(app: &AppHandle, text: &str) -> Result<DeliveryResult, String> {
+    let app = app.clone();
+    let text = text.to_string();
+    crate::run_blocking(move || {
+        let conceal = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .paste
+            .conceal_clipboard;
+        let started = Instant::now();
+        app.state::<Injector>().write_clipboard(&text, conceal)?;
+        let total_ms = started.elapsed().as_secs_f64() * 1000.0;
+        Ok(DeliveryResult {
+            destination: DestinationKind::Paste,
+            pasted: false,
+            location: None,
+            paste_timings: Some(PasteTimings {
+                clipboard_ms: total_ms,
+                keystroke_ms: 0.0,
+                total_ms,
+            }),
+            paste_blocked: None,
+        })
+    })
+    .await
+}
+
+struct Trial {
+    record_start_ms: u64,
+    final_transcript_ms: u64,
+    paste_ms: u64,
+}
+
+/// Transitions observed through the same event the overlay listens to.
+struct Transitions(Receiver<(Instant, DictationTransition)>);
+
+impl Transitions {
+    /// Waits for the first transition of `session` that leaves `phase`.
+    fn leaving(
+        &self,
+        session: u64,
+        phase: DictationPhase,
+        timeout: Duration,
+    ) -> Result<(Instant, DictationTransition), String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.0.recv_timeout(remaining) {
+                Ok((at, transition))
+                    if transition.session == session && transition.from == phase =>
+                {
+                    return Ok((at, transition));
+                }
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(format!("Timed out after {}s.", timeout.as_secs()))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err("Stopped observing dictation state.".to_string())
+                }
+            }
+        }
+    }
+
+    fn drain(&self) {
+        while self.0.try_recv().is_ok() {}
+    }
+}
+
+fn ms_between(start: Instant, end: Instant) -> u64 {
+    end.saturating_duration_since(start).as_millis() as u64
+}
+
+fn expect_phase(
+    transition: &DictationTransition,
+    phase: DictationPhase,
+    stage: &'static str,
+) -> Result<(), (&'static str, String)> {
+    if transition.to == phase {
+        return Ok(());
+    }
+    let error = transition
+        .error
+        .clone()
+        .unwrap_or_else(|| format!("Ended in {:?} after {:?}.", transition.to, transition.event));
+    Err((stage, error))
+}
+
+/// One dictation driven through the real recorder, transcription provider,
+/// and pipeline, timed from the transitions each stage produces.
+fn run_trial(
+    app: &AppHandle,
+    transitions: &Transitions,
+    clip: Duration,
+) -> Result<Trial, (&'static str, String)> {
+    let machine = app.state::<DictationMachine>();
+    transitions.drain();
+
+    let armed_at = Instant::now();
+    machine
+        .handle(app, DictationEvent::Arm, None)
+        .map_err(|err| ("arm", err))?;
+    let session = machine.snapshot().session;
+    let (recording_at, started) = transitions
+        .leaving(session, DictationPhase::Arming, RECORD_START_TIMEOUT)
+        .map_err(|err| ("recordStart", err))?;
+    expect_phase(&started, DictationPhase::Recording, "recordStart")?;
+
+    thread::sleep(clip);
+    let stopped_at = Instant::now();
+    machine
+        .handle(app, DictationEvent::Stop, None)
+        .map_err(|err| ("stop", err))?;
+    let (transcript_at, transcribed) = transitions
+        .leaving(session, DictationPhase::Transcribing, TRANSCRIPT_TIMEOUT)
+        .map_err(|err| ("finalTranscript", err))?;
+    expect_phase(&transcribed, DictationPhase::Processing, "finalTranscript")?;
+
+    let (_, delivering) = transitions
+        .leaving(session, DictationPhase::Processing, DELIVERY_TIMEOUT)
+        .map_err(|err| ("pasteComplete", err))?;
+    expect_phase(&delivering, DictationPhase::Injecting, "pasteComplete")?;
+    let (pasted_at, finished) = transitions
+        .leaving(session, DictationPhase::Injecting, DELIVERY_TIMEOUT)
+        .map_err(|err| ("pasteComplete", err))?;
+    if finished.event != DictationEvent::Finished {
+        return Err((
+            "pasteComplete",
+            finished
+                .error
+                .unwrap_or_else(|| format!("Delivery ended with {:?}.", finished.event)),
+        ));
+    }
+
+    Ok(Trial {
+        record_start_ms: ms_between(armed_at, recording_at),
+        final_transcript_ms: ms_between(stopped_at, transcript_at),
+        paste_ms: ms_between(transcript_at, pasted_at),
+    })
+}
+
+fn run(app: &AppHandle, trials: u32, clip_ms: u64) -> Result<BenchmarkReport, String> {
+    let benchmark = app.state::<LatencyBenchmark>();
+    let machine = app.state::<DictationMachine>();
+    if machine.phase() != DictationPhase::Idle {
+        return Err("Finish the current dictation before benchmarking.".to_string());
+    }
+    let model = app.state::<ModelState>().status();
+    if !model.as_ref().is_some_and(|status| status.ready) {
+        return Err("The transcription model isn't loaded yet.".to_string());
+    }
+    if benchmark.0.swap(true, Ordering::AcqRel) {
+        return Err("A latency benchmark is already running.".to_string());
+    }
+    let _running = RunningGuard(&benchmark);
+
+    let (sender, receiver) = mpsc::channel();
+    let listener = app.listen(STATE_EVENT, move |event| {
+        if let Ok(transition) = serde_json::from_str(event.payload()) {
+            let _ = sender.send((Instant::now(), transition));
+        }
+    });
+    let transitions = Transitions(receiver);
+
+    let clip = Duration::from_millis(clip_ms);
+    let mut record_start = Vec::new();
+    let mut final_transcript = Vec::new();
+    let mut paste = Vec::new();
+    let mut failures = Vec::new();
+    for trial in 1..=trials {
+        match run_trial(app, &transitions, clip) {
+            Ok(timing) => {
+                record_start.push(timing.record_start_ms);
+                final_transcript.push(timing.final_transcript_ms);
+                paste.push(timing.paste_ms);
+            }
+            Err((stage, error)) => {
+                // Leave the machine idle so the next trial starts clean.
+                let _ = machine.handle(app, DictationEvent::Cancel, None);
+                failures.push(TrialFailure {
+                    trial,
+                    stage,
+                    error,
+                });
+            }
+        }
+        thread::sleep(TRIAL_GAP);
+    }
+    app.unlisten(listener);
+
+    Ok(BenchmarkReport {
+        generated_at: chrono::Local::now().to_rfc3339(),
+        trials,
+        completed: paste.len() as u32,
+        clip_ms,
+        backend: model.and_then(|status| status.backend),
+        hotkey_to_record_start: analytics::summarize(record_start),
+        stop_to_final_transcript: analytics::summarize(final_transcript),
+        transcript_to_paste_complete: analytics::summarize(paste),
+        failures,
+    })
+}
+
+/// Runs `trials` full dictations (default 5) with the current provider and
+/// pipeline, each recording for `clip_ms`, and reports per-stage latency.
+/// The paste keystroke is skipped; its clipboard write is still timed.
+#[tauri::command]
+pub async fn run_latency_benchmark(
+    app: AppHandle,
+    trials: Option<u32>,
+    clip_ms: Option<u64>,
+) -> Result<BenchmarkReport, String> {
+    let trials = trials.unwrap_or(DEFAULT_TRIALS).clamp(1, MAX_TRIALS);
+    let clip_ms = clip_ms.unwrap_or(DEFAULT_CLIP_MS);
+    crate::run_blocking(move || run(&app, trials, clip_ms)).await
+}
@@ -0,0 +1,178 @@
+use tauri::{AppHandle, Manager};
+
+use crate::model_warmup::ModelState;
+
+/// Cargo features this binary was built with.
+const FEATURES: &[(&str, bool)] = &[
+    ("osc-trigger", cfg!(feature = "osc-trigger")),
+    ("midi-trigger", cfg!(feature = "midi-trigger")),
+    ("local-whisper", cfg!(feature = "local-whisper")),
+    ("coreml", cfg!(feature = "coreml")),
+    ("http-api", cfg!(feature = "http-api")),
+];
+
+/// The Cargo features compiled in.
+pub fn features() -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Whether an optional provider or integration is compiled in, and whether
+/// it can actually be used on this machine right now.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capability {
+    pub id: &'static str,
+    pub compiled: bool,
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub features: Vec<&'static str>,
+    pub capabilities: Vec<Capability>,
+}
+
+fn lipreading_server(app: &AppHandle) -> Capability {
+    let status = app.state::<ModelState>().status();
+    Capability {
+        id: "lipreadingServer",
+        compiled: true,
+        available: status.as_ref().is_some_and(|status| status.ready),
+        detail: status.map(|status| status.stage),
+    }
+}
+
+#[cfg(feature = "midi-trigger")]
+fn midi_trigger() -> Capability {
+    let ports = midir::MidiInput::new("Telepathy probe").map(|input| input.port_count());
+    Capability {
+        id: "midiTrigger",
+        compiled: true,
+        available: ports.as_ref().is_ok_and(|count| *count > 0),
+        detail: Some(match ports {
+            Ok(count) => format!("{count} input port(s)"),
+            Err(err) => err.to_string(),
+        }),
+    }
+}
+
+#[cfg(not(feature = "midi-trigger"))]
+fn midi_trigger() -> Capability {
+    Capability {
+        id: "midiTrigger",
+        compiled: false,
+        available: false,
+        detail: None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn x11_paste() -> Capability {
+    let probe = crate::x11::probe();
+    Capability {
+        id: "x11Paste",
+        compiled: true,
+        available: probe.is_ok(),
+        detail: probe.err(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn x11_paste() -> Capability {
+    Capability {
+        id: "x11Paste",
+        compiled: false,
+        available: false,
+        detail: None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn wayland_capabilities() -> [Capability; 2] {
+    let wayland = crate::x11::session() == crate::x11::Session::Wayland;
+    let capability = |id, probe: fn() -> Result<String, String>| {
+        let result = if wayland {
+            probe()
+        } else {
+            Err("Not a Wayland session.".to_string())
+        };
+        Capability {
+            id,
+            compiled: true,
+            available: result.is_ok(),
+            detail: Some(result.unwrap_or_else(|err| err)),
+        }
+    };
+    [
+        capability("waylandPaste", crate::wayland::probe_paste),
+        capability("waylandShortcuts", crate::wayland::probe_shortcuts),
+    ]
+}
+
+#[cfg(not(target_os = "linux"))]
+fn wayland_capabilities() -> [Capability; 2] {
+    ["waylandPaste", "waylandShortcuts"].map(|id| Capability {
+        id,
+        compiled: false,
+        available: false,
+        detail: None,
+    })
+}
+
+/// Reports the compiled feature set plus runtime availability, so the UI can
+/// hide options a slim build or this machine can't offer.
+#[tauri::command]
+pub async fn get_build_info(app: AppHandle) -> Result<BuildInfo, String> {
+    crate::run_blocking(move || {
+        let mut capabilities = vec![
+            lipreading_server(&app),
+            Capability {
+                id: "oscTrigger",
+                compiled: cfg!(feature = "osc-trigger"),
+                available: cfg!(feature = "osc-trigger"),
+                detail: None,
+            },
+            midi_trigger(),
+            Capability {
+                id: "localWhisper",
+                compiled: cfg!(feature = "local-whisper"),
+                available: cfg!(feature = "local-whisper"),
+                detail: None,
+            },
+            Capability {
+                id: "coreml",
+                compiled: cfg!(feature = "coreml"),
+                available: cfg!(feature = "coreml"),
+                detail: None,
+            },
+            Capability {
+                id: "httpApi",
+                compiled: cfg!(feature = "http-api"),
+                available: cfg!(feature = "http-api"),
+                detail: None,
+            },
+            Capability {
+                id: "eventTapHotkey",
+                compiled: cfg!(target_os = "macos"),
+                available: cfg!(target_os = "macos"),
+                detail: None,
+            },
+            x11_paste(),
+        ];
+        capabilities.extend(wayland_capabilities());
+        Ok(BuildInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            features: features(),
+            capabilities,
+        })
+    })
+    .await
+}
@@ -0,0 +1,332 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Manager};
+
+use crate::audio::RecordedAudio;
+#[cfg(target_os = "linux")]
+use crate::pipewire as backend;
+#[cfg(target_os = "windows")]
+use crate::wasapi as backend;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Longest capture kept; recording stops here so a forgotten capture can't
+/// grow without bound (about 115 MB at 16 kHz mono).
+const MAX_CAPTURE_SECS: usize = 30 * 60;
+
+/// Where captured audio comes from.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum CaptureSource {
+    /// Everything playing on an output device; the default one when
+    /// `device_id` is absent.
+    SystemAudio {
+        #[serde(default)]
+        device_id: Option<String>,
+    },
+    /// One application (and its child processes) by process id.
+    Application { pid: u32 },
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureApplication {
+    pub pid: u32,
+    pub name: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureSources {
+    /// Output devices usable as `systemAudio` sources.
+    pub devices: Vec<CaptureDevice>,
+    /// Apps currently playing audio, where the platform can list them.
+    pub applications: Vec<CaptureApplication>,
+    /// Whether `application` sources can be opened here.
+    pub per_application: bool,
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureSummary {
+    pub duration_ms: u64,
+    /// Chunks dropped because the consumer fell behind.
+    pub overruns: u64,
+    /// The capture hit the length limit and stopped recording there.
+    pub truncated: bool,
+}
+
+/// There is no ScreenCaptureKit source on macOS yet, so the capture API is
+/// present but reports no devices and refuses to start.
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod backend {
+    use super::{CaptureApplication, CaptureDevice, CaptureFormat, CaptureSource};
+    use crate::audio_buffer::AudioChunk;
+
+    pub const UNSUPPORTED: &str = "System audio capture is not implemented on this platform yet.";
+
+    pub fn list_devices() -> Result<Vec<CaptureDevice>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn list_applications() -> Result<Vec<CaptureApplication>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn probe() -> Result<String, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn supports_applications() -> bool {
+        false
+    }
+
+    /// Never constructed; `open` always fails here.
+    pub enum Stream {}
+
+    impl Stream {
+        pub fn open(_source: &CaptureSource) -> Result<Self, String> {
+            Err(UNSUPPORTED.to_string())
+        }
+
+        pub fn format(&self) -> CaptureFormat {
+            match *self {}
+        }
+
+        pub fn overruns(&self) -> u64 {
+            match *self {}
+        }
+
+        pub fn read(&mut self, _sink: impl FnMut(AudioChunk)) -> Result<(), String> {
+            match *self {}
+        }
+    }
+}
+
+struct ActiveCapture {
+    stop: Arc<AtomicBool>,
+    format: CaptureFormat,
+    thread: thread::JoinHandle<Result<(Vec<f32>, u64), String>>,
+}
+
+/// The one system-audio capture that may run at a time, and the last one
+/// finished.
+#[derive(Default)]
+pub struct AudioCapture {
+    active: Mutex<Option<ActiveCapture>>,
+    last: Mutex<Option<RecordedAudio>>,
+}
+
+impl AudioCapture {
+    pub fn is_running(&self) -> bool {
+        self.active
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .is_some()
+    }
+
+    /// Stops the running capture, releases its device, and keeps the audio
+    /// for `take_capture`.
+    pub fn stop(&self) -> Result<CaptureSummary, String> {
+        let capture = self
+            .active
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+            .ok_or("Audio capture is not running.".to_string())?;
+        capture.stop.store(true, Ordering::Release);
+        let (recording, overruns) = capture
+            .thread
+            .join()
+            .map_err(|_| "Audio capture thread panicked.".to_string())??;
+        let truncated = recording.len() >= max_samples(capture.format);
+        let audio = RecordedAudio {
+            sample_rate: capture.format.sample_rate,
+            channels: capture.format.channels,
+            samples: recording,
+        };
+        let duration_ms = audio.duration_ms();
+        tracing::info!(duration_ms, overruns, truncated, "audio capture stopped");
+        *self.last.lock().unwrap_or_else(|err| err.into_inner()) = Some(audio);
+        Ok(CaptureSummary {
+            duration_ms,
+            overruns,
+            truncated,
+        })
+    }
+
+    /// Hands over the last finished capture, leaving none behind.
+    pub fn take(&self) -> Option<RecordedAudio> {
+        self.last
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+    }
+}
+
+/// The most samples a capture in `format` keeps.
+fn max_samples(format: CaptureFormat) -> usize {
+    MAX_CAPTURE_SECS * format.sample_rate as usize * usize::from(format.channels.max(1))
+}
+
+/// Appends `samples` to `recording` up to `limit` samples; returns whether
+/// the limit has been reached.
+fn append_capped(recording: &mut Vec<f32>, samples: &[f32], limit: usize) -> bool {
+    let room = limit.saturating_sub(recording.len());
+    recording.extend_from_slice(&samples[..samples.len().min(room)]);
+    recording.len() >= limit
+}
+
+/// Opens `source` on a dedicated thread (platform audio objects stay on the
+/// thread that created them) and reads until `stop` is set or the capture
+/// reaches [`MAX_CAPTURE_SECS`].
+fn spawn(source: CaptureSource) -> Result<ActiveCapture, String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let (opened, ready) = mpsc::channel();
+    let thread = {
+        let stop = Arc::clone(&stop);
+        thread::Builder::new()
+            .name("telepathy-capture".to_string())
+            .spawn(move || {
+                let mut stream = match backend::Stream::open(&source) {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        let _ = opened.send(Err(err.clone()));
+                        return Err(err);
+                    }
+                };
+                let format = stream.format();
+                let _ = opened.send(Ok(format));
+                let limit = max_samples(format);
+                let mut recording = Vec::new();
+                let mut full = false;
+                while !stop.load(Ordering::Acquire) && !full {
+                    stream.read(|chunk| full = append_capped(&mut recording, &chunk, limit))?;
+                    thread::sleep(POLL_INTERVAL);
+                }
+                if full {
+                    tracing::warn!(
+                        max_secs = MAX_CAPTURE_SECS,
+                        "audio capture reached its length limit; recording stopped"
+                    );
+                }
+                Ok((recording, stream.overruns()))
+            })
+            .map_err(|err| format!("Unable to start audio capture: {err}"))?
+    };
+    let format = ready
+        .recv()
+        .map_err(|_| "Audio capture stopped while starting.".to_string())??;
+    Ok(ActiveCapture {
+        stop,
+        format,
+        thread,
+    })
+}
+
+/// Whether system audio can be captured here, for the capability report.
+pub fn probe() -> Result<String, String> {
+    backend::probe()
+}
+
+#[tauri::command]
+pub async fn list_capture_sources() -> Result<CaptureSources, String> {
+    crate::run_blocking(|| {
+        Ok(CaptureSources {
+            devices: backend::list_devices()?,
+            applications: backend::list_applications()?,
+            per_application: backend::supports_applications(),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn start_capture(app: AppHandle, source: CaptureSource) -> Result<CaptureFormat, String> {
+    crate::run_blocking(move || {
+        let state = app.state::<AudioCapture>();
+        let mut active = state.active.lock().unwrap_or_else(|err| err.into_inner());
+        if active.is_some() {
+            return Err("Audio capture is already running.".to_string());
+        }
+        let capture = spawn(source).inspect_err(|err| {
+            tracing::warn!(%err, "audio capture failed to start");
+        })?;
+        let format = capture.format;
+        tracing::info!(
+            sample_rate = format.sample_rate,
+            channels = format.channels,
+            "audio capture started"
+        );
+        *active = Some(capture);
+        Ok(format)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn stop_capture(app: AppHandle) -> Result<CaptureSummary, String> {
+    crate::run_blocking(move || app.state::<AudioCapture>().stop()).await
+}
+
+/// The last finished capture's PCM, ready for `transcribe_local` or
+/// `transcribe_with_provider`; `None` once taken.
+#[tauri::command]
+pub fn take_capture(app: AppHandle) -> Option<RecordedAudio> {
+    app.state::<AudioCapture>().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_capped_stops_at_the_limit() {
+        let mut recording = Vec::new();
+        assert!(!append_capped(&mut recording, &[0.1, 0.2], 5));
+        assert!(append_capped(&mut recording, &[0.3, 0.4, 0.5, 0.6], 5));
+        assert_eq!(recording, [0.1, 0.2, 0.3, 0.4, 0.5]);
+        assert!(append_capped(&mut recording, &[0.7], 5));
+        assert_eq!(recording.len(), 5);
+    }
+
+    #[test]
+    fn max_samples_counts_every_channel() {
+        let mono = CaptureFormat {
+            sample_rate: 16_000,
+            channels: 1,
+        };
+        let stereo = CaptureFormat {
+            channels: 2,
+            ..mono
+        };
+        assert_eq!(max_samples(mono), 16_000 * MAX_CAPTURE_SECS);
+        assert_eq!(max_samples(stereo), 2 * max_samples(mono));
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::{
+    audio::RecordedAudio,
+    file_transcription::{self, Segment, PIECE_SECS},
+    locale, pipeline,
+    settings::Settings,
+    storage::Storage,
+    transcription::{
+        local::{Decoding, LocalWhisper},
+        models,
+        providers::{self, ProviderKind, ProviderSettings},
+    },
+    vocabulary::VocabularySettings,
+};
+
+/// Subtitles are transcribed in shorter pieces so cue timings stay close.
+const SUBTITLE_PIECE_SECS: usize = 30;
+/// Words per subtitle cue at most; a sentence end also ends a cue.
+const CUE_WORDS: usize = 12;
+
+#[derive(Parser)]
+#[command(
+    name = "telepathy",
+    version,
+    about = "Run without arguments to open the app."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Transcribe audio files without opening the app.
+    Transcribe(TranscribeArgs),
+}
+
+#[derive(Args)]
+struct TranscribeArgs {
+    /// WAV, MP3, or M4A files, transcribed one after another.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Plain text, or SubRip subtitles with timings.
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Write `<name>.txt` or `<name>.srt` per file into this directory
+    /// instead of printing to stdout.
+    #[arg(short, long, value_name = "DIR")]
+    output: Option<PathBuf>,
+    /// Use this in place of the provider selected in the app.
+    #[arg(short, long, value_enum)]
+    provider: Option<Engine>,
+    /// The provider's model, or the whisper.cpp model with `local`.
+    #[arg(short, long)]
+    model: Option<String>,
+    /// Spoken language as an ISO 639-1 code (e.g. `de`); defaults to the
+    /// app's language setting.
+    #[arg(short, long)]
+    language: Option<String>,
+    /// Skip the app's replacements.
+    #[arg(long)]
+    raw: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Srt,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Srt => "srt",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Engine {
+    #[value(name = "openai")]
+    OpenAi,
+    Deepgram,
+    #[value(name = "assemblyai")]
+    AssemblyAi,
+    /// whisper.cpp, with a model downloaded in the app.
+    Local,
+}
+
+/// How each piece is transcribed, resolved once from the arguments and the
+/// app's settings.
+enum Transcriber {
+    Cloud {
+        kind: ProviderKind,
+        settings: ProviderSettings,
+        vocabulary: VocabularySettings,
+        language: Option<String>,
+    },
+    Local {
+        whisper: LocalWhisper,
+        model: String,
+        path: PathBuf,
+        decoding: Decoding,
+    },
+}
+
+impl Transcriber {
+    /// The engine the app would use unless `--provider` names another: the
+    /// selected cloud provider, or the local model when none is selected.
+    fn new(args: &TranscribeArgs, settings: &Settings, storage: &Storage) -> Result<Self, String> {
+        let language = args.language.clone().or_else(|| {
+            locale::configured_language(&settings.locale)
+                .and_then(|tag| tag.split(['-', '_']).next().map(str::to_lowercase))
+        });
+        let kind = match args.provider {
+            Some(Engine::OpenAi) => Some(ProviderKind::OpenAi),
+            Some(Engine::Deepgram) => Some(ProviderKind::Deepgram),
+            Some(Engine::AssemblyAi) => Some(ProviderKind::AssemblyAi),
+            Some(Engine::Local) => None,
+            None => settings.provider.provider,
+        };
+        if let Some(kind) = kind {
+            return Ok(Self::Cloud {
+                kind,
+                settings: settings
+                    .provider
+                    .clone()
+                    .with_model(kind, args.model.clone()),
+                vocabulary: settings.vocabulary.clone(),
+                language,
+            });
+        }
+        let model = args
+            .model
+            .clone()
+            .unwrap_or_else(|| settings.local_transcription.model.clone());
+        let data_dir = storage
+            .data_dir
+            .as_deref()
+            .ok_or("No data directory for local models.".to_string())?;
+        Ok(Self::Local {
+            whisper: LocalWhisper::default(),
+            path: models::model_file(data_dir, &model)?,
+            model,
+            decoding: Decoding {
+                language,
+                threads: settings.local_transcription.threads,
+                prompt: settings.vocabulary.prompt(),
+            },
+        })
+    }
+
+    async fn transcribe(&self, piece: RecordedAudio) -> Result<(String, String), String> {
+        match self {
+            Self::Cloud {
+                kind,
+                settings,
+                vocabulary,
+                language,
+            } => {
+                let transcript =
+                    providers::request(&piece, *kind, settings, vocabulary, language.clone())
+                        .await?;
+                Ok((transcript.text, kind.name().to_string()))
+            }
+            Self::Local {
+                whisper,
+                model,
+                path,
+                decoding,
+            } => {
+                let transcript = whisper.transcribe(&piece, model, path, decoding.clone())?;
+                Ok((transcript.text, format!("whisper.cpp {model}")))
+            }
+        }
+    }
+}
+
+fn timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// SubRip cues, each piece split at sentence ends or every `CUE_WORDS`
+/// words. Providers don't time words, so cues share their piece's span in
+/// proportion to their length.
+fn srt(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    let mut index = 0;
+    for segment in segments {
+        let words: Vec<&str> = segment.text.split_whitespace().collect();
+        let weight = |words: &[&str]| -> u64 {
+            words
+                .iter()
+                .map(|word| word.chars().count() as u64 + 1)
+                .sum()
+        };
+        let total = weight(&words).max(1);
+        let span = segment.end_ms - segment.start_ms;
+        let (mut done, mut start) = (0, 0);
+        for (at, word) in words.iter().enumerate() {
+            let cue = &words[start..=at];
+            if cue.len() < CUE_WORDS && !word.ends_with(['.', '?', '!']) && at + 1 < words.len() {
+                continue;
+            }
+            let from = segment.start_ms + span * done / total;
+            done += weight(cue);
+            let to = segment.start_ms + span * done / total;
+            index += 1;
+            let _ = write!(
+                out,
+                "{index}\n{} --> {}\n{}\n\n",
+                timestamp(from),
+                timestamp(to),
+                cue.join(" ")
+            );
+            start = at + 1;
+        }
+    }
+    out
+}
+
+/// Decodes and transcribes one file, rendered as `args.format` asks.
+fn transcribe(
+    args: &TranscribeArgs,
+    settings: &Settings,
+    transcriber: &Transcriber,
+    path: &Path,
+) -> Result<String, String> {
+    if !file_transcription::is_supported(path) {
+        return Err(format!(
+            "Not a supported audio file ({}).",
+            file_transcription::EXTENSIONS.join(", ")
+        ));
+    }
+    let audio = file_transcription::decode(path, |_| {})?;
+    let piece_secs = match args.format {
+        Format::Text => PIECE_SECS,
+        Format::Srt => SUBTITLE_PIECE_SECS,
+    };
+    let mut transcript = tauri::async_runtime::block_on(file_transcription::transcribe_pieces(
+        &audio,
+        piece_secs,
+        |piece| transcriber.transcribe(piece),
+        |_| {},
+    ))?;
+    let process = |text: &str| {
+        if args.raw {
+            text.trim().to_string()
+        } else {
+            pipeline::process(settings, None, text)
+        }
+    };
+    Ok(match args.format {
+        Format::Text => process(&transcript.text()) + "\n",
+        Format::Srt => {
+            for segment in &mut transcript.segments {
+                segment.text = process(&segment.text);
+            }
+            srt(&transcript.segments)
+        }
+    })
+}
+
+/// Writes `output` beside the others in `dir`, named after `path`.
+fn write(dir: &Path, path: &Path, format: Format, output: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let mut name = path.file_stem().unwrap_or(path.as_os_str()).to_os_string();
+    name.push(".");
+    name.push(format.extension());
+    let target = dir.join(name);
+    fs::write(&target, output)
+        .map_err(|err| format!("Unable to write {}: {err}", target.display()))?;
+    Ok(target)
+}
+
+/// Release builds on Windows use the GUI subsystem and start without a
+/// console, so output would vanish; borrow the one the command was typed
+/// into.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // SAFETY: no preconditions; it fails harmlessly without a parent console.
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+/// Runs `telepathy transcribe …` without starting the app, reusing its
+/// settings and stored API keys. Returns `None` for any other command line,
+/// which opens the app as usual.
+pub fn run() -> Option<ExitCode> {
+    let argv: Vec<_> = std::env::args_os().collect();
+    if argv.get(1).is_none_or(|command| command != "transcribe") {
+        return None;
+    }
+    #[cfg(target_os = "windows")]
+    attach_console();
+    let Command::Transcribe(args) = Cli::parse_from(argv).command;
+
+    let storage = Storage::standalone();
+    let settings = Settings::read(storage.config_dir.as_deref());
+    let transcriber = match Transcriber::new(&args, &settings, &storage) {
+        Ok(transcriber) => transcriber,
+        Err(err) => {
+            eprintln!("telepathy: {err}");
+            return Some(ExitCode::FAILURE);
+        }
+    };
+    let mut failed = false;
+    for path in &args.files {
+        let result =
+            transcribe(&args, &settings, &transcriber, path).and_then(|output| {
+                match &args.output {
+                    Some(dir) => write(dir, path, args.format, &output)
+                        .map(|target| eprintln!("{} -> {}", path.display(), target.display())),
+                    None => {
+                        print!("{output}");
+                        Ok(())
+                    }
+                }
+            });
+        if let Err(err) = result {
+            eprintln!("{}: {err}", path.display());
+            failed = true;
+        }
+    }
+    Some(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u64, end_ms: u64, text: &str) -> Segment {
+        Segment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn timestamps_use_subrip_format() {
+        assert_eq!(timestamp(0), "00:00:00,000");
+        assert_eq!(timestamp(3_723_004), "01:02:03,004");
+        assert_eq!(timestamp(100 * 3_600_000), "100:00:00,000");
+    }
+
+    #[test]
+    fn cues_split_at_sentence_ends_in_proportion_to_length() {
+        let segments = [
+            segment(0, 10_000, "Hello there. How are you?"),
+            segment(30_000, 31_000, "Fine"),
+        ];
+        assert_eq!(
+            srt(&segments),
+            "1\n00:00:00,000 --> 00:00:05,000\nHello there.\n\n\
+             2\n00:00:05,000 --> 00:00:10,000\nHow are you?\n\n\
+             3\n00:00:30,000 --> 00:00:31,000\nFine\n\n"
+        );
+    }
+
+    #[test]
+    fn long_cues_are_cut_every_few_words() {
+        let text = vec!["word"; CUE_WORDS + 1].join(" ");
+        let srt = srt(&[segment(0, 1_000, &text)]);
+        let cues: Vec<&str> = srt.split("\n\n").filter(|cue| !cue.is_empty()).collect();
+        assert_eq!(cues.len(), 2);
+        assert!(cues[0].ends_with(&["word"; CUE_WORDS].join(" ")));
+        assert!(cues[1].starts_with("2\n"));
+        assert!(cues[1].ends_with("--> 00:00:01,000\nword"));
+    }
+
+    #[test]
+    fn empty_segments_have_no_cues() {
+        assert_eq!(srt(&[segment(0, 1_000, "  ")]), "");
+    }
+}
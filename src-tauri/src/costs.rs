@@ -0,0 +1,459 @@
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{history::History, settings::SettingsStore, transcription::providers::ProviderKind};
+
+/// What a provider charges, in US dollars.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Pricing {
+    pub per_audio_minute: f64,
+    /// Chat completions, for post-processing and translation.
+    pub per_million_input_tokens: f64,
+    pub per_million_output_tokens: f64,
+}
+
+impl Pricing {
+    fn cost(&self, totals: &Totals) -> f64 {
+        totals.audio_ms as f64 / 60_000.0 * self.per_audio_minute
+            + totals.input_tokens as f64 / 1_000_000.0 * self.per_million_input_tokens
+            + totals.output_tokens as f64 / 1_000_000.0 * self.per_million_output_tokens
+    }
+}
+
+/// What happens once a month's spending reaches `monthlyBudget`.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverBudget {
+    /// Emit `telepathy://budget-exceeded` and carry on.
+    Warn,
+    /// Also stop cloud transcription; files fall back to the local model.
+    Block,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CostSettings {
+    pub openai: Pricing,
+    pub deepgram: Pricing,
+    pub assemblyai: Pricing,
+    /// US dollars per calendar month; `None` for no budget.
+    pub monthly_budget: Option<f64>,
+    pub over_budget: OverBudget,
+}
+
+impl Default for CostSettings {
+    fn default() -> Self {
+        Self {
+            openai: Pricing {
+                per_audio_minute: 0.006,
+                per_million_input_tokens: 0.15,
+                per_million_output_tokens: 0.6,
+            },
+            deepgram: Pricing {
+                per_audio_minute: 0.0043,
+                ..Pricing::default()
+            },
+            assemblyai: Pricing {
+                per_audio_minute: 0.0025,
+                ..Pricing::default()
+            },
+            monthly_budget: None,
+            over_budget: OverBudget::Warn,
+        }
+    }
+}
+
+impl CostSettings {
+    fn pricing(&self, kind: ProviderKind) -> &Pricing {
+        match kind {
+            ProviderKind::OpenAi => &self.openai,
+            ProviderKind::Deepgram => &self.deepgram,
+            ProviderKind::AssemblyAi => &self.assemblyai,
+        }
+    }
+
+    fn spent(&self, rows: &[(ProviderKind, Totals)]) -> f64 {
+        rows.iter()
+            .map(|(kind, totals)| self.pricing(*kind).cost(totals))
+            .sum()
+    }
+}
+
+/// What one request sent to a provider.
+#[derive(Default)]
+pub struct Metered {
+    pub audio_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// A provider's totals for one month, as stored.
+#[derive(Default)]
+struct Totals {
+    requests: u64,
+    audio_ms: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// Payload of `telepathy://budget-exceeded`, emitted once when a month's
+/// spending first reaches the budget.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BudgetExceeded {
+    month: String,
+    spent: f64,
+    budget: f64,
+    blocked: bool,
+}
+
+/// The monthly per-provider table, in the history database.
+pub(crate) fn prepare(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS provider_usage (
+             month TEXT NOT NULL,
+             provider TEXT NOT NULL,
+             requests INTEGER NOT NULL DEFAULT 0,
+             audio_ms INTEGER NOT NULL DEFAULT 0,
+             input_tokens INTEGER NOT NULL DEFAULT 0,
+             output_tokens INTEGER NOT NULL DEFAULT 0,
+             PRIMARY KEY (month, provider)
+         );",
+    )
+}
+
+/// The provider's name in the table, as its settings value.
+fn key(kind: ProviderKind) -> &'static str {
+    match kind {
+        ProviderKind::OpenAi => "openAi",
+        ProviderKind::Deepgram => "deepgram",
+        ProviderKind::AssemblyAi => "assemblyAi",
+    }
+}
+
+fn this_month() -> String {
+    chrono::Local::now().format("%Y-%m").to_string()
+}
+
+impl History {
+    fn add_provider_usage(
+        &self,
+        month: &str,
+        kind: ProviderKind,
+        metered: &Metered,
+    ) -> Result<(), String> {
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO provider_usage (month, provider, requests, audio_ms, input_tokens, output_tokens)
+                 VALUES (?1, ?2, 1, ?3, ?4, ?5)
+                 ON CONFLICT (month, provider) DO UPDATE SET
+                     requests = requests + 1,
+                     audio_ms = audio_ms + excluded.audio_ms,
+                     input_tokens = input_tokens + excluded.input_tokens,
+                     output_tokens = output_tokens + excluded.output_tokens",
+                params![
+                    month,
+                    key(kind),
+                    metered.audio_ms,
+                    metered.input_tokens,
+                    metered.output_tokens,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// `month`'s totals for every provider, used or not, in
+    /// `ProviderKind::ALL` order.
+    fn provider_usage(&self, month: &str) -> Result<Vec<(ProviderKind, Totals)>, String> {
+        let mut rows: Vec<(ProviderKind, Totals)> = ProviderKind::ALL
+            .into_iter()
+            .map(|kind| (kind, Totals::default()))
+            .collect();
+        self.with(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT provider, requests, audio_ms, input_tokens, output_tokens
+                 FROM provider_usage
+                 WHERE month = ?1",
+            )?;
+            let mut query = statement.query(params![month])?;
+            while let Some(row) = query.next()? {
+                let provider: String = row.get(0)?;
+                let Some((_, totals)) = rows.iter_mut().find(|(kind, _)| key(*kind) == provider)
+                else {
+                    continue;
+                };
+                *totals = Totals {
+                    requests: row.get(1)?,
+                    audio_ms: row.get(2)?,
+                    input_tokens: row.get(3)?,
+                    output_tokens: row.get(4)?,
+                };
+            }
+            Ok(())
+        })?;
+        Ok(rows)
+    }
+}
+
+/// The `telepathy://budget-exceeded` payload when spending went from
+/// `before` to `after` across the budget, which happens once a month.
+fn exceeded(
+    settings: &CostSettings,
+    month: String,
+    before: f64,
+    after: f64,
+) -> Option<BudgetExceeded> {
+    settings
+        .monthly_budget
+        .filter(|budget| before < *budget && after >= *budget)
+        .map(|budget| BudgetExceeded {
+            month,
+            spent: after,
+            budget,
+            blocked: settings.over_budget == OverBudget::Block,
+        })
+}
+
+/// Adds a finished request to this month's totals, and emits
+/// `telepathy://budget-exceeded` if it took spending past the budget.
+/// Metering is best-effort: failures are logged, never returned.
+pub async fn record(app: &AppHandle, kind: ProviderKind, metered: Metered) {
+    let handle = app.clone();
+    let result = crate::run_blocking(move || {
+        let settings = handle.state::<SettingsStore>().snapshot().costs;
+        let history = handle.state::<History>();
+        let month = this_month();
+        let before = settings.spent(&history.provider_usage(&month)?);
+        history.add_provider_usage(&month, kind, &metered)?;
+        let after = settings.spent(&history.provider_usage(&month)?);
+        Ok(exceeded(&settings, month, before, after))
+    })
+    .await;
+    match result {
+        Ok(Some(exceeded)) => {
+            tracing::warn!(
+                spent = exceeded.spent,
+                budget = exceeded.budget,
+                blocked = exceeded.blocked,
+                "monthly cloud budget reached"
+            );
+            let _ = app.emit("telepathy://budget-exceeded", exceeded);
+        }
+        Ok(None) => {}
+        Err(err) => tracing::warn!(%err, provider = kind.name(), "provider usage not recorded"),
+    }
+}
+
+/// Whether cloud transcription may run: always, unless this month's
+/// spending has reached `costs.monthlyBudget` and `costs.overBudget` is
+/// `block`.
+pub async fn cloud_allowed(app: &AppHandle) -> bool {
+    let settings = app.state::<SettingsStore>().snapshot().costs;
+    let Some(budget) = settings.monthly_budget else {
+        return true;
+    };
+    if settings.over_budget != OverBudget::Block {
+        return true;
+    }
+    let handle = app.clone();
+    let spent = crate::run_blocking(move || {
+        let rows = handle.state::<History>().provider_usage(&this_month())?;
+        Ok(settings.spent(&rows))
+    })
+    .await;
+    match spent {
+        Ok(spent) => spent < budget,
+        Err(err) => {
+            // An unreadable meter shouldn't stop transcription.
+            tracing::warn!(%err, "monthly cloud spending unknown");
+            true
+        }
+    }
+}
+
+/// The error for cloud requests refused by `cloud_allowed`.
+pub fn blocked_error(app: &AppHandle) -> String {
+    let budget = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .costs
+        .monthly_budget
+        .unwrap_or_default();
+    format!("This month's cloud transcription budget of ${budget:.2} is used up.")
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCost {
+    pub provider: ProviderKind,
+    pub requests: u64,
+    pub audio_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// US dollars, at the current `costs` pricing.
+    pub cost: f64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostReport {
+    /// `YYYY-MM`, local time.
+    pub month: String,
+    pub providers: Vec<ProviderCost>,
+    pub total: f64,
+    pub budget: Option<f64>,
+    /// What's left of the budget; never negative.
+    pub remaining: Option<f64>,
+    pub over_budget: bool,
+    /// Whether cloud transcription is blocked now; only ever for the
+    /// current month.
+    pub blocked: bool,
+}
+
+/// `month`'s report from its per-provider `rows`. Only the `current` month
+/// can be blocked.
+fn report(
+    settings: &CostSettings,
+    month: String,
+    current: &str,
+    rows: Vec<(ProviderKind, Totals)>,
+) -> CostReport {
+    let total = settings.spent(&rows);
+    let over_budget = settings
+        .monthly_budget
+        .is_some_and(|budget| total >= budget);
+    CostReport {
+        blocked: over_budget && settings.over_budget == OverBudget::Block && month == current,
+        providers: rows
+            .into_iter()
+            .map(|(provider, totals)| ProviderCost {
+                provider,
+                cost: settings.pricing(provider).cost(&totals),
+                requests: totals.requests,
+                audio_ms: totals.audio_ms,
+                input_tokens: totals.input_tokens,
+                output_tokens: totals.output_tokens,
+            })
+            .collect(),
+        month,
+        total,
+        budget: settings.monthly_budget,
+        remaining: settings
+            .monthly_budget
+            .map(|budget| (budget - total).max(0.0)),
+        over_budget,
+    }
+}
+
+/// Audio minutes, tokens, and estimated cost per cloud provider for `month`
+/// (`YYYY-MM`), or the current month, against `costs.monthlyBudget`.
+#[tauri::command]
+pub async fn get_cost_report(app: AppHandle, month: Option<String>) -> Result<CostReport, String> {
+    let current = this_month();
+    let month = match month.map(|month| month.trim().to_string()) {
+        Some(month) => {
+            chrono::NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+                .map_err(|_| format!("{month} is not a month like 2026-01."))?;
+            month
+        }
+        None => current.clone(),
+    };
+    let settings = app.state::<SettingsStore>().snapshot().costs;
+    crate::run_blocking(move || {
+        let rows = app.state::<History>().provider_usage(&month)?;
+        Ok(report(&settings, month, &current, rows))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn totals(audio_ms: u64, input_tokens: u64, output_tokens: u64) -> Totals {
+        Totals {
+            requests: 1,
+            audio_ms,
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    fn budget(monthly_budget: f64, over_budget: OverBudget) -> CostSettings {
+        CostSettings {
+            monthly_budget: Some(monthly_budget),
+            over_budget,
+            ..CostSettings::default()
+        }
+    }
+
+    #[test]
+    fn pricing_charges_audio_minutes_and_tokens() {
+        let pricing = Pricing {
+            per_audio_minute: 0.006,
+            per_million_input_tokens: 0.15,
+            per_million_output_tokens: 0.6,
+        };
+        let cost = pricing.cost(&totals(90_000, 2_000_000, 500_000));
+        assert!((cost - (0.009 + 0.3 + 0.3)).abs() < 1e-9);
+        assert_eq!(pricing.cost(&Totals::default()), 0.0);
+    }
+
+    #[test]
+    fn spending_uses_each_providers_pricing() {
+        let settings = CostSettings::default();
+        let rows = [
+            (ProviderKind::OpenAi, totals(60_000, 0, 0)),
+            (ProviderKind::Deepgram, totals(120_000, 0, 0)),
+            (ProviderKind::AssemblyAi, totals(0, 0, 0)),
+        ];
+        assert!((settings.spent(&rows) - (0.006 + 2.0 * 0.0043)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn budget_is_exceeded_once_when_crossed() {
+        let settings = budget(1.0, OverBudget::Block);
+        let exceeded = exceeded(&settings, "2026-10".into(), 0.99, 1.0).unwrap();
+        assert_eq!(exceeded.spent, 1.0);
+        assert!(exceeded.blocked);
+        assert!(super::exceeded(&settings, "2026-10".into(), 1.0, 1.5).is_none());
+        assert!(super::exceeded(&settings, "2026-10".into(), 0.2, 0.5).is_none());
+        assert!(super::exceeded(&CostSettings::default(), "2026-10".into(), 0.0, 9.0).is_none());
+    }
+
+    #[test]
+    fn report_blocks_only_the_current_month() {
+        // 200 OpenAI minutes at $0.006 is $1.20.
+        let rows = || vec![(ProviderKind::OpenAi, totals(12_000_000, 0, 0))];
+        let settings = budget(1.0, OverBudget::Block);
+
+        let current = report(&settings, "2026-10".into(), "2026-10", rows());
+        assert!((current.total - 1.2).abs() < 1e-9);
+        assert!(current.over_budget && current.blocked);
+        assert_eq!(current.remaining, Some(0.0));
+        assert_eq!(current.providers[0].requests, 1);
+
+        let past = report(&settings, "2026-09".into(), "2026-10", rows());
+        assert!(past.over_budget && !past.blocked);
+
+        let warned = report(
+            &budget(1.0, OverBudget::Warn),
+            "2026-10".into(),
+            "2026-10",
+            rows(),
+        );
+        assert!(warned.over_budget && !warned.blocked);
+    }
+
+    #[test]
+    fn report_without_a_budget_is_never_over() {
+        let rows = vec![(ProviderKind::Deepgram, totals(60_000, 0, 0))];
+        let report = report(&CostSettings::default(), "2026-10".into(), "2026-10", rows);
+        assert_eq!(report.budget, None);
+        assert_eq!(report.remaining, None);
+        assert!(!report.over_budget && !report.blocked);
+        assert!((report.total - 0.0043).abs() < 1e-9);
+    }
+}
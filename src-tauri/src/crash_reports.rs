@@ -0,0 +1,179 @@
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{settings::SettingsStore, storage::Storage};
+
+const REPORT_DIR: &str = "crash-reports";
+
+/// Read by the panic hook, which can't reach managed state safely.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CrashReportSettings {
+    /// Writes a report when the backend panics. Off unless the user opts in;
+    /// nothing is sent until they submit a report themselves.
+    pub enabled: bool,
+    /// Where `submit_crash_report` posts reports. Unset means reports can
+    /// only be reviewed and dismissed.
+    pub endpoint: Option<String>,
+}
+
+/// A backend panic, as written to disk and shown for review.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: String,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic.".to_string())
+}
+
+fn write_report(dir: &Path, info: &PanicHookInfo<'_>) -> Result<(), String> {
+    let now = chrono::Local::now();
+    let report = CrashReport {
+        id: format!("crash-{}", now.format("%Y%m%d-%H%M%S%3f")),
+        created_at: now.to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: thread::current().name().unwrap_or("unnamed").to_string(),
+        message: panic_message(info),
+        location: info.location().map(|location| location.to_string()),
+        backtrace: Backtrace::force_capture().to_string(),
+    };
+    fs::create_dir_all(dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let raw = serde_json::to_string_pretty(&report)
+        .map_err(|err| format!("Unable to serialize the crash report: {err}"))?;
+    let path = dir.join(format!("{}.json", report.id));
+    fs::write(&path, raw).map_err(|err| format!("Unable to write {}: {err}", path.display()))
+}
+
+/// Installs a panic hook that logs every panic and, when enabled, also
+/// writes a crash report. The previous hook (stderr output) still runs.
+pub fn install(app: &AppHandle) {
+    configure(&app.state::<SettingsStore>().snapshot().crash_reports);
+    let dir = Storage::data_dir(app).map(|dir| dir.join(REPORT_DIR));
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        tracing::error!(
+            message = %panic_message(info),
+            location = ?info.location().map(|location| location.to_string()),
+            "backend panicked"
+        );
+        if ENABLED.load(Ordering::Relaxed) {
+            if let Some(dir) = dir.as_deref() {
+                let _ = write_report(dir, info);
+            }
+        }
+        previous(info);
+    }));
+}
+
+pub fn configure(settings: &CrashReportSettings) {
+    ENABLED.store(settings.enabled, Ordering::Relaxed);
+}
+
+fn report_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Storage::data_dir(app)
+        .map(|dir| dir.join(REPORT_DIR))
+        .ok_or("App data directory is unavailable.".to_string())
+}
+
+/// Report ids come from the webview, so they must name a file in the
+/// report dir and nothing else.
+fn report_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid crash report id {id:?}."));
+    }
+    Ok(report_dir(app)?.join(format!("{id}.json")))
+}
+
+fn read_report(path: &Path) -> Result<CrashReport, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read {}: {err}", path.display()))?;
+    serde_json::from_str(&raw).map_err(|err| format!("Invalid crash report: {err}"))
+}
+
+/// Reports left by earlier crashes, oldest first, for the user to review
+/// and then submit or dismiss.
+#[tauri::command]
+pub async fn get_pending_crash_reports(app: AppHandle) -> Result<Vec<CrashReport>, String> {
+    crate::run_blocking(move || {
+        let dir = report_dir(&app)?;
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+        let mut reports: Vec<CrashReport> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| read_report(&path).ok())
+            .collect();
+        reports.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(reports)
+    })
+    .await
+}
+
+/// Posts the report to `crashReports.endpoint` and removes it once accepted.
+#[tauri::command]
+pub async fn submit_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    let endpoint = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .crash_reports
+        .endpoint
+        .ok_or("No crash report endpoint is configured.".to_string())?;
+    let path = report_path(&app, &id)?;
+    let report = {
+        let path = path.clone();
+        crate::run_blocking(move || read_report(&path)).await?
+    };
+    let response = reqwest::Client::new()
+        .post(&endpoint)
+        .json(&report)
+        .send()
+        .await
+        .map_err(|err| format!("Crash report upload failed: {err}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Crash report upload responded with {status}."));
+    }
+    tracing::info!(id = %id, "crash report submitted");
+    crate::run_blocking(move || {
+        fs::remove_file(&path).map_err(|err| format!("Unable to remove {}: {err}", path.display()))
+    })
+    .await
+}
+
+/// Deletes a report without sending it.
+#[tauri::command]
+pub async fn dismiss_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    crate::run_blocking(move || {
+        let path = report_path(&app, &id)?;
+        fs::remove_file(&path).map_err(|err| format!("Unable to remove {}: {err}", path.display()))
+    })
+    .await
+}
@@ -0,0 +1,138 @@
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::{
+    file_transcription, panels, pipeline,
+    triggers::{self, TriggerAction, TriggerSource},
+};
+
+const SCHEME: &str = "telepathy://";
+
+/// Payload for `telepathy://deep-link-error`, so failures from links opened
+/// by other apps are still visible in the overlay.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeepLinkError {
+    url: String,
+    message: String,
+}
+
+/// Whether a launch argument is a `telepathy://` link rather than a flag or
+/// a path.
+pub fn is_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+}
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn report_error(app: &AppHandle, url: &Url, message: String) {
+    let _ = app.emit(
+        "telepathy://deep-link-error",
+        DeepLinkError {
+            url: url.to_string(),
+            message,
+        },
+    );
+}
+
+/// Routes `telepathy://<action>?…` links. Supported:
+/// - `telepathy://process?text=…&profile=…` runs text through the pipeline and pastes it.
+/// - `telepathy://start-dictation`, `stop-dictation`, `toggle-dictation`, and
+///   `cancel-dictation` drive the dictation state machine like the tray does.
+/// - `telepathy://transcribe-file?path=…` transcribes an audio file.
+/// - `telepathy://settings[/<section>]` and `telepathy://history` open those windows.
+fn handle(app: &AppHandle, url: Url) {
+    tracing::info!(
+        action = url.host_str().unwrap_or_default(),
+        "deep link opened"
+    );
+    match url.host_str() {
+        Some("start-dictation") => triggers::start(app, TriggerSource::DeepLink),
+        Some("toggle-dictation") => triggers::toggle(app, TriggerSource::DeepLink),
+        Some("stop-dictation") => {
+            triggers::trigger(app, TriggerAction::Stop, TriggerSource::DeepLink)
+        }
+        Some("cancel-dictation") => {
+            triggers::trigger(app, TriggerAction::Cancel, TriggerSource::DeepLink)
+        }
+        Some("transcribe-file") => {
+            let Some(path) = query_param(&url, "path").filter(|path| !path.is_empty()) else {
+                report_error(app, &url, "Missing `path` parameter.".to_string());
+                return;
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = file_transcription::run(&app, path.into()).await {
+                    report_error(&app, &url, err);
+                }
+            });
+        }
+        Some("settings") => {
+            let section = url
+                .path_segments()
+                .and_then(|mut segments| segments.next())
+                .filter(|section| !section.is_empty());
+            if section.is_some_and(|section| {
+                !section
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+            }) {
+                report_error(app, &url, "Unknown settings section.".to_string());
+                return;
+            }
+            if let Err(err) = panels::open_settings(app, section) {
+                report_error(app, &url, err);
+            }
+        }
+        Some("history") => {
+            if let Err(err) = panels::open_history(app) {
+                report_error(app, &url, err);
+            }
+        }
+        Some("process") => {
+            let Some(text) = query_param(&url, "text").filter(|text| !text.trim().is_empty())
+            else {
+                report_error(app, &url, "Missing `text` parameter.".to_string());
+                return;
+            };
+            let profile = query_param(&url, "profile");
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) =
+                    pipeline::process_and_deliver(&app, &text, profile.as_deref(), None).await
+                {
+                    report_error(&app, &url, err);
+                }
+            });
+        }
+        _ => report_error(app, &url, "Unsupported Telepathy link.".to_string()),
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    // Linux and Windows dev builds are not installed, so the scheme has to be
+    // registered at runtime; bundles register it through their manifest.
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    let _ = app.deep_link().register_all();
+
+    let app_handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle(&app_handle, url);
+        }
+    });
+
+    // Links that launched the app; Windows/Linux pass them as arguments
+    // while macOS delivers them through the open-url event above.
+    #[cfg(not(target_os = "macos"))]
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            handle(app, url);
+        }
+    }
+}
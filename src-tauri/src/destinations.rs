@@ -0,0 +1,831 @@
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::format::{Item, StrftimeItems};
+use tauri::{AppHandle, Manager};
+use tracing::Instrument;
+
+use crate::{
+    analytics::Analytics,
+    benchmark::{self, LatencyBenchmark},
+    dictation::{DictationEvent, DictationMachine},
+    email::{self, EmailSettings},
+    focus,
+    history::{History, NewEntry},
+    injection::{self, Injector, PasteBlock, PasteTimings},
+    jobs::{self, JobPriority},
+    notifications, pipeline, postprocess, recordings,
+    secrets::{self, SecretKey},
+    settings::{Settings, SettingsStore},
+    transcript_stream,
+    translation::{self, PendingTranslation},
+    voice_commands,
+    webhooks::{self, TranscriptPayload, TranscriptSource},
+};
+
+const DEFAULT_DAILY_NOTE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_ENTRY_TEMPLATE: &str = "- {{time}} {{text}}";
+const TITLE_MAX_CHARS: usize = 60;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DestinationKind {
+    #[default]
+    Paste,
+    ObsidianDailyNote,
+    AppleNote,
+    ChatWebhook,
+    Email,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DestinationSettings {
+    /// Destination used when the hotkey is held with the extra Alt modifier;
+    /// a paste, like the plain hotkey, until another one is chosen.
+    pub modifier_destination: DestinationKind,
+    pub obsidian: ObsidianSettings,
+    pub apple_notes: AppleNotesSettings,
+    pub chat_webhook: ChatWebhookSettings,
+    pub email: EmailSettings,
+}
+
+impl Default for DestinationSettings {
+    fn default() -> Self {
+        Self {
+            modifier_destination: DestinationKind::Paste,
+            obsidian: ObsidianSettings::default(),
+            apple_notes: AppleNotesSettings::default(),
+            chat_webhook: ChatWebhookSettings::default(),
+            email: EmailSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ObsidianSettings {
+    pub vault_path: Option<PathBuf>,
+    /// Folder inside the vault holding daily notes, e.g. `Daily`.
+    pub daily_folder: String,
+    /// chrono format string for the daily note file name.
+    pub date_format: String,
+    /// Entry template; supports `{{text}}`, `{{time}}`, and `{{date}}`.
+    pub template: String,
+}
+
+impl Default for ObsidianSettings {
+    fn default() -> Self {
+        Self {
+            vault_path: None,
+            daily_folder: String::new(),
+            date_format: DEFAULT_DAILY_NOTE_FORMAT.to_string(),
+            template: DEFAULT_ENTRY_TEMPLATE.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppleNotesSettings {
+    /// Notes folder to create entries in; the default folder when unset.
+    pub folder: Option<String>,
+}
+
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChatService {
+    #[default]
+    Slack,
+    Discord,
+}
+
+impl ChatService {
+    /// Largest message body the service accepts in a single webhook call.
+    fn max_message_chars(self) -> usize {
+        match self {
+            ChatService::Slack => 40_000,
+            ChatService::Discord => 2_000,
+        }
+    }
+
+    fn payload(self, text: &str) -> serde_json::Value {
+        match self {
+            ChatService::Slack => serde_json::json!({ "text": text }),
+            ChatService::Discord => serde_json::json!({ "content": text }),
+        }
+    }
+}
+
+/// The webhook URL itself is a secret (`SecretKey::ChatWebhookUrl`).
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChatWebhookSettings {
+    pub service: ChatService,
+}
+
+/// Destination chosen by a modifier hotkey for the dictation in progress.
+#[derive(Default)]
+pub struct PendingDestination(Mutex<Option<DestinationKind>>);
+
+impl PendingDestination {
+    pub fn set(&self, destination: Option<DestinationKind>) {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner()) = destination;
+    }
+
+    fn take(&self) -> Option<DestinationKind> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).take()
+    }
+}
+
+/// The most recent finished transcript, for `paste_last_transcript`.
+#[derive(Default)]
+pub struct LastTranscript(Mutex<Option<String>>);
+
+impl LastTranscript {
+    fn set(&self, text: &str) {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner()) = Some(text.to_string());
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryResult {
+    pub destination: DestinationKind,
+    pub pasted: bool,
+    /// Where the transcript ended up, e.g. the daily note path.
+    pub location: Option<String>,
+    pub paste_timings: Option<PasteTimings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paste_blocked: Option<PasteBlock>,
+}
+
+/// First line of a transcript, shortened for note titles and email subjects.
+pub fn title_from(text: &str) -> String {
+    text.lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(TITLE_MAX_CHARS)
+        .collect()
+}
+
+fn render_template(template: &str, text: &str, now: &chrono::DateTime<chrono::Local>) -> String {
+    template
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &now.format("%H:%M").to_string())
+        .replace("{{text}}", text)
+}
+
+/// Refuses settings that route dictations to the Obsidian daily note, from
+/// the modifier chord or a profile, while no vault is configured; every such
+/// dictation would otherwise fail.
+pub fn validate(settings: &Settings) -> Result<(), String> {
+    if settings.destinations.obsidian.vault_path.is_some() {
+        return Ok(());
+    }
+    let modifier = settings.destinations.modifier_destination == DestinationKind::ObsidianDailyNote;
+    let profile = settings
+        .profiles
+        .iter()
+        .find(|profile| profile.destination == DestinationKind::ObsidianDailyNote);
+    match (modifier, profile) {
+        (true, _) => Err(
+            "Set the Obsidian vault path before choosing the daily note as the modifier destination."
+                .to_string(),
+        ),
+        (false, Some(profile)) => Err(format!(
+            "Set the Obsidian vault path before sending the \"{}\" profile to the daily note.",
+            profile.name
+        )),
+        (false, None) => Ok(()),
+    }
+}
+
+/// A path from the settings, checked to stay inside the vault: absolute
+/// paths and `..` are refused.
+fn vault_relative(path: &str, what: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    if path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(path.to_path_buf())
+    } else {
+        Err(format!("The {what} must be inside the Obsidian vault."))
+    }
+}
+
+/// The daily note's file name for `now`. An invalid `date_format` is an
+/// error here; `format!` would panic on it.
+fn daily_note_name(
+    date_format: &str,
+    now: &chrono::DateTime<chrono::Local>,
+) -> Result<String, String> {
+    let invalid = || format!("\"{date_format}\" is not a valid daily note date format.");
+    let items: Vec<Item> = StrftimeItems::new(date_format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(invalid());
+    }
+    let mut name = String::new();
+    write!(name, "{}", now.format_with_items(items.into_iter())).map_err(|_| invalid())?;
+    name.push_str(".md");
+    Ok(name)
+}
+
+fn append_to_obsidian_daily_note(
+    settings: &ObsidianSettings,
+    text: &str,
+) -> Result<String, String> {
+    let vault = settings
+        .vault_path
+        .as_ref()
+        .ok_or("Obsidian vault path is not configured.".to_string())?;
+    if !vault.is_dir() {
+        return Err(format!("Obsidian vault not found at {}.", vault.display()));
+    }
+
+    let now = chrono::Local::now();
+    let note = vault
+        .join(vault_relative(&settings.daily_folder, "daily note folder")?)
+        .join(vault_relative(
+            &daily_note_name(&settings.date_format, &now)?,
+            "daily note name",
+        )?);
+    if let Some(folder) = note.parent() {
+        fs::create_dir_all(folder)
+            .map_err(|err| format!("Unable to create daily note folder: {err}"))?;
+    }
+
+    let needs_newline = fs::read(&note)
+        .map(|existing| !existing.is_empty() && !existing.ends_with(b"\n"))
+        .unwrap_or(false);
+    let mut entry = render_template(&settings.template, text, &now);
+    if needs_newline {
+        entry.insert(0, '\n');
+    }
+    entry.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&note)
+        .map_err(|err| format!("Unable to open daily note: {err}"))?;
+    file.write_all(entry.as_bytes())
+        .map_err(|err| format!("Unable to append to daily note: {err}"))?;
+    Ok(note.display().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+fn create_apple_note(settings: &AppleNotesSettings, text: &str) -> Result<String, String> {
+    #[cfg(target_os = "macos")]
+    {
+        let title = title_from(text);
+        // Text is passed through argv so transcripts never need AppleScript escaping.
+        let target = if settings.folder.is_some() {
+            "at folder (item 3 of argv) "
+        } else {
+            ""
+        };
+        let script = format!(
+            "tell application \"Notes\" to make new note {target}with properties {{name:item 1 of argv, body:item 2 of argv}}"
+        );
+        let mut command = Command::new("osascript");
+        command
+            .args(["-e", "on run argv", "-e", &script, "-e", "end run"])
+            .arg(&title)
+            .arg(escape_html(text));
+        if let Some(folder) = &settings.folder {
+            command.arg(folder);
+        }
+        let output = command
+            .output()
+            .map_err(|err| format!("Unable to run osascript for Apple Notes: {err}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(format!("Apple Notes rejected the new note: {stderr}"));
+        }
+        Ok(title)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (settings, text);
+        Err("Apple Notes is only available on macOS.".to_string())
+    }
+}
+
+/// Splits `text` into pieces of at most `max_chars`, preferring whitespace
+/// breaks. Pieces never start or end with whitespace.
+fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    fn finish(chunks: &mut Vec<String>, current: &mut String) {
+        if !current.is_empty() {
+            chunks.push(current.trim_end().to_string());
+            current.clear();
+        }
+    }
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for word in text.split_inclusive(char::is_whitespace) {
+        if current.chars().count() + word.trim_end().chars().count() > max_chars {
+            finish(&mut chunks, &mut current);
+        }
+        for ch in word.chars() {
+            if current.chars().count() == max_chars {
+                finish(&mut chunks, &mut current);
+            }
+            if !(current.is_empty() && ch.is_whitespace()) {
+                current.push(ch);
+            }
+        }
+    }
+    finish(&mut chunks, &mut current);
+    chunks
+}
+
+async fn post_to_chat_webhook(settings: &ChatWebhookSettings, text: &str) -> Result<(), String> {
+    let url = crate::run_blocking(|| secrets::read_secret(SecretKey::ChatWebhookUrl))
+        .await?
+        .ok_or("Chat webhook URL is not configured.".to_string())?;
+    let client = reqwest::Client::new();
+    for chunk in split_message(text, settings.service.max_message_chars()) {
+        let response = client
+            .post(&url)
+            .json(&settings.service.payload(&chunk))
+            .send()
+            .await
+            .map_err(|err| format!("Chat webhook request failed: {err}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!(
+                "Chat webhook responded with {status}: {}",
+                body.trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub async fn deliver(
+    app: &AppHandle,
+    settings: &Settings,
+    destination: DestinationKind,
+    text: &str,
+) -> Result<DeliveryResult, String> {
+    match destination {
+        DestinationKind::Paste => {
+            let handle = app.clone();
+            let text = text.to_string();
+            let options = settings.paste.clone();
+            let result = jobs::run(app, JobPriority::Live, "paste", move |_| {
+                let options = options.for_target(&handle);
+                injection::paste(&handle, &text, &options)
+            })
+            .await?;
+            Ok(DeliveryResult {
+                destination,
+                pasted: result.pasted,
+                location: None,
+                paste_timings: Some(result.timings),
+                paste_blocked: result.blocked,
+            })
+        }
+        DestinationKind::ObsidianDailyNote => {
+            let obsidian = settings.destinations.obsidian.clone();
+            let text = text.to_string();
+            let note = crate::run_blocking(move || append_to_obsidian_daily_note(&obsidian, &text))
+                .await?;
+            Ok(DeliveryResult {
+                destination,
+                pasted: false,
+                location: Some(note),
+                paste_timings: None,
+                paste_blocked: None,
+            })
+        }
+        DestinationKind::AppleNote => {
+            let apple_notes = settings.destinations.apple_notes.clone();
+            let text = text.to_string();
+            let title = crate::run_blocking(move || create_apple_note(&apple_notes, &text)).await?;
+            Ok(DeliveryResult {
+                destination,
+                pasted: false,
+                location: Some(title),
+                paste_timings: None,
+                paste_blocked: None,
+            })
+        }
+        DestinationKind::Email => {
+            let app = app.clone();
+            let email = settings.destinations.email.clone();
+            let text = text.to_string();
+            let result =
+                crate::run_blocking(move || email::compose(&app, &email, None, text)).await?;
+            Ok(DeliveryResult {
+                destination,
+                pasted: false,
+                location: result.truncated.then(|| "clipboard".to_string()),
+                paste_timings: None,
+                paste_blocked: None,
+            })
+        }
+        DestinationKind::ChatWebhook => {
+            post_to_chat_webhook(&settings.destinations.chat_webhook, text).await?;
+            Ok(DeliveryResult {
+                destination,
+                pasted: false,
+                location: None,
+                paste_timings: None,
+                paste_blocked: None,
+            })
+        }
+    }
+}
+
+/// Turns the recognized text into what gets delivered: replacements and
+/// voice commands, then, outside benchmarks, post-processing and a pending
+/// translation. "Scratch that" deletes the previous paste here.
+async fn prepare_text(
+    app: &AppHandle,
+    settings: &Settings,
+    destination: DestinationKind,
+    profile: Option<&str>,
+    preset: Option<&str>,
+    raw: &str,
+    benchmarking: bool,
+) -> String {
+    let translate = app.state::<PendingTranslation>().take();
+    let commands = voice_commands::interpret(
+        &settings.voice_commands,
+        &pipeline::process(settings, profile, raw),
+    );
+    let mut text = commands.text;
+    if benchmarking {
+        return text;
+    }
+    if commands.erase_previous && destination == DestinationKind::Paste {
+        let handle = app.clone();
+        match crate::run_blocking(move || handle.state::<Injector>().erase_last()).await {
+            Ok(erased) => tracing::info!(erased, "previous dictation deleted"),
+            Err(err) => tracing::warn!(%err, "previous dictation not deleted"),
+        }
+    }
+    text = postprocess::rewrite(app, &settings.postprocess, preset, text).await;
+    if translate {
+        // A failed translation still delivers what was said.
+        match translation::translate(app, &text, &settings.translation).await {
+            Ok(translated) => text = translated,
+            Err(err) => tracing::warn!(%err, "transcript not translated"),
+        }
+    }
+    text
+}
+
+/// The app a paste will go to, looked up while it still has focus.
+async fn paste_target_app(app: &AppHandle) -> Option<String> {
+    let handle = app.clone();
+    crate::run_blocking(move || focus::paste_target(&handle))
+        .await
+        .ok()
+        .and_then(|target| target.app)
+        .and_then(|app| app.name.or(app.id))
+}
+
+/// How a dictation ended, for analytics, history, webhooks, and scripts.
+struct Outcome {
+    /// The transcript as recognized.
+    raw: String,
+    text: String,
+    destination: DestinationKind,
+    target_app: Option<String>,
+    /// As the frontend reported it.
+    provider: Option<String>,
+    error: Option<String>,
+    /// Reached its destination; a paste that fell back to the clipboard
+    /// still needs recovering.
+    delivered: bool,
+    clipboard_fallback: bool,
+}
+
+/// Saves the dictation to history, with its recording when those are kept.
+fn save_to_history(app: &AppHandle, settings: &Settings, entry: &NewEntry) -> Option<i64> {
+    match app.state::<History>().record(entry) {
+        Ok(id) => {
+            recordings::save(app, &settings.recordings, id);
+            Some(id)
+        }
+        Err(err) => {
+            tracing::warn!(%err, "transcript not saved to history");
+            None
+        }
+    }
+}
+
+/// Records a finished dictation in analytics and history, sends it to the
+/// webhooks and the transcript stream, and notifies about a clipboard
+/// fallback. All of it is best-effort file and database work, so it runs on
+/// the blocking pool and never fails the dictation.
+async fn record_finished(app: &AppHandle, settings: Settings, outcome: Outcome) {
+    let app = app.clone();
+    let result = crate::run_blocking(move || {
+        let failed = outcome.error.is_some();
+        let session = app
+            .state::<Analytics>()
+            .dictation_finished(&outcome.text, !failed);
+        // Counts and timings only, so they're kept even with history off.
+        if let Err(err) = app.state::<History>().record_usage(&session.usage) {
+            tracing::warn!(%err, "dictation usage not recorded");
+        }
+        let latency_ms = session.latency_ms.filter(|_| !failed);
+        let provider = outcome.provider.clone().or(session.provider);
+        let original_text = (outcome.raw.trim() != outcome.text).then_some(outcome.raw.as_str());
+        let history_id = if settings.history.enabled {
+            let entry = NewEntry {
+                text: &outcome.text,
+                original_text,
+                duration_ms: session.recording_ms,
+                app: outcome.target_app.as_deref(),
+                provider: provider.as_deref(),
+                latency_ms,
+                destination: outcome.destination,
+                delivered: outcome.delivered,
+                error: outcome.error.as_deref(),
+                source_file: None,
+            };
+            save_to_history(&app, &settings, &entry)
+        } else {
+            None
+        };
+        webhooks::send(
+            &app,
+            &settings.webhooks,
+            TranscriptPayload {
+                original_text: original_text.map(str::to_string),
+                app: outcome.target_app.clone(),
+                provider,
+                destination: Some(outcome.destination),
+                delivered: outcome.delivered,
+                history_id,
+                ..TranscriptPayload::new(TranscriptSource::Dictation, outcome.text.clone())
+            }
+            .with_timings(session.recording_ms, latency_ms),
+        );
+        if outcome.clipboard_fallback {
+            if let Err(err) = notifications::notify_clipboard_fallback(
+                &app,
+                &settings.notifications,
+                &outcome.text,
+            ) {
+                tracing::warn!(%err, "clipboard fallback notification failed");
+            }
+        }
+        // Script consumers are best-effort; they never fail the dictation itself.
+        let _ = transcript_stream::write_final(
+            &settings.transcript_stream,
+            &outcome.text,
+            outcome.provider.as_deref(),
+            outcome.destination,
+            !failed,
+        );
+        Ok(())
+    })
+    .await;
+    if let Err(err) = result {
+        tracing::warn!(%err, "finished dictation not recorded");
+    }
+}
+
+/// Sends a finished transcript to its destination. An explicit `destination`
+/// wins, then a modifier-hotkey choice, then the profile's default. `preset`
+/// picks the post-processing preset for this transcript; an empty name
+/// skips post-processing.
+#[tauri::command]
+pub async fn deliver_transcript(
+    app: AppHandle,
+    text: String,
+    destination: Option<DestinationKind>,
+    profile: Option<String>,
+    provider: Option<String>,
+    preset: Option<String>,
+) -> Result<DeliveryResult, String> {
+    let span = app.state::<DictationMachine>().span();
+    async move {
+        let analytics = app.state::<Analytics>();
+        let benchmarking = app.state::<LatencyBenchmark>().is_running();
+        if !benchmarking {
+            analytics.first_partial(provider.as_deref());
+        }
+        let settings = app.state::<SettingsStore>().snapshot();
+        let destination = destination
+            .or_else(|| app.state::<PendingDestination>().take())
+            .or_else(|| {
+                settings
+                    .profile(profile.as_deref())
+                    .map(|profile| profile.destination)
+            })
+            .unwrap_or_default();
+
+        // Phase tracking is advisory here: text may arrive outside a recording.
+        let machine = app.state::<DictationMachine>();
+        let _ = machine.handle(&app, DictationEvent::TranscriptReady, None);
+        analytics.transcript_ready();
+        let raw = text;
+        let text = prepare_text(
+            &app,
+            &settings,
+            destination,
+            profile.as_deref(),
+            preset.as_deref(),
+            &raw,
+            benchmarking,
+        )
+        .await;
+        if !benchmarking && !text.trim().is_empty() {
+            app.state::<LastTranscript>().set(&text);
+        }
+        let _ = machine.handle(&app, DictationEvent::Deliver, None);
+        let target_app = if destination == DestinationKind::Paste && !benchmarking {
+            paste_target_app(&app).await
+        } else {
+            None
+        };
+        let result = if benchmarking {
+            benchmark::deliver_dry_run(&app, &text).await
+        } else {
+            deliver(&app, &settings, destination, &text).await
+        };
+        match &result {
+            Ok(delivery) => tracing::info!(
+                destination = ?delivery.destination,
+                pasted = delivery.pasted,
+                chars = text.chars().count(),
+                "transcript delivered"
+            ),
+            Err(err) => tracing::warn!(destination = ?destination, %err, "transcript delivery failed"),
+        }
+        let _ = match &result {
+            Ok(_) => machine.handle(&app, DictationEvent::Finished, None),
+            Err(err) => machine.handle(&app, DictationEvent::Fail, Some(err.clone())),
+        };
+        if !benchmarking {
+            let outcome = Outcome {
+                delivered: result
+                    .as_ref()
+                    .is_ok_and(|delivery| delivery.pasted || destination != DestinationKind::Paste),
+                clipboard_fallback: matches!(&result, Ok(delivery) if delivery.destination == DestinationKind::Paste && !delivery.pasted),
+                error: result.as_ref().err().cloned(),
+                raw,
+                text,
+                destination,
+                target_app,
+                provider,
+            };
+            record_finished(&app, settings, outcome).await;
+        }
+        result
+    }
+    .instrument(span)
+    .await
+}
+
+/// Pastes the most recent transcript into the focused app again, for when
+/// focus moved away mid-dictation. Falls back to the newest history entry
+/// after a restart.
+pub async fn paste_last(app: &AppHandle) -> Result<DeliveryResult, String> {
+    let text = match app.state::<LastTranscript>().get() {
+        Some(text) => text,
+        None => {
+            let handle = app.clone();
+            crate::run_blocking(move || handle.state::<History>().latest_text())
+                .await?
+                .ok_or("There is no transcript to paste yet.".to_string())?
+        }
+    };
+    let settings = app.state::<SettingsStore>().snapshot();
+    let result = deliver(app, &settings, DestinationKind::Paste, &text).await;
+    match &result {
+        Ok(delivery) => tracing::info!(
+            pasted = delivery.pasted,
+            chars = text.chars().count(),
+            "last transcript pasted again"
+        ),
+        Err(err) => tracing::warn!(%err, "re-pasting the last transcript failed"),
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn paste_last_transcript(app: AppHandle) -> Result<DeliveryResult, String> {
+    paste_last(&app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn new_year() -> chrono::DateTime<chrono::Local> {
+        chrono::Local
+            .with_ymd_and_hms(2026, 1, 2, 9, 30, 0)
+            .single()
+            .expect("unambiguous local time")
+    }
+
+    #[test]
+    fn split_message_breaks_on_whitespace() {
+        assert_eq!(
+            split_message("the quick brown fox", 10),
+            ["the quick", "brown fox"]
+        );
+        assert_eq!(split_message("short", 10), ["short"]);
+        assert!(split_message("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn split_message_cuts_words_longer_than_a_piece() {
+        assert_eq!(
+            split_message("abcdefghij xy", 4),
+            ["abcd", "efgh", "ij", "xy"]
+        );
+        assert_eq!(split_message("ééééé", 2), ["éé", "éé", "é"]);
+    }
+
+    #[test]
+    fn split_message_never_sends_blank_pieces() {
+        let chunks = split_message("a      b", 2);
+        assert_eq!(chunks, ["a", "b"]);
+    }
+
+    #[test]
+    fn daily_note_name_uses_the_date_format() {
+        assert_eq!(
+            daily_note_name("%Y-%m-%d", &new_year()).as_deref(),
+            Ok("2026-01-02.md")
+        );
+        assert_eq!(
+            daily_note_name("%Y/%m/%d", &new_year()).as_deref(),
+            Ok("2026/01/02.md")
+        );
+    }
+
+    #[test]
+    fn daily_note_name_rejects_invalid_formats() {
+        assert!(daily_note_name("%Q", &new_year()).is_err());
+        assert!(daily_note_name("%Y-%", &new_year()).is_err());
+    }
+
+    #[test]
+    fn vault_relative_keeps_notes_inside_the_vault() {
+        assert_eq!(
+            vault_relative("Daily/Notes", "folder"),
+            Ok(PathBuf::from("Daily/Notes"))
+        );
+        assert_eq!(vault_relative("", "folder"), Ok(PathBuf::new()));
+        assert!(vault_relative("../Elsewhere", "folder").is_err());
+        assert!(vault_relative("Daily/../../Elsewhere", "folder").is_err());
+        assert!(vault_relative("/tmp/notes", "folder").is_err());
+    }
+
+    #[test]
+    fn the_daily_note_needs_a_vault_before_it_can_be_chosen() {
+        let mut settings = Settings::default();
+        assert_eq!(
+            settings.destinations.modifier_destination,
+            DestinationKind::Paste
+        );
+        assert!(validate(&settings).is_ok());
+
+        settings.destinations.modifier_destination = DestinationKind::ObsidianDailyNote;
+        assert!(validate(&settings).is_err());
+        settings.destinations.obsidian.vault_path = Some(PathBuf::from("/notes"));
+        assert!(validate(&settings).is_ok());
+
+        let mut settings = Settings::default();
+        settings.profiles.push(crate::settings::Profile {
+            name: "Journal".to_string(),
+            destination: DestinationKind::ObsidianDailyNote,
+            replacements: Vec::new(),
+        });
+        assert!(validate(&settings).is_err_and(|err| err.contains("Journal")));
+    }
+}
@@ -0,0 +1,376 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Emitter, State};
+
+/// Carries a [`DictationTransition`] for every phase change; the backend's
+/// own listeners subscribe to it too.
+pub const STATE_EVENT: &str = "telepathy://state";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DictationPhase {
+    #[default]
+    Idle,
+    /// Trigger pressed; the recorder is being started.
+    Arming,
+    Recording,
+    /// Recording held with `pause_recording`; what is captured after
+    /// `resume_recording` joins the same buffer.
+    Paused,
+    /// Capture finished; waiting on the transcription provider.
+    Transcribing,
+    /// Transcript received; running the post-processing pipeline.
+    Processing,
+    /// Handing the text to its destination.
+    Injecting,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DictationEvent {
+    Arm,
+    RecorderStarted,
+    Pause,
+    Resume,
+    Stop,
+    TranscriptReady,
+    Deliver,
+    Finished,
+    Cancel,
+    Fail,
+    /// Forced back to idle by the watchdog.
+    Reset,
+}
+
+/// Payload of [`STATE_EVENT`] (`telepathy://state`), emitted on every
+/// transition.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictationTransition {
+    pub from: DictationPhase,
+    pub to: DictationPhase,
+    pub event: DictationEvent,
+    /// Increments each time a new dictation is armed.
+    pub session: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    phase: DictationPhase,
+    session: u64,
+    entered: Option<Instant>,
+    /// Last sign of life from the recorder while recording.
+    heartbeat: Option<Instant>,
+}
+
+impl DictationPhase {
+    /// A dictation is capturing audio, or will again once resumed.
+    pub fn recording(self) -> bool {
+        matches!(self, Self::Arming | Self::Recording | Self::Paused)
+    }
+}
+
+/// What the watchdog needs to judge whether the current phase is stuck.
+pub struct PhaseSnapshot {
+    pub phase: DictationPhase,
+    pub session: u64,
+    pub in_phase: Duration,
+    pub since_heartbeat: Option<Duration>,
+}
+
+fn next_phase(phase: DictationPhase, event: DictationEvent) -> Option<DictationPhase> {
+    use DictationEvent as E;
+    use DictationPhase as P;
+    match (phase, event) {
+        (P::Idle, E::Arm) => Some(P::Arming),
+        (P::Arming, E::RecorderStarted) => Some(P::Recording),
+        // Released before the recorder came up: nothing was captured.
+        (P::Arming, E::Stop) => Some(P::Idle),
+        (P::Recording, E::Pause) => Some(P::Paused),
+        (P::Paused, E::Resume) => Some(P::Recording),
+        (P::Recording | P::Paused, E::Stop) => Some(P::Transcribing),
+        // Text can also arrive without a recording (pasted in, or a late
+        // transcript after the hotkey was released early).
+        (P::Idle | P::Transcribing, E::TranscriptReady) => Some(P::Processing),
+        (P::Processing, E::Deliver) => Some(P::Injecting),
+        (P::Injecting, E::Finished) => Some(P::Idle),
+        (phase, E::Cancel | E::Fail | E::Reset) if phase != P::Idle => Some(P::Idle),
+        _ => None,
+    }
+}
+
+/// The span a dictation's log lines are recorded in, so the hotkey, audio,
+/// transcription, and paste lines of one session read as one story.
+pub fn span(session: u64) -> tracing::Span {
+    tracing::info_span!("dictation", session)
+}
+
+/// Backend-owned dictation lifecycle. Triggers, the webview, and the
+/// delivery path all feed events in; the resulting phase is the single
+/// source of truth the overlay renders from.
+#[derive(Default)]
+pub struct DictationMachine(Mutex<Inner>);
+
+impl DictationMachine {
+    pub fn phase(&self) -> DictationPhase {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).phase
+    }
+
+    /// [`span`] for the current (or last) session.
+    pub fn span(&self) -> tracing::Span {
+        span(self.0.lock().unwrap_or_else(|err| err.into_inner()).session)
+    }
+
+    pub fn snapshot(&self) -> PhaseSnapshot {
+        let inner = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        PhaseSnapshot {
+            phase: inner.phase,
+            session: inner.session,
+            in_phase: inner.entered.map(|at| at.elapsed()).unwrap_or_default(),
+            since_heartbeat: inner.heartbeat.map(|at| at.elapsed()),
+        }
+    }
+
+    pub fn heartbeat(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .heartbeat = Some(Instant::now());
+    }
+
+    /// Resets to idle only if `session` is still in `phase`, so a watchdog
+    /// decision made on a stale snapshot can't clobber a newer dictation.
+    pub fn force_reset(
+        &self,
+        app: &AppHandle,
+        session: u64,
+        phase: DictationPhase,
+        reason: String,
+    ) -> bool {
+        self.handle_if(
+            app,
+            Some((session, phase)),
+            DictationEvent::Reset,
+            Some(reason),
+        )
+        .is_ok()
+    }
+
+    /// Applies `event`, emitting the transition. Events that don't apply to
+    /// the current phase are rejected and leave it unchanged.
+    pub fn handle(
+        &self,
+        app: &AppHandle,
+        event: DictationEvent,
+        error: Option<String>,
+    ) -> Result<DictationPhase, String> {
+        self.handle_if(app, None, event, error)
+    }
+
+    /// [`DictationMachine::handle`], applied only while the machine is still
+    /// in the `expected` session and phase. The check and the transition
+    /// happen under one lock, so nothing can arm in between.
+    fn handle_if(
+        &self,
+        app: &AppHandle,
+        expected: Option<(u64, DictationPhase)>,
+        event: DictationEvent,
+        error: Option<String>,
+    ) -> Result<DictationPhase, String> {
+        let mut inner = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let transition = inner.apply(expected, event, error)?;
+        let to = transition.to;
+        // Emitted under the lock so listeners see transitions in order.
+        let _ = app.emit(STATE_EVENT, transition);
+        Ok(to)
+    }
+}
+
+impl Inner {
+    /// Moves to the phase `event` leads to, if `expected` (when given)
+    /// still matches the session and phase, and returns the transition.
+    fn apply(
+        &mut self,
+        expected: Option<(u64, DictationPhase)>,
+        event: DictationEvent,
+        error: Option<String>,
+    ) -> Result<DictationTransition, String> {
+        let from = self.phase;
+        if let Some((session, phase)) = expected {
+            if self.session != session || from != phase {
+                tracing::debug!(?event, session, ?phase, "dictation moved on; event dropped");
+                return Err(format!("Dictation {session} is no longer {phase:?}."));
+            }
+        }
+        let Some(to) = next_phase(from, event) else {
+            tracing::debug!(?event, ?from, "dictation event rejected");
+            return Err(format!("Cannot apply {event:?} while {from:?}."));
+        };
+        if event == DictationEvent::Arm {
+            self.session += 1;
+        }
+        self.phase = to;
+        self.entered = Some(Instant::now());
+        self.heartbeat = None;
+        match &error {
+            Some(error) => {
+                tracing::warn!(session = self.session, ?from, ?to, ?event, %error, "dictation transition")
+            }
+            None => tracing::info!(
+                session = self.session,
+                ?from,
+                ?to,
+                ?event,
+                "dictation transition"
+            ),
+        }
+        Ok(DictationTransition {
+            from,
+            to,
+            event,
+            session: self.session,
+            error,
+        })
+    }
+}
+
+#[tauri::command]
+pub fn get_dictation_state(machine: State<'_, DictationMachine>) -> DictationPhase {
+    machine.phase()
+}
+
+/// Called periodically by the webview while its recorder is producing data.
+#[tauri::command]
+pub fn dictation_heartbeat(machine: State<'_, DictationMachine>) {
+    machine.heartbeat();
+}
+
+/// Pauses the dictation being recorded; the native recording drops audio
+/// until `resume_recording`.
+#[tauri::command]
+pub fn pause_recording(
+    app: AppHandle,
+    machine: State<'_, DictationMachine>,
+) -> Result<DictationPhase, String> {
+    machine.handle(&app, DictationEvent::Pause, None)
+}
+
+#[tauri::command]
+pub fn resume_recording(
+    app: AppHandle,
+    machine: State<'_, DictationMachine>,
+) -> Result<DictationPhase, String> {
+    machine.handle(&app, DictationEvent::Resume, None)
+}
+
+/// Lets the webview report recorder and transcription progress.
+#[tauri::command]
+pub fn dictation_event(
+    app: AppHandle,
+    machine: State<'_, DictationMachine>,
+    event: DictationEvent,
+    error: Option<String>,
+) -> Result<DictationPhase, String> {
+    machine.handle(&app, event, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DictationEvent as E;
+    use DictationPhase as P;
+
+    /// Runs `events` from idle, failing on the first one that is refused.
+    fn walk(events: &[DictationEvent]) -> DictationPhase {
+        events.iter().fold(P::Idle, |phase, &event| {
+            next_phase(phase, event).unwrap_or_else(|| panic!("{event:?} refused in {phase:?}"))
+        })
+    }
+
+    #[test]
+    fn a_dictation_runs_through_every_phase() {
+        let mut phase = P::Idle;
+        for (event, expected) in [
+            (E::Arm, P::Arming),
+            (E::RecorderStarted, P::Recording),
+            (E::Pause, P::Paused),
+            (E::Resume, P::Recording),
+            (E::Stop, P::Transcribing),
+            (E::TranscriptReady, P::Processing),
+            (E::Deliver, P::Injecting),
+            (E::Finished, P::Idle),
+        ] {
+            phase = next_phase(phase, event).unwrap();
+            assert_eq!(phase, expected);
+        }
+    }
+
+    #[test]
+    fn stopping_before_the_recorder_starts_goes_back_to_idle() {
+        assert_eq!(walk(&[E::Arm, E::Stop]), P::Idle);
+        assert_eq!(
+            walk(&[E::Arm, E::RecorderStarted, E::Pause, E::Stop]),
+            P::Transcribing
+        );
+    }
+
+    #[test]
+    fn text_can_arrive_without_a_recording() {
+        assert_eq!(walk(&[E::TranscriptReady]), P::Processing);
+    }
+
+    #[test]
+    fn cancel_fail_and_reset_end_any_busy_phase() {
+        for phase in [
+            P::Arming,
+            P::Recording,
+            P::Paused,
+            P::Transcribing,
+            P::Processing,
+            P::Injecting,
+        ] {
+            for event in [E::Cancel, E::Fail, E::Reset] {
+                assert_eq!(next_phase(phase, event), Some(P::Idle));
+            }
+        }
+        assert_eq!(next_phase(P::Idle, E::Cancel), None);
+    }
+
+    #[test]
+    fn out_of_order_events_are_refused() {
+        assert_eq!(next_phase(P::Idle, E::Stop), None);
+        assert_eq!(next_phase(P::Recording, E::Arm), None);
+        assert_eq!(next_phase(P::Paused, E::Pause), None);
+        assert_eq!(next_phase(P::Arming, E::Pause), None);
+        assert_eq!(next_phase(P::Transcribing, E::Deliver), None);
+        assert_eq!(next_phase(P::Injecting, E::TranscriptReady), None);
+    }
+
+    #[test]
+    fn a_reset_for_a_stale_session_leaves_the_new_one_alone() {
+        let mut inner = Inner::default();
+        inner.apply(None, E::Arm, None).unwrap();
+        // The watchdog snapshots session 1 while it is arming...
+        let stale = (inner.session, inner.phase);
+        // ...then it stops and session 2 arms before the reset lands.
+        inner.apply(None, E::Stop, None).unwrap();
+        inner.apply(None, E::Arm, None).unwrap();
+        assert_eq!((inner.session, inner.phase), (2, P::Arming));
+
+        assert!(inner.apply(Some(stale), E::Reset, None).is_err());
+        assert_eq!((inner.session, inner.phase), (2, P::Arming));
+
+        let current = (inner.session, inner.phase);
+        let reset = inner.apply(Some(current), E::Reset, None).unwrap();
+        assert_eq!(
+            (reset.from, reset.to, reset.session),
+            (P::Arming, P::Idle, 2)
+        );
+        assert_eq!(inner.phase, P::Idle);
+    }
+}
@@ -0,0 +1,255 @@
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{destinations, injection::Injector, settings::SettingsStore};
+
+/// Conservative cap: Outlook and several Windows handlers reject mailto URLs
+/// longer than ~2 KB, and macOS Mail silently truncates far larger ones.
+const MAILTO_MAX_URL_LEN: usize = 2_000;
+const TRUNCATION_NOTICE: &str =
+    "\r\n\r\n[Transcript truncated; the full text is on the clipboard.]";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EmailComposer {
+    #[default]
+    Mailto,
+    /// macOS `NSSharingService` compose sheet; has no URL length limit.
+    SharingService,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmailSettings {
+    pub to: Option<String>,
+    pub composer: EmailComposer,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailResult {
+    pub composer: EmailComposer,
+    /// True when the body was cut to fit the mailto URL limit.
+    pub truncated: bool,
+}
+
+/// Percent-encodes everything outside RFC 3986 unreserved characters, as
+/// RFC 6068 requires for mailto header values.
+fn mailto_encode(value: &str, out: &mut String) {
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+}
+
+/// Encodes one address for the mailto path. RFC 6068 lets an addr-spec keep
+/// its `@` and the other sub-delimiters; only characters that would end or
+/// confuse the path (`%`, `/`, `?`, `#`, brackets, spaces, non-ASCII) are
+/// percent-encoded.
+fn mailto_encode_address(address: &str, out: &mut String) {
+    for byte in address.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            b'!' | b'$' | b'\'' | b'(' | b')' | b'*' | b'+' | b';' | b':' | b'@' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+}
+
+/// The addresses in `to`, which may be separated by commas or semicolons.
+fn recipients(to: &str) -> impl Iterator<Item = &str> {
+    to.split([',', ';'])
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+}
+
+fn build_mailto(to: Option<&str>, subject: &str, body: &str) -> (String, bool) {
+    let mut url = String::from("mailto:");
+    for (index, address) in recipients(to.unwrap_or_default()).enumerate() {
+        if index > 0 {
+            url.push(',');
+        }
+        mailto_encode_address(address, &mut url);
+    }
+    url.push_str("?subject=");
+    mailto_encode(subject, &mut url);
+    url.push_str("&body=");
+
+    // mailto bodies use CRLF line breaks.
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    let mut encoded_body = String::new();
+    mailto_encode(&body, &mut encoded_body);
+    if url.len() + encoded_body.len() <= MAILTO_MAX_URL_LEN {
+        url.push_str(&encoded_body);
+        return (url, false);
+    }
+
+    let mut notice = String::new();
+    mailto_encode(TRUNCATION_NOTICE, &mut notice);
+    let limit = MAILTO_MAX_URL_LEN.saturating_sub(notice.len());
+    let mut encoded_char = String::new();
+    for ch in body.chars() {
+        encoded_char.clear();
+        mailto_encode(ch.encode_utf8(&mut [0; 4]), &mut encoded_char);
+        if url.len() + encoded_char.len() > limit {
+            break;
+        }
+        url.push_str(&encoded_char);
+    }
+    url.push_str(&notice);
+    (url, true)
+}
+
+#[cfg(target_os = "macos")]
+fn compose_with_sharing_service(
+    app: &AppHandle,
+    to: Option<String>,
+    subject: String,
+    body: String,
+) -> Result<(), String> {
+    use objc2::rc::Retained;
+    use objc2_app_kit::{NSSharingService, NSSharingServiceNameComposeEmail};
+    use objc2_foundation::{NSArray, NSString};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.run_on_main_thread(move || {
+        let result = (|| {
+            let service =
+                NSSharingService::sharingServiceNamed(unsafe { NSSharingServiceNameComposeEmail })
+                    .ok_or("Mail sharing service is unavailable.".to_string())?;
+            service.setSubject(Some(&NSString::from_str(&subject)));
+            if let Some(to) = &to {
+                let addresses: Vec<_> = recipients(to).map(NSString::from_str).collect();
+                service.setRecipients(Some(&NSArray::from_retained_slice(&addresses)));
+            }
+            let body = Retained::into_super(Retained::into_super(NSString::from_str(&body)));
+            let items = NSArray::from_retained_slice(&[body]);
+            // SAFETY: NSString conforms to NSPasteboardWriting, as the service requires.
+            unsafe {
+                if !service.canPerformWithItems(Some(&items)) {
+                    return Err("No mail account is configured for sharing.".to_string());
+                }
+                service.performWithItems(&items);
+            }
+            Ok(())
+        })();
+        let _ = tx.send(result);
+    })
+    .map_err(|err| format!("Unable to reach the main thread: {err}"))?;
+    rx.recv()
+        .map_err(|_| "Mail sharing service did not respond.".to_string())?
+}
+
+pub fn compose(
+    app: &AppHandle,
+    settings: &EmailSettings,
+    subject: Option<String>,
+    body: String,
+) -> Result<EmailResult, String> {
+    let subject = subject
+        .filter(|subject| !subject.trim().is_empty())
+        .unwrap_or_else(|| destinations::title_from(&body));
+
+    #[cfg(target_os = "macos")]
+    if settings.composer == EmailComposer::SharingService {
+        compose_with_sharing_service(app, settings.to.clone(), subject, body)?;
+        return Ok(EmailResult {
+            composer: EmailComposer::SharingService,
+            truncated: false,
+        });
+    }
+
+    let (url, truncated) = build_mailto(settings.to.as_deref(), &subject, &body);
+    if truncated {
+        let conceal = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .paste
+            .conceal_clipboard;
+        app.state::<Injector>().write_clipboard(&body, conceal)?;
+    }
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|err| format!("Unable to open mail client: {err}"))?;
+    Ok(EmailResult {
+        composer: EmailComposer::Mailto,
+        truncated,
+    })
+}
+
+#[tauri::command]
+pub async fn compose_email(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    subject: Option<String>,
+    body: String,
+) -> Result<EmailResult, String> {
+    let settings = store.snapshot().destinations.email;
+    crate::run_blocking(move || compose(&app, &settings, subject, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: &str) -> String {
+        let mut out = String::new();
+        mailto_encode(value, &mut out);
+        out
+    }
+
+    #[test]
+    fn mailto_encode_keeps_only_unreserved_characters() {
+        assert_eq!(encode("a-Z.0_~"), "a-Z.0_~");
+        assert_eq!(encode("a b&c=d?"), "a%20b%26c%3Dd%3F");
+        assert_eq!(encode("é"), "%C3%A9");
+    }
+
+    #[test]
+    fn build_mailto_encodes_fields_and_uses_crlf() {
+        let (url, truncated) =
+            build_mailto(Some(" me@example.com "), "Hi there", "one\ntwo\r\nthree");
+        assert_eq!(
+            url,
+            "mailto:me@example.com?subject=Hi%20there&body=one%0D%0Atwo%0D%0Athree"
+        );
+        assert!(!truncated);
+
+        let (url, _) = build_mailto(None, "s", "b");
+        assert_eq!(url, "mailto:?subject=s&body=b");
+    }
+
+    #[test]
+    fn build_mailto_keeps_addresses_readable_and_joins_them_with_commas() {
+        let (url, _) = build_mailto(Some("a+tag@x.com, b@y.org;; c@z.net "), "s", "b");
+        assert_eq!(url, "mailto:a+tag@x.com,b@y.org,c@z.net?subject=s&body=b");
+
+        // Characters that would end the path or the query are still encoded.
+        let (url, _) = build_mailto(Some("odd%?#/name@x.com"), "s", "b");
+        assert_eq!(url, "mailto:odd%25%3F%23%2Fname@x.com?subject=s&body=b");
+    }
+
+    #[test]
+    fn build_mailto_truncates_long_bodies_on_character_boundaries() {
+        let body = "é".repeat(1_000);
+        let (url, truncated) = build_mailto(None, "s", &body);
+        assert!(truncated);
+        assert!(url.len() <= MAILTO_MAX_URL_LEN);
+        let notice = encode(TRUNCATION_NOTICE);
+        let kept = url
+            .strip_prefix("mailto:?subject=s&body=")
+            .and_then(|rest| rest.strip_suffix(notice.as_str()))
+            .unwrap();
+        assert!(!kept.is_empty());
+        assert_eq!(kept.len() % "%C3%A9".len(), 0);
+        assert!(kept.split("%C3%A9").all(str::is_empty));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    cell::Cell,
+    ffi::c_void,
+    ptr::{self, NonNull},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use objc2_core_foundation::{kCFRunLoopDefaultMode, CFMachPort, CFRetained, CFRunLoop};
+use objc2_core_graphics::{
+    CGEvent, CGEventField, CGEventFlags, CGEventTapLocation, CGEventTapOptions,
+    CGEventTapPlacement, CGEventTapProxy, CGEventType,
+};
+use tauri::AppHandle;
+
+use crate::triggers::{self, Chord};
+
+/// `kVK_Space` from HIToolbox's Events.h.
+const KEY_CODE_SPACE: i64 = 0x31;
+/// How long the run loop sleeps between checks of the stop flag.
+const RUN_LOOP_POLL_SECONDS: f64 = 0.25;
+
+/// Per-thread tap state; only ever touched from the tap's own run loop.
+struct TapContext {
+    app: AppHandle,
+    port: Cell<Option<NonNull<CFMachPort>>>,
+    /// `Some(with_modifier)` while the chord is held.
+    held: Cell<Option<bool>>,
+}
+
+impl TapContext {
+    /// Returns true when the event belongs to the hold-to-record chord and
+    /// should be swallowed instead of reaching the focused app.
+    fn handle(&self, event_type: CGEventType, event: &CGEvent) -> bool {
+        let key_code =
+            CGEvent::integer_value_field(Some(event), CGEventField::KeyboardEventKeycode);
+        if key_code != KEY_CODE_SPACE {
+            return false;
+        }
+        if event_type == CGEventType::KeyDown {
+            if self.held.get().is_some() {
+                // Autorepeat while held.
+                return true;
+            }
+            let flags = CGEvent::flags(Some(event));
+            let chord = flags.contains(CGEventFlags::MaskCommand | CGEventFlags::MaskShift)
+                && !flags.contains(CGEventFlags::MaskControl);
+            if !chord {
+                return false;
+            }
+            let with_modifier = flags.contains(CGEventFlags::MaskAlternate);
+            self.held.set(Some(with_modifier));
+            triggers::hold_to_record(&self.app, true, Chord::main_or_modifier(with_modifier));
+            true
+        } else if event_type == CGEventType::KeyUp {
+            // Space up ends the hold even if the modifiers were let go first.
+            let Some(with_modifier) = self.held.take() else {
+                return false;
+            };
+            triggers::hold_to_record(&self.app, false, Chord::main_or_modifier(with_modifier));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+unsafe extern "C-unwind" fn tap_callback(
+    _proxy: CGEventTapProxy,
+    event_type: CGEventType,
+    event: NonNull<CGEvent>,
+    user_info: *mut c_void,
+) -> *mut CGEvent {
+    // SAFETY: `user_info` is the TapContext owned by the tap thread, which
+    // outlives the tap's run loop source.
+    let context = unsafe { &*(user_info as *const TapContext) };
+    if event_type == CGEventType::TapDisabledByTimeout
+        || event_type == CGEventType::TapDisabledByUserInput
+    {
+        // macOS disables slow taps; turn ours straight back on.
+        if let Some(port) = context.port.get() {
+            // SAFETY: the port is retained by the tap thread for as long as
+            // the context is alive.
+            CGEvent::tap_enable(unsafe { port.as_ref() }, true);
+        }
+        return event.as_ptr();
+    }
+    // SAFETY: CoreGraphics passes a valid event for the duration of the call.
+    if context.handle(event_type, unsafe { event.as_ref() }) {
+        ptr::null_mut()
+    } else {
+        event.as_ptr()
+    }
+}
+
+fn create_tap(context: &TapContext) -> Result<CFRetained<CFMachPort>, String> {
+    let mask = (1u64 << CGEventType::KeyDown.0) | (1u64 << CGEventType::KeyUp.0);
+    // SAFETY: the callback matches CGEventTapCallBack and `context` outlives
+    // the port, which is invalidated before the tap thread returns.
+    unsafe {
+        CGEvent::tap_create(
+            CGEventTapLocation::SessionEventTap,
+            CGEventTapPlacement::HeadInsertEventTap,
+            CGEventTapOptions::Default,
+            mask,
+            Some(tap_callback),
+            context as *const TapContext as *mut c_void,
+        )
+    }
+    .ok_or(
+        "Unable to create the hotkey event tap. Enable Accessibility and Input Monitoring access for Telepathy."
+            .to_string(),
+    )
+}
+
+/// Hold-to-record hotkey read straight from a session event tap. Unlike the
+/// global-shortcut plugin it sees press and release without a hop through
+/// the Carbon hotkey API, and it swallows the chord so it never leaks into
+/// the focused app.
+pub struct EventTap {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl EventTap {
+    pub fn spawn(app: AppHandle) -> Result<Self, String> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("telepathy-hotkey-tap".to_string())
+            .spawn(move || {
+                let context = TapContext {
+                    app,
+                    port: Cell::new(None),
+                    held: Cell::new(None),
+                };
+                let port = match create_tap(&context) {
+                    Ok(port) => port,
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                        return;
+                    }
+                };
+                context.port.set(Some(NonNull::from(&*port)));
+                let Some(source) = CFMachPort::new_run_loop_source(None, Some(&port), 0) else {
+                    let _ =
+                        ready_tx.send(Err("Unable to schedule the hotkey event tap.".to_string()));
+                    return;
+                };
+                let Some(run_loop) = CFRunLoop::current() else {
+                    let _ = ready_tx.send(Err("Hotkey thread has no run loop.".to_string()));
+                    return;
+                };
+                // SAFETY: kCFRunLoopDefaultMode is an immutable CFString constant.
+                let mode = unsafe { kCFRunLoopDefaultMode };
+                run_loop.add_source(Some(&source), mode);
+                CGEvent::tap_enable(&port, true);
+                let _ = ready_tx.send(Ok(()));
+
+                while !stop_flag.load(Ordering::Relaxed) {
+                    CFRunLoop::run_in_mode(mode, RUN_LOOP_POLL_SECONDS, false);
+                }
+                CGEvent::tap_enable(&port, false);
+                CFMachPort::invalidate(&port);
+            })
+            .map_err(|err| format!("Unable to start hotkey event tap: {err}"))?;
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                stop,
+                thread: Some(thread),
+            }),
+            Ok(Err(err)) => {
+                let _ = thread.join();
+                Err(err)
+            }
+            Err(_) => Err("Hotkey event tap thread exited unexpectedly.".to_string()),
+        }
+    }
+}
+
+impl Drop for EventTap {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
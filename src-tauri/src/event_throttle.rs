@@ -0,0 +1,77 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Default ceiling for high-frequency events (audio levels, partials, progress).
+pub const DEFAULT_MAX_HZ: u32 = 30;
+
+#[derive(Default)]
+struct Slot {
+    last_sent: Option<Instant>,
+    /// Latest payload not yet delivered; newer payloads replace it.
+    pending: Option<serde_json::Value>,
+    flush_scheduled: bool,
+}
+
+/// Latest-wins coalescing for events that can fire faster than the webview
+/// renders. Each event name keeps at most one pending payload, so a busy
+/// renderer costs dropped intermediate values rather than a growing queue.
+#[derive(Default)]
+pub struct EventThrottle(Mutex<HashMap<&'static str, Slot>>);
+
+impl EventThrottle {
+    /// Emits `payload` now if `event` hasn't fired within the last
+    /// `1 / max_hz` seconds; otherwise holds it (replacing any older held
+    /// payload) and emits it when the interval elapses.
+    pub fn emit<S: serde::Serialize>(
+        app: &AppHandle,
+        event: &'static str,
+        max_hz: u32,
+        payload: S,
+    ) {
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return;
+        };
+        let interval = Duration::from_secs(1) / max_hz.max(1);
+        let throttle = app.state::<EventThrottle>();
+        let mut slots = throttle.0.lock().unwrap_or_else(|err| err.into_inner());
+        let slot = slots.entry(event).or_default();
+        let now = Instant::now();
+        let wait = slot
+            .last_sent
+            .map(|sent| interval.saturating_sub(now.duration_since(sent)))
+            .unwrap_or_default();
+
+        if wait.is_zero() && !slot.flush_scheduled {
+            slot.last_sent = Some(now);
+            let _ = app.emit(event, payload);
+            return;
+        }
+        slot.pending = Some(payload);
+        if !slot.flush_scheduled {
+            slot.flush_scheduled = true;
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(wait).await;
+                Self::flush(&app, event);
+            });
+        }
+    }
+
+    fn flush(app: &AppHandle, event: &'static str) {
+        let throttle = app.state::<EventThrottle>();
+        let mut slots = throttle.0.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(slot) = slots.get_mut(event) else {
+            return;
+        };
+        slot.flush_scheduled = false;
+        if let Some(payload) = slot.pending.take() {
+            slot.last_sent = Some(Instant::now());
+            let _ = app.emit(event, payload);
+        }
+    }
+}
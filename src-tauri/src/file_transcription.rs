@@ -0,0 +1,464 @@
+use std::{
+    fs,
+    future::Future,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS},
+    errors::Error as DecodeError,
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    audio::RecordedAudio,
+    destinations::DestinationKind,
+    event_throttle::{EventThrottle, DEFAULT_MAX_HZ},
+    history::{History, NewEntry},
+    injection::Injector,
+    pipeline,
+    settings::SettingsStore,
+    transcription::{local, providers},
+    webhooks::{self, TranscriptPayload, TranscriptSource},
+};
+
+const PROGRESS_EVENT: &str = "telepathy://file-transcription";
+/// Audio files accepted by `transcribe_file` and by dropping onto a window.
+pub const EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "opus"];
+/// Long files are transcribed in pieces of at most this length. Ten minutes
+/// stays under OpenAI's 25 MB upload limit as 16 kHz WAV.
+pub const PIECE_SECS: usize = 10 * 60;
+/// How far before a piece's full length to look for a pause to cut at.
+const CUT_SEARCH_SECS: usize = 5;
+/// Pauses are measured over windows of this length.
+const CUT_WINDOW_MS: usize = 50;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileTranscriptionSettings {
+    pub copy_to_clipboard: bool,
+    pub save_to_history: bool,
+}
+
+impl Default for FileTranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            copy_to_clipboard: true,
+            save_to_history: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Stage {
+    Decoding,
+    Transcribing,
+    Done,
+    Failed,
+}
+
+/// Payload of `telepathy://file-transcription`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileProgress<'a> {
+    path: &'a Path,
+    stage: Stage,
+    percent: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTranscript {
+    pub text: String,
+    pub duration_ms: u64,
+    pub provider: String,
+    pub copied: bool,
+    pub history_id: Option<i64>,
+}
+
+/// One transcribed piece of a file and where it sits in the audio.
+pub struct Segment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// A whole file's pieces, in order, and the provider that transcribed them.
+pub struct Transcript {
+    pub segments: Vec<Segment>,
+    pub provider: String,
+}
+
+impl Transcript {
+    /// The pieces joined into one transcript.
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Files being transcribed, so dropping one twice doesn't run it twice.
+#[derive(Default)]
+pub struct FileTranscriptions(Mutex<Vec<PathBuf>>);
+
+fn emit(app: &AppHandle, path: &Path, stage: Stage, percent: Option<u32>, error: Option<&str>) {
+    EventThrottle::emit(
+        app,
+        PROGRESS_EVENT,
+        DEFAULT_MAX_HZ,
+        FileProgress {
+            path,
+            stage,
+            percent,
+            error,
+        },
+    );
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Decodes the first audio track of `path` to interleaved `f32` PCM,
+/// reporting progress as a percentage when the length is known.
+pub fn decode(path: &Path, mut on_progress: impl FnMut(u32)) -> Result<RecordedAudio, String> {
+    let name = path.display();
+    let file = fs::File::open(path).map_err(|err| format!("Unable to open {name}: {err}"))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|err| format!("Unable to read {name}: {err}"))?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or(format!("{name} has no audio."))?;
+    if track.codec_params.codec == CODEC_TYPE_OPUS {
+        return Err(format!(
+            "Opus audio can't be decoded yet; convert {name} to WAV, MP3, or M4A first."
+        ));
+    }
+    let track_id = track.id;
+    let total_frames = track.codec_params.n_frames;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|err| format!("Unable to decode {name}: {err}"))?;
+
+    let mut samples = Vec::new();
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or_default();
+    let mut channels = track
+        .codec_params
+        .channels
+        .map_or(1, |channels| channels.count() as u16);
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(err) => return Err(format!("Unable to read {name}: {err}")),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet costs a moment of audio, not the whole file.
+            Err(DecodeError::DecodeError(err)) => {
+                tracing::debug!(err, "audio packet skipped");
+                continue;
+            }
+            Err(err) => return Err(format!("Unable to decode {name}: {err}")),
+        };
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        channels = spec.channels.count() as u16;
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+        if let Some(total) = total_frames.filter(|total| *total > 0) {
+            let percent = (packet.ts() + packet.dur()).min(total) * 100 / total;
+            on_progress(percent as u32);
+        }
+    }
+    if samples.is_empty() {
+        return Err(format!("{name} has no audio."));
+    }
+    Ok(RecordedAudio {
+        sample_rate,
+        channels,
+        samples,
+    })
+}
+
+/// Frame ranges of at most `piece_secs` each. Every cut but the last is
+/// moved back to the quietest moment shortly before it, so a word spoken
+/// across the boundary isn't split between two pieces.
+fn pieces(audio: &RecordedAudio, piece_secs: usize) -> Vec<Range<usize>> {
+    let channels = usize::from(audio.channels.max(1));
+    let rate = audio.sample_rate.max(1) as usize;
+    let frames = audio.samples.len() / channels;
+    let piece = (piece_secs * rate).max(1);
+    let window = (rate * CUT_WINDOW_MS / 1000).max(1);
+    let energy = |start: usize| -> f32 {
+        audio.samples[start * channels..(start + window) * channels]
+            .iter()
+            .map(|sample| sample * sample)
+            .sum()
+    };
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while frames - start > piece {
+        let end = start + piece;
+        let earliest = end.saturating_sub(CUT_SEARCH_SECS * rate).max(start + 1);
+        let cut = (earliest..end - window)
+            .step_by(window)
+            .min_by(|a, b| energy(*a).total_cmp(&energy(*b)))
+            .map_or(end, |quietest| quietest + window / 2);
+        ranges.push(start..cut);
+        start = cut;
+    }
+    ranges.push(start..frames);
+    ranges
+}
+
+/// Splits `audio` into pieces of at most `piece_secs` and transcribes them
+/// in order with `transcribe_piece`, which returns the text and the name of
+/// the provider that produced it. `on_progress` gets the percentage done
+/// before each piece. This is the pipeline `transcribe_file` and the
+/// `telepathy transcribe` command line share; only how a piece is
+/// transcribed differs.
+pub async fn transcribe_pieces<F, Fut>(
+    audio: &RecordedAudio,
+    piece_secs: usize,
+    mut transcribe_piece: F,
+    mut on_progress: impl FnMut(u32),
+) -> Result<Transcript, String>
+where
+    F: FnMut(RecordedAudio) -> Fut,
+    Fut: Future<Output = Result<(String, String), String>>,
+{
+    let channels = usize::from(audio.channels.max(1));
+    let frame_ms = |frame: usize| frame as u64 * 1000 / u64::from(audio.sample_rate.max(1));
+    let ranges = pieces(audio, piece_secs);
+    let mut transcript = Transcript {
+        segments: Vec::with_capacity(ranges.len()),
+        provider: String::new(),
+    };
+    for (index, range) in ranges.iter().enumerate() {
+        on_progress((index * 100 / ranges.len()) as u32);
+        let piece = RecordedAudio {
+            sample_rate: audio.sample_rate,
+            channels: audio.channels,
+            samples: audio.samples[range.start * channels..range.end * channels].to_vec(),
+        };
+        let (text, provider) = transcribe_piece(piece).await?;
+        transcript.segments.push(Segment {
+            start_ms: frame_ms(range.start),
+            end_ms: frame_ms(range.end),
+            text: text.trim().to_string(),
+        });
+        transcript.provider = provider;
+    }
+    Ok(transcript)
+}
+
+/// Transcribes one piece with the selected cloud provider, or with the
+/// local model when none is selected or the monthly budget blocks cloud
+/// transcription.
+async fn transcribe_piece(
+    app: &AppHandle,
+    piece: RecordedAudio,
+) -> Result<(String, String), String> {
+    let settings = app.state::<SettingsStore>().snapshot();
+    let transcript = if settings.provider.provider.is_some() {
+        providers::transcribe(app, &piece).await?
+    } else {
+        let handle = app.clone();
+        let model = settings.local_transcription.model;
+        crate::run_blocking(move || local::transcribe(&handle, &piece, &model))
+            .await
+            .map(providers::Transcript::Local)?
+    };
+    Ok(match transcript {
+        providers::Transcript::Cloud(transcript) => {
+            (transcript.text, transcript.provider.name().to_string())
+        }
+        providers::Transcript::Local(transcript) => {
+            (transcript.text, format!("whisper.cpp {}", transcript.model))
+        }
+    })
+}
+
+async fn transcribe(app: &AppHandle, path: &Path) -> Result<FileTranscript, String> {
+    emit(app, path, Stage::Decoding, Some(0), None);
+    let (handle, file) = (app.clone(), path.to_path_buf());
+    let audio = crate::run_blocking(move || {
+        decode(&file, |percent| {
+            emit(&handle, &file, Stage::Decoding, Some(percent), None)
+        })
+    })
+    .await?;
+    let duration_ms = audio.duration_ms();
+    tracing::info!(duration_ms, "audio file decoded");
+
+    let transcript = transcribe_pieces(
+        &audio,
+        PIECE_SECS,
+        |piece| transcribe_piece(app, piece),
+        |percent| emit(app, path, Stage::Transcribing, Some(percent), None),
+    )
+    .await?;
+    let (raw, provider, pieces) = (
+        transcript.text(),
+        transcript.provider,
+        transcript.segments.len(),
+    );
+
+    let settings = app.state::<SettingsStore>().snapshot();
+    let text = pipeline::process(&settings, None, &raw);
+    let output = settings.file_transcription;
+    let (handle, copy) = (app.clone(), text.clone());
+    let conceal = settings.paste.conceal_clipboard;
+    let copied = output.copy_to_clipboard
+        && crate::run_blocking(move || handle.state::<Injector>().write_clipboard(&copy, conceal))
+            .await
+            .inspect_err(|err| tracing::warn!(%err, "file transcript not copied"))
+            .is_ok();
+    let history_id = if output.save_to_history && settings.history.enabled {
+        let source_file = path.to_string_lossy();
+        app.state::<History>()
+            .record(&NewEntry {
+                text: &text,
+                original_text: (raw.trim() != text).then_some(raw.as_str()),
+                duration_ms: Some(duration_ms),
+                app: None,
+                provider: Some(&provider),
+                latency_ms: None,
+                destination: DestinationKind::default(),
+                delivered: true,
+                error: None,
+                source_file: Some(&source_file),
+            })
+            .inspect_err(|err| tracing::warn!(%err, "file transcript not saved to history"))
+            .ok()
+    } else {
+        None
+    };
+    webhooks::send(
+        app,
+        &settings.webhooks,
+        TranscriptPayload {
+            original_text: (raw.trim() != text).then(|| raw.clone()),
+            duration_ms: Some(duration_ms),
+            provider: Some(provider.clone()),
+            history_id,
+            source_file: Some(path.to_string_lossy().into_owned()),
+            ..TranscriptPayload::new(TranscriptSource::File, text.clone())
+        },
+    );
+    emit(app, path, Stage::Done, Some(100), None);
+    tracing::info!(
+        duration_ms,
+        pieces,
+        chars = text.chars().count(),
+        "audio file transcribed"
+    );
+    Ok(FileTranscript {
+        text,
+        duration_ms,
+        provider,
+        copied,
+        history_id,
+    })
+}
+
+/// Transcribes one file, as `transcribe_file` does; failures are also
+/// reported as a `failed` progress event.
+pub async fn run(app: &AppHandle, path: PathBuf) -> Result<FileTranscript, String> {
+    if !is_supported(&path) {
+        return Err(format!(
+            "{} is not a supported audio file ({}).",
+            path.display(),
+            EXTENSIONS.join(", ")
+        ));
+    }
+    {
+        let transcriptions = app.state::<FileTranscriptions>();
+        let mut running = transcriptions
+            .0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if running.contains(&path) {
+            return Err(format!("{} is already being transcribed.", path.display()));
+        }
+        running.push(path.clone());
+    }
+    let result = transcribe(app, &path).await;
+    app.state::<FileTranscriptions>()
+        .0
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .retain(|running| *running != path);
+    if let Err(err) = &result {
+        tracing::warn!(%err, "audio file not transcribed");
+        emit(app, &path, Stage::Failed, None, Some(err));
+    }
+    result
+}
+
+/// Transcribes the audio files dropped onto a window, one after another.
+/// Other files are ignored; results arrive as `telepathy://file-transcription`
+/// events.
+pub fn on_drop(app: &AppHandle, paths: &[PathBuf]) {
+    let paths: Vec<PathBuf> = paths
+        .iter()
+        .filter(|path| is_supported(path))
+        .cloned()
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for path in paths {
+            let _ = run(&app, path).await;
+        }
+    });
+}
+
+/// Transcribes a WAV, MP3, or M4A file with the selected provider (or the
+/// local model when none is selected), emitting
+/// `telepathy://file-transcription` as it decodes and transcribes. The
+/// transcript is copied to the clipboard and saved to history as
+/// `fileTranscription` settings ask.
+#[tauri::command]
+pub async fn transcribe_file(app: AppHandle, path: PathBuf) -> Result<FileTranscript, String> {
+    run(&app, path).await
+}
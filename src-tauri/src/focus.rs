@@ -0,0 +1,227 @@
+#[cfg(target_os = "linux")]
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+
+#[cfg(target_os = "linux")]
+use crate::atspi;
+use crate::injection::{Injector, PasteBlock};
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontmostApp {
+    pub name: Option<String>,
+    /// Bundle identifier on macOS, executable name on Windows and Linux;
+    /// what per-app rules match on.
+    pub id: Option<String>,
+    pub pid: Option<u32>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Caret {
+    /// Character offset into the focused field.
+    pub offset: Option<i64>,
+    /// Screen coordinates of the character at the caret.
+    pub bounds: Option<Rect>,
+}
+
+/// Where a paste would land right now. Fields are `None` when the platform
+/// API can't tell, which is different from "no".
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteTarget {
+    pub app: Option<FrontmostApp>,
+    pub editable_text: Option<bool>,
+    pub caret: Option<Caret>,
+    /// Why a paste would be withheld right now, as in the paste result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<PasteBlock>,
+    /// Which API answered: `nsWorkspace`, `win32`, or `atspi`.
+    pub source: &'static str,
+}
+
+impl PasteTarget {
+    pub fn new(source: &'static str) -> Self {
+        Self {
+            app: None,
+            editable_text: None,
+            caret: None,
+            blocked: None,
+            source,
+        }
+    }
+}
+
+/// Long-lived focus tracking where the platform only reports focus as
+/// events. macOS and Windows answer synchronously and need no state.
+#[derive(Default)]
+pub struct FocusTracker {
+    #[cfg(target_os = "linux")]
+    atspi: Mutex<Option<atspi::FocusListener>>,
+}
+
+impl FocusTracker {
+    #[cfg(target_os = "linux")]
+    pub fn start(&self) -> Result<(), String> {
+        let listener = atspi::FocusListener::spawn()?;
+        *self.atspi.lock().unwrap_or_else(|err| err.into_inner()) = Some(listener);
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn paste_target(&self) -> Result<PasteTarget, String> {
+        self.atspi
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .map(atspi::FocusListener::paste_target)
+            .ok_or(
+                "AT-SPI focus tracking is not running; is the accessibility bus enabled?"
+                    .to_string(),
+            )
+    }
+
+    #[cfg(target_os = "macos")]
+    fn paste_target(&self) -> Result<PasteTarget, String> {
+        use objc2_app_kit::NSWorkspace;
+
+        let app = NSWorkspace::sharedWorkspace()
+            .frontmostApplication()
+            .map(|app| FrontmostApp {
+                name: app.localizedName().map(|name| name.to_string()),
+                id: app.bundleIdentifier().map(|id| id.to_string()),
+                pid: u32::try_from(app.processIdentifier()).ok(),
+            });
+        Ok(PasteTarget {
+            app,
+            ..PasteTarget::new("nsWorkspace")
+        })
+    }
+
+    /// On the secure desktop the foreground window belongs to the normal
+    /// desktop the user can't see, so no app is reported.
+    #[cfg(target_os = "windows")]
+    fn paste_target(&self) -> Result<PasteTarget, String> {
+        if crate::win32::secure_desktop_active() {
+            return Ok(PasteTarget::new("win32"));
+        }
+        let app = crate::win32::foreground_process().map(|(pid, image)| {
+            let path = std::path::Path::new(&image);
+            FrontmostApp {
+                name: path
+                    .file_stem()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                id: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                pid: Some(pid),
+            }
+        });
+        Ok(PasteTarget {
+            app,
+            ..PasteTarget::new("win32")
+        })
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    fn paste_target(&self) -> Result<PasteTarget, String> {
+        Err("Paste target inspection is not supported on this platform.".to_string())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn caret_bounds(&self, _app: &AppHandle) -> Option<Rect> {
+        self.paste_target().ok()?.caret?.bounds
+    }
+
+    /// AX reports points; they are scaled by the display the caret is on.
+    #[cfg(target_os = "macos")]
+    fn caret_bounds(&self, app: &AppHandle) -> Option<Rect> {
+        let rect = crate::ax::caret_bounds()?;
+        let monitors = app.available_monitors().ok()?;
+        let scale = monitors
+            .iter()
+            .map(|monitor| {
+                let scale = monitor.scale_factor();
+                let (position, size) = (monitor.position(), monitor.size());
+                let left = f64::from(position.x) / scale;
+                let top = f64::from(position.y) / scale;
+                let right = left + f64::from(size.width) / scale;
+                let bottom = top + f64::from(size.height) / scale;
+                (
+                    scale,
+                    (left..right).contains(&rect.x) && (top..bottom).contains(&rect.y),
+                )
+            })
+            .find_map(|(scale, contains)| contains.then_some(scale))?;
+        Some(Rect {
+            x: rect.x * scale,
+            y: rect.y * scale,
+            width: rect.width * scale,
+            height: rect.height * scale,
+        })
+    }
+
+    /// UI Automation first; classic controls that only keep a system caret
+    /// answer through `GetGUIThreadInfo`.
+    #[cfg(target_os = "windows")]
+    fn caret_bounds(&self, _app: &AppHandle) -> Option<Rect> {
+        crate::win32::caret_bounds()
+            .inspect_err(|err| tracing::debug!(%err, "caret not found through UI Automation"))
+            .ok()
+            .flatten()
+            .or_else(crate::win32::system_caret)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    fn caret_bounds(&self, _app: &AppHandle) -> Option<Rect> {
+        None
+    }
+}
+
+/// Current paste target; blocking, so async callers go through
+/// [`crate::run_blocking`].
+pub fn paste_target(app: &AppHandle) -> Result<PasteTarget, String> {
+    let mut target = app.state::<FocusTracker>().paste_target()?;
+    target.blocked = app.state::<Injector>().paste_block();
+    Ok(target)
+}
+
+/// The app a paste would land in, if the platform can tell.
+pub fn frontmost_app(app: &AppHandle) -> Option<FrontmostApp> {
+    app.state::<FocusTracker>().paste_target().ok()?.app
+}
+
+/// Screen box of the focused field's text caret, in physical pixels. `None`
+/// when nothing has focus or the app doesn't report its caret. Blocking.
+pub fn caret_bounds(app: &AppHandle) -> Option<Rect> {
+    app.state::<FocusTracker>().caret_bounds(app)
+}
+
+#[tauri::command]
+pub async fn get_frontmost_app(app: AppHandle) -> Result<Option<FrontmostApp>, String> {
+    crate::run_blocking(move || Ok(frontmost_app(&app))).await
+}
+
+#[tauri::command]
+pub async fn inspect_paste_target(app: AppHandle) -> Result<PasteTarget, String> {
+    crate::run_blocking(move || paste_target(&app)).await
+}
+
+/// Where the text caret of the focused field is on screen, through the
+/// Accessibility API on macOS (`AXBoundsForRange`), UI Automation on
+/// Windows, and AT-SPI on Linux.
+#[tauri::command]
+pub async fn get_caret_bounds(app: AppHandle) -> Result<Option<Rect>, String> {
+    crate::run_blocking(move || Ok(caret_bounds(&app))).await
+}
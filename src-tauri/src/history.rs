@@ -0,0 +1,608 @@
+use std::{fs, sync::Mutex};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::{AppHandle, Manager};
+
+use crate::{costs, destinations::DestinationKind, recordings, storage::Storage, usage};
+
+const HISTORY_FILE: &str = "history.sqlite3";
+const PAGE_SIZE: u32 = 50;
+const SEARCH_LIMIT: u32 = 20;
+/// Tokens around each match in a search snippet.
+const SNIPPET_TOKENS: u32 = 12;
+/// Highlight markers handed to FTS5's `snippet()`; control characters that
+/// never occur in a transcript, split back out in [`snippet_parts`].
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistorySettings {
+    /// Keep every transcript in a local SQLite database. Off stops new
+    /// entries; existing ones stay until cleared.
+    pub enabled: bool,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// One finished dictation.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub text: String,
+    /// The transcript as recognized, when post-processing or translation
+    /// changed it.
+    pub original_text: Option<String>,
+    /// RFC 3339, local time.
+    pub created_at: String,
+    /// How long the hotkey was held.
+    pub duration_ms: Option<u64>,
+    /// The app the transcript was pasted into.
+    pub app: Option<String>,
+    pub provider: Option<String>,
+    /// Hotkey release to delivery.
+    pub latency_ms: Option<u64>,
+    pub destination: DestinationKind,
+    /// False when delivery failed or the paste fell back to the clipboard;
+    /// these are the entries worth recovering.
+    pub delivered: bool,
+    pub error: Option<String>,
+    /// The dictation's audio is saved, for `load_recording`.
+    pub has_recording: bool,
+    /// Re-transcriptions of the saved audio, listed by `list_revisions`.
+    pub revisions: u32,
+    /// The audio file transcribed, for entries from `transcribe_file`.
+    pub source_file: Option<String>,
+}
+
+/// A later transcript of an entry's saved audio, made by `retranscribe`.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Revision {
+    pub id: i64,
+    pub entry_id: i64,
+    pub text: String,
+    /// The transcript as recognized, when the pipeline changed it.
+    pub original_text: Option<String>,
+    pub provider: String,
+    pub model: Option<String>,
+    /// RFC 3339, local time.
+    pub created_at: String,
+}
+
+/// What [`History::record`] stores; the id and timestamp are assigned there.
+pub struct NewEntry<'a> {
+    pub text: &'a str,
+    pub original_text: Option<&'a str>,
+    pub duration_ms: Option<u64>,
+    pub app: Option<&'a str>,
+    pub provider: Option<&'a str>,
+    pub latency_ms: Option<u64>,
+    pub destination: DestinationKind,
+    pub delivered: bool,
+    pub error: Option<&'a str>,
+    pub source_file: Option<&'a str>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryFilter {
+    /// Case-insensitive substring of the transcript.
+    pub text: Option<String>,
+    pub app: Option<String>,
+    pub provider: Option<String>,
+    /// Only entries that were not delivered.
+    pub failed_only: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    /// Matching entries across all pages.
+    pub total: u64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// A run of snippet text, highlighted when it matched the query.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub matched: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub entry: HistoryEntry,
+    /// The best-matching stretch of the transcript, elided with `…`.
+    pub snippet: Vec<SnippetPart>,
+    /// FTS5's bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+/// The transcript history database, opened once at launch. `None` when the
+/// data directory or database is unavailable; recording is then skipped.
+pub struct History(Mutex<Option<Connection>>);
+
+fn open(app: &AppHandle) -> Result<Connection, String> {
+    let dir = Storage::data_dir(app).ok_or("App data directory is unavailable.".to_string())?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let path = dir.join(HISTORY_FILE);
+    let conn = Connection::open(&path)
+        .map_err(|err| format!("Unable to open {}: {err}", path.display()))?;
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         CREATE TABLE IF NOT EXISTS entries (
+             id INTEGER PRIMARY KEY,
+             text TEXT NOT NULL,
+             created_at TEXT NOT NULL,
+             duration_ms INTEGER,
+             app TEXT,
+             provider TEXT,
+             latency_ms INTEGER,
+             destination TEXT NOT NULL,
+             delivered INTEGER NOT NULL,
+             error TEXT,
+             original_text TEXT,
+             has_recording INTEGER NOT NULL DEFAULT 0,
+             source_file TEXT
+         );
+         CREATE INDEX IF NOT EXISTS entries_created_at ON entries (created_at);
+         CREATE TABLE IF NOT EXISTS revisions (
+             id INTEGER PRIMARY KEY,
+             entry_id INTEGER NOT NULL,
+             text TEXT NOT NULL,
+             original_text TEXT,
+             provider TEXT NOT NULL,
+             model TEXT,
+             created_at TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS revisions_entry_id ON revisions (entry_id);",
+    )
+    .map_err(|err| format!("Unable to prepare the history database: {err}"))?;
+    migrate(&conn).map_err(|err| format!("Unable to update the history database: {err}"))?;
+    index(&conn).map_err(|err| format!("Unable to prepare history search: {err}"))?;
+    usage::prepare(&conn).map_err(|err| format!("Unable to prepare usage stats: {err}"))?;
+    costs::prepare(&conn).map_err(|err| format!("Unable to prepare cost tracking: {err}"))?;
+    Ok(conn)
+}
+
+/// Adds columns that databases created by earlier versions lack.
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let has_original = conn
+        .prepare("SELECT 1 FROM pragma_table_info('entries') WHERE name = 'original_text'")?
+        .exists([])?;
+    if !has_original {
+        conn.execute("ALTER TABLE entries ADD COLUMN original_text TEXT", [])?;
+    }
+    let has_recording = conn
+        .prepare("SELECT 1 FROM pragma_table_info('entries') WHERE name = 'has_recording'")?
+        .exists([])?;
+    if !has_recording {
+        conn.execute(
+            "ALTER TABLE entries ADD COLUMN has_recording INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+    }
+    let has_source_file = conn
+        .prepare("SELECT 1 FROM pragma_table_info('entries') WHERE name = 'source_file'")?
+        .exists([])?;
+    if !has_source_file {
+        conn.execute("ALTER TABLE entries ADD COLUMN source_file TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// The FTS5 index over transcript text, kept in sync by triggers. Databases
+/// from before search existed are indexed once when it is first created.
+fn index(conn: &Connection) -> rusqlite::Result<()> {
+    let exists = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'entries_fts'")?
+        .exists([])?;
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts USING fts5 (
+             text,
+             content = 'entries',
+             content_rowid = 'id',
+             tokenize = 'porter unicode61 remove_diacritics 2'
+         );
+         CREATE TRIGGER IF NOT EXISTS entries_fts_insert AFTER INSERT ON entries BEGIN
+             INSERT INTO entries_fts (rowid, text) VALUES (new.id, new.text);
+         END;
+         CREATE TRIGGER IF NOT EXISTS entries_fts_delete AFTER DELETE ON entries BEGIN
+             INSERT INTO entries_fts (entries_fts, rowid, text) VALUES ('delete', old.id, old.text);
+         END;
+         CREATE TRIGGER IF NOT EXISTS entries_fts_update AFTER UPDATE OF text ON entries BEGIN
+             INSERT INTO entries_fts (entries_fts, rowid, text) VALUES ('delete', old.id, old.text);
+             INSERT INTO entries_fts (rowid, text) VALUES (new.id, new.text);
+         END;",
+    )?;
+    if !exists {
+        conn.execute(
+            "INSERT INTO entries_fts (entries_fts) VALUES ('rebuild')",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// Turns free text into an FTS5 query: every word must appear, the last as
+/// a prefix so results follow along while typing. Quoting each word keeps
+/// FTS5 operators and punctuation in the input from being parsed as syntax.
+fn match_query(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    let last = words.len().checked_sub(1)?;
+    Some(
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == last {
+                    format!("{word}*")
+                } else {
+                    word.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn snippet_parts(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut rest = snippet;
+    while let Some(start) = rest.find(MATCH_START) {
+        let end = rest[start..]
+            .find(MATCH_END)
+            .map_or(rest.len(), |end| start + end);
+        if start > 0 {
+            parts.push(SnippetPart {
+                text: rest[..start].to_string(),
+                matched: false,
+            });
+        }
+        parts.push(SnippetPart {
+            text: rest[start + MATCH_START.len_utf8()..end].to_string(),
+            matched: true,
+        });
+        rest = rest.get(end + MATCH_END.len_utf8()..).unwrap_or_default();
+    }
+    if !rest.is_empty() {
+        parts.push(SnippetPart {
+            text: rest.to_string(),
+            matched: false,
+        });
+    }
+    parts
+}
+
+fn destination_name(destination: DestinationKind) -> String {
+    serde_json::to_value(destination)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let destination: String = row.get(7)?;
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        created_at: row.get(2)?,
+        duration_ms: row.get(3)?,
+        app: row.get(4)?,
+        provider: row.get(5)?,
+        latency_ms: row.get(6)?,
+        destination: serde_json::from_value(destination.into()).unwrap_or_default(),
+        delivered: row.get(8)?,
+        error: row.get(9)?,
+        original_text: row.get(10)?,
+        has_recording: row.get(11)?,
+        revisions: row.get(12)?,
+        source_file: row.get(13)?,
+    })
+}
+
+/// `WHERE` clause and parameters for `filter`. `LIKE` is case-insensitive
+/// for ASCII in SQLite.
+fn filter_clause(filter: &HistoryFilter) -> (String, Vec<String>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(text) = filter.text.as_deref().filter(|text| !text.is_empty()) {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(c, '\\' | '%' | '_') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        conditions.push("text LIKE '%' || ? || '%' ESCAPE '\\'");
+        values.push(escaped);
+    }
+    if let Some(app) = filter.app.as_deref() {
+        conditions.push("app = ?");
+        values.push(app.to_string());
+    }
+    if let Some(provider) = filter.provider.as_deref() {
+        conditions.push("provider = ?");
+        values.push(provider.to_string());
+    }
+    if filter.failed_only {
+        conditions.push("delivered = 0");
+    }
+    let clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    (clause, values)
+}
+
+impl History {
+    pub fn load(app: &AppHandle) -> Self {
+        let conn = open(app)
+            .inspect_err(|err| tracing::warn!(%err, "transcript history unavailable"))
+            .ok();
+        Self(Mutex::new(conn))
+    }
+
+    pub(crate) fn with<T>(
+        &self,
+        run: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let conn = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let conn = conn
+            .as_ref()
+            .ok_or("The history database is unavailable.".to_string())?;
+        run(conn).map_err(|err| format!("History query failed: {err}"))
+    }
+
+    pub fn record(&self, entry: &NewEntry) -> Result<i64, String> {
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO entries
+                     (text, created_at, duration_ms, app, provider, latency_ms, destination, delivered, error, original_text, source_file)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    entry.text,
+                    chrono::Local::now().to_rfc3339(),
+                    entry.duration_ms,
+                    entry.app,
+                    entry.provider,
+                    entry.latency_ms,
+                    destination_name(entry.destination),
+                    entry.delivered,
+                    entry.error,
+                    entry.original_text,
+                    entry.source_file,
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    fn page(&self, page: u32, filter: &HistoryFilter) -> Result<HistoryPage, String> {
+        let (clause, values) = filter_clause(filter);
+        self.with(|conn| {
+            let total: u64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM entries {clause}"),
+                rusqlite::params_from_iter(&values),
+                |row| row.get(0),
+            )?;
+            let mut statement = conn.prepare(&format!(
+                "SELECT id, text, created_at, duration_ms, app, provider, latency_ms, destination, delivered, error, original_text, has_recording,
+                        (SELECT COUNT(*) FROM revisions r WHERE r.entry_id = entries.id), source_file
+                 FROM entries {clause}
+                 ORDER BY id DESC
+                 LIMIT {PAGE_SIZE} OFFSET {}",
+                u64::from(page) * u64::from(PAGE_SIZE)
+            ))?;
+            let entries = statement
+                .query_map(rusqlite::params_from_iter(&values), entry_from_row)?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(HistoryPage {
+                entries,
+                total,
+                page,
+                page_size: PAGE_SIZE,
+            })
+        })
+    }
+
+    fn search(&self, query: &str, limit: u32) -> Result<Vec<SearchResult>, String> {
+        let Some(query) = match_query(query) else {
+            return Ok(Vec::new());
+        };
+        self.with(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT e.id, e.text, e.created_at, e.duration_ms, e.app, e.provider, e.latency_ms, e.destination, e.delivered, e.error, e.original_text, e.has_recording,
+                        (SELECT COUNT(*) FROM revisions r WHERE r.entry_id = e.id), e.source_file,
+                        snippet(entries_fts, 0, '{MATCH_START}', '{MATCH_END}', '…', {SNIPPET_TOKENS}),
+                        entries_fts.rank
+                 FROM entries_fts JOIN entries e ON e.id = entries_fts.rowid
+                 WHERE entries_fts MATCH ?1
+                 ORDER BY entries_fts.rank
+                 LIMIT ?2"
+            ))?;
+            let results = statement
+                .query_map(params![query, limit], |row| {
+                    let snippet: String = row.get(14)?;
+                    Ok(SearchResult {
+                        entry: entry_from_row(row)?,
+                        snippet: snippet_parts(&snippet),
+                        rank: row.get(15)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(results)
+        })
+    }
+
+    /// The newest non-empty transcript.
+    pub fn latest_text(&self) -> Result<Option<String>, String> {
+        self.with(|conn| {
+            conn.query_row(
+                "SELECT text FROM entries WHERE trim(text) != '' ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+    }
+
+    /// Marks whether entry `id` has its audio saved.
+    pub fn set_recording(&self, id: i64, saved: bool) -> Result<(), String> {
+        self.with(|conn| {
+            conn.execute(
+                "UPDATE entries SET has_recording = ?2 WHERE id = ?1",
+                params![id, saved],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn add_revision(
+        &self,
+        entry_id: i64,
+        text: &str,
+        original_text: Option<&str>,
+        provider: &str,
+        model: Option<&str>,
+    ) -> Result<Revision, String> {
+        let created_at = chrono::Local::now().to_rfc3339();
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO revisions (entry_id, text, original_text, provider, model, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![entry_id, text, original_text, provider, model, created_at],
+            )?;
+            Ok(Revision {
+                id: conn.last_insert_rowid(),
+                entry_id,
+                text: text.to_string(),
+                original_text: original_text.map(str::to_string),
+                provider: provider.to_string(),
+                model: model.map(str::to_string),
+                created_at,
+            })
+        })
+    }
+
+    fn revisions(&self, entry_id: i64) -> Result<Vec<Revision>, String> {
+        self.with(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, entry_id, text, original_text, provider, model, created_at
+                 FROM revisions WHERE entry_id = ?1 ORDER BY id DESC",
+            )?;
+            let revisions = statement
+                .query_map([entry_id], |row| {
+                    Ok(Revision {
+                        id: row.get(0)?,
+                        entry_id: row.get(1)?,
+                        text: row.get(2)?,
+                        original_text: row.get(3)?,
+                        provider: row.get(4)?,
+                        model: row.get(5)?,
+                        created_at: row.get(6)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(revisions)
+        })
+    }
+
+    pub fn exists(&self, id: i64) -> Result<bool, String> {
+        self.with(|conn| {
+            conn.prepare("SELECT 1 FROM entries WHERE id = ?1")?
+                .exists([id])
+        })
+    }
+
+    fn delete(&self, id: i64) -> Result<bool, String> {
+        self.with(|conn| {
+            conn.execute("DELETE FROM revisions WHERE entry_id = ?1", [id])?;
+            Ok(conn.execute("DELETE FROM entries WHERE id = ?1", [id])? > 0)
+        })
+    }
+
+    fn clear(&self) -> Result<u64, String> {
+        self.with(|conn| {
+            conn.execute("DELETE FROM revisions", [])?;
+            let removed = conn.execute("DELETE FROM entries", [])?;
+            // Give the transcripts' pages back to the filesystem.
+            conn.execute_batch("VACUUM")?;
+            Ok(removed as u64)
+        })
+    }
+}
+
+/// Newest entries first, `page` counting from 0.
+#[tauri::command]
+pub async fn list_history(
+    app: AppHandle,
+    page: Option<u32>,
+    filter: Option<HistoryFilter>,
+) -> Result<HistoryPage, String> {
+    crate::run_blocking(move || {
+        app.state::<History>()
+            .page(page.unwrap_or(0), &filter.unwrap_or_default())
+    })
+    .await
+}
+
+/// Full-text search over transcripts, best matches first. Words are
+/// stemmed, so "meeting" also finds "meetings"; `limit` defaults to 20.
+#[tauri::command]
+pub async fn search_history(
+    app: AppHandle,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<SearchResult>, String> {
+    crate::run_blocking(move || {
+        app.state::<History>()
+            .search(&query, limit.unwrap_or(SEARCH_LIMIT).max(1))
+    })
+    .await
+}
+
+/// An entry's re-transcriptions, newest first.
+#[tauri::command]
+pub async fn list_revisions(app: AppHandle, id: i64) -> Result<Vec<Revision>, String> {
+    crate::run_blocking(move || app.state::<History>().revisions(id)).await
+}
+
+/// Returns whether an entry was removed. Its saved audio and revisions go
+/// with it.
+#[tauri::command]
+pub async fn delete_entry(app: AppHandle, id: i64) -> Result<bool, String> {
+    crate::run_blocking(move || {
+        let removed = app.state::<History>().delete(id)?;
+        recordings::remove(&app, id);
+        Ok(removed)
+    })
+    .await
+}
+
+/// Removes every entry and returns how many there were.
+#[tauri::command]
+pub async fn clear_history(app: AppHandle) -> Result<u64, String> {
+    let removed = crate::run_blocking(move || {
+        let removed = app.state::<History>().clear()?;
+        recordings::remove_all(&app);
+        Ok(removed)
+    })
+    .await?;
+    tracing::info!(removed, "transcript history cleared");
+    Ok(removed)
+}
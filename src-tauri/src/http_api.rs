@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    secrets::{self, SecretKey},
+    settings::SettingsStore,
+};
+
+#[cfg(feature = "http-api")]
+mod server;
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    /// TCP port bound on the loopback interface.
+    pub port: u16,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9010,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Running {
+    /// What the server was last configured with; `None` forces a restart.
+    settings: Option<HttpApiSettings>,
+    #[cfg(feature = "http-api")]
+    server: Option<server::ApiServer>,
+}
+
+/// The opt-in localhost API for editor plugins and automation scripts.
+#[derive(Default)]
+pub struct HttpApi(Mutex<Running>);
+
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|err| format!("Unable to generate a token: {err}"))?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// The bearer token clients must send, created on first use.
+#[cfg_attr(not(feature = "http-api"), allow(dead_code))]
+fn token() -> Result<String, String> {
+    if let Some(token) = secrets::read_secret(SecretKey::HttpApiToken)? {
+        return Ok(token);
+    }
+    let token = generate_token()?;
+    secrets::write_secret(SecretKey::HttpApiToken, &token)?;
+    Ok(token)
+}
+
+impl HttpApi {
+    /// Starts, restarts, or stops the server to match `settings`. Stopping
+    /// joins the server thread so the port is free for a replacement, and
+    /// the token may come from the credential store, so this blocks.
+    pub fn reconfigure(&self, app: &AppHandle, settings: &HttpApiSettings) -> Result<(), String> {
+        let mut running = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        if running.settings.as_ref() == Some(settings) {
+            return Ok(());
+        }
+        #[cfg(feature = "http-api")]
+        {
+            running.server = None;
+            if settings.enabled {
+                running.server = Some(server::ApiServer::spawn(
+                    app.clone(),
+                    settings.port,
+                    token()?,
+                )?);
+            }
+        }
+        #[cfg(not(feature = "http-api"))]
+        {
+            let _ = app;
+            if settings.enabled {
+                return Err("This build does not include the HTTP API.".to_string());
+            }
+        }
+        running.settings = Some(settings.clone());
+        Ok(())
+    }
+
+    fn restart(&self, app: &AppHandle) -> Result<(), String> {
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .settings = None;
+        let settings = app.state::<SettingsStore>().snapshot().http_api;
+        self.reconfigure(app, &settings)
+    }
+}
+
+/// Replaces the HTTP API token and returns the new one. Tokens stay in the
+/// credential store and are never read back, so this is the only time the
+/// caller sees it; a running server switches to it straight away.
+#[tauri::command]
+pub async fn reset_http_api_token(app: AppHandle) -> Result<String, String> {
+    crate::run_blocking(move || {
+        let token = generate_token()?;
+        secrets::write_secret(SecretKey::HttpApiToken, &token)?;
+        app.state::<HttpApi>().restart(&app)?;
+        tracing::info!("HTTP API token replaced");
+        Ok(token)
+    })
+    .await
+}
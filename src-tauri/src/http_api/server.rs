@@ -0,0 +1,444 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    destinations::LastTranscript,
+    dictation::DictationMachine,
+    file_transcription,
+    history::History,
+    triggers::{self, TriggerAction, TriggerSource},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Requests handled at once; more wait in tiny_http's queue, so a flood
+/// from a local process can't exhaust threads.
+const WORKERS: usize = 4;
+/// Uploads are streamed to a temporary file, so this bounds disk use, not
+/// memory.
+const MAX_UPLOAD_BYTES: u64 = 200 * 1024 * 1024;
+/// The most a multipart part's headers may take.
+const MAX_PART_HEADERS: usize = 16 * 1024;
+
+static NEXT_UPLOAD: AtomicU64 = AtomicU64::new(0);
+
+type Reply = (u16, serde_json::Value);
+
+fn error(status: u16, message: impl Into<String>) -> Reply {
+    (status, serde_json::json!({ "error": message.into() }))
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+/// Compares every byte so response timing doesn't reveal a matching prefix.
+fn authorized(request: &Request, token: &str) -> bool {
+    let Some(presented) = header(request, "Authorization").and_then(|value| {
+        value
+            .strip_prefix("Bearer ")
+            .or_else(|| value.strip_prefix("bearer "))
+    }) else {
+        return false;
+    };
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn dictation(app: &AppHandle, action: Option<TriggerAction>) -> Reply {
+    if let Some(action) = action {
+        triggers::trigger(app, action, TriggerSource::HttpApi);
+    }
+    let phase = app.state::<DictationMachine>().phase();
+    (
+        if action.is_some() { 202 } else { 200 },
+        serde_json::json!({ "phase": phase }),
+    )
+}
+
+fn latest_transcript(app: &AppHandle) -> Reply {
+    let text = match app.state::<LastTranscript>().get() {
+        Some(text) => Ok(Some(text)),
+        None => app.state::<History>().latest_text(),
+    };
+    match text {
+        Ok(Some(text)) => (200, serde_json::json!({ "text": text })),
+        Ok(None) => error(404, "There is no transcript yet."),
+        Err(err) => error(500, err),
+    }
+}
+
+/// The `boundary` parameter of a `multipart/form-data` content type.
+fn boundary(content_type: &str) -> Option<&str> {
+    let (kind, params) = content_type.split_once(';')?;
+    if !kind.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Reads `reader` up to and including `delimiter`, handing the bytes
+/// before it to `sink` as they arrive; only a window about the size of the
+/// delimiter is held back. Returns `false` when the input ends first.
+fn copy_until(
+    reader: &mut impl BufRead,
+    delimiter: &[u8],
+    mut sink: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<bool> {
+    let mut window = Vec::new();
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            sink(&window)?;
+            return Ok(false);
+        }
+        let held = window.len();
+        window.extend_from_slice(available);
+        if let Some(at) = find(&window, delimiter) {
+            sink(&window[..at])?;
+            reader.consume(at + delimiter.len() - held);
+            return Ok(true);
+        }
+        let consumed = window.len() - held;
+        reader.consume(consumed);
+        // Keep enough to spot a delimiter split across reads.
+        let keep = (delimiter.len() - 1).min(window.len());
+        sink(&window[..window.len() - keep])?;
+        window.drain(..window.len() - keep);
+    }
+}
+
+/// The `filename` of a part's `Content-Disposition` header.
+fn part_filename(headers: &str) -> Option<String> {
+    headers
+        .lines()
+        .filter(|line| {
+            line.to_ascii_lowercase()
+                .starts_with("content-disposition:")
+        })
+        .find_map(|line| {
+            let start = line.find("filename=\"")? + "filename=\"".len();
+            let len = line[start..].find('"')?;
+            Some(line[start..start + len].to_string())
+        })
+}
+
+/// Streams the first part of a multipart body that carries a file into
+/// `out` and returns its file name, or `None` when there is no complete
+/// file part.
+fn copy_file_part(
+    body: impl Read,
+    boundary: &str,
+    out: &mut impl Write,
+) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(body);
+    let delimiter = format!("\r\n--{boundary}").into_bytes();
+    // The first delimiter has no preceding line break.
+    if !copy_until(&mut reader, &delimiter[2..], |_| Ok(()))? {
+        return Ok(None);
+    }
+    loop {
+        // `--` after a delimiter closes the body; a line break starts a part.
+        let mut after = [0u8; 2];
+        if reader.read_exact(&mut after).is_err() || after == *b"--" {
+            return Ok(None);
+        }
+        let mut headers = Vec::new();
+        let complete = copy_until(&mut reader, b"\r\n\r\n", |bytes| {
+            headers.extend_from_slice(bytes);
+            if headers.len() > MAX_PART_HEADERS {
+                return Err(io::Error::other("multipart headers are too long"));
+            }
+            Ok(())
+        })?;
+        if !complete {
+            return Ok(None);
+        }
+        let Some(filename) = part_filename(&String::from_utf8_lossy(&headers)) else {
+            if !copy_until(&mut reader, &delimiter, |_| Ok(()))? {
+                return Ok(None);
+            }
+            continue;
+        };
+        let complete = copy_until(&mut reader, &delimiter, |bytes| out.write_all(bytes))?;
+        return Ok(complete.then_some(filename));
+    }
+}
+
+/// Picks the upload's file extension, which decides how it is decoded.
+fn extension(filename: Option<&str>, content_type: Option<&str>) -> Option<String> {
+    filename
+        .and_then(|name| Path::new(name).extension())
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .or_else(|| {
+            let ext = match content_type?.split(';').next()?.trim() {
+                "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+                "audio/mpeg" | "audio/mp3" => "mp3",
+                "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+                _ => return None,
+            };
+            Some(ext.to_string())
+        })
+}
+
+/// `POST /transcribe`: a multipart upload with the audio in a file field, or
+/// the raw audio with an `audio/*` content type. The audio is streamed to a
+/// temporary file rather than held in memory.
+fn transcribe(app: &AppHandle, request: &mut Request) -> Reply {
+    if request
+        .body_length()
+        .is_some_and(|len| len as u64 > MAX_UPLOAD_BYTES)
+    {
+        return error(413, "The upload is larger than 200 MB.");
+    }
+    let content_type = header(request, "Content-Type").map(str::to_string);
+    let boundary = content_type.as_deref().and_then(boundary);
+    let mut path: PathBuf = std::env::temp_dir().join(format!(
+        "telepathy-upload-{}-{}",
+        std::process::id(),
+        NEXT_UPLOAD.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut file = match fs::File::create(&path) {
+        Ok(file) => file,
+        Err(err) => return error(500, format!("Unable to save the upload: {err}")),
+    };
+    let mut body = request.as_reader().take(MAX_UPLOAD_BYTES + 1);
+    let copied = match boundary {
+        Some(boundary) => copy_file_part(&mut body, boundary, &mut file),
+        None => io::copy(&mut body, &mut file).map(|_| None),
+    };
+    drop(file);
+    let ext = match copied {
+        _ if body.limit() == 0 => Err(error(413, "The upload is larger than 200 MB.")),
+        Ok(Some(filename)) => Ok(extension(Some(&filename), None)),
+        Ok(None) if boundary.is_some() => Err(error(400, "The form has no file field.")),
+        Ok(None) => Ok(extension(None, content_type.as_deref())),
+        Err(err) => Err(error(400, format!("Unable to read the upload: {err}"))),
+    }
+    .and_then(|ext| ext.ok_or_else(|| error(415, "Send a WAV, MP3, or M4A file.")));
+    let ext = match ext {
+        Ok(ext) => ext,
+        Err(reply) => {
+            let _ = fs::remove_file(&path);
+            return reply;
+        }
+    };
+    // The extension decides how the file is decoded.
+    let named = path.with_extension(&ext);
+    if let Err(err) = fs::rename(&path, &named) {
+        let _ = fs::remove_file(&path);
+        return error(500, format!("Unable to save the upload: {err}"));
+    }
+    path = named;
+    let result = tauri::async_runtime::block_on(file_transcription::run(app, path.clone()));
+    let _ = fs::remove_file(&path);
+    match result {
+        Ok(transcript) => match serde_json::to_value(transcript) {
+            Ok(value) => (200, value),
+            Err(err) => error(500, err.to_string()),
+        },
+        Err(err) => error(422, err),
+    }
+}
+
+fn route(app: &AppHandle, request: &mut Request) -> Reply {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match (request.method(), path.as_str()) {
+        (Method::Get, "/dictation") => dictation(app, None),
+        (Method::Post, "/dictation/start") => dictation(app, Some(TriggerAction::Start)),
+        (Method::Post, "/dictation/stop") => dictation(app, Some(TriggerAction::Stop)),
+        (Method::Post, "/dictation/cancel") => dictation(app, Some(TriggerAction::Cancel)),
+        (Method::Get, "/transcripts/latest") => latest_transcript(app),
+        (Method::Post, "/transcribe") => transcribe(app, request),
+        _ => error(404, "Not found."),
+    }
+}
+
+fn respond(app: &AppHandle, token: &str, mut request: Request) {
+    let (status, body) = if authorized(&request, token) {
+        route(app, &mut request)
+    } else {
+        error(401, "Missing or wrong bearer token.")
+    };
+    tracing::debug!(
+        method = %request.method(),
+        url = request.url(),
+        status,
+        "HTTP API request"
+    );
+    let mut response = Response::from_string(body.to_string()).with_status_code(status);
+    if let Ok(content_type) = Header::from_bytes("Content-Type", "application/json") {
+        response = response.with_header(content_type);
+    }
+    let _ = request.respond(response);
+}
+
+pub struct ApiServer {
+    stop: Arc<AtomicBool>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ApiServer {
+    /// Binds the port and starts a fixed pool of [`WORKERS`] threads that
+    /// take requests from the same server, so a slow transcription only
+    /// holds up one of them.
+    pub fn spawn(app: AppHandle, port: u16, token: String) -> Result<Self, String> {
+        let server = Arc::new(
+            Server::http((Ipv4Addr::LOCALHOST, port))
+                .map_err(|err| format!("Unable to bind HTTP API port {port}: {err}"))?,
+        );
+        let token: Arc<str> = token.into();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut workers = Vec::with_capacity(WORKERS);
+        for _ in 0..WORKERS {
+            let (app, token, server, stop_flag) =
+                (app.clone(), token.clone(), server.clone(), stop.clone());
+            let worker = thread::Builder::new()
+                .name("telepathy-http-api".to_string())
+                .spawn(move || {
+                    while !stop_flag.load(Ordering::Relaxed) {
+                        // Timeouts just re-check the stop flag.
+                        if let Ok(Some(request)) = server.recv_timeout(POLL_INTERVAL) {
+                            respond(&app, &token, request);
+                        }
+                    }
+                });
+            match worker {
+                Ok(worker) => workers.push(worker),
+                Err(err) => {
+                    // Stops the workers already running.
+                    drop(Self { stop, workers });
+                    return Err(format!("Unable to start the HTTP API: {err}"));
+                }
+            }
+        }
+        tracing::info!(port, workers = WORKERS, "HTTP API listening");
+        Ok(Self { stop, workers })
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Join so the port is released before a replacement server binds it.
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out at most `step` bytes per read, so delimiters straddle reads.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let len = self.step.min(buffer.len()).min(self.bytes.len());
+            buffer[..len].copy_from_slice(&self.bytes[..len]);
+            self.bytes = &self.bytes[len..];
+            Ok(len)
+        }
+    }
+
+    const FORM: &[u8] = b"preamble\r\n--XyZ\r\n\
+        Content-Disposition: form-data; name=\"note\"\r\n\r\n\
+        not the file\r\n--XyZ\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"memo.m4a\"\r\n\
+        Content-Type: audio/mp4\r\n\r\n\
+        \x00\x01audio\r\n-bytes\r\n--XyZ--\r\n";
+
+    fn extract(body: &[u8], step: usize) -> (Option<String>, Vec<u8>) {
+        let mut out = Vec::new();
+        let filename = copy_file_part(Trickle { bytes: body, step }, "XyZ", &mut out).unwrap();
+        (filename, out)
+    }
+
+    #[test]
+    fn the_file_part_is_streamed_out_whatever_the_read_sizes() {
+        for step in [1, 2, 3, 7, 64, FORM.len()] {
+            let (filename, audio) = extract(FORM, step);
+            assert_eq!(filename.as_deref(), Some("memo.m4a"), "step {step}");
+            assert_eq!(audio, b"\x00\x01audio\r\n-bytes", "step {step}");
+        }
+    }
+
+    #[test]
+    fn forms_without_a_complete_file_part_yield_nothing() {
+        let no_file =
+            b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nx\r\n--XyZ--\r\n";
+        assert_eq!(extract(no_file, 5).0, None);
+        // Cut off before the closing delimiter, e.g. by the size limit.
+        assert_eq!(extract(&FORM[..FORM.len() - 20], 5).0, None);
+        assert_eq!(extract(b"", 5).0, None);
+    }
+
+    #[test]
+    fn copy_until_stops_right_after_the_delimiter() {
+        let mut reader = BufReader::new(Trickle {
+            bytes: b"abc--XY--rest",
+            step: 3,
+        });
+        let mut before = Vec::new();
+        let found = copy_until(&mut reader, b"--XY--", |bytes| {
+            before.extend_from_slice(bytes);
+            Ok(())
+        })
+        .unwrap();
+        assert!(found);
+        assert_eq!(before, b"abc");
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "rest");
+    }
+
+    #[test]
+    fn boundary_reads_the_content_type_parameter() {
+        assert_eq!(
+            boundary("multipart/form-data; boundary=\"a b\""),
+            Some("a b")
+        );
+        assert_eq!(boundary("audio/wav"), None);
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    audio::Recorder,
+    capture::AudioCapture,
+    dictation::{DictationMachine, DictationPhase},
+    model_warmup::ModelState,
+    settings::SettingsStore,
+    transcription::local::LocalWhisper,
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IdleSettings {
+    /// After this many minutes with no keyboard or mouse input and no
+    /// dictation, unload the model, release audio capture, and pause the
+    /// background polls until the next hotkey press. 0 never goes dormant.
+    pub dormant_after_minutes: u64,
+}
+
+/// Payload of `telepathy://idle` and `get_idle_state`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleState {
+    pub dormant: bool,
+    /// Seconds since the last keyboard or mouse input, when the platform
+    /// reports it.
+    pub system_idle_secs: Option<u64>,
+}
+
+/// Quartz's time since the last input event of any kind.
+#[cfg(target_os = "macos")]
+fn system_idle() -> Option<Duration> {
+    use objc2_core_graphics::{CGEventSource, CGEventSourceStateID, CGEventType};
+
+    /// `kCGAnyInputEventType`.
+    const ANY_INPUT: CGEventType = CGEventType(u32::MAX);
+
+    let secs = CGEventSource::seconds_since_last_event_type(
+        CGEventSourceStateID::HIDSystemState,
+        ANY_INPUT,
+    );
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(target_os = "windows")]
+fn system_idle() -> Option<Duration> {
+    use windows_sys::Win32::{
+        System::SystemInformation::GetTickCount,
+        UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+    };
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: `info` is a valid LASTINPUTINFO with its size filled in.
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // Both are tick counts that wrap after 49.7 days.
+    // SAFETY: no arguments.
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(u64::from(
+        now.wrapping_sub(info.dwTime),
+    )))
+}
+
+/// Mutter's idle monitor on GNOME, or the ScreenSaver interface KDE and
+/// others implement. Neither is available on bare X11 or wlroots sessions.
+#[cfg(target_os = "linux")]
+fn system_idle() -> Option<Duration> {
+    use std::sync::OnceLock;
+
+    use zbus::blocking::{Connection, Proxy};
+
+    static BUS: OnceLock<Option<Connection>> = OnceLock::new();
+    let conn = BUS.get_or_init(|| Connection::session().ok()).as_ref()?;
+    let mutter = Proxy::new(
+        conn,
+        "org.gnome.Mutter.IdleMonitor",
+        "/org/gnome/Mutter/IdleMonitor/Core",
+        "org.gnome.Mutter.IdleMonitor",
+    )
+    .ok()
+    .and_then(|proxy| proxy.call::<_, _, u64>("GetIdletime", &()).ok())
+    .map(Duration::from_millis);
+    mutter.or_else(|| {
+        Proxy::new(
+            conn,
+            "org.freedesktop.ScreenSaver",
+            "/org/freedesktop/ScreenSaver",
+            "org.freedesktop.ScreenSaver",
+        )
+        .ok()?
+        .call::<_, _, u32>("GetSessionIdleTime", &())
+        .ok()
+        .map(|secs| Duration::from_secs(u64::from(secs)))
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn system_idle() -> Option<Duration> {
+    None
+}
+
+/// Whether the app has torn down its background work for inactivity.
+#[derive(Default)]
+pub struct Dormancy(AtomicBool);
+
+impl Dormancy {
+    /// Polls skip their work while this is set.
+    pub fn active(app: &AppHandle) -> bool {
+        app.state::<Dormancy>().0.load(Ordering::Acquire)
+    }
+
+    /// Leaves dormancy after a hotkey press; the model reload is left to
+    /// [`ModelState::ensure_loaded`].
+    pub fn wake(app: &AppHandle) {
+        if app.state::<Dormancy>().0.swap(false, Ordering::AcqRel) {
+            tracing::info!("leaving dormancy");
+            emit(app, false, None);
+        }
+    }
+}
+
+fn emit(app: &AppHandle, dormant: bool, idle: Option<Duration>) {
+    let state = IdleState {
+        dormant,
+        system_idle_secs: idle.map(|idle| idle.as_secs()),
+    };
+    let _ = app.emit("telepathy://idle", state);
+}
+
+async fn enter(app: &AppHandle, idle: Option<Duration>) {
+    if app.state::<Dormancy>().0.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tracing::info!(idle_secs = ?idle.map(|idle| idle.as_secs()), "going dormant");
+    ModelState::release(app).await;
+    app.state::<LocalWhisper>().release();
+    let recorder = app.state::<Recorder>();
+    if recorder.is_running() {
+        if let Err(err) = recorder.stop() {
+            tracing::warn!(%err, "recording did not stop cleanly");
+        }
+    }
+    let capture = app.state::<AudioCapture>();
+    if capture.is_running() {
+        if let Err(err) = capture.stop() {
+            tracing::warn!(%err, "audio capture did not stop cleanly");
+        }
+    }
+    emit(app, true, idle);
+}
+
+async fn watch(app: AppHandle) {
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+        let minutes = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .idle
+            .dormant_after_minutes;
+        if minutes == 0 || Dormancy::active(&app) {
+            continue;
+        }
+        let threshold = Duration::from_secs(minutes * 60);
+        let idle = app.state::<DictationMachine>().phase() == DictationPhase::Idle;
+        if !idle || app.state::<ModelState>().idle_for() < threshold {
+            continue;
+        }
+        // Without a platform idle time, dictation inactivity alone decides.
+        let system = crate::run_blocking(|| Ok(system_idle()))
+            .await
+            .ok()
+            .flatten();
+        if system.is_none_or(|system| system >= threshold) {
+            enter(&app, system).await;
+        }
+    }
+}
+
+/// Starts the inactivity check; settings are re-read each tick.
+pub fn init(app: &AppHandle) {
+    tauri::async_runtime::spawn(watch(app.clone()));
+}
+
+#[tauri::command]
+pub async fn get_idle_state(app: AppHandle) -> Result<IdleState, String> {
+    let idle = crate::run_blocking(|| Ok(system_idle())).await?;
+    Ok(IdleState {
+        dormant: Dormancy::active(&app),
+        system_idle_secs: idle.map(|idle| idle.as_secs()),
+    })
+}
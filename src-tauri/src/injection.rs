@@ -0,0 +1,842 @@
+use std::{
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::SettingsStore;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasteSettings {
+    /// Put back whatever was on the clipboard before a paste once the
+    /// keystroke has landed. Skipped when the text is left on the clipboard
+    /// for a manual paste.
+    pub restore_clipboard: bool,
+    /// How long to wait after the keystroke before restoring. Apps read the
+    /// clipboard asynchronously, so too short a delay pastes the old contents.
+    pub restore_delay_ms: u64,
+    /// Type the transcript key by key instead of pasting it, for terminals,
+    /// VMs, and remote desktops that ignore a synthesized paste.
+    pub typewriter: bool,
+    /// Apps that are always typed into, matched against the `id` that
+    /// `inspect_paste_target` reports (bundle identifier on macOS,
+    /// executable name on Windows and Linux).
+    pub typewriter_apps: Vec<String>,
+    pub typing: TypingSettings,
+    /// Send the text into the focused app. Off leaves it on the clipboard
+    /// for a manual paste.
+    pub auto_paste: bool,
+    /// Added after every transcript, e.g. a space so the next dictation
+    /// doesn't run into this one.
+    pub append: String,
+    /// Per-app overrides; the first profile whose `app` matches the
+    /// frontmost app applies.
+    pub app_profiles: Vec<AppPasteProfile>,
+    /// Mark clipboard writes as concealed and transient so clipboard
+    /// managers and cloud clipboard sync skip them and dictations stay out
+    /// of clipboard history.
+    pub conceal_clipboard: bool,
+}
+
+impl Default for PasteSettings {
+    fn default() -> Self {
+        Self {
+            restore_clipboard: false,
+            restore_delay_ms: 250,
+            typewriter: false,
+            typewriter_apps: Vec::new(),
+            typing: TypingSettings::default(),
+            auto_paste: true,
+            append: String::new(),
+            app_profiles: Vec::new(),
+            conceal_clipboard: true,
+        }
+    }
+}
+
+/// Paste behavior for one app. Unset fields keep the global setting.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppPasteProfile {
+    /// Matched like `typewriter_apps`, ignoring ASCII case.
+    pub app: String,
+    #[serde(default)]
+    pub typewriter: Option<bool>,
+    #[serde(default)]
+    pub auto_paste: Option<bool>,
+    #[serde(default)]
+    pub append: Option<String>,
+    #[serde(default)]
+    pub restore_clipboard: Option<bool>,
+}
+
+impl PasteSettings {
+    /// Applies `typewriter_apps` and the first matching app profile for the
+    /// frontmost app. Blocking, like [`crate::focus::paste_target`].
+    pub fn for_target(mut self, app: &AppHandle) -> Self {
+        if self.typewriter_apps.is_empty() && self.app_profiles.is_empty() {
+            return self;
+        }
+        let Some(id) = crate::focus::frontmost_app(app).and_then(|app| app.id) else {
+            return self;
+        };
+        self.typewriter |= self
+            .typewriter_apps
+            .iter()
+            .any(|listed| listed.eq_ignore_ascii_case(&id));
+        let profile = self
+            .app_profiles
+            .iter()
+            .find(|profile| profile.app.eq_ignore_ascii_case(&id))
+            .cloned();
+        if let Some(profile) = profile {
+            tracing::debug!(app = %id, "paste profile applied");
+            self.typewriter = profile.typewriter.unwrap_or(self.typewriter);
+            self.auto_paste = profile.auto_paste.unwrap_or(self.auto_paste);
+            self.append = profile.append.unwrap_or(self.append);
+            self.restore_clipboard = profile.restore_clipboard.unwrap_or(self.restore_clipboard);
+        }
+        self
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TypingSettings {
+    /// Characters sent per batch of key events.
+    pub chunk_chars: usize,
+    /// Pause after each batch. Slow remote sessions drop keys that arrive
+    /// faster than they can forward them.
+    pub char_delay_ms: u64,
+}
+
+impl Default for TypingSettings {
+    fn default() -> Self {
+        Self {
+            chunk_chars: 1,
+            char_delay_ms: 5,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteResult {
+    pub pasted: bool,
+    pub method: InsertionMethod,
+    /// Why the keystroke was withheld; the text is still on the clipboard.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<PasteBlock>,
+    /// Whether the previous clipboard contents were put back afterwards.
+    pub restore: bool,
+    pub timings: PasteTimings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InsertionMethod {
+    /// Clipboard write plus a synthesized paste keystroke.
+    Clipboard,
+    /// Written straight into the focused control; the clipboard is untouched.
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    Accessibility,
+    /// Individual key events, one character at a time; the clipboard is
+    /// untouched.
+    Typing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteBlock {
+    /// The focused app runs as administrator and Windows (UIPI) drops
+    /// synthesized input from non-elevated processes.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    ElevatedTarget,
+    /// The UAC prompt, lock screen, or another secure desktop has the
+    /// input focus; nothing can be typed there.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    SecureDesktop,
+    /// A Wayland session where neither the RemoteDesktop portal nor ydotool
+    /// is available; `get_build_info` reports why.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    NoInputMethod,
+    /// A password field has the focus, or secure keyboard entry is on.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    SecureField,
+}
+
+/// Injection overhead in fractional milliseconds, measured inside the backend.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteTimings {
+    pub clipboard_ms: f64,
+    pub keystroke_ms: f64,
+    pub total_ms: f64,
+}
+
+fn elapsed_ms(since: Instant) -> f64 {
+    since.elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use objc2_core_foundation::CFRetained;
+    use objc2_core_graphics::{
+        CGEvent, CGEventFlags, CGEventSource, CGEventSourceStateID, CGEventTapLocation,
+    };
+
+    /// `kVK_ANSI_V` from HIToolbox's Events.h.
+    const KEY_CODE_V: u16 = 0x09;
+    /// `kVK_Delete`, the backspace key.
+    const KEY_CODE_DELETE: u16 = 0x33;
+    /// Text past this many UTF-16 units in one keyboard event is dropped.
+    const MAX_EVENT_UNITS: usize = 20;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn IsSecureEventInputEnabled() -> u8;
+    }
+
+    /// Secure keyboard entry, which password prompts, some terminals, and
+    /// login dialogs turn on while they have focus.
+    pub fn secure_input_enabled() -> bool {
+        // SAFETY: no arguments; only reads the process-wide flag.
+        unsafe { IsSecureEventInputEnabled() != 0 }
+    }
+
+    /// A long-lived HID event source, so each paste is two CGEventPost calls
+    /// instead of an `osascript` process.
+    pub struct KeystrokeSource(CFRetained<CGEventSource>);
+
+    // SAFETY: CGEventSource is an immutable CF object and the CGEvent
+    // creation/posting APIs are documented as thread-safe.
+    unsafe impl Send for KeystrokeSource {}
+
+    impl KeystrokeSource {
+        pub fn new() -> Result<Self, String> {
+            CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+                .map(Self)
+                .ok_or("Unable to create a keyboard event source.".to_string())
+        }
+
+        pub fn press_paste(&self) -> Result<(), String> {
+            for key_down in [true, false] {
+                let event = CGEvent::new_keyboard_event(Some(&self.0), KEY_CODE_V, key_down)
+                    .ok_or("Unable to create paste keystroke.".to_string())?;
+                CGEvent::set_flags(Some(&event), CGEventFlags::MaskCommand);
+                CGEvent::post(CGEventTapLocation::HIDEventTap, Some(&event));
+            }
+            Ok(())
+        }
+
+        pub fn press_backspace(&self, count: usize) -> Result<(), String> {
+            for _ in 0..count {
+                for key_down in [true, false] {
+                    let event =
+                        CGEvent::new_keyboard_event(Some(&self.0), KEY_CODE_DELETE, key_down)
+                            .ok_or("Unable to create backspace keystroke.".to_string())?;
+                    CGEvent::set_flags(Some(&event), CGEventFlags::empty());
+                    CGEvent::post(CGEventTapLocation::HIDEventTap, Some(&event));
+                }
+            }
+            Ok(())
+        }
+
+        /// Types `text` as keyboard events carrying Unicode strings, so the
+        /// keyboard layout doesn't matter. Chunks never split a character.
+        pub fn type_text(&self, text: &str) -> Result<(), String> {
+            let mut units: Vec<u16> = Vec::with_capacity(MAX_EVENT_UNITS);
+            let mut buf = [0u16; 2];
+            for ch in text.chars() {
+                let encoded = ch.encode_utf16(&mut buf);
+                if units.len() + encoded.len() > MAX_EVENT_UNITS {
+                    self.post_units(&units)?;
+                    units.clear();
+                }
+                units.extend_from_slice(encoded);
+            }
+            if !units.is_empty() {
+                self.post_units(&units)?;
+            }
+            Ok(())
+        }
+
+        fn post_units(&self, units: &[u16]) -> Result<(), String> {
+            for key_down in [true, false] {
+                let event = CGEvent::new_keyboard_event(Some(&self.0), 0, key_down)
+                    .ok_or("Unable to create a typing event.".to_string())?;
+                // SAFETY: `units` outlives the call and its length is passed
+                // alongside it.
+                unsafe {
+                    CGEvent::keyboard_set_unicode_string(
+                        Some(&event),
+                        units.len() as _,
+                        units.as_ptr(),
+                    );
+                }
+                CGEvent::post(CGEventTapLocation::HIDEventTap, Some(&event));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// What was on the clipboard before a paste, in the richest form arboard
+/// can read back and write again.
+enum ClipboardSnapshot {
+    Files(Vec<PathBuf>),
+    Image(arboard::ImageData<'static>),
+    Text(String),
+    Empty,
+}
+
+/// Clipboard and keystroke resources kept alive across pastes. Both are
+/// created lazily on first use and recreated if they go stale.
+#[derive(Default)]
+pub struct Injector {
+    clipboard: Mutex<Option<arboard::Clipboard>>,
+    #[cfg(target_os = "macos")]
+    keystrokes: Mutex<Option<macos::KeystrokeSource>>,
+    #[cfg(target_os = "linux")]
+    keystrokes: Mutex<Option<crate::x11::KeystrokeSource>>,
+    #[cfg(target_os = "linux")]
+    wayland: Mutex<Option<crate::wayland::Keyboard>>,
+    /// Characters the last paste put into the focused app, for
+    /// [`Injector::erase_last`].
+    last_inserted: Mutex<Option<usize>>,
+}
+
+/// Writes `text`, flagged with `conceal` so clipboard history tools leave it
+/// out: `org.nspasteboard.ConcealedType` on macOS, the KDE password manager
+/// hint on Linux, and `ExcludeClipboardContentFromMonitorProcessing` (plus
+/// the history and cloud sync opt-outs) on Windows.
+fn set_text(
+    handle: &mut arboard::Clipboard,
+    text: &str,
+    conceal: bool,
+) -> Result<(), arboard::Error> {
+    let set = handle.set();
+    if !conceal {
+        return set.text(text);
+    }
+    #[cfg(target_os = "macos")]
+    let set = {
+        use arboard::SetExtApple;
+        set.exclude_from_history()
+    };
+    #[cfg(target_os = "linux")]
+    let set = {
+        use arboard::SetExtLinux;
+        set.exclude_from_history()
+    };
+    #[cfg(target_os = "windows")]
+    let set = {
+        use arboard::SetExtWindows;
+        set.exclude_from_monitoring()
+            .exclude_from_history()
+            .exclude_from_cloud()
+    };
+    set.text(text)
+}
+
+impl Injector {
+    /// Puts `text` on the clipboard, concealed from clipboard managers when
+    /// `conceal` is set.
+    pub fn write_clipboard(&self, text: &str, conceal: bool) -> Result<(), String> {
+        let mut clipboard = self.clipboard.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(handle) = clipboard.as_mut() {
+            if set_text(handle, text, conceal).is_ok() {
+                return Ok(());
+            }
+            // The cached handle can die (e.g. X11 connection reset); retry fresh.
+            *clipboard = None;
+        }
+        let mut handle =
+            arboard::Clipboard::new().map_err(|err| format!("Clipboard init failed: {err}"))?;
+        set_text(&mut handle, text, conceal)
+            .map_err(|err| format!("Clipboard write failed: {err}"))?;
+        *clipboard = Some(handle);
+        Ok(())
+    }
+
+    /// Reads the current clipboard, or `None` when it cannot be opened (in
+    /// which case there is nothing safe to restore).
+    fn snapshot_clipboard(&self) -> Option<ClipboardSnapshot> {
+        let mut clipboard = self.clipboard.lock().unwrap_or_else(|err| err.into_inner());
+        if clipboard.is_none() {
+            *clipboard = arboard::Clipboard::new().ok();
+        }
+        let handle = clipboard.as_mut()?;
+        // File copies usually carry a text form too, so check the richer
+        // formats first.
+        if let Ok(files) = handle.get().file_list() {
+            if !files.is_empty() {
+                return Some(ClipboardSnapshot::Files(files));
+            }
+        }
+        if let Ok(image) = handle.get_image() {
+            return Some(ClipboardSnapshot::Image(image));
+        }
+        match handle.get_text() {
+            Ok(text) => Some(ClipboardSnapshot::Text(text)),
+            Err(arboard::Error::ContentNotAvailable) => Some(ClipboardSnapshot::Empty),
+            Err(_) => None,
+        }
+    }
+
+    /// Puts `snapshot` back unless something other than `pasted` has been
+    /// copied since, which the user would not want overwritten.
+    fn restore_clipboard(&self, snapshot: ClipboardSnapshot, pasted: &str) -> Result<bool, String> {
+        let mut clipboard = self.clipboard.lock().unwrap_or_else(|err| err.into_inner());
+        let handle = match clipboard.as_mut() {
+            Some(handle) => handle,
+            None => clipboard.insert(
+                arboard::Clipboard::new().map_err(|err| format!("Clipboard init failed: {err}"))?,
+            ),
+        };
+        if handle.get_text().ok().as_deref() != Some(pasted) {
+            return Ok(false);
+        }
+        match snapshot {
+            ClipboardSnapshot::Files(files) => handle.set().file_list(&files),
+            ClipboardSnapshot::Image(image) => handle.set_image(image),
+            ClipboardSnapshot::Text(text) => handle.set_text(text),
+            ClipboardSnapshot::Empty => handle.clear(),
+        }
+        .map_err(|err| format!("Clipboard restore failed: {err}"))?;
+        Ok(true)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn with_keystroke_source(
+        &self,
+        send: impl FnOnce(&macos::KeystrokeSource) -> Result<(), String>,
+    ) -> Result<bool, String> {
+        if !crate::ax::is_process_trusted(false) {
+            return Err(
+                "Paste keystroke was blocked. Enable Accessibility access for Telepathy."
+                    .to_string(),
+            );
+        }
+        let mut source = self
+            .keystrokes
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if source.is_none() {
+            *source = Some(macos::KeystrokeSource::new()?);
+        }
+        if let Some(source) = source.as_ref() {
+            send(source)?;
+        }
+        Ok(true)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn send_paste_keystroke(&self) -> Result<bool, String> {
+        self.with_keystroke_source(macos::KeystrokeSource::press_paste)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn send_typed_text(&self, text: &str) -> Result<bool, String> {
+        self.with_keystroke_source(|source| source.type_text(text))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn send_typed_text(&self, text: &str) -> Result<bool, String> {
+        crate::win32::type_text(text)?;
+        Ok(true)
+    }
+
+    /// xdotool on X11, where XTest alone can't reach characters missing
+    /// from the layout; the paste path's Wayland keyboard otherwise.
+    #[cfg(target_os = "linux")]
+    fn send_typed_text(&self, text: &str) -> Result<bool, String> {
+        use crate::x11;
+
+        match x11::session() {
+            x11::Session::X11 if x11::on_path("xdotool") => {
+                x11::xdotool_type(text)?;
+                Ok(true)
+            }
+            x11::Session::X11 => Err("Typing on X11 needs xdotool.".to_string()),
+            x11::Session::Wayland => {
+                self.with_wayland_keyboard(|keyboard| keyboard.type_text(text))
+            }
+            x11::Session::Unknown => Ok(false),
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    fn send_typed_text(&self, _text: &str) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    /// Types `text` in batches of `chunk_chars`, pausing between them.
+    pub fn type_text(&self, text: &str, typing: &TypingSettings) -> Result<bool, String> {
+        let chars: Vec<char> = text.chars().collect();
+        let delay = Duration::from_millis(typing.char_delay_ms);
+        for (index, chunk) in chars.chunks(typing.chunk_chars.max(1)).enumerate() {
+            if index > 0 && !delay.is_zero() {
+                std::thread::sleep(delay);
+            }
+            let chunk: String = chunk.iter().collect();
+            if !self.send_typed_text(&chunk)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    #[cfg(target_os = "windows")]
+    fn send_paste_keystroke(&self) -> Result<bool, String> {
+        crate::win32::press_paste()?;
+        Ok(true)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn send_paste_keystroke(&self) -> Result<bool, String> {
+        use crate::x11::{self, KeystrokeSource};
+
+        match x11::session() {
+            x11::Session::X11 => {}
+            x11::Session::Wayland => return self.send_wayland_paste(),
+            x11::Session::Unknown => return Ok(false),
+        }
+        self.with_x11_keystrokes(KeystrokeSource::press_paste, x11::xdotool_paste)
+    }
+
+    /// Sends through the cached XTest connection, falling back to xdotool
+    /// when XTest is unavailable.
+    #[cfg(target_os = "linux")]
+    fn with_x11_keystrokes(
+        &self,
+        send: impl Fn(&crate::x11::KeystrokeSource) -> Result<(), String>,
+        xdotool: impl FnOnce() -> Result<(), String>,
+    ) -> Result<bool, String> {
+        use crate::x11::{self, KeystrokeSource};
+
+        let mut source = self
+            .keystrokes
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if let Some(cached) = source.as_ref() {
+            if send(cached).is_ok() {
+                return Ok(true);
+            }
+            // The X connection may have been reset; retry on a fresh one.
+            *source = None;
+        }
+        let fresh = match KeystrokeSource::new() {
+            Ok(fresh) => fresh,
+            Err(_) if x11::on_path("xdotool") => {
+                xdotool()?;
+                return Ok(true);
+            }
+            Err(err) => return Err(err),
+        };
+        send(&fresh)?;
+        *source = Some(fresh);
+        Ok(true)
+    }
+
+    /// Connects lazily: the first paste may wait on the compositor's consent
+    /// dialog, later ones reuse the granted session.
+    #[cfg(target_os = "linux")]
+    fn with_wayland_keyboard(
+        &self,
+        send: impl Fn(&crate::wayland::Keyboard) -> Result<(), String>,
+    ) -> Result<bool, String> {
+        let mut keyboard = self.wayland.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(cached) = keyboard.as_ref() {
+            if send(cached).is_ok() {
+                return Ok(true);
+            }
+            // The portal session was closed (e.g. revoked); start a new one.
+            *keyboard = None;
+        }
+        let fresh = crate::wayland::Keyboard::connect()?;
+        send(&fresh)?;
+        *keyboard = Some(fresh);
+        Ok(true)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn send_wayland_paste(&self) -> Result<bool, String> {
+        self.with_wayland_keyboard(crate::wayland::Keyboard::press_paste)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    fn send_paste_keystroke(&self) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn send_backspace(&self, count: usize) -> Result<bool, String> {
+        self.with_keystroke_source(|source| source.press_backspace(count))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn send_backspace(&self, count: usize) -> Result<bool, String> {
+        crate::win32::press_backspace(count)?;
+        Ok(true)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn send_backspace(&self, count: usize) -> Result<bool, String> {
+        use crate::x11;
+
+        match x11::session() {
+            x11::Session::X11 => self.with_x11_keystrokes(
+                |source| source.press_backspace(count),
+                || x11::xdotool_backspace(count),
+            ),
+            x11::Session::Wayland => {
+                self.with_wayland_keyboard(|keyboard| keyboard.press_backspace(count))
+            }
+            x11::Session::Unknown => Ok(false),
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    fn send_backspace(&self, _count: usize) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    /// Backspaces over the text the last paste inserted, for "delete that"
+    /// spoken before anything else in a dictation. Only works once per
+    /// paste, and only while the cursor hasn't moved since; returns whether
+    /// anything was erased.
+    pub fn erase_last(&self) -> Result<bool, String> {
+        let Some(count) = self
+            .last_inserted
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+        else {
+            return Ok(false);
+        };
+        if self.paste_block().is_some() {
+            return Ok(false);
+        }
+        self.send_backspace(count)
+    }
+
+    /// Checked before sending, since a keystroke dropped by the OS is
+    /// otherwise indistinguishable from one that landed.
+    pub fn paste_block(&self) -> Option<PasteBlock> {
+        #[cfg(target_os = "macos")]
+        if macos::secure_input_enabled() || crate::ax::focused_is_secure() {
+            return Some(PasteBlock::SecureField);
+        }
+        #[cfg(target_os = "windows")]
+        if crate::win32::secure_desktop_active() {
+            return Some(PasteBlock::SecureDesktop);
+        }
+        #[cfg(target_os = "windows")]
+        if crate::win32::foreground_blocks_input() {
+            return Some(PasteBlock::ElevatedTarget);
+        }
+        #[cfg(target_os = "linux")]
+        if crate::x11::session() == crate::x11::Session::Wayland
+            && self
+                .wayland
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .is_none()
+            && crate::wayland::probe_paste().is_err()
+        {
+            return Some(PasteBlock::NoInputMethod);
+        }
+        None
+    }
+
+    #[cfg(target_os = "windows")]
+    fn insert_via_accessibility(&self, text: &str) -> Result<(), String> {
+        crate::win32::insert_text(text)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn insert_via_accessibility(&self, text: &str) -> Result<(), String> {
+        if !crate::ax::is_process_trusted(false) {
+            return Err("Accessibility access has not been granted.".to_string());
+        }
+        crate::ax::insert_text(text)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn insert_via_accessibility(&self, _text: &str) -> Result<(), String> {
+        Err("Direct insertion is not supported on this platform.".to_string())
+    }
+
+    /// Inserts `text` with `method` alone, with no fallback, and reports
+    /// whether it was sent. Used by the injection test.
+    pub fn inject_with(&self, method: InsertionMethod, text: &str) -> Result<bool, String> {
+        match method {
+            InsertionMethod::Accessibility => Ok(self.insert_via_accessibility(text).is_ok()),
+            InsertionMethod::Clipboard => {
+                self.write_clipboard(text, true)?;
+                self.send_paste_keystroke()
+            }
+            InsertionMethod::Typing => self.type_text(text, &TypingSettings::default()),
+        }
+    }
+
+    /// Inserts `text` into the focused control through the accessibility
+    /// API where it supports that; otherwise puts it on the clipboard and,
+    /// where supported, sends the paste keystroke to the frontmost app.
+    /// With `restore_clipboard` set, the previous clipboard contents are put
+    /// back after the keystroke. In typewriter mode the text is typed
+    /// instead, falling back to the clipboard only when typing is
+    /// unavailable. With `auto_paste` off, the text only goes on the
+    /// clipboard.
+    pub fn paste(&self, text: &str, options: &PasteSettings) -> Result<PasteResult, String> {
+        let result = self.insert(text, options);
+        *self
+            .last_inserted
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = match &result {
+            Ok(result) if result.pasted => {
+                Some(text.chars().count() + options.append.chars().count())
+            }
+            _ => None,
+        };
+        result
+    }
+
+    fn insert(&self, text: &str, options: &PasteSettings) -> Result<PasteResult, String> {
+        let started = Instant::now();
+        let text = &format!("{text}{}", options.append);
+        let blocked = self.paste_block();
+        let send = blocked.is_none() && options.auto_paste;
+        if send && options.typewriter {
+            let typed = self
+                .type_text(text, &options.typing)
+                .inspect_err(|err| tracing::warn!(%err, "typing failed"))?;
+            if typed {
+                return Ok(PasteResult {
+                    pasted: true,
+                    method: InsertionMethod::Typing,
+                    blocked: None,
+                    restore: false,
+                    timings: PasteTimings {
+                        clipboard_ms: 0.0,
+                        keystroke_ms: elapsed_ms(started),
+                        total_ms: elapsed_ms(started),
+                    },
+                });
+            }
+        }
+        if send {
+            match self.insert_via_accessibility(text) {
+                Ok(()) => {
+                    return Ok(PasteResult {
+                        pasted: true,
+                        method: InsertionMethod::Accessibility,
+                        blocked: None,
+                        restore: false,
+                        timings: PasteTimings {
+                            clipboard_ms: 0.0,
+                            keystroke_ms: elapsed_ms(started),
+                            total_ms: elapsed_ms(started),
+                        },
+                    })
+                }
+                Err(err) => tracing::debug!(%err, "direct insertion refused; pasting instead"),
+            }
+        }
+
+        let clipboard_started = Instant::now();
+        let snapshot = options
+            .restore_clipboard
+            .then(|| self.snapshot_clipboard())
+            .flatten();
+        self.write_clipboard(text, options.conceal_clipboard)?;
+        let clipboard_ms = elapsed_ms(clipboard_started);
+
+        let keystroke_started = Instant::now();
+        let pasted = send
+            && self
+                .send_paste_keystroke()
+                .inspect_err(|err| tracing::warn!(%err, "paste keystroke failed"))?;
+        if !pasted {
+            tracing::info!(?blocked, "left transcript on the clipboard");
+        }
+        let timings = PasteTimings {
+            clipboard_ms,
+            keystroke_ms: elapsed_ms(keystroke_started),
+            total_ms: elapsed_ms(started),
+        };
+
+        let restore = match snapshot {
+            Some(snapshot) if pasted => {
+                std::thread::sleep(Duration::from_millis(options.restore_delay_ms));
+                self.restore_clipboard(snapshot, text)
+                    .inspect_err(|err| tracing::warn!(%err, "clipboard restore failed"))
+                    .unwrap_or(false)
+            }
+            _ => false,
+        };
+        Ok(PasteResult {
+            pasted,
+            method: InsertionMethod::Clipboard,
+            blocked,
+            restore,
+            timings,
+        })
+    }
+}
+
+/// [`Injector::paste`], plus `telepathy://blocked-secure-field` when a
+/// password field kept the text from being sent.
+pub fn paste(app: &AppHandle, text: &str, options: &PasteSettings) -> Result<PasteResult, String> {
+    let result = app.state::<Injector>().paste(text, options)?;
+    if result.blocked == Some(PasteBlock::SecureField) {
+        let _ = app.emit("telepathy://blocked-secure-field", ());
+    }
+    Ok(result)
+}
+
+/// `restore`, `typewriter`, and `conceal` override the
+/// `paste.restoreClipboard`, `paste.typewriter`, and `paste.concealClipboard`
+/// settings, and any per-app profile, for this call.
+#[tauri::command]
+pub async fn paste_text(
+    app: AppHandle,
+    text: String,
+    restore: Option<bool>,
+    typewriter: Option<bool>,
+    conceal: Option<bool>,
+) -> Result<PasteResult, String> {
+    let options = app.state::<SettingsStore>().snapshot().paste;
+    crate::run_blocking(move || {
+        let mut options = options.for_target(&app);
+        if let Some(restore) = restore {
+            options.restore_clipboard = restore;
+        }
+        if let Some(typewriter) = typewriter {
+            options.typewriter = typewriter;
+        }
+        if let Some(conceal) = conceal {
+            options.conceal_clipboard = conceal;
+        }
+        paste(&app, &text, &options)
+    })
+    .await
+}
+
+/// Writes `text` straight into the focused element through the platform
+/// accessibility API (AXUIElement on macOS, UI Automation on Windows) and
+/// falls back to a clipboard paste when the element isn't writable. The
+/// result's `method` says which strategy ran.
+#[tauri::command]
+pub async fn insert_text_ax(app: AppHandle, text: String) -> Result<PasteResult, String> {
+    let options = PasteSettings {
+        typewriter: false,
+        typewriter_apps: Vec::new(),
+        ..app.state::<SettingsStore>().snapshot().paste
+    };
+    crate::run_blocking(move || paste(&app, &text, &options)).await
+}
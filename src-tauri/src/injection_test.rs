@@ -0,0 +1,169 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Condvar, Mutex, MutexGuard,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::injection::{Injector, InsertionMethod, PasteBlock};
+
+const WINDOW_LABEL: &str = "injection-test";
+/// Mixed case, digits, and an accented letter, so keymap and encoding
+/// mistakes show up as a mismatch rather than a pass.
+const SAMPLE_TEXT: &str = "Telepathy test: café 42";
+const PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+const RESULT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Time for the window manager to hand focus to the scratch window.
+const FOCUS_SETTLE: Duration = Duration::from_millis(250);
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InjectionTestReport {
+    pub strategy: InsertionMethod,
+    /// Whether the strategy ran at all; `false` means it isn't available
+    /// in this session.
+    pub sent: bool,
+    /// The scratch field ended up holding exactly the sample text.
+    pub verified: bool,
+    pub expected: &'static str,
+    pub observed: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<PasteBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Default)]
+struct Page {
+    ready: bool,
+    value: String,
+}
+
+/// What the scratch window's text field reports back, plus a flag so only
+/// one test runs at a time.
+#[derive(Default)]
+pub struct InjectionTest {
+    running: AtomicBool,
+    page: Mutex<Page>,
+    changed: Condvar,
+}
+
+impl InjectionTest {
+    fn page(&self) -> MutexGuard<'_, Page> {
+        self.page.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Waits until `done` holds for the page, returning whether it did.
+    fn wait_for(&self, timeout: Duration, done: impl Fn(&Page) -> bool) -> bool {
+        let (page, _) = self
+            .changed
+            .wait_timeout_while(self.page(), timeout, |page| !done(page))
+            .unwrap_or_else(|err| err.into_inner());
+        done(&page)
+    }
+}
+
+/// Clears the running flag and closes the scratch window however the test ends.
+struct Cleanup<'a> {
+    app: &'a AppHandle,
+    test: &'a InjectionTest,
+}
+
+impl Drop for Cleanup<'_> {
+    fn drop(&mut self) {
+        if let Some(window) = self.app.get_webview_window(WINDOW_LABEL) {
+            let _ = window.destroy();
+        }
+        self.test.running.store(false, Ordering::Release);
+    }
+}
+
+fn run(app: &AppHandle, strategy: InsertionMethod) -> Result<InjectionTestReport, String> {
+    let test = app.state::<InjectionTest>();
+    if test.running.swap(true, Ordering::AcqRel) {
+        return Err("An injection test is already running.".to_string());
+    }
+    let _cleanup = Cleanup { app, test: &test };
+    *test.page() = Page::default();
+    if let Some(stale) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = stale.destroy();
+    }
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        WINDOW_LABEL,
+        WebviewUrl::App("index.html#injection-test".into()),
+    )
+    .title("Telepathy injection test")
+    .inner_size(360.0, 160.0)
+    .resizable(false)
+    .always_on_top(true)
+    .center()
+    .focused(true)
+    .build()
+    .map_err(|err| format!("Unable to open the test window: {err}"))?;
+    if !test.wait_for(PAGE_TIMEOUT, |page| page.ready) {
+        return Err("The test window did not load.".to_string());
+    }
+    let _ = window.set_focus();
+    thread::sleep(FOCUS_SETTLE);
+
+    let started = Instant::now();
+    let injector = app.state::<Injector>();
+    let blocked = injector.paste_block();
+    let sent = match blocked {
+        Some(_) => false,
+        None => injector.inject_with(strategy, SAMPLE_TEXT)?,
+    };
+    let verified = sent && test.wait_for(RESULT_TIMEOUT, |page| page.value == SAMPLE_TEXT);
+    let observed = test.page().value.clone();
+    let detail = match (blocked, sent, verified) {
+        (Some(_), _, _) => Some("Input is blocked in this session.".to_string()),
+        (None, false, _) => Some("This strategy is not available here.".to_string()),
+        (None, true, false) if observed.is_empty() => {
+            Some("The text was sent but never arrived.".to_string())
+        }
+        (None, true, false) => Some("Different text arrived than was sent.".to_string()),
+        (None, true, true) => None,
+    };
+    Ok(InjectionTestReport {
+        strategy,
+        sent,
+        verified,
+        expected: SAMPLE_TEXT,
+        observed,
+        blocked,
+        detail,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Opens a scratch window with a single text field, injects a sample string
+/// with `strategy`, and checks what the field received. The clipboard
+/// strategy leaves the sample on the clipboard.
+#[tauri::command]
+pub async fn test_injection(
+    app: AppHandle,
+    strategy: InsertionMethod,
+) -> Result<InjectionTestReport, String> {
+    crate::run_blocking(move || run(&app, strategy)).await
+}
+
+/// Sent by the scratch window once its field has focus.
+#[tauri::command]
+pub fn injection_test_ready(test: State<'_, InjectionTest>) {
+    test.page().ready = true;
+    test.changed.notify_all();
+}
+
+/// Sent by the scratch window on every edit of its field.
+#[tauri::command]
+pub fn injection_test_input(test: State<'_, InjectionTest>, value: String) {
+    test.page().value = value;
+    test.changed.notify_all();
+}
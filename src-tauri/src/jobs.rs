@@ -0,0 +1,280 @@
+use std::{
+    collections::VecDeque,
+    sync::{Condvar, Mutex, MutexGuard},
+    thread,
+    time::Instant,
+};
+
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobPriority {
+    /// Work a user is waiting on right now (hold-to-dictate, paste).
+    Live,
+    /// Long-running work nobody is watching (file transcription, imports).
+    Background,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JobSettings {
+    pub workers: usize,
+    /// Cap on workers busy with background jobs at once; the rest stay free
+    /// for live work.
+    pub background_workers: usize,
+}
+
+impl Default for JobSettings {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            background_workers: 1,
+        }
+    }
+}
+
+impl JobSettings {
+    fn limits(&self) -> (usize, usize) {
+        let workers = self.workers.max(1);
+        (workers, self.background_workers.clamp(1, workers))
+    }
+}
+
+type Work = Box<dyn FnOnce(&JobContext) + Send>;
+
+struct QueuedJob {
+    id: u64,
+    priority: JobPriority,
+    label: &'static str,
+    queued_at: Instant,
+    work: Work,
+}
+
+struct RunningJob {
+    id: u64,
+    priority: JobPriority,
+    label: &'static str,
+    started_at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    live: VecDeque<QueuedJob>,
+    background: VecDeque<QueuedJob>,
+    running: Vec<RunningJob>,
+    next_id: u64,
+    workers: usize,
+    target_workers: usize,
+    background_limit: usize,
+}
+
+impl Inner {
+    fn running_background(&self) -> usize {
+        self.running
+            .iter()
+            .filter(|job| job.priority == JobPriority::Background)
+            .count()
+    }
+
+    /// Live jobs always go first; background jobs only while under the cap.
+    fn next_job(&mut self) -> Option<QueuedJob> {
+        if let Some(job) = self.live.pop_front() {
+            return Some(job);
+        }
+        if self.running_background() < self.background_limit {
+            return self.background.pop_front();
+        }
+        None
+    }
+}
+
+/// Handed to every job. Background jobs call [`JobContext::yield_to_live`]
+/// between chunks of work so live jobs never wait behind them.
+pub struct JobContext<'a> {
+    pool: &'a JobPool,
+}
+
+impl JobContext<'_> {
+    /// Runs any queued live jobs on this thread before returning, so a live
+    /// job preempts a long background job at its next checkpoint even when
+    /// every worker is busy.
+    pub fn yield_to_live(&self) {
+        loop {
+            let job = self.pool.lock().live.pop_front();
+            match job {
+                Some(job) => self.pool.execute(job),
+                None => return,
+            }
+        }
+    }
+}
+
+/// Fixed set of worker threads draining a two-level priority queue.
+#[derive(Default)]
+pub struct JobPool {
+    inner: Mutex<Inner>,
+    wake: Condvar,
+}
+
+impl JobPool {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn execute(&self, job: QueuedJob) {
+        self.lock().running.push(RunningJob {
+            id: job.id,
+            priority: job.priority,
+            label: job.label,
+            started_at: Instant::now(),
+        });
+        (job.work)(&JobContext { pool: self });
+        self.lock().running.retain(|running| running.id != job.id);
+        // A finished background job may let a queued one start.
+        self.wake.notify_all();
+    }
+
+    fn worker(app: AppHandle) {
+        let pool = app.state::<JobPool>();
+        loop {
+            let job = {
+                let mut inner = pool.lock();
+                loop {
+                    if inner.workers > inner.target_workers {
+                        inner.workers -= 1;
+                        return;
+                    }
+                    if let Some(job) = inner.next_job() {
+                        break job;
+                    }
+                    inner = pool.wake.wait(inner).unwrap_or_else(|err| err.into_inner());
+                }
+            };
+            pool.execute(job);
+        }
+    }
+
+    /// Applies new thread counts. Extra workers are spawned immediately;
+    /// surplus ones exit once their current job finishes.
+    pub fn configure(app: &AppHandle, settings: &JobSettings) {
+        let pool = app.state::<JobPool>();
+        let (workers, background_limit) = settings.limits();
+        let spawn = {
+            let mut inner = pool.lock();
+            inner.target_workers = workers;
+            inner.background_limit = background_limit;
+            let spawn = workers.saturating_sub(inner.workers);
+            inner.workers += spawn;
+            spawn
+        };
+        for _ in 0..spawn {
+            let app = app.clone();
+            thread::spawn(move || Self::worker(app));
+        }
+        pool.wake.notify_all();
+    }
+
+    fn submit(&self, priority: JobPriority, label: &'static str, work: Work) {
+        let mut inner = self.lock();
+        inner.next_id += 1;
+        let job = QueuedJob {
+            id: inner.next_id,
+            priority,
+            label,
+            queued_at: Instant::now(),
+            work,
+        };
+        match priority {
+            JobPriority::Live => inner.live.push_back(job),
+            JobPriority::Background => inner.background.push_back(job),
+        }
+        drop(inner);
+        self.wake.notify_all();
+    }
+
+    fn snapshot(&self) -> JobQueue {
+        let inner = self.lock();
+        let queued = inner
+            .live
+            .iter()
+            .chain(inner.background.iter())
+            .map(|job| JobInfo {
+                id: job.id,
+                priority: job.priority,
+                label: job.label,
+                elapsed_ms: job.queued_at.elapsed().as_millis() as u64,
+            })
+            .collect();
+        let running = inner
+            .running
+            .iter()
+            .map(|job| JobInfo {
+                id: job.id,
+                priority: job.priority,
+                label: job.label,
+                elapsed_ms: job.started_at.elapsed().as_millis() as u64,
+            })
+            .collect();
+        JobQueue {
+            workers: inner.target_workers,
+            background_workers: inner.background_limit,
+            running,
+            queued,
+        }
+    }
+}
+
+/// Runs `work` on the job pool at `priority` and waits for its result;
+/// the pool counterpart of [`crate::run_blocking`].
+pub async fn run<T, F>(
+    app: &AppHandle,
+    priority: JobPriority,
+    label: &'static str,
+    work: F,
+) -> Result<T, String>
+where
+    F: FnOnce(&JobContext) -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    app.state::<JobPool>().submit(
+        priority,
+        label,
+        Box::new(move |context| {
+            let result = work(context);
+            if let Err(err) = &result {
+                tracing::warn!(job = label, %err, "job failed");
+            }
+            let _ = sender.send(result);
+        }),
+    );
+    receiver
+        .await
+        .map_err(|_| format!("The {label} job was dropped before finishing."))?
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: u64,
+    pub priority: JobPriority,
+    pub label: &'static str,
+    /// Time spent queued, or running for jobs in `running`.
+    pub elapsed_ms: u64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobQueue {
+    pub workers: usize,
+    pub background_workers: usize,
+    pub running: Vec<JobInfo>,
+    /// Queued jobs in the order they will start.
+    pub queued: Vec<JobInfo>,
+}
+
+#[tauri::command]
+pub fn get_job_queue(pool: State<'_, JobPool>) -> JobQueue {
+    pool.snapshot()
+}
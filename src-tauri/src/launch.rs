@@ -0,0 +1,238 @@
+use tauri::AppHandle;
+#[cfg(not(target_os = "macos"))]
+use tauri::Manager;
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LaunchSettings {
+    /// Run from the menu bar or tray alone: no Dock icon on macOS and no
+    /// taskbar button elsewhere.
+    pub background_agent: bool,
+}
+
+/// Whether the app starts when the user logs in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LaunchAtLogin {
+    Enabled,
+    Disabled,
+    /// Registered, but macOS holds it until the user allows it under Login
+    /// Items in System Settings.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    RequiresApproval,
+}
+
+/// The main app as an SMAppService login item (macOS 13 and later).
+#[cfg(target_os = "macos")]
+mod login_item {
+    use objc2::runtime::AnyClass;
+    use objc2_service_management::{SMAppService, SMAppServiceStatus};
+
+    use super::LaunchAtLogin;
+
+    fn service() -> Result<objc2::rc::Retained<SMAppService>, String> {
+        if AnyClass::get(c"SMAppService").is_none() {
+            return Err("Launch at login needs macOS 13 or later.".to_string());
+        }
+        // SAFETY: the class exists, checked above.
+        Ok(unsafe { SMAppService::mainAppService() })
+    }
+
+    pub fn status(_app: &tauri::AppHandle) -> Result<LaunchAtLogin, String> {
+        // SAFETY: a status query has no preconditions.
+        let status = unsafe { service()?.status() };
+        Ok(match status {
+            SMAppServiceStatus::Enabled => LaunchAtLogin::Enabled,
+            SMAppServiceStatus::RequiresApproval => LaunchAtLogin::RequiresApproval,
+            _ => LaunchAtLogin::Disabled,
+        })
+    }
+
+    pub fn set(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+        let service = service()?;
+        if enabled == (status(app)? != LaunchAtLogin::Disabled) {
+            return Ok(());
+        }
+        // SAFETY: registering the main app has no preconditions; failures
+        // come back as an NSError.
+        let result = unsafe {
+            if enabled {
+                service.registerAndReturnError()
+            } else {
+                service.unregisterAndReturnError()
+            }
+        };
+        result.map_err(|err| format!("Login item not updated: {}", err.localizedDescription()))?;
+        if status(app)? == LaunchAtLogin::RequiresApproval {
+            // SAFETY: opens System Settings; no preconditions.
+            unsafe { SMAppService::openSystemSettingsLoginItems() };
+        }
+        Ok(())
+    }
+}
+
+/// A value under the per-user `Run` key.
+#[cfg(target_os = "windows")]
+mod login_item {
+    use windows_sys::Win32::System::Registry::HKEY_CURRENT_USER;
+
+    use super::LaunchAtLogin;
+    use crate::win32;
+
+    const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+    fn value_name(app: &tauri::AppHandle) -> String {
+        app.package_info().name.clone()
+    }
+
+    pub fn status(app: &tauri::AppHandle) -> Result<LaunchAtLogin, String> {
+        Ok(
+            match win32::registry_string(HKEY_CURRENT_USER, RUN_KEY, &value_name(app)) {
+                Some(_) => LaunchAtLogin::Enabled,
+                None => LaunchAtLogin::Disabled,
+            },
+        )
+    }
+
+    pub fn set(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+        if !enabled {
+            return win32::delete_registry_value(HKEY_CURRENT_USER, RUN_KEY, &value_name(app));
+        }
+        let exe = std::env::current_exe()
+            .map_err(|err| format!("Unable to find the app executable: {err}"))?;
+        win32::set_registry_string(
+            HKEY_CURRENT_USER,
+            RUN_KEY,
+            &value_name(app),
+            &format!("\"{}\"", exe.display()),
+        )
+    }
+}
+
+/// An XDG autostart entry in `~/.config/autostart`.
+#[cfg(target_os = "linux")]
+mod login_item {
+    use std::{fs, path::PathBuf};
+
+    use tauri::Manager;
+
+    use super::LaunchAtLogin;
+
+    fn entry_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let dir = app
+            .path()
+            .config_dir()
+            .map_err(|err| format!("No config directory for autostart: {err}"))?;
+        Ok(dir
+            .join("autostart")
+            .join(format!("{}.desktop", app.config().identifier)))
+    }
+
+    /// Quoted for an `Exec` key, per the Desktop Entry spec.
+    fn exec_arg(path: &str) -> String {
+        let mut quoted = String::from("\"");
+        for ch in path.chars() {
+            if matches!(ch, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(ch);
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    pub fn status(app: &tauri::AppHandle) -> Result<LaunchAtLogin, String> {
+        Ok(if entry_path(app)?.exists() {
+            LaunchAtLogin::Enabled
+        } else {
+            LaunchAtLogin::Disabled
+        })
+    }
+
+    pub fn set(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+        let path = entry_path(app)?;
+        if !enabled {
+            return match fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    Err(format!("Unable to remove {}: {err}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+        // An AppImage runs from a temporary mount; start the image itself.
+        let exe = match std::env::var("APPIMAGE") {
+            Ok(image) => image,
+            Err(_) => std::env::current_exe()
+                .map_err(|err| format!("Unable to find the app executable: {err}"))?
+                .to_string_lossy()
+                .into_owned(),
+        };
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={}\nX-GNOME-Autostart-enabled=true\n",
+            app.package_info().name,
+            exec_arg(&exe)
+        );
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+        }
+        fs::write(&path, entry).map_err(|err| format!("Unable to write {}: {err}", path.display()))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod login_item {
+    use super::LaunchAtLogin;
+
+    pub fn status(_app: &tauri::AppHandle) -> Result<LaunchAtLogin, String> {
+        Ok(LaunchAtLogin::Disabled)
+    }
+
+    pub fn set(_app: &tauri::AppHandle, _enabled: bool) -> Result<(), String> {
+        Err("Launch at login is not supported on this platform.".to_string())
+    }
+}
+
+/// Shows or hides the Dock icon (the activation policy on macOS) or the
+/// taskbar button for `background_agent`.
+pub fn apply(app: &AppHandle, settings: &LaunchSettings) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let policy = if settings.background_agent {
+            tauri::ActivationPolicy::Accessory
+        } else {
+            tauri::ActivationPolicy::Regular
+        };
+        app.set_activation_policy(policy)
+            .map_err(|err| format!("Unable to change the activation policy: {err}"))
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Main window not found.".to_string())?;
+        window
+            .set_skip_taskbar(settings.background_agent)
+            .map_err(|err| format!("Unable to update the taskbar button: {err}"))
+    }
+}
+
+#[tauri::command]
+pub async fn get_launch_at_login(app: AppHandle) -> Result<LaunchAtLogin, String> {
+    crate::run_blocking(move || login_item::status(&app)).await
+}
+
+/// Registers or removes the app as a login item: SMAppService on macOS, the
+/// `Run` registry key on Windows, and an XDG autostart entry on Linux. On
+/// macOS the result can be `requiresApproval`, with System Settings opened
+/// to Login Items for the user to allow it.
+#[tauri::command]
+pub async fn set_launch_at_login(app: AppHandle, enabled: bool) -> Result<LaunchAtLogin, String> {
+    crate::run_blocking(move || {
+        login_item::set(&app, enabled)?;
+        let status = login_item::status(&app)?;
+        tracing::info!(?status, "launch at login updated");
+        Ok(status)
+    })
+    .await
+}
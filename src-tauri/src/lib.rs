@@ -1,168 +1,99 @@
-use std::process::Command;
-
-use tauri::{Emitter, Manager};
+use tauri::{Manager, WindowEvent};
 use tauri_plugin_global_shortcut::ShortcutState;
 
-const HOLD_TO_RECORD_SHORTCUT: &str = "CommandOrControl+Shift+Space";
-
-#[derive(Clone, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct HotkeyEventPayload {
-    state: &'static str,
-    shortcut: &'static str,
-}
-
-#[derive(serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PasteResult {
-    pasted: bool,
-}
-
-#[derive(serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct AccessibilityStatus {
-    granted: bool,
-    detail: Option<String>,
-}
-
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-enum SettingsPanel {
-    Camera,
-    Accessibility,
-}
-
-#[tauri::command]
-fn paste_text(text: String) -> Result<PasteResult, String> {
-    let mut clipboard =
-        arboard::Clipboard::new().map_err(|err| format!("Clipboard init failed: {err}"))?;
-    clipboard
-        .set_text(text)
-        .map_err(|err| format!("Clipboard write failed: {err}"))?;
-
-    #[cfg(target_os = "macos")]
-    {
-        let status = Command::new("osascript")
-            .args([
-                "-e",
-                "tell application \"System Events\" to keystroke \"v\" using command down",
-            ])
-            .status()
-            .map_err(|err| format!("Unable to trigger paste keystroke: {err}"))?;
-        if !status.success() {
-            return Err(
-                "Paste keystroke was blocked. Enable Accessibility access for Telepathy."
-                    .to_string(),
-            );
-        }
-        return Ok(PasteResult { pasted: true });
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        Ok(PasteResult { pasted: false })
-    }
-}
-
-#[tauri::command]
-fn check_accessibility_permission() -> AccessibilityStatus {
-    #[cfg(target_os = "macos")]
-    {
-        let output = Command::new("osascript")
-            .args([
-                "-e",
-                "tell application \"System Events\" to get UI elements enabled",
-            ])
-            .output();
-
-        match output {
-            Ok(result) if result.status.success() => {
-                let stdout = String::from_utf8_lossy(&result.stdout)
-                    .trim()
-                    .to_lowercase();
-                AccessibilityStatus {
-                    granted: stdout == "true",
-                    detail: None,
-                }
-            }
-            Ok(result) => {
-                let stderr = String::from_utf8_lossy(&result.stderr).trim().to_string();
-                AccessibilityStatus {
-                    granted: false,
-                    detail: if stderr.is_empty() {
-                        None
-                    } else {
-                        Some(stderr)
-                    },
-                }
-            }
-            Err(err) => AccessibilityStatus {
-                granted: false,
-                detail: Some(err.to_string()),
-            },
-        }
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        AccessibilityStatus {
-            granted: true,
-            detail: None,
-        }
-    }
-}
-
-#[tauri::command]
-fn open_system_settings(panel: SettingsPanel) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        let urls: &[&str] = match panel {
-            SettingsPanel::Camera => &[
-                "x-apple.systempreferences:com.apple.preference.security?Privacy_Camera",
-                "x-apple.systempreferences:com.apple.settings.PrivacySecurity.extension?Privacy_Camera",
-                "x-apple.systempreferences:com.apple.preference.security",
-            ],
-            SettingsPanel::Accessibility => &[
-                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility",
-                "x-apple.systempreferences:com.apple.settings.PrivacySecurity.extension?Privacy_Accessibility",
-                "x-apple.systempreferences:com.apple.preference.security",
-            ],
-        };
-
-        let mut last_error: Option<String> = None;
-
-        for url in urls {
-            let output = Command::new("open")
-                .arg(url)
-                .output()
-                .map_err(|err| format!("Unable to execute 'open' for {url}: {err}"))?;
-            if output.status.success() {
-                return Ok(());
-            }
-
-            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-            let status = output
-                .status
-                .code()
-                .map_or_else(|| "unknown".to_string(), |code| code.to_string());
-            let detail = if stderr.is_empty() {
-                format!("open exited with status {status} for {url}")
-            } else {
-                format!("open exited with status {status} for {url}: {stderr}")
-            };
-            last_error = Some(detail);
-        }
+mod accessibility;
+mod analytics;
+#[cfg(target_os = "linux")]
+mod atspi;
+mod audio;
+pub mod audio_buffer;
+#[cfg(target_os = "macos")]
+mod ax;
+mod benchmark;
+mod build_info;
+mod capture;
+pub mod cli;
+mod costs;
+mod crash_reports;
+mod deep_links;
+mod destinations;
+mod dictation;
+mod email;
+#[cfg(target_os = "macos")]
+mod event_tap;
+mod event_throttle;
+mod file_transcription;
+mod focus;
+mod history;
+mod http_api;
+mod idle;
+mod injection;
+mod injection_test;
+mod jobs;
+mod launch;
+mod llm;
+mod locale;
+mod logging;
+mod microphone;
+mod model_warmup;
+mod notifications;
+mod overlay;
+mod panels;
+mod permissions;
+mod pipeline;
+#[cfg(target_os = "linux")]
+mod pipewire;
+mod platform;
+mod postprocess;
+mod power;
+mod quiet_mode;
+mod recordings;
+mod screen_context;
+#[cfg(target_os = "macos")]
+mod screen_ocr;
+#[cfg(target_os = "macos")]
+mod scripting;
+mod secrets;
+mod settings;
+mod sounds;
+mod startup;
+mod storage;
+mod system_events;
+mod system_settings;
+mod transcript_stream;
+mod transcription;
+mod translation;
+mod tray;
+mod triggers;
+mod updater;
+mod usage;
+mod vad;
+mod vocabulary;
+mod voice_commands;
+#[cfg(target_os = "windows")]
+mod wasapi;
+mod watchdog;
+#[cfg(target_os = "linux")]
+mod wayland;
+mod webhooks;
+#[cfg(target_os = "windows")]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
 
-        return Err(
-            last_error.unwrap_or_else(|| "Unable to open System Settings.".to_string()),
-        );
-    }
+/// Default hold-to-record accelerator; `set_hotkey` replaces it at runtime.
+pub(crate) const HOLD_TO_RECORD_SHORTCUT: &str = "CommandOrControl+Shift+Space";
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        let _ = panel;
-        Err("System Settings deep link is only implemented on macOS.".to_string())
-    }
+/// Runs process spawning or blocking FFI on the blocking pool, so a slow
+/// child process never stalls the async runtime or the main thread.
+pub(crate) async fn run_blocking<T, F>(work: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|err| format!("Background task failed: {err}"))?
 }
 
 #[tauri::command]
@@ -170,51 +101,329 @@ fn set_overlay_passthrough(
     app: tauri::AppHandle,
     ignore_cursor_events: bool,
 ) -> Result<(), String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or("Main window not found.".to_string())?;
-    window
-        .set_ignore_cursor_events(ignore_cursor_events)
-        .map_err(|err| format!("Unable to update overlay passthrough mode: {err}"))
+    overlay::set_passthrough(&app, ignore_cursor_events)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let started = std::time::Instant::now();
     tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            // Links are routed by the deep-link plugin; focusing the overlay
+            // would pull focus from the app a dictation should paste into.
+            if argv.iter().any(|arg| deep_links::is_link(arg)) {
+                return;
+            }
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.show();
                 let _ = window.unminimize();
                 let _ = window.set_focus();
             }
         }))
-        .setup(|_app| Ok(()))
+        .plugin(tauri_plugin_deep_link::init())
+        .setup(move |app| {
+            let startup = startup::Startup::new(started);
+            let handle = app.handle();
+            app.manage(storage::Storage::detect(handle));
+            app.manage(startup.measure("settings", || settings::SettingsStore::load(handle)));
+            let log_settings = handle.state::<settings::SettingsStore>().snapshot().logging;
+            app.manage(logging::Logging::init(handle, &log_settings));
+            crash_reports::install(handle);
+            app.manage(startup.measure("analytics", || analytics::Analytics::load(handle)));
+            app.manage(startup.measure("history", || history::History::load(handle)));
+            app.manage(destinations::PendingDestination::default());
+            app.manage(translation::PendingTranslation::default());
+            app.manage(destinations::LastTranscript::default());
+            app.manage(dictation::DictationMachine::default());
+            app.manage(event_throttle::EventThrottle::default());
+            app.manage(injection::Injector::default());
+            app.manage(injection_test::InjectionTest::default());
+            app.manage(triggers::InputTriggers::default());
+            app.manage(accessibility::AccessibilityMonitor::default());
+            app.manage(permissions::PermissionMonitor::default());
+            app.manage(model_warmup::ModelState::default());
+            app.manage(benchmark::LatencyBenchmark::default());
+            app.manage(jobs::JobPool::default());
+            app.manage(focus::FocusTracker::default());
+            app.manage(capture::AudioCapture::default());
+            app.manage(audio::Recorder::default());
+            app.manage(audio::InputDeviceMonitor::default());
+            app.manage(transcription::local::LocalWhisper::default());
+            app.manage(transcription::models::ModelManager::default());
+            app.manage(file_transcription::FileTranscriptions::default());
+            app.manage(notifications::Notifier::default());
+            app.manage(overlay::ClickThrough::default());
+            app.manage(panels::Panels::load(handle));
+            app.manage(http_api::HttpApi::default());
+            app.manage(webhooks::Webhooks::default());
+            app.manage(screen_context::ScreenContexts::default());
+            app.manage(sounds::Player::default());
+            app.manage(updater::Updates::default());
+            app.manage(power::Power::default());
+            app.manage(quiet_mode::QuietMode::default());
+            app.manage(locale::InputSources::default());
+            app.manage(idle::Dormancy::default());
+            handle.state::<triggers::InputTriggers>().register_hotkey(
+                handle,
+                handle
+                    .state::<settings::SettingsStore>()
+                    .snapshot()
+                    .triggers
+                    .hotkey
+                    .as_deref(),
+            );
+            jobs::JobPool::configure(
+                handle,
+                &handle.state::<settings::SettingsStore>().snapshot().jobs,
+            );
+            startup.measure("deepLinks", || deep_links::init(handle));
+            #[cfg(target_os = "macos")]
+            scripting::init(handle);
+            sounds::init(handle);
+            analytics::init(handle);
+            quiet_mode::init(handle);
+            triggers::init(handle);
+            updater::init(handle);
+            audio::init(handle);
+            recordings::init(handle);
+            overlay::init(handle);
+            if let Err(err) = overlay::apply(
+                handle,
+                &handle.state::<settings::SettingsStore>().snapshot().overlay,
+            ) {
+                tracing::warn!(%err, "overlay not moved");
+            }
+            if let Err(err) = launch::apply(
+                handle,
+                &handle.state::<settings::SettingsStore>().snapshot().launch,
+            ) {
+                tracing::warn!(%err, "background agent mode not applied");
+            }
+            transcription::streaming::init(handle);
+            if let Err(err) = tray::init(handle) {
+                tracing::warn!(%err, "tray icon unavailable");
+            }
+            watchdog::spawn(handle);
+            startup.mark_interactive();
+            app.manage(startup);
+
+            // Probes, device enumeration, and network calls run after the
+            // window is up; commands see defaults until they finish.
+            let deferred: Vec<(&str, startup::DeferredStep)> = vec![
+                (
+                    "triggers",
+                    Box::new(|app: &tauri::AppHandle| {
+                        let settings = app.state::<settings::SettingsStore>().snapshot();
+                        app.state::<triggers::InputTriggers>()
+                            .reconfigure(app, &settings.triggers)
+                    }),
+                ),
+                (
+                    "httpApi",
+                    Box::new(|app: &tauri::AppHandle| {
+                        let settings = app.state::<settings::SettingsStore>().snapshot();
+                        app.state::<http_api::HttpApi>()
+                            .reconfigure(app, &settings.http_api)
+                    }),
+                ),
+                (
+                    "accessibility",
+                    Box::new(|app: &tauri::AppHandle| {
+                        accessibility::init(app);
+                        permissions::init(app);
+                        Ok(())
+                    }),
+                ),
+                ("systemEvents", Box::new(system_events::init)),
+                (
+                    "settingsLinks",
+                    Box::new(|_: &tauri::AppHandle| {
+                        system_settings::init();
+                        Ok(())
+                    }),
+                ),
+                (
+                    "locale",
+                    Box::new(|app: &tauri::AppHandle| {
+                        locale::init(app);
+                        Ok(())
+                    }),
+                ),
+                (
+                    "power",
+                    Box::new(|app: &tauri::AppHandle| {
+                        power::init(app);
+                        Ok(())
+                    }),
+                ),
+                (
+                    "model",
+                    Box::new(|app: &tauri::AppHandle| {
+                        model_warmup::init(app);
+                        Ok(())
+                    }),
+                ),
+                (
+                    "idle",
+                    Box::new(|app: &tauri::AppHandle| {
+                        idle::init(app);
+                        Ok(())
+                    }),
+                ),
+                #[cfg(target_os = "linux")]
+                (
+                    "overlayHints",
+                    Box::new(|app: &tauri::AppHandle| {
+                        let window = app
+                            .get_webview_window("main")
+                            .ok_or("Main window not found.".to_string())?;
+                        let overlay = app.state::<settings::SettingsStore>().snapshot().overlay;
+                        x11::apply_overlay_hints(
+                            &window,
+                            overlay.visibility == overlay::OverlayVisibility::Everywhere,
+                        )
+                    }),
+                ),
+                #[cfg(target_os = "linux")]
+                (
+                    "focusTracking",
+                    Box::new(|app: &tauri::AppHandle| app.state::<focus::FocusTracker>().start()),
+                ),
+            ];
+            startup::Startup::defer(handle, deferred);
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // Returning from System Settings is the usual moment a grant lands.
+            match event {
+                WindowEvent::Focused(true) => {
+                    accessibility::AccessibilityMonitor::refresh_in_background(window.app_handle());
+                    permissions::PermissionMonitor::refresh_in_background(window.app_handle());
+                }
+                WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                    file_transcription::on_drop(window.app_handle(), paths);
+                }
+                _ => {}
+            }
+        })
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_shortcuts([HOLD_TO_RECORD_SHORTCUT])
-                .expect("failed to register global shortcut")
-                .with_handler(|app, _shortcut, event| {
-                    let state = match event.state {
-                        ShortcutState::Pressed => "pressed",
-                        ShortcutState::Released => "released",
-                    };
-                    let _ = app.emit(
-                        "telepathy://hotkey",
-                        HotkeyEventPayload {
-                            state,
-                            shortcut: HOLD_TO_RECORD_SHORTCUT,
-                        },
+                .with_handler(|app, shortcut, event| {
+                    triggers::on_global_shortcut(
+                        app,
+                        shortcut,
+                        event.state == ShortcutState::Pressed,
                     );
                 })
                 .build(),
         )
         .invoke_handler(tauri::generate_handler![
-            paste_text,
-            check_accessibility_permission,
-            open_system_settings,
-            set_overlay_passthrough
+            injection::paste_text,
+            injection::insert_text_ax,
+            injection_test::test_injection,
+            injection_test::injection_test_ready,
+            injection_test::injection_test_input,
+            accessibility::check_accessibility_permission,
+            microphone::check_microphone_permission,
+            microphone::request_microphone_permission,
+            permissions::get_permission_report,
+            system_settings::open_system_settings,
+            set_overlay_passthrough,
+            overlay::set_overlay_anchor,
+            overlay::move_overlay_to_display,
+            overlay::list_displays,
+            overlay::set_overlay_visibility_policy,
+            overlay::set_overlay_interactive_regions,
+            panels::open_settings_window,
+            panels::open_history_window,
+            http_api::reset_http_api_token,
+            webhooks::list_webhook_deliveries,
+            settings::get_settings,
+            settings::update_settings,
+            destinations::deliver_transcript,
+            destinations::paste_last_transcript,
+            triggers::set_hotkey_paused,
+            translation::set_translation_target,
+            email::compose_email,
+            analytics::export_metrics,
+            usage::get_usage_stats,
+            costs::get_cost_report,
+            vocabulary::import_dictionary,
+            vocabulary::list_replacements,
+            vocabulary::add_replacement,
+            vocabulary::update_replacement,
+            vocabulary::remove_replacement,
+            pipeline::process_and_paste,
+            secrets::store_secret,
+            secrets::delete_secret,
+            secrets::has_secret,
+            model_warmup::get_model_status,
+            dictation::get_dictation_state,
+            dictation::dictation_event,
+            dictation::dictation_heartbeat,
+            dictation::pause_recording,
+            dictation::resume_recording,
+            build_info::get_build_info,
+            platform::get_platform_capabilities,
+            benchmark::run_latency_benchmark,
+            jobs::get_job_queue,
+            launch::get_launch_at_login,
+            launch::set_launch_at_login,
+            focus::get_frontmost_app,
+            focus::inspect_paste_target,
+            focus::get_caret_bounds,
+            capture::list_capture_sources,
+            capture::start_capture,
+            capture::stop_capture,
+            capture::take_capture,
+            storage::get_storage_paths,
+            logging::set_log_level,
+            logging::export_logs,
+            logging::get_recent_logs,
+            logging::export_debug_bundle,
+            crash_reports::get_pending_crash_reports,
+            crash_reports::submit_crash_report,
+            crash_reports::dismiss_crash_report,
+            updater::check_for_updates,
+            updater::install_update,
+            power::get_power_state,
+            quiet_mode::get_quiet_mode,
+            idle::get_idle_state,
+            audio::start_recording,
+            audio::stop_recording,
+            audio::take_recording,
+            audio::list_audio_devices,
+            audio::set_audio_device,
+            transcription::models::list_local_models,
+            transcription::models::download_local_model,
+            transcription::models::delete_local_model,
+            transcription::models::delete_unused_local_models,
+            transcription::models::set_local_model,
+            transcription::local::transcribe_local,
+            transcription::providers::list_providers,
+            transcription::providers::set_provider,
+            transcription::providers::transcribe_with_provider,
+            transcription::providers::store_api_key,
+            transcription::providers::get_api_key,
+            transcription::providers::delete_api_key,
+            history::list_history,
+            history::search_history,
+            history::delete_entry,
+            history::clear_history,
+            history::list_revisions,
+            recordings::load_recording,
+            recordings::retranscribe,
+            file_transcription::transcribe_file,
+            triggers::get_hotkey,
+            triggers::set_hotkey,
+            locale::get_system_locale_info,
+            notifications::notification_action,
+            screen_context::request_screen_context_permission,
+            screen_context::take_screen_context,
+            startup::get_startup_report
         ])
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+use crate::{
+    costs::{self, Metered},
+    secrets,
+    transcription::providers::ProviderKind,
+};
+
+const NAME: &str = "OpenAI";
+
+/// One OpenAI chat completion with `instructions` as the system prompt and
+/// `text` as the user message, using the API key stored for transcription.
+/// Returns the reply, trimmed. The tokens used are metered.
+pub async fn complete(
+    app: &AppHandle,
+    model: &str,
+    timeout_secs: u64,
+    instructions: &str,
+    text: &str,
+) -> Result<String, String> {
+    let secret = ProviderKind::OpenAi.secret();
+    let api_key = crate::run_blocking(move || secrets::read_secret(secret))
+        .await?
+        .ok_or_else(|| format!("No API key is stored for {NAME}."))?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()
+        .map_err(|err| format!("Unable to create an HTTP client: {err}"))?;
+    let response = client
+        .post("https://api.openai.com/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({
+            "model": model,
+            "temperature": 0,
+            "messages": [
+                { "role": "system", "content": instructions },
+                { "role": "user", "content": text },
+            ],
+        }))
+        .send()
+        .await
+        .map_err(|err| format!("{NAME} request failed: {err}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{NAME} responded with {status}: {}", body.trim()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|err| format!("{NAME} sent an unreadable response: {err}"))?;
+    let metered = Metered {
+        input_tokens: body["usage"]["prompt_tokens"].as_u64().unwrap_or(0),
+        output_tokens: body["usage"]["completion_tokens"].as_u64().unwrap_or(0),
+        ..Metered::default()
+    };
+    costs::record(app, ProviderKind::OpenAi, metered).await;
+    body["choices"][0]["message"]["content"]
+        .as_str()
+        .map(|text| text.trim().to_string())
+        .ok_or_else(|| format!("{NAME}'s response has no text."))
+}
@@ -0,0 +1,394 @@
+use std::{sync::Mutex, time::Duration};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::SettingsStore;
+
+const INPUT_SOURCE_EVENT: &str = "telepathy://input-source";
+/// Windows and Linux have no app-wide input source notification to hook.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocaleSettings {
+    /// BCP 47 tag (e.g. `de-DE`) for transcription and post-processing.
+    /// Follows the system when unset.
+    pub language: Option<String>,
+    /// Let the keyboard input source pick the language, so switching to a
+    /// German layout mid-session switches dictation too.
+    pub follow_input_source: bool,
+}
+
+impl Default for LocaleSettings {
+    fn default() -> Self {
+        Self {
+            language: None,
+            follow_input_source: true,
+        }
+    }
+}
+
+/// The active keyboard layout or input method.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputSource {
+    /// Platform id, e.g. `com.apple.keylayout.German` or `xkb:de`.
+    pub id: String,
+    pub name: Option<String>,
+    /// BCP 47 tag of the language it types, when the platform says.
+    pub language: Option<String>,
+}
+
+/// Payload of `get_system_locale_info` and `telepathy://input-source`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleInfo {
+    pub ui_language: Option<String>,
+    pub region: Option<String>,
+    pub input_source: Option<InputSource>,
+    /// What dictation uses: the `locale.language` setting, else the input
+    /// source's language (when followed), else the UI language.
+    pub language: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+mod backend {
+    use std::{ffi::c_void, ptr::NonNull, sync::mpsc, time::Duration};
+
+    use block2::RcBlock;
+    use objc2_core_foundation::{CFRetained, CFType};
+    use objc2_foundation::{
+        NSArray, NSDistributedNotificationCenter, NSLocale, NSNotification, NSString,
+    };
+    use tauri::AppHandle;
+
+    use super::InputSource;
+
+    /// Input source properties may only be read on the main thread.
+    const MAIN_THREAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        static kTISPropertyInputSourceID: *const c_void;
+        static kTISPropertyLocalizedName: *const c_void;
+        static kTISPropertyInputSourceLanguages: *const c_void;
+        static kTISNotifySelectedKeyboardInputSourceChanged: *const c_void;
+        fn TISCopyCurrentKeyboardInputSource() -> *mut c_void;
+        fn TISGetInputSourceProperty(source: *mut c_void, key: *const c_void) -> *const c_void;
+    }
+
+    pub fn ui_language() -> Option<String> {
+        NSLocale::preferredLanguages()
+            .firstObject()
+            .map(|language| language.to_string())
+    }
+
+    pub fn region() -> Option<String> {
+        NSLocale::currentLocale()
+            .regionCode()
+            .map(|code| code.to_string())
+    }
+
+    /// Must run on the main thread.
+    fn read_input_source() -> Option<InputSource> {
+        // SAFETY: TIS returns a retained source (or null) that is released
+        // when `source` drops. Its properties are CFStrings and a CFArray of
+        // CFStrings, toll-free bridged to NSString and NSArray, and owned by
+        // the source.
+        unsafe {
+            let raw = NonNull::new(TISCopyCurrentKeyboardInputSource())?;
+            let source = CFRetained::from_raw(raw.cast::<CFType>());
+            let source_ptr = CFRetained::as_ptr(&source).as_ptr().cast::<c_void>();
+            let string = |key: *const c_void| {
+                TISGetInputSourceProperty(source_ptr, key)
+                    .cast::<NSString>()
+                    .as_ref()
+                    .map(|value| value.to_string())
+            };
+            let id = string(kTISPropertyInputSourceID)?;
+            let name = string(kTISPropertyLocalizedName);
+            let language = TISGetInputSourceProperty(source_ptr, kTISPropertyInputSourceLanguages)
+                .cast::<NSArray<NSString>>()
+                .as_ref()
+                .and_then(|languages| languages.firstObject())
+                .map(|language| language.to_string());
+            Some(InputSource { id, name, language })
+        }
+    }
+
+    pub fn input_source(app: &AppHandle) -> Option<InputSource> {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        app.run_on_main_thread(move || {
+            let _ = sender.send(read_input_source());
+        })
+        .ok()?;
+        receiver.recv_timeout(MAIN_THREAD_TIMEOUT).ok().flatten()
+    }
+
+    /// Text Input Sources posts a distributed notification on every switch.
+    pub fn watch(app: &AppHandle, changed: fn(&AppHandle, Option<InputSource>)) {
+        let handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            let block = RcBlock::new(move |_: NonNull<NSNotification>| {
+                changed(&handle, read_input_source());
+            });
+            // SAFETY: the notification name is an immutable CFString
+            // constant, bridged to NSString. No sender filter or queue, so
+            // the block runs on the main thread where TIS may be read, and
+            // the center keeps it for the life of the app.
+            unsafe {
+                let name = kTISNotifySelectedKeyboardInputSourceChanged
+                    .cast::<NSString>()
+                    .as_ref();
+                let _ = NSDistributedNotificationCenter::defaultCenter()
+                    .addObserverForName_object_queue_usingBlock(name, None, None, &block);
+            }
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod backend {
+    use tauri::AppHandle;
+    use windows_sys::Win32::{
+        Globalization::{
+            GetLocaleInfoEx, GetUserDefaultGeoName, GetUserDefaultUILanguage, LCIDToLocaleName,
+            LOCALE_SLOCALIZEDDISPLAYNAME,
+        },
+        System::SystemServices::LOCALE_NAME_MAX_LENGTH,
+        UI::{
+            Input::KeyboardAndMouse::GetKeyboardLayout,
+            WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
+        },
+    };
+
+    use super::InputSource;
+
+    fn from_wide(buffer: &[u16], written: i32) -> Option<String> {
+        // Counts include the terminating NUL.
+        let len = usize::try_from(written).ok()?.checked_sub(1)?;
+        (len > 0).then(|| String::from_utf16_lossy(&buffer[..len]))
+    }
+
+    fn locale_name(lcid: u32) -> Option<String> {
+        let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+        // SAFETY: the buffer length is passed alongside it.
+        let written =
+            unsafe { LCIDToLocaleName(lcid, buffer.as_mut_ptr(), buffer.len() as i32, 0) };
+        from_wide(&buffer, written)
+    }
+
+    fn display_name(locale: &str) -> Option<String> {
+        let locale: Vec<u16> = locale.encode_utf16().chain([0]).collect();
+        let mut buffer = [0u16; 128];
+        // SAFETY: `locale` is NUL-terminated and the buffer length is passed.
+        let written = unsafe {
+            GetLocaleInfoEx(
+                locale.as_ptr(),
+                LOCALE_SLOCALIZEDDISPLAYNAME,
+                buffer.as_mut_ptr(),
+                buffer.len() as i32,
+            )
+        };
+        from_wide(&buffer, written)
+    }
+
+    pub fn ui_language() -> Option<String> {
+        // SAFETY: no arguments.
+        locale_name(u32::from(unsafe { GetUserDefaultUILanguage() }))
+    }
+
+    pub fn region() -> Option<String> {
+        let mut buffer = [0u16; 16];
+        // SAFETY: the buffer length is passed alongside it.
+        let written = unsafe { GetUserDefaultGeoName(buffer.as_mut_ptr(), buffer.len() as i32) };
+        from_wide(&buffer, written)
+    }
+
+    /// Layouts are per thread, so this reads the one of the window being
+    /// typed into.
+    pub fn input_source(_app: &AppHandle) -> Option<InputSource> {
+        // SAFETY: plain queries; a null window yields thread 0, which reads
+        // the calling thread's layout.
+        let layout = unsafe {
+            let thread = GetWindowThreadProcessId(GetForegroundWindow(), std::ptr::null_mut());
+            GetKeyboardLayout(thread)
+        } as usize;
+        if layout == 0 {
+            return None;
+        }
+        let language = locale_name((layout & 0xffff) as u32);
+        Some(InputSource {
+            id: format!("{layout:08x}"),
+            name: language.as_deref().and_then(display_name),
+            language,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use std::process::Command;
+
+    use tauri::AppHandle;
+
+    use super::InputSource;
+
+    /// `de_DE.UTF-8` (or `de_DE@euro`) to `de-DE`.
+    fn posix_to_bcp47(value: &str) -> Option<String> {
+        let value = value.split(['.', '@']).next()?.trim();
+        (!value.is_empty() && value != "C" && value != "POSIX").then(|| value.replace('_', "-"))
+    }
+
+    fn env_locale(keys: &[&str]) -> Option<String> {
+        keys.iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| posix_to_bcp47(&value))
+    }
+
+    pub fn ui_language() -> Option<String> {
+        env_locale(&["LC_ALL", "LC_MESSAGES", "LANG"])
+    }
+
+    pub fn region() -> Option<String> {
+        let locale = env_locale(&["LC_ALL", "LC_CTYPE", "LANG"])?;
+        locale.split_once('-').map(|(_, region)| region.to_string())
+    }
+
+    /// GNOME keeps the most recently used source first, e.g.
+    /// `[('xkb', 'de'), ('xkb', 'us')]`. Other desktops report nothing.
+    pub fn input_source(_app: &AppHandle) -> Option<InputSource> {
+        let output = Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.input-sources", "mru-sources"])
+            .output()
+            .ok()?;
+        let raw = String::from_utf8_lossy(&output.stdout);
+        let mut fields = raw.split('\'').skip(1).step_by(2).map(str::to_string);
+        let kind = fields.next()?;
+        let layout = fields.next()?;
+        Some(InputSource {
+            id: format!("{kind}:{layout}"),
+            name: Some(layout),
+            language: None,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod backend {
+    use tauri::AppHandle;
+
+    use super::InputSource;
+
+    pub fn ui_language() -> Option<String> {
+        None
+    }
+
+    pub fn region() -> Option<String> {
+        None
+    }
+
+    pub fn input_source(_app: &AppHandle) -> Option<InputSource> {
+        None
+    }
+}
+
+/// The last input source seen, so only real switches are emitted.
+#[derive(Default)]
+pub struct InputSources(Mutex<Option<InputSource>>);
+
+impl InputSources {
+    /// Stores `source`, returning whether it differs from the last one.
+    fn replace(&self, source: &Option<InputSource>) -> bool {
+        let mut last = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let changed = *last != *source;
+        last.clone_from(source);
+        changed
+    }
+
+    fn current(&self) -> Option<InputSource> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+/// The language dictation should use, from the last input source seen.
+pub fn dictation_language(app: &AppHandle) -> Option<String> {
+    let input_source = app.state::<InputSources>().current();
+    locale_info(app, input_source).language
+}
+
+/// The language to transcribe in without a running app: the configured
+/// one, or the UI language. There is no input source to follow.
+pub fn configured_language(settings: &LocaleSettings) -> Option<String> {
+    settings.language.clone().or_else(backend::ui_language)
+}
+
+fn locale_info(app: &AppHandle, input_source: Option<InputSource>) -> LocaleInfo {
+    let settings = app.state::<SettingsStore>().snapshot().locale;
+    let ui_language = backend::ui_language();
+    let followed = input_source
+        .as_ref()
+        .filter(|_| settings.follow_input_source)
+        .and_then(|source| source.language.clone());
+    LocaleInfo {
+        language: settings.language.or(followed).or(ui_language.clone()),
+        ui_language,
+        region: backend::region(),
+        input_source,
+    }
+}
+
+fn input_source_changed(app: &AppHandle, input_source: Option<InputSource>) {
+    if !app.state::<InputSources>().replace(&input_source) {
+        return;
+    }
+    let info = locale_info(app, input_source);
+    tracing::info!(
+        input_source = ?info.input_source.as_ref().map(|source| &source.id),
+        language = ?info.language,
+        "input source changed"
+    );
+    let _ = app.emit(INPUT_SOURCE_EVENT, info);
+}
+
+/// Records the current input source and emits `telepathy://input-source`
+/// whenever the user switches to another one.
+pub fn init(app: &AppHandle) {
+    app.state::<InputSources>()
+        .replace(&backend::input_source(app));
+
+    #[cfg(target_os = "macos")]
+    backend::watch(app, input_source_changed);
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                if crate::idle::Dormancy::active(&app) {
+                    continue;
+                }
+                let handle = app.clone();
+                if let Ok(source) =
+                    crate::run_blocking(move || Ok(backend::input_source(&handle))).await
+                {
+                    input_source_changed(&app, source);
+                }
+            }
+        });
+    }
+}
+
+/// UI language, region, and keyboard input source, plus the language
+/// dictation resolves to from them and the `locale` settings.
+#[tauri::command]
+pub async fn get_system_locale_info(app: AppHandle) -> Result<LocaleInfo, String> {
+    crate::run_blocking(move || {
+        let input_source = backend::input_source(&app);
+        Ok(locale_info(&app, input_source))
+    })
+    .await
+}
@@ -0,0 +1,367 @@
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use tauri::{AppHandle, Manager, State};
+use tracing::Level;
+use tracing_appender::{non_blocking::WorkerGuard, rolling};
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, reload, Registry};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{
+    build_info,
+    settings::{Settings, SettingsStore},
+    storage::Storage,
+};
+
+const LOG_PREFIX: &str = "telepathy";
+const LOG_SUFFIX: &str = "log";
+/// Daily files kept on disk; older ones are deleted on rotation.
+const KEPT_LOG_FILES: usize = 7;
+/// The most lines `get_recent_logs` returns.
+const MAX_RECENT_LINES: usize = 2000;
+/// Settings that point at the user or can carry a credential (webhook URLs
+/// often embed a token). API keys live in the credential store, never in
+/// settings.
+const REDACTED_SETTINGS: &[&str] = &[
+    "/webhooks/urls",
+    "/crashReports/endpoint",
+    "/destinations/email/to",
+];
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogSettings {
+    /// `error`, `warn`, `info`, `debug`, or `trace`; applied at launch.
+    pub level: String,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+        }
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse()
+        .map_err(|_| format!("Unknown log level {level:?}."))
+}
+
+/// The file writer's flush guard and the handle used to change the level
+/// at runtime. Dropping the guard loses buffered lines, so it lives in
+/// managed state for the life of the app.
+pub struct Logging {
+    dir: Option<PathBuf>,
+    level: reload::Handle<LevelFilter, Registry>,
+    _guard: Option<WorkerGuard>,
+}
+
+impl Logging {
+    /// Installs the global subscriber writing daily-rotated files to the log
+    /// dir (see [`Storage`]). Without a usable dir, events are dropped.
+    pub fn init(app: &AppHandle, settings: &LogSettings) -> Self {
+        let dir = Storage::log_dir(app);
+        let level = parse_level(&settings.level).unwrap_or(LevelFilter::INFO);
+        let (filter, handle) = reload::Layer::new(level);
+        let appender = dir.as_ref().and_then(|dir| {
+            rolling::Builder::new()
+                .rotation(rolling::Rotation::DAILY)
+                .filename_prefix(LOG_PREFIX)
+                .filename_suffix(LOG_SUFFIX)
+                .max_log_files(KEPT_LOG_FILES)
+                .build(dir)
+                .ok()
+        });
+        let (writer, guard) = match appender {
+            Some(appender) => {
+                let (writer, guard) = tracing_appender::non_blocking(appender);
+                (Some(writer), Some(guard))
+            }
+            None => (None, None),
+        };
+        let file_layer = writer.map(|writer| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_target(true)
+        });
+        let subscriber = Registry::default().with(filter).with(file_layer);
+        let _ = tracing::subscriber::set_global_default(subscriber);
+        tracing::info!(version = env!("CARGO_PKG_VERSION"), "telepathy starting");
+        Self {
+            dir,
+            level: handle,
+            _guard: guard,
+        }
+    }
+
+    pub fn set_level(&self, level: &str) -> Result<(), String> {
+        let level = parse_level(level)?;
+        self.level
+            .modify(|filter| *filter = level)
+            .map_err(|err| format!("Unable to change the log level: {err}"))?;
+        tracing::info!(%level, "log level changed");
+        Ok(())
+    }
+
+    pub fn configure(app: &AppHandle, settings: &LogSettings) -> Result<(), String> {
+        app.state::<Logging>().set_level(&settings.level)
+    }
+}
+
+/// Spoken text is only ever logged in `text`, `transcript`, or `partial`
+/// fields; exports blank their values.
+fn redact(line: &str) -> String {
+    static FIELDS: OnceLock<Option<Regex>> = OnceLock::new();
+    let pattern = FIELDS
+        .get_or_init(|| Regex::new(r#"\b(text|transcript|partial)=("(?:[^"\\]|\\.)*"|\S+)"#).ok());
+    match pattern {
+        Some(pattern) => pattern.replace_all(line, "$1=[redacted]").into_owned(),
+        None => line.to_string(),
+    }
+}
+
+impl Logging {
+    fn dir(&self) -> Result<&Path, String> {
+        self.dir
+            .as_deref()
+            .ok_or("The log directory is unavailable.".to_string())
+    }
+}
+
+/// The kept log files, oldest first; their names end in the date.
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|err| format!("Unable to read {}: {err}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_PREFIX))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// One log line, as `get_recent_logs` returns it.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    /// RFC 3339, UTC.
+    pub timestamp: String,
+    /// `error`, `warn`, `info`, `debug`, or `trace`.
+    pub level: String,
+    /// Everything after the level: the dictation span if any, the module,
+    /// the message, and its fields.
+    pub message: String,
+}
+
+/// Splits a line the file layer wrote; lines a multi-line message wrapped
+/// onto don't parse.
+fn parse_line(line: &str) -> Option<(Level, LogLine)> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    let (level, message) = rest.trim_start().split_once(' ')?;
+    let level: Level = level.parse().ok()?;
+    Some((
+        level,
+        LogLine {
+            timestamp: timestamp.to_string(),
+            level: level.as_str().to_ascii_lowercase(),
+            message: message.to_string(),
+        },
+    ))
+}
+
+/// The last `limit` lines at `level` or more severe, oldest first.
+fn recent(dir: &Path, level: LevelFilter, limit: usize) -> Result<Vec<LogLine>, String> {
+    let mut lines = Vec::new();
+    'files: for file in log_files(dir)?.iter().rev() {
+        let raw = fs::read_to_string(file)
+            .map_err(|err| format!("Unable to read {}: {err}", file.display()))?;
+        // Read backwards, so wrapped lines come before the line they belong to.
+        let mut wrapped: Vec<&str> = Vec::new();
+        for line in raw.lines().rev() {
+            let Some((line_level, mut entry)) = parse_line(line) else {
+                wrapped.push(line);
+                continue;
+            };
+            for extra in wrapped.drain(..).rev() {
+                entry.message.push('\n');
+                entry.message.push_str(extra);
+            }
+            if line_level <= level {
+                lines.push(entry);
+                if lines.len() >= limit {
+                    break 'files;
+                }
+            }
+        }
+    }
+    lines.reverse();
+    Ok(lines)
+}
+
+fn bundle_error(err: impl Display) -> String {
+    format!("Unable to write the log bundle: {err}")
+}
+
+fn options() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated)
+}
+
+/// Adds the kept log files under `folder`, with transcript text redacted.
+fn add_logs(zip: &mut ZipWriter<File>, dir: &Path, folder: &str) -> Result<(), String> {
+    for file in log_files(dir)? {
+        let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let raw = fs::read_to_string(&file)
+            .map_err(|err| format!("Unable to read {}: {err}", file.display()))?;
+        let redacted: Vec<String> = raw.lines().map(redact).collect();
+        zip.start_file(format!("{folder}{name}"), options())
+            .map_err(bundle_error)?;
+        zip.write_all(redacted.join("\n").as_bytes())
+            .map_err(bundle_error)?;
+    }
+    Ok(())
+}
+
+fn export(logs: &Logging, path: PathBuf) -> Result<PathBuf, String> {
+    let dir = logs.dir()?;
+    let archive =
+        File::create(&path).map_err(|err| format!("Unable to create {}: {err}", path.display()))?;
+    let mut zip = ZipWriter::new(archive);
+    add_logs(&mut zip, dir, "")?;
+    zip.finish().map_err(bundle_error)?;
+    Ok(path)
+}
+
+/// `settings` as pretty JSON with [`REDACTED_SETTINGS`] blanked. Lists keep
+/// their length, so it still shows how many webhooks were set.
+fn redacted_settings(settings: &Settings) -> Result<String, String> {
+    let mut value = serde_json::to_value(settings)
+        .map_err(|err| format!("Unable to serialize settings: {err}"))?;
+    for pointer in REDACTED_SETTINGS {
+        match value.pointer_mut(pointer) {
+            Some(serde_json::Value::Array(items)) => {
+                for item in items {
+                    *item = "[redacted]".into();
+                }
+            }
+            Some(field) if !field.is_null() => *field = "[redacted]".into(),
+            _ => {}
+        }
+    }
+    serde_json::to_string_pretty(&value)
+        .map_err(|err| format!("Unable to serialize settings: {err}"))
+}
+
+fn export_bundle(
+    logs: &Logging,
+    storage: &Storage,
+    settings: &Settings,
+    path: PathBuf,
+) -> Result<PathBuf, String> {
+    let about = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": build_info::features(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "portable": storage.portable,
+        "createdAt": chrono::Local::now().to_rfc3339(),
+    });
+    let archive =
+        File::create(&path).map_err(|err| format!("Unable to create {}: {err}", path.display()))?;
+    let mut zip = ZipWriter::new(archive);
+    zip.start_file("about.json", options())
+        .map_err(bundle_error)?;
+    zip.write_all(about.to_string().as_bytes())
+        .map_err(bundle_error)?;
+    zip.start_file("settings.json", options())
+        .map_err(bundle_error)?;
+    zip.write_all(redacted_settings(settings)?.as_bytes())
+        .map_err(bundle_error)?;
+    // A bundle without logs still helps, e.g. when the log dir is unusable.
+    if let Ok(dir) = logs.dir() {
+        add_logs(&mut zip, dir, "logs/")?;
+    }
+    zip.finish().map_err(bundle_error)?;
+    Ok(path)
+}
+
+/// `path`, or a new time-stamped `<prefix>-….zip` in the data dir.
+fn bundle_path(app: &AppHandle, path: Option<PathBuf>, prefix: &str) -> Result<PathBuf, String> {
+    if let Some(path) = path {
+        return Ok(path);
+    }
+    let dir = Storage::data_dir(app).ok_or("App data directory is unavailable.".to_string())?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    Ok(dir.join(format!("{prefix}-{stamp}.zip")))
+}
+
+/// Changes the level until the next launch, e.g. `debug` while reproducing
+/// a bug; `logging.level` in settings is the startup default.
+#[tauri::command]
+pub fn set_log_level(logs: State<'_, Logging>, level: String) -> Result<(), String> {
+    logs.set_level(&level)
+}
+
+/// Zips the kept log files, with transcript text redacted, for attaching
+/// to a bug report. Writes to `path` when given, else to the data dir.
+#[tauri::command]
+pub async fn export_logs(app: AppHandle, path: Option<PathBuf>) -> Result<PathBuf, String> {
+    crate::run_blocking(move || {
+        let path = bundle_path(&app, path, "telepathy-logs")?;
+        // Lines still queued for the writer thread may be missing.
+        export(&app.state::<Logging>(), path)
+    })
+    .await
+}
+
+/// The last `limit` (at most 2000) log lines at `level` or more severe,
+/// oldest first, across the kept files. Lines still queued for the writer
+/// thread may be missing.
+#[tauri::command]
+pub async fn get_recent_logs(
+    app: AppHandle,
+    level: String,
+    limit: usize,
+) -> Result<Vec<LogLine>, String> {
+    let level = parse_level(&level)?;
+    crate::run_blocking(move || {
+        let logs = app.state::<Logging>();
+        recent(logs.dir()?, level, limit.min(MAX_RECENT_LINES))
+    })
+    .await
+}
+
+/// Zips everything a bug report needs: the logs with transcript text
+/// redacted, the settings with webhook URLs and addresses redacted, and
+/// the version, OS, and build features. Writes to `path` when given, else
+/// to the data dir.
+#[tauri::command]
+pub async fn export_debug_bundle(app: AppHandle, path: Option<PathBuf>) -> Result<PathBuf, String> {
+    crate::run_blocking(move || {
+        let path = bundle_path(&app, path, "telepathy-debug")?;
+        let settings = app.state::<SettingsStore>().snapshot();
+        let path = export_bundle(
+            &app.state::<Logging>(),
+            &app.state::<Storage>(),
+            &settings,
+            path,
+        )?;
+        tracing::info!("debug bundle exported");
+        Ok(path)
+    })
+    .await
+}
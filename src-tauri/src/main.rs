@@ -1,6 +1,13 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-fn main() {
-    telepathy_lib::run()
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // `telepathy transcribe …` runs headless and exits.
+    if let Some(code) = telepathy_lib::cli::run() {
+        return code;
+    }
+    telepathy_lib::run();
+    ExitCode::SUCCESS
 }
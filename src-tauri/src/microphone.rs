@@ -0,0 +1,171 @@
+use tauri::AppHandle;
+
+use crate::system_settings::{self, SettingsPanel};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MicrophonePermission {
+    Granted,
+    Denied,
+    /// Not asked yet; requesting shows the system prompt. Only macOS asks.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    Undetermined,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicrophoneStatus {
+    pub permission: MicrophonePermission,
+    /// What to tell the user when access is blocked.
+    pub detail: Option<String>,
+}
+
+impl MicrophoneStatus {
+    fn new(permission: MicrophonePermission) -> Self {
+        Self {
+            permission,
+            detail: None,
+        }
+    }
+
+    fn denied(detail: &str) -> Self {
+        Self {
+            permission: MicrophonePermission::Denied,
+            detail: Some(detail.to_string()),
+        }
+    }
+}
+
+/// AVFoundation's authorization for audio capture. Restricted (parental
+/// controls or MDM) counts as denied; the user can't change it either way.
+#[cfg(target_os = "macos")]
+pub fn probe() -> MicrophoneStatus {
+    use objc2_av_foundation::{AVAuthorizationStatus, AVCaptureDevice, AVMediaTypeAudio};
+
+    // SAFETY: AVMediaTypeAudio is an immutable framework constant.
+    let Some(audio) = (unsafe { AVMediaTypeAudio }) else {
+        return MicrophoneStatus::denied("AVFoundation does not support audio capture here.");
+    };
+    // SAFETY: audio is one of the two media types the call accepts.
+    match unsafe { AVCaptureDevice::authorizationStatusForMediaType(audio) } {
+        AVAuthorizationStatus::Authorized => MicrophoneStatus::new(MicrophonePermission::Granted),
+        AVAuthorizationStatus::NotDetermined => {
+            MicrophoneStatus::new(MicrophonePermission::Undetermined)
+        }
+        AVAuthorizationStatus::Restricted => {
+            MicrophoneStatus::denied("Microphone access is restricted by a device profile.")
+        }
+        _ => MicrophoneStatus::denied(
+            "Allow Telepathy under Privacy & Security > Microphone in System Settings.",
+        ),
+    }
+}
+
+/// Shows the system prompt and blocks until it is answered.
+#[cfg(target_os = "macos")]
+fn prompt() {
+    use std::sync::mpsc;
+
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_av_foundation::{AVCaptureDevice, AVMediaTypeAudio};
+
+    // SAFETY: AVMediaTypeAudio is an immutable framework constant.
+    let Some(audio) = (unsafe { AVMediaTypeAudio }) else {
+        return;
+    };
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let on_answer = RcBlock::new(move |granted: Bool| {
+        let _ = sender.try_send(granted.as_bool());
+    });
+    // SAFETY: the handler is retained by AVFoundation until it runs, on a
+    // queue of its choosing.
+    unsafe { AVCaptureDevice::requestAccessForMediaType_completionHandler(audio, &on_answer) };
+    if let Ok(granted) = receiver.recv() {
+        tracing::info!(granted, "microphone prompt answered");
+    }
+}
+
+/// Windows keeps the privacy toggles in the capability consent store: one
+/// for the device, one for the user, and one covering desktop apps. Any of
+/// them set to `Deny` blocks capture; missing values mean allowed.
+#[cfg(target_os = "windows")]
+pub fn probe() -> MicrophoneStatus {
+    use windows_sys::Win32::System::Registry::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+
+    const CONSENT_STORE: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+
+    let denied = |root, key: &str| {
+        crate::win32::registry_string(root, key, "Value").as_deref() == Some("Deny")
+    };
+    if denied(HKEY_LOCAL_MACHINE, CONSENT_STORE) {
+        MicrophoneStatus::denied("Microphone access is turned off for this device in Settings > Privacy & security > Microphone.")
+    } else if denied(HKEY_CURRENT_USER, CONSENT_STORE) {
+        MicrophoneStatus::denied(
+            "Turn on Microphone access in Settings > Privacy & security > Microphone.",
+        )
+    } else if denied(HKEY_CURRENT_USER, &format!(r"{CONSENT_STORE}\NonPackaged")) {
+        MicrophoneStatus::denied("Turn on \"Let desktop apps access your microphone\" in Settings > Privacy & security > Microphone.")
+    } else {
+        MicrophoneStatus::new(MicrophonePermission::Granted)
+    }
+}
+
+/// Desktop Linux has no microphone permission, except under Snap, where the
+/// `audio-record` interface must be connected.
+#[cfg(target_os = "linux")]
+pub fn probe() -> MicrophoneStatus {
+    if std::env::var_os("SNAP").is_none() {
+        return MicrophoneStatus::new(MicrophonePermission::Granted);
+    }
+    let connected = std::process::Command::new("snapctl")
+        .args(["is-connected", "audio-record"])
+        .status()
+        .is_ok_and(|status| status.success());
+    if connected {
+        MicrophoneStatus::new(MicrophonePermission::Granted)
+    } else {
+        MicrophoneStatus::denied(
+            "Run `snap connect telepathy:audio-record` to allow microphone access.",
+        )
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn probe() -> MicrophoneStatus {
+    MicrophoneStatus::new(MicrophonePermission::Granted)
+}
+
+/// Asks for access the only way each platform allows: the system prompt on
+/// macOS when it hasn't been shown, otherwise the privacy settings page.
+fn request(app: &AppHandle) -> MicrophoneStatus {
+    let status = probe();
+    match status.permission {
+        MicrophonePermission::Granted => status,
+        #[cfg(target_os = "macos")]
+        MicrophonePermission::Undetermined => {
+            prompt();
+            probe()
+        }
+        _ => {
+            if let Err(err) = system_settings::open_panel(app, SettingsPanel::Microphone) {
+                tracing::warn!(%err, "microphone settings not opened");
+            }
+            status
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn check_microphone_permission() -> Result<MicrophoneStatus, String> {
+    crate::run_blocking(|| Ok(probe())).await
+}
+
+/// Prompts for microphone access, or opens the privacy settings when the
+/// prompt can't be shown (already answered, or not a macOS feature). Returns
+/// the status afterwards; a change made in settings shows up on the next
+/// check.
+#[tauri::command]
+pub async fn request_microphone_permission(app: AppHandle) -> Result<MicrophoneStatus, String> {
+    crate::run_blocking(move || Ok(request(&app))).await
+}
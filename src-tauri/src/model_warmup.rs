@@ -0,0 +1,296 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    dictation::{DictationMachine, DictationPhase},
+    event_throttle::{EventThrottle, DEFAULT_MAX_HZ},
+    power,
+    settings::SettingsStore,
+};
+
+const SERVER_BASE_URL: &str = "http://127.0.0.1:8000";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Stop polling after this long; the overlay's own health poll takes over.
+const MAX_POLLS: u32 = 20 * 60 * 2;
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelSettings {
+    /// Ask the transcription server to load its model right after launch.
+    pub warm_on_startup: bool,
+    /// Grace period before warming, so the load doesn't compete with startup.
+    pub warmup_delay_ms: u64,
+    /// Unload the model after this many minutes without a dictation to give
+    /// its memory back; it reloads when the hotkey is pressed. 0 keeps it loaded.
+    pub unload_after_idle_minutes: u64,
+}
+
+impl Default for ModelSettings {
+    fn default() -> Self {
+        Self {
+            warm_on_startup: true,
+            warmup_delay_ms: 2000,
+            unload_after_idle_minutes: 0,
+        }
+    }
+}
+
+/// Mirrors the server's `/health` payload; `stage` is `idle`, `loading`,
+/// `ready`, `error`, or `offline` when the server can't be reached.
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStatus {
+    #[serde(default)]
+    pub stage: String,
+    #[serde(default)]
+    pub ready: bool,
+    #[serde(default)]
+    pub backend: Option<String>,
+    #[serde(default)]
+    pub model_load_ms: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Which model the server loaded; see [`ModelVariant`].
+    #[serde(default)]
+    pub variant: Option<ModelVariant>,
+}
+
+/// The server's regular model, or the one it is configured to use on
+/// battery (usually smaller). Servers without a battery model load the
+/// regular one for both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelVariant {
+    #[default]
+    Default,
+    Battery,
+}
+
+impl ModelStatus {
+    fn offline(error: String) -> Self {
+        Self {
+            stage: "offline".to_string(),
+            ready: false,
+            backend: None,
+            model_load_ms: None,
+            error: Some(error),
+            variant: None,
+        }
+    }
+}
+
+/// Why the model is being (re)loaded; sent with `telepathy://model-loading`
+/// so the overlay can explain a warm-up delay.
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum LoadReason {
+    Startup,
+    /// The hotkey was pressed while the model was unloaded.
+    Arm,
+    /// The power state calls for the other model variant.
+    Power,
+}
+
+/// Model residency: the last status seen from the server (for views opened
+/// after the events fired) and when a dictation last needed the model.
+pub struct ModelState {
+    status: Mutex<Option<ModelStatus>>,
+    last_used: Mutex<Instant>,
+    loading: AtomicBool,
+}
+
+impl Default for ModelState {
+    fn default() -> Self {
+        Self {
+            status: Mutex::new(None),
+            last_used: Mutex::new(Instant::now()),
+            loading: AtomicBool::new(false),
+        }
+    }
+}
+
+impl ModelState {
+    pub fn status(&self) -> Option<ModelStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Time since a dictation last needed the model.
+    pub fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .elapsed()
+    }
+
+    /// Marks the model as in use and reloads it if an idle unload dropped it.
+    pub fn ensure_loaded(app: &AppHandle) {
+        let state = app.state::<ModelState>();
+        *state
+            .last_used
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Instant::now();
+        let unloaded = state.status().is_some_and(|status| status.stage == "idle");
+        if unloaded {
+            spawn_load(app, LoadReason::Arm, Duration::ZERO);
+        }
+    }
+
+    /// Unloads the model if it is loaded and no dictation or load is running.
+    pub async fn release(app: &AppHandle) {
+        let state = app.state::<ModelState>();
+        let ready = state.status().is_some_and(|status| status.ready);
+        let idle = app.state::<DictationMachine>().phase() == DictationPhase::Idle;
+        if ready && idle && !state.loading.load(Ordering::Acquire) {
+            unload(app, &reqwest::Client::new()).await;
+        }
+    }
+
+    /// Reloads the model as `variant` if a different one is loaded. Waits
+    /// for an idle moment so a dictation never loses its model mid-way.
+    pub fn match_variant(app: &AppHandle, variant: ModelVariant) {
+        let state = app.state::<ModelState>();
+        let loaded = state
+            .status()
+            .filter(|status| status.ready)
+            .map(|status| status.variant.unwrap_or_default());
+        let idle = app.state::<DictationMachine>().phase() == DictationPhase::Idle;
+        if idle && loaded.is_some_and(|loaded| loaded != variant) {
+            spawn_load(app, LoadReason::Power, Duration::ZERO);
+        }
+    }
+
+    /// Stores `status` and emits `telepathy://model-status` when it changed,
+    /// plus `telepathy://model-ready` on the transition to ready.
+    fn update(&self, app: &AppHandle, status: ModelStatus) {
+        let mut current = self.status.lock().unwrap_or_else(|err| err.into_inner());
+        if current.as_ref() == Some(&status) {
+            return;
+        }
+        let was_ready = current.as_ref().is_some_and(|status| status.ready);
+        *current = Some(status.clone());
+        drop(current);
+        match &status.error {
+            Some(error) => tracing::warn!(stage = %status.stage, %error, "model status"),
+            None => tracing::info!(
+                stage = %status.stage,
+                ready = status.ready,
+                backend = ?status.backend,
+                load_ms = ?status.model_load_ms,
+                "model status"
+            ),
+        }
+        EventThrottle::emit(app, "telepathy://model-status", DEFAULT_MAX_HZ, &status);
+        if status.ready && !was_ready {
+            let _ = app.emit("telepathy://model-ready", &status);
+        }
+    }
+}
+
+async fn request_status(request: reqwest::RequestBuilder) -> ModelStatus {
+    match request.send().await {
+        // /health answers 503 while loading, with the same JSON body.
+        Ok(response) => response
+            .json::<ModelStatus>()
+            .await
+            .unwrap_or_else(|err| ModelStatus::offline(format!("Invalid health response: {err}"))),
+        Err(err) => ModelStatus::offline(format!("Transcription server unreachable: {err}")),
+    }
+}
+
+async fn load(app: AppHandle, delay: Duration) {
+    tokio::time::sleep(delay).await;
+    let client = reqwest::Client::new();
+    let state = app.state::<ModelState>();
+    let variant = power::preferred_variant(&app);
+    let mut status = request_status(
+        client
+            .post(format!("{SERVER_BASE_URL}/warmup"))
+            .json(&serde_json::json!({ "variant": variant })),
+    )
+    .await;
+
+    for _ in 0..MAX_POLLS {
+        let settled = status.ready || status.stage == "error";
+        state.update(&app, status);
+        if settled {
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+        status = request_status(client.get(format!("{SERVER_BASE_URL}/health"))).await;
+    }
+    state.loading.store(false, Ordering::Release);
+}
+
+/// Starts a load unless one is already in flight.
+fn spawn_load(app: &AppHandle, reason: LoadReason, delay: Duration) {
+    if app
+        .state::<ModelState>()
+        .loading
+        .swap(true, Ordering::AcqRel)
+    {
+        return;
+    }
+    let _ = app.emit("telepathy://model-loading", reason);
+    tauri::async_runtime::spawn(load(app.clone(), delay));
+}
+
+/// Periodically unloads the model once it has sat unused for the configured
+/// time. Settings are re-read each tick so changes apply without a restart.
+async fn unload_when_idle(app: AppHandle) {
+    let client = reqwest::Client::new();
+    loop {
+        tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+        let minutes = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .model
+            .unload_after_idle_minutes;
+        let state = app.state::<ModelState>();
+        let ready = state.status().is_some_and(|status| status.ready);
+        let idle = app.state::<DictationMachine>().phase() == DictationPhase::Idle;
+        if minutes == 0 || !ready || !idle || state.loading.load(Ordering::Acquire) {
+            continue;
+        }
+        if state.idle_for() < Duration::from_secs(minutes * 60) {
+            continue;
+        }
+        unload(&app, &client).await;
+    }
+}
+
+/// Asks the server to drop the model; the next arm reloads it.
+async fn unload(app: &AppHandle, client: &reqwest::Client) {
+    let status = request_status(client.post(format!("{SERVER_BASE_URL}/unload"))).await;
+    app.state::<ModelState>().update(app, status);
+}
+
+/// Starts warming the transcription model in the background, so the first
+/// dictation after launch doesn't pay the model load, and starts the idle
+/// unload policy.
+pub fn init(app: &AppHandle) {
+    let settings = app.state::<SettingsStore>().snapshot().model;
+    if settings.warm_on_startup {
+        spawn_load(
+            app,
+            LoadReason::Startup,
+            Duration::from_millis(settings.warmup_delay_ms),
+        );
+    }
+    tauri::async_runtime::spawn(unload_when_idle(app.clone()));
+}
+
+#[tauri::command]
+pub fn get_model_status(state: State<'_, ModelState>) -> Option<ModelStatus> {
+    state.status()
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    destinations, injection::Injector, panels, quiet_mode::QuietMode, settings::SettingsStore,
+};
+
+/// Transcripts kept for notification actions; older notifications stop
+/// responding once their transcript is evicted.
+const KEPT_TRANSCRIPTS: usize = 20;
+#[cfg(target_os = "linux")]
+const APP_NAME: &str = "Telepathy";
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    /// Notify when a transcript could only be copied, not pasted.
+    pub enabled: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Buttons shown on a notification, routed back into [`run_action`].
+#[derive(Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationAction {
+    CopyAgain,
+    OpenHistory,
+}
+
+/// Only the Windows and Linux backends show buttons.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+impl NotificationAction {
+    const ALL: [Self; 2] = [Self::CopyAgain, Self::OpenHistory];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::CopyAgain => "copyAgain",
+            Self::OpenHistory => "openHistory",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::CopyAgain => "Copy again",
+            Self::OpenHistory => "Open history",
+        }
+    }
+
+    /// `<id>:<key>`, the action string handed to the OS, so an activation
+    /// carries everything needed to route it.
+    fn encode(self, key: u64) -> String {
+        format!("{}:{key}", self.id())
+    }
+
+    fn decode(raw: &str) -> Option<(Self, u64)> {
+        let (id, key) = raw.split_once(':')?;
+        let action = Self::ALL.into_iter().find(|action| action.id() == id)?;
+        Some((action, key.parse().ok()?))
+    }
+}
+
+/// Recent notified transcripts by key, plus the Linux bus connection that
+/// receives action signals for the life of the app.
+#[derive(Default)]
+pub struct Notifier {
+    next_key: AtomicU64,
+    transcripts: Mutex<VecDeque<(u64, String)>>,
+    #[cfg(target_os = "linux")]
+    bus: Mutex<Option<zbus::blocking::Connection>>,
+}
+
+impl Notifier {
+    fn remember(&self, text: &str) -> u64 {
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        let mut transcripts = self
+            .transcripts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if transcripts.len() == KEPT_TRANSCRIPTS {
+            transcripts.pop_front();
+        }
+        transcripts.push_back((key, text.to_string()));
+        key
+    }
+
+    fn transcript(&self, key: u64) -> Option<String> {
+        self.transcripts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .find(|(kept, _)| *kept == key)
+            .map(|(_, text)| text.clone())
+    }
+}
+
+fn run_action(app: &AppHandle, action: NotificationAction, key: u64) -> Result<(), String> {
+    match action {
+        NotificationAction::CopyAgain => {
+            let text = app
+                .state::<Notifier>()
+                .transcript(key)
+                .ok_or("That transcript is no longer available.".to_string())?;
+            let conceal = app
+                .state::<SettingsStore>()
+                .snapshot()
+                .paste
+                .conceal_clipboard;
+            app.state::<Injector>().write_clipboard(&text, conceal)
+        }
+        NotificationAction::OpenHistory => panels::open_history(app),
+    }
+}
+
+/// Routes an action string from either backend; unknown or stale actions
+/// are ignored.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+fn activated(app: &AppHandle, raw: &str) {
+    if let Some((action, key)) = NotificationAction::decode(raw) {
+        let _ = run_action(app, action, key);
+    }
+}
+
+/// Shows a toast through the WinRT notifier registered under the bundle
+/// identifier, which the installer uses as the AppUserModelID.
+#[cfg(target_os = "windows")]
+fn show(app: &AppHandle, key: u64, summary: &str, body: &str) -> Result<(), String> {
+    use quick_xml::escape::escape;
+    use windows::{
+        core::{IInspectable, Interface, Ref, HSTRING},
+        Data::Xml::Dom::XmlDocument,
+        Foundation::TypedEventHandler,
+        Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED},
+        UI::Notifications::{ToastActivatedEventArgs, ToastNotification, ToastNotificationManager},
+    };
+
+    let toast_error = |err: windows::core::Error| format!("Unable to show notification: {err}");
+    // SAFETY: initializing COM on the calling thread has no preconditions;
+    // S_FALSE (already initialized) is fine.
+    let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+
+    let actions: String = NotificationAction::ALL
+        .into_iter()
+        .map(|action| {
+            format!(
+                r#"<action content="{}" arguments="{}" activationType="foreground"/>"#,
+                escape(action.label()),
+                escape(action.encode(key)),
+            )
+        })
+        .collect();
+    let xml = format!(
+        r#"<toast><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual><actions>{actions}</actions></toast>"#,
+        escape(summary),
+        escape(body),
+    );
+    let document = XmlDocument::new().map_err(toast_error)?;
+    document.LoadXml(&HSTRING::from(xml)).map_err(toast_error)?;
+    let toast = ToastNotification::CreateToastNotification(&document).map_err(toast_error)?;
+
+    let handle = app.clone();
+    let on_activated = TypedEventHandler::new(
+        move |_: Ref<'_, ToastNotification>, args: Ref<'_, IInspectable>| {
+            let args = args.ok()?.cast::<ToastActivatedEventArgs>()?;
+            activated(&handle, &args.Arguments()?.to_string());
+            Ok(())
+        },
+    );
+    toast.Activated(&on_activated).map_err(toast_error)?;
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(&app.config().identifier))
+        .and_then(|notifier| notifier.Show(&toast))
+        .map_err(toast_error)
+}
+
+/// Session bus connection whose listener thread routes `ActionInvoked`
+/// signals. Signals go to the connection that sent `Notify`, so the same
+/// connection is reused for every notification.
+#[cfg(target_os = "linux")]
+fn listen(app: &AppHandle) -> Result<zbus::blocking::Connection, String> {
+    use zbus::{
+        blocking::{Connection, MessageIterator},
+        message::Type,
+        MatchRule,
+    };
+
+    let bus_error = |err: zbus::Error| format!("Desktop notifications are unavailable: {err}");
+    let conn = Connection::session().map_err(bus_error)?;
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .interface("org.freedesktop.Notifications")
+        .and_then(|builder| builder.member("ActionInvoked"))
+        .map_err(bus_error)?
+        .build();
+    let signals = MessageIterator::for_match_rule(rule, &conn, None).map_err(bus_error)?;
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name("telepathy-notifications".to_string())
+        .spawn(move || {
+            for message in signals.flatten() {
+                if let Ok((_, action)) = message.body().deserialize::<(u32, String)>() {
+                    activated(&app, &action);
+                }
+            }
+        })
+        .map_err(|err| format!("Unable to start notification listener: {err}"))?;
+    Ok(conn)
+}
+
+/// `org.freedesktop.Notifications.Notify`; servers without the `actions`
+/// capability show the text and drop the buttons.
+#[cfg(target_os = "linux")]
+fn show(app: &AppHandle, key: u64, summary: &str, body: &str) -> Result<(), String> {
+    use std::collections::HashMap;
+    use zbus::{blocking::Proxy, zvariant::Value};
+
+    let notifier = app.state::<Notifier>();
+    let mut bus = notifier.bus.lock().unwrap_or_else(|err| err.into_inner());
+    let conn = match bus.as_ref() {
+        Some(conn) => conn,
+        None => bus.insert(listen(app)?),
+    };
+    let actions: Vec<String> = NotificationAction::ALL
+        .into_iter()
+        .flat_map(|action| [action.encode(key), action.label().to_string()])
+        .collect();
+    let hints: HashMap<&str, Value<'_>> = HashMap::new();
+    Proxy::new(
+        conn,
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        "org.freedesktop.Notifications",
+    )
+    .and_then(|proxy| {
+        proxy.call::<_, _, u32>(
+            "Notify",
+            &(APP_NAME, 0u32, "", summary, body, actions, hints, -1i32),
+        )
+    })
+    .map(|_| ())
+    .map_err(|err| format!("Unable to show notification: {err}"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn show(_app: &AppHandle, _key: u64, _summary: &str, _body: &str) -> Result<(), String> {
+    Err("Notifications are not supported on this platform.".to_string())
+}
+
+/// Tells the user a transcript landed on the clipboard instead of in the
+/// target app. Talks to D-Bus or WinRT, so callers run it off the async
+/// runtime.
+pub fn notify_clipboard_fallback(
+    app: &AppHandle,
+    settings: &NotificationSettings,
+    text: &str,
+) -> Result<(), String> {
+    if !settings.enabled || QuietMode::silenced(app) {
+        return Ok(());
+    }
+    let key = app.state::<Notifier>().remember(text);
+    let body = format!(
+        "Couldn't paste, so it's on the clipboard: {}",
+        destinations::title_from(text)
+    );
+    show(app, key, "Transcript copied", &body)
+}
+
+/// Same handling as a notification button, so the frontend can offer the
+/// actions too.
+#[tauri::command]
+pub async fn notification_action(
+    app: AppHandle,
+    action: NotificationAction,
+    key: u64,
+) -> Result<(), String> {
+    crate::run_blocking(move || run_action(&app, action, key)).await
+}
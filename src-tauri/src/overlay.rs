@@ -0,0 +1,562 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Listener, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use crate::{
+    dictation::{DictationMachine, DictationPhase, DictationTransition, STATE_EVENT},
+    focus,
+    idle::Dormancy,
+    settings::SettingsStore,
+};
+
+/// Gap between the overlay and the screen edges, in logical pixels.
+const MARGIN: f64 = 24.0;
+/// Gap between the overlay and the menu bar in `NearNotch`, and between
+/// the caret and the overlay in `FollowCaret`, in logical pixels.
+const NOTCH_GAP: f64 = 4.0;
+/// How often a dictation in `FollowCursor` moves the overlay after the
+/// pointer.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(50);
+/// How often the set of displays is checked for changes.
+const DISPLAY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How often the pointer is checked against the interactive regions; about
+/// one frame, so a button takes the click the moment it is hovered.
+const REGION_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Set while a thread is moving the overlay after the pointer.
+static FOLLOWING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayPosition {
+    /// Where the window config puts it.
+    #[default]
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    /// Centered on the display just below the menu bar, where the MacBook
+    /// camera notch is.
+    NearNotch,
+    /// Below the pointer, following it on whichever display it is on while
+    /// a dictation runs.
+    FollowCursor,
+    /// Just below the text caret of the focused field when a dictation
+    /// starts, or below the pointer when the app doesn't report its caret.
+    FollowCaret,
+}
+
+/// Where the overlay stays visible.
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayVisibility {
+    /// On every Space or workspace, and over fullscreen apps on macOS.
+    #[default]
+    Everywhere,
+    /// Above other windows on the Space it was opened on; fullscreen apps
+    /// cover it.
+    CurrentSpace,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OverlaySettings {
+    /// Corner or edge of the monitor's work area the overlay sits in, or a
+    /// spot that moves with the user.
+    pub position: OverlayPosition,
+    /// `id` from `list_displays` of the display to show the overlay on.
+    /// Unset, or when that display is disconnected, the overlay stays on
+    /// the display it is on. `FollowCursor` and `FollowCaret` ignore it.
+    pub display: Option<String>,
+    pub visibility: OverlayVisibility,
+}
+
+/// A part of the overlay that takes clicks, in logical pixels from the
+/// top-left of the window's content, as `getBoundingClientRect` reports.
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Region {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+enum ClickMode {
+    /// The whole window takes clicks, or (`true`) passes them all through.
+    Whole(bool),
+    /// Only these regions take clicks.
+    Regions(Vec<Region>),
+}
+
+/// How the overlay handles clicks, and whether a thread is tracking the
+/// pointer against its interactive regions.
+pub struct ClickThrough {
+    mode: Mutex<ClickMode>,
+    tracking: AtomicBool,
+}
+
+impl Default for ClickThrough {
+    fn default() -> Self {
+        Self {
+            mode: Mutex::new(ClickMode::Whole(false)),
+            tracking: AtomicBool::new(false),
+        }
+    }
+}
+
+impl ClickThrough {
+    fn regions(&self) -> Option<Vec<Region>> {
+        match &*self.mode.lock().unwrap_or_else(|err| err.into_inner()) {
+            ClickMode::Regions(regions) => Some(regions.clone()),
+            ClickMode::Whole(_) => None,
+        }
+    }
+
+    fn whole(&self) -> Option<bool> {
+        match *self.mode.lock().unwrap_or_else(|err| err.into_inner()) {
+            ClickMode::Whole(ignore) => Some(ignore),
+            ClickMode::Regions(_) => None,
+        }
+    }
+}
+
+/// One entry of `list_displays`.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayInfo {
+    pub id: String,
+    pub primary: bool,
+    pub scale_factor: f64,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+/// The monitor's name where the platform reports one, otherwise its origin
+/// on the desktop.
+fn display_id(monitor: &Monitor) -> String {
+    match monitor.name() {
+        Some(name) => name.clone(),
+        None => format!("{},{}", monitor.position().x, monitor.position().y),
+    }
+}
+
+fn main_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    app.get_webview_window("main")
+        .ok_or("Main window not found.".to_string())
+}
+
+/// The chosen display when it is connected, then the one the overlay is
+/// on, then the primary display.
+fn target_monitor(window: &WebviewWindow, settings: &OverlaySettings) -> Result<Monitor, String> {
+    let chosen = settings.display.as_deref().and_then(|id| {
+        window
+            .available_monitors()
+            .ok()?
+            .into_iter()
+            .find(|monitor| display_id(monitor) == id)
+    });
+    chosen
+        .or_else(|| window.current_monitor().ok().flatten())
+        .or_else(|| window.primary_monitor().ok().flatten())
+        .ok_or("No monitor to place the overlay on.".to_string())
+}
+
+fn outer_size(window: &WebviewWindow) -> Result<PhysicalSize<u32>, String> {
+    window
+        .outer_size()
+        .map_err(|err| format!("Unable to read the overlay size: {err}"))
+}
+
+fn move_to(window: &WebviewWindow, x: i32, y: i32) -> Result<(), String> {
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|err| format!("Unable to move the overlay: {err}"))
+}
+
+/// Puts the overlay centered below the span from `top` to `bottom` at `x`,
+/// or above it when there is no room below, kept inside the work area of
+/// the display at that point. Coordinates are physical pixels.
+fn place_near(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    x: f64,
+    (top, bottom): (f64, f64),
+    gap: f64,
+) -> Result<(), String> {
+    let monitor = app
+        .monitor_from_point(x, bottom)
+        .ok()
+        .flatten()
+        .ok_or("That point is not on a display.".to_string())?;
+    let size = outer_size(window)?;
+    let area = monitor.work_area();
+    let gap = (gap * monitor.scale_factor()).round() as i32;
+    let (width, height) = (size.width as i32, size.height as i32);
+
+    let (min_x, min_y) = (area.position.x, area.position.y);
+    let max_x = min_x + area.size.width as i32 - width;
+    let max_y = min_y + area.size.height as i32 - height;
+    let below = bottom.round() as i32 + gap;
+    let y = if below <= max_y {
+        below
+    } else {
+        top.round() as i32 - gap - height
+    };
+    let x = (x.round() as i32 - width / 2).clamp(min_x, max_x.max(min_x));
+    move_to(window, x, y.clamp(min_y, max_y.max(min_y)))
+}
+
+fn place_at_cursor(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    let cursor = app
+        .cursor_position()
+        .map_err(|err| format!("Unable to read the pointer position: {err}"))?;
+    place_near(app, window, cursor.x, (cursor.y, cursor.y), MARGIN)
+}
+
+/// Docks the overlay below the focused field's caret, or at the pointer
+/// when the caret can't be found. Blocking.
+fn place_at_caret(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    match focus::caret_bounds(app) {
+        Some(caret) => place_near(
+            app,
+            window,
+            caret.x,
+            (caret.y, caret.y + caret.height),
+            NOTCH_GAP,
+        ),
+        None => place_at_cursor(app, window),
+    }
+}
+
+/// Joins the overlay to every Space as a fullscreen auxiliary window at
+/// status-bar level, so it floats over fullscreen apps, or returns it to
+/// an ordinary floating window.
+#[cfg(target_os = "macos")]
+fn apply_visibility(window: &WebviewWindow, visibility: OverlayVisibility) -> Result<(), String> {
+    use objc2_app_kit::{
+        NSFloatingWindowLevel, NSStatusWindowLevel, NSWindow, NSWindowCollectionBehavior,
+    };
+
+    let (behavior, level) = match visibility {
+        OverlayVisibility::Everywhere => (
+            NSWindowCollectionBehavior::CanJoinAllSpaces
+                | NSWindowCollectionBehavior::FullScreenAuxiliary
+                | NSWindowCollectionBehavior::Stationary,
+            NSStatusWindowLevel,
+        ),
+        OverlayVisibility::CurrentSpace => {
+            (NSWindowCollectionBehavior::Managed, NSFloatingWindowLevel)
+        }
+    };
+    let target = window.clone();
+    window
+        .run_on_main_thread(move || {
+            let Ok(ns_window) = target.ns_window() else {
+                return;
+            };
+            // SAFETY: Tauri hands out the window's live NSWindow, and this
+            // runs on the main thread as AppKit requires.
+            let ns_window = unsafe { &*ns_window.cast::<NSWindow>() };
+            ns_window.setCollectionBehavior(behavior);
+            ns_window.setLevel(level);
+        })
+        .map_err(|err| format!("Unable to reach the main thread: {err}"))
+}
+
+/// Always on top everywhere; `Everywhere` also makes the window sticky
+/// across workspaces where the window manager supports it.
+#[cfg(not(target_os = "macos"))]
+fn apply_visibility(window: &WebviewWindow, visibility: OverlayVisibility) -> Result<(), String> {
+    window
+        .set_always_on_top(true)
+        .map_err(|err| format!("Unable to keep the overlay above other windows: {err}"))?;
+    window
+        .set_visible_on_all_workspaces(visibility == OverlayVisibility::Everywhere)
+        .map_err(|err| format!("Unable to update the overlay's workspaces: {err}"))
+}
+
+/// Applies the visibility policy and moves the overlay into place on its
+/// display (see [`OverlaySettings::display`]), inside the work area so it
+/// clears the menu bar, dock, and taskbar. Wayland compositors place
+/// windows themselves and ignore the position.
+pub fn apply(app: &AppHandle, settings: &OverlaySettings) -> Result<(), String> {
+    let window = main_window(app)?;
+    apply_visibility(&window, settings.visibility)?;
+    let monitor = target_monitor(&window, settings)?;
+    let size = outer_size(&window)?;
+    let area = monitor.work_area();
+    let margin = (MARGIN * monitor.scale_factor()).round() as i32;
+
+    let (left, top) = (area.position.x + margin, area.position.y + margin);
+    let right = area.position.x + area.size.width as i32 - size.width as i32 - margin;
+    let bottom = area.position.y + area.size.height as i32 - size.height as i32 - margin;
+    let center = area.position.x + (area.size.width as i32 - size.width as i32) / 2;
+    // The notch sits at the middle of the whole screen, which a side Dock
+    // shifts the work area away from.
+    let notch = (
+        monitor.position().x + (monitor.size().width as i32 - size.width as i32) / 2,
+        area.position.y + (NOTCH_GAP * monitor.scale_factor()).round() as i32,
+    );
+    let (x, y) = match settings.position {
+        OverlayPosition::TopLeft => (left, top),
+        OverlayPosition::TopCenter => (center, top),
+        OverlayPosition::TopRight => (right, top),
+        OverlayPosition::BottomLeft => (left, bottom),
+        OverlayPosition::BottomCenter => (center, bottom),
+        OverlayPosition::BottomRight => (right, bottom),
+        OverlayPosition::NearNotch => notch,
+        OverlayPosition::FollowCursor => return place_at_cursor(app, &window),
+        OverlayPosition::FollowCaret => return place_at_caret(app, &window),
+    };
+    move_to(&window, x, y)
+}
+
+/// Moves the overlay after the pointer until the dictation ends.
+fn follow_cursor(app: &AppHandle) {
+    if FOLLOWING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let Ok(window) = main_window(app) else {
+        FOLLOWING.store(false, Ordering::Release);
+        return;
+    };
+    while app.state::<DictationMachine>().phase() != DictationPhase::Idle {
+        if let Err(err) = place_at_cursor(app, &window) {
+            tracing::debug!(%err, "overlay not moved to the pointer");
+        }
+        thread::sleep(FOLLOW_INTERVAL);
+    }
+    FOLLOWING.store(false, Ordering::Release);
+}
+
+/// Whether the pointer is over one of `regions`, or `None` when the
+/// pointer or window can't be located.
+fn pointer_in(app: &AppHandle, window: &WebviewWindow, regions: &[Region]) -> Option<bool> {
+    let cursor = app.cursor_position().ok()?;
+    let origin = window.inner_position().ok()?;
+    let scale = window.scale_factor().ok()?;
+    let x = (cursor.x - f64::from(origin.x)) / scale;
+    let y = (cursor.y - f64::from(origin.y)) / scale;
+    Some(regions.iter().any(|region| region.contains(x, y)))
+}
+
+/// Lets clicks through everywhere but the interactive regions by turning
+/// click-through off while the pointer is over one and back on when it
+/// leaves. The OS then delivers each click to the overlay or to the window
+/// beneath it. Ends once regions are replaced by all-or-nothing
+/// passthrough, then re-applies that setting in case a toggle here raced
+/// with it.
+fn track_regions(app: &AppHandle) {
+    let clicks = app.state::<ClickThrough>();
+    if clicks.tracking.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    if let Ok(window) = main_window(app) {
+        let mut interactive = None;
+        while let Some(regions) = clicks.regions() {
+            if let Some(inside) = pointer_in(app, &window, &regions) {
+                if interactive != Some(inside) {
+                    let _ = window.set_ignore_cursor_events(!inside);
+                    interactive = Some(inside);
+                }
+            }
+            thread::sleep(REGION_POLL_INTERVAL);
+        }
+        if let Some(ignore) = clicks.whole() {
+            let _ = window.set_ignore_cursor_events(ignore);
+        }
+    }
+    clicks.tracking.store(false, Ordering::Release);
+}
+
+/// Makes the whole overlay pass clicks through (`ignore`) or take them,
+/// replacing any interactive regions.
+pub fn set_passthrough(app: &AppHandle, ignore: bool) -> Result<(), String> {
+    *app.state::<ClickThrough>()
+        .mode
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = ClickMode::Whole(ignore);
+    main_window(app)?
+        .set_ignore_cursor_events(ignore)
+        .map_err(|err| format!("Unable to update overlay passthrough mode: {err}"))
+}
+
+/// Every display's id, position, size, and scale, to notice a display
+/// being connected, removed, rearranged, or rescaled.
+fn display_layout(app: &AppHandle) -> Vec<(String, i32, i32, u32, u32, u64)> {
+    app.available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            (
+                display_id(monitor),
+                position.x,
+                position.y,
+                size.width,
+                size.height,
+                monitor.scale_factor().to_bits(),
+            )
+        })
+        .collect()
+}
+
+/// Follows the pointer during dictations in `FollowCursor`, docks at the
+/// caret as each dictation starts in `FollowCaret`, and puts the
+/// overlay back in place whenever displays are added, removed, or change
+/// resolution.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(STATE_EVENT, move |event| {
+        let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) else {
+            return;
+        };
+        if transition.from != DictationPhase::Idle || transition.to == DictationPhase::Idle {
+            return;
+        }
+        let settings = handle.state::<SettingsStore>().snapshot().overlay;
+        let handle = handle.clone();
+        match settings.position {
+            OverlayPosition::FollowCursor => {
+                thread::spawn(move || follow_cursor(&handle));
+            }
+            // The caret stays put while the user speaks, so it is found once.
+            OverlayPosition::FollowCaret => {
+                tauri::async_runtime::spawn_blocking(move || {
+                    if let Err(err) = apply(&handle, &settings) {
+                        tracing::debug!(%err, "overlay not moved to the caret");
+                    }
+                });
+            }
+            _ => {}
+        }
+    });
+
+    let handle = app.clone();
+    thread::spawn(move || {
+        let mut layout = display_layout(&handle);
+        loop {
+            thread::sleep(DISPLAY_POLL_INTERVAL);
+            if Dormancy::active(&handle) {
+                continue;
+            }
+            let current = display_layout(&handle);
+            if current == layout {
+                continue;
+            }
+            layout = current;
+            tracing::info!(displays = layout.len(), "displays changed");
+            let settings = handle.state::<SettingsStore>().snapshot().overlay;
+            if let Err(err) = apply(&handle, &settings) {
+                tracing::warn!(%err, "overlay not moved");
+            }
+        }
+    });
+}
+
+/// Connected displays, for `move_overlay_to_display`.
+#[tauri::command]
+pub async fn list_displays(app: AppHandle) -> Result<Vec<DisplayInfo>, String> {
+    crate::run_blocking(move || {
+        let primary = app
+            .primary_monitor()
+            .ok()
+            .flatten()
+            .map(|monitor| display_id(&monitor));
+        let monitors = app
+            .available_monitors()
+            .map_err(|err| format!("Unable to list displays: {err}"))?;
+        Ok(monitors
+            .iter()
+            .map(|monitor| {
+                let id = display_id(monitor);
+                DisplayInfo {
+                    primary: primary.as_deref() == Some(id.as_str()),
+                    id,
+                    scale_factor: monitor.scale_factor(),
+                    position: *monitor.position(),
+                    size: *monitor.size(),
+                }
+            })
+            .collect())
+    })
+    .await
+}
+
+/// Saves `change` to the overlay settings and moves the overlay to match.
+fn update(app: &AppHandle, change: impl FnOnce(&mut OverlaySettings)) -> Result<(), String> {
+    let store = app.state::<SettingsStore>();
+    let mut settings = store.snapshot();
+    change(&mut settings.overlay);
+    store.replace(settings)?;
+    apply(app, &store.snapshot().overlay)
+}
+
+/// Anchors the overlay at `position` and saves it as `overlay.position`.
+#[tauri::command]
+pub async fn set_overlay_anchor(app: AppHandle, position: OverlayPosition) -> Result<(), String> {
+    crate::run_blocking(move || update(&app, |overlay| overlay.position = position)).await
+}
+
+/// Moves the overlay to display `id` from `list_displays` and keeps it
+/// there, saved as `overlay.display`.
+#[tauri::command]
+pub async fn move_overlay_to_display(app: AppHandle, id: String) -> Result<(), String> {
+    crate::run_blocking(move || {
+        let connected = app
+            .available_monitors()
+            .map_err(|err| format!("Unable to list displays: {err}"))?
+            .iter()
+            .any(|monitor| display_id(monitor) == id);
+        if !connected {
+            return Err(format!("Display {id} is not connected."));
+        }
+        update(&app, |overlay| overlay.display = Some(id))
+    })
+    .await
+}
+
+/// Sets where the overlay stays visible and saves it as
+/// `overlay.visibility`: `everywhere` keeps it on every Space and over
+/// fullscreen apps, `currentSpace` leaves it on one Space like an ordinary
+/// floating window.
+#[tauri::command]
+pub async fn set_overlay_visibility_policy(
+    app: AppHandle,
+    policy: OverlayVisibility,
+) -> Result<(), String> {
+    crate::run_blocking(move || update(&app, |overlay| overlay.visibility = policy)).await
+}
+
+/// Lets clicks on the transparent parts of the overlay through to the apps
+/// beneath while `regions` (the pill and its buttons) stay clickable. An
+/// empty list passes every click through. `set_overlay_passthrough`
+/// replaces the regions with all-or-nothing passthrough again.
+#[tauri::command]
+pub fn set_overlay_interactive_regions(app: AppHandle, regions: Vec<Region>) -> Result<(), String> {
+    if regions.is_empty() {
+        return set_passthrough(&app, true);
+    }
+    *app.state::<ClickThrough>()
+        .mode
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = ClickMode::Regions(regions);
+    thread::spawn(move || track_regions(&app));
+    Ok(())
+}
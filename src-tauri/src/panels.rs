@@ -0,0 +1,213 @@
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::Mutex};
+
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, WindowEvent,
+};
+
+use crate::storage::Storage;
+
+const GEOMETRY_FILE: &str = "windows.json";
+const SECTION_EVENT: &str = "telepathy://open-section";
+
+/// Regular app windows opened beside the overlay, which stays small and
+/// always on top.
+#[derive(Clone, Copy)]
+enum Panel {
+    Settings,
+    History,
+}
+
+impl Panel {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Settings => "settings",
+            Self::History => "history",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Settings => "Telepathy Settings",
+            Self::History => "Telepathy History",
+        }
+    }
+
+    /// Logical size on first open, and the smallest it can be resized to.
+    fn sizes(self) -> ((f64, f64), (f64, f64)) {
+        match self {
+            Self::Settings => ((720.0, 560.0), (560.0, 420.0)),
+            Self::History => ((820.0, 620.0), (480.0, 360.0)),
+        }
+    }
+}
+
+/// Outer position and inner size in physical pixels, as last seen.
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Geometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// Window geometry per label, persisted as JSON in the config dir so the
+/// panels reopen where they were left.
+pub struct Panels {
+    path: Option<PathBuf>,
+    geometry: Mutex<BTreeMap<String, Geometry>>,
+}
+
+impl Panels {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = Storage::config_dir(app).map(|dir| dir.join(GEOMETRY_FILE));
+        let geometry = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            geometry: Mutex::new(geometry),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Geometry>> {
+        self.geometry.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn saved(&self, label: &str) -> Option<Geometry> {
+        self.lock().get(label).copied()
+    }
+
+    fn record(&self, window: &WebviewWindow) {
+        if window.is_minimized().unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+            return;
+        }
+        let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+            return;
+        };
+        self.lock().insert(
+            window.label().to_string(),
+            Geometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            },
+        );
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("App config directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Unable to create config directory: {err}"))?;
+        }
+        let raw = serde_json::to_string_pretty(&*self.lock())
+            .map_err(|err| format!("Unable to serialize window geometry: {err}"))?;
+        fs::write(path, raw).map_err(|err| format!("Unable to write window geometry: {err}"))
+    }
+}
+
+/// A saved position is only reused while its top-left corner is still on a
+/// connected display; otherwise the window would open off screen.
+fn on_screen(window: &WebviewWindow, geometry: &Geometry) -> bool {
+    window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .any(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            let right = position.x.saturating_add_unsigned(size.width);
+            let bottom = position.y.saturating_add_unsigned(size.height);
+            (position.x..right).contains(&geometry.x) && (position.y..bottom).contains(&geometry.y)
+        })
+}
+
+fn track(app: &AppHandle, window: &WebviewWindow) {
+    let app = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        let panels = app.state::<Panels>();
+        match event {
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) => panels.record(&tracked),
+            // Writes are batched to moments the user is done arranging.
+            WindowEvent::Focused(false) | WindowEvent::CloseRequested { .. } => {
+                panels.record(&tracked);
+                if let Err(err) = panels.persist() {
+                    tracing::warn!(%err, "window geometry not saved");
+                }
+            }
+            _ => {}
+        }
+    });
+}
+
+/// Focuses the panel if it's already open, otherwise creates it at its saved
+/// geometry (or centered at its default size). `section` scrolls to part of
+/// the page: through the URL for a new window, and as
+/// `telepathy://open-section` for an open one.
+fn open(app: &AppHandle, panel: Panel, section: Option<&str>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(panel.label()) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        if let Some(section) = section {
+            let _ = app.emit_to(panel.label(), SECTION_EVENT, section);
+        }
+        return window
+            .set_focus()
+            .map_err(|err| format!("Unable to focus the {} window: {err}", panel.label()));
+    }
+
+    let route = match section {
+        Some(section) => format!("index.html#{}/{section}", panel.label()),
+        None => format!("index.html#{}", panel.label()),
+    };
+    let ((width, height), (min_width, min_height)) = panel.sizes();
+    let window = WebviewWindowBuilder::new(app, panel.label(), WebviewUrl::App(route.into()))
+        .title(panel.title())
+        .inner_size(width, height)
+        .min_inner_size(min_width, min_height)
+        .center()
+        .visible(false)
+        .build()
+        .map_err(|err| format!("Unable to open the {} window: {err}", panel.label()))?;
+
+    if let Some(geometry) = app.state::<Panels>().saved(panel.label()) {
+        if on_screen(&window, &geometry) {
+            let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+            let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        }
+    }
+    track(app, &window);
+    window
+        .show()
+        .and_then(|()| window.set_focus())
+        .map_err(|err| format!("Unable to show the {} window: {err}", panel.label()))
+}
+
+pub fn open_settings(app: &AppHandle, section: Option<&str>) -> Result<(), String> {
+    open(app, Panel::Settings, section)
+}
+
+pub fn open_history(app: &AppHandle) -> Result<(), String> {
+    open(app, Panel::History, None)
+}
+
+/// Opens the settings window, or focuses it if it's already open, scrolled
+/// to `section` when given (e.g. `hotkey`).
+#[tauri::command]
+pub async fn open_settings_window(app: AppHandle, section: Option<String>) -> Result<(), String> {
+    open_settings(&app, section.as_deref())
+}
+
+/// Opens the history window, or focuses it if it's already open.
+#[tauri::command]
+pub async fn open_history_window(app: AppHandle) -> Result<(), String> {
+    open_history(&app)
+}
@@ -0,0 +1,220 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use crate::{
+    accessibility::{AccessibilityMonitor, AccessibilityStatus},
+    idle::Dormancy,
+    microphone::{self, MicrophonePermission, MicrophoneStatus},
+    settings::{Settings, SettingsStore},
+};
+
+/// The microphone and input-monitoring probes are cheap native calls, so
+/// onboarding can poll them often enough to notice a grant promptly.
+/// Accessibility keeps its own slower timer and is folded in from its cache.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionKind {
+    Microphone,
+    Accessibility,
+    InputMonitoring,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    Undetermined,
+    /// The platform has no such permission.
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    NotRequired,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCheck {
+    pub state: PermissionState,
+    /// Whether the current settings need this permission to work.
+    pub required: bool,
+    pub detail: Option<String>,
+}
+
+impl PermissionCheck {
+    fn blocking(&self) -> bool {
+        self.required
+            && !matches!(
+                self.state,
+                PermissionState::Granted | PermissionState::NotRequired
+            )
+    }
+}
+
+/// Everything onboarding needs in one payload. `next` is the first required
+/// permission still missing, in the order onboarding asks for them; `None`
+/// means dictation and pasting have what they need.
+#[derive(Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionReport {
+    pub microphone: PermissionCheck,
+    pub accessibility: PermissionCheck,
+    pub input_monitoring: PermissionCheck,
+    pub next: Option<PermissionKind>,
+}
+
+fn microphone_check(status: MicrophoneStatus) -> PermissionCheck {
+    PermissionCheck {
+        state: match status.permission {
+            MicrophonePermission::Granted => PermissionState::Granted,
+            MicrophonePermission::Denied => PermissionState::Denied,
+            MicrophonePermission::Undetermined => PermissionState::Undetermined,
+        },
+        required: true,
+        detail: status.detail,
+    }
+}
+
+/// Only macOS gates synthesized keystrokes; elsewhere the status is always
+/// granted and `detail` carries any caveat.
+fn accessibility_check(status: AccessibilityStatus) -> PermissionCheck {
+    PermissionCheck {
+        state: if status.granted {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        },
+        required: cfg!(target_os = "macos"),
+        detail: status.detail,
+    }
+}
+
+/// The hotkey event tap listens to every key, which macOS gates behind Input
+/// Monitoring. The preflight can't tell "never asked" from "denied", so both
+/// read as denied.
+#[cfg(target_os = "macos")]
+fn input_monitoring_check(settings: &Settings) -> PermissionCheck {
+    let granted = objc2_core_graphics::CGPreflightListenEventAccess();
+    PermissionCheck {
+        state: if granted {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        },
+        required: settings.triggers.event_tap,
+        detail: (!granted).then(|| {
+            "Allow Telepathy under Privacy & Security > Input Monitoring in System Settings."
+                .to_string()
+        }),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn input_monitoring_check(_settings: &Settings) -> PermissionCheck {
+    PermissionCheck {
+        state: PermissionState::NotRequired,
+        required: false,
+        detail: None,
+    }
+}
+
+fn report(app: &AppHandle) -> PermissionReport {
+    let settings = app.state::<SettingsStore>().snapshot();
+    let microphone = microphone_check(microphone::probe());
+    let accessibility = accessibility_check(app.state::<AccessibilityMonitor>().status());
+    let input_monitoring = input_monitoring_check(&settings);
+    let next = [
+        (PermissionKind::Microphone, &microphone),
+        (PermissionKind::Accessibility, &accessibility),
+        (PermissionKind::InputMonitoring, &input_monitoring),
+    ]
+    .into_iter()
+    .find(|(_, check)| check.blocking())
+    .map(|(kind, _)| kind);
+    PermissionReport {
+        microphone,
+        accessibility,
+        input_monitoring,
+        next,
+    }
+}
+
+/// The last report sent to the webview, so polling only emits on change.
+#[derive(Default)]
+pub struct PermissionMonitor {
+    report: Mutex<Option<PermissionReport>>,
+    refreshing: AtomicBool,
+}
+
+impl PermissionMonitor {
+    /// Rebuilds the report and emits `telepathy://permissions-changed` if it
+    /// differs from the last one.
+    pub fn refresh(&self, app: &AppHandle) -> PermissionReport {
+        let fresh = report(app);
+        let previous = self
+            .report
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .replace(fresh.clone());
+        if previous.as_ref() != Some(&fresh) {
+            tracing::info!(next = ?fresh.next, "permissions changed");
+            let _ = app.emit("telepathy://permissions-changed", &fresh);
+        }
+        fresh
+    }
+
+    /// Refreshes off the calling thread; overlapping requests collapse into
+    /// the one already running.
+    pub fn refresh_in_background(app: &AppHandle) {
+        let monitor = app.state::<PermissionMonitor>();
+        if monitor.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let app = app.clone();
+        thread::spawn(move || {
+            let monitor = app.state::<PermissionMonitor>();
+            monitor.refresh(&app);
+            monitor.refreshing.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Builds the first report, follows accessibility and settings changes, and
+/// polls the rest.
+pub fn init(app: &AppHandle) {
+    app.state::<PermissionMonitor>().refresh(app);
+
+    // Settings decide which permissions are required.
+    for event in [
+        "telepathy://accessibility-changed",
+        "telepathy://settings-changed",
+    ] {
+        let handle = app.clone();
+        app.listen(event, move |_| {
+            PermissionMonitor::refresh_in_background(&handle);
+        });
+    }
+
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        if !Dormancy::active(&app) {
+            PermissionMonitor::refresh_in_background(&app);
+        }
+    });
+}
+
+/// Microphone, accessibility, and input-monitoring status together, with the
+/// permission onboarding should ask for next.
+#[tauri::command]
+pub async fn get_permission_report(app: AppHandle) -> Result<PermissionReport, String> {
+    crate::run_blocking(move || Ok(app.state::<PermissionMonitor>().refresh(&app))).await
+}
@@ -0,0 +1,166 @@
+use regex::{Regex, RegexBuilder};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    destinations::{self, DeliveryResult, DestinationKind},
+    postprocess,
+    settings::{Settings, SettingsStore},
+    vocabulary::Replacement,
+};
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Case-insensitive match on whole words; `\b` is only added next to word
+/// characters so spoken forms like "c plus plus" or "dot com" still match.
+/// Regex rules are used as written.
+fn replacement_pattern(rule: &Replacement) -> Option<Regex> {
+    if rule.regex {
+        return Regex::new(&rule.from)
+            .inspect_err(|err| tracing::warn!(%err, from = %rule.from, "replacement skipped"))
+            .ok();
+    }
+    let from = rule.from.trim();
+    let first = from.chars().next()?;
+    let last = from.chars().last()?;
+    let mut pattern = String::new();
+    if is_word_char(first) {
+        pattern.push_str(r"\b");
+    }
+    pattern.push_str(&regex::escape(from));
+    if is_word_char(last) {
+        pattern.push_str(r"\b");
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .ok()
+}
+
+fn apply_replacements(rules: &[Replacement], text: String) -> String {
+    rules.iter().fold(text, |text, rule| {
+        let Some(pattern) = replacement_pattern(rule) else {
+            return text;
+        };
+        if rule.regex {
+            pattern.replace_all(&text, rule.to.as_str()).into_owned()
+        } else {
+            pattern
+                .replace_all(&text, regex::NoExpand(&rule.to))
+                .into_owned()
+        }
+    })
+}
+
+/// Text post-processing shared by dictation and externally supplied text:
+/// the global replacements, then those of `profile` (or the active one).
+pub fn process(settings: &Settings, profile: Option<&str>, text: &str) -> String {
+    let text = apply_replacements(&settings.vocabulary.replacements, text.trim().to_string());
+    match settings.profile(profile) {
+        Some(profile) => apply_replacements(&profile.replacements, text),
+        None => text,
+    }
+}
+
+/// Runs `text` through the post-processing pipeline and delivers it to the
+/// profile's destination, exactly as if it had been dictated. `preset`
+/// overrides the LLM post-processing preset as in `deliver_transcript`.
+pub async fn process_and_deliver(
+    app: &AppHandle,
+    text: &str,
+    profile: Option<&str>,
+    preset: Option<&str>,
+) -> Result<DeliveryResult, String> {
+    let settings = app.state::<SettingsStore>().snapshot();
+    let destination = settings
+        .profile(profile)
+        .map(|profile| profile.destination)
+        .unwrap_or(DestinationKind::Paste);
+    let processed = process(&settings, profile, text);
+    let processed = postprocess::rewrite(app, &settings.postprocess, preset, processed).await;
+    destinations::deliver(app, &settings, destination, &processed).await
+}
+
+#[tauri::command]
+pub async fn process_and_paste(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    text: String,
+    profile: Option<String>,
+    preset: Option<String>,
+) -> Result<DeliveryResult, String> {
+    if let Some(name) = profile.as_deref() {
+        if store.snapshot().profile(Some(name)).is_none() {
+            return Err(format!("Unknown profile \"{name}\"."));
+        }
+    }
+    process_and_deliver(&app, &text, profile.as_deref(), preset.as_deref()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(from: &str, to: &str) -> Replacement {
+        Replacement {
+            from: from.to_string(),
+            to: to.to_string(),
+            regex: false,
+        }
+    }
+
+    fn regex_rule(from: &str, to: &str) -> Replacement {
+        Replacement {
+            regex: true,
+            ..rule(from, to)
+        }
+    }
+
+    #[test]
+    fn phrases_match_whole_words_ignoring_case() {
+        let rules = [rule("api", "API"), rule("my email", "me@example.com")];
+        assert_eq!(
+            apply_replacements(&rules, "The Api sends to My Email; rapid apis".to_string()),
+            "The API sends to me@example.com; rapid apis"
+        );
+    }
+
+    #[test]
+    fn word_boundaries_only_sit_next_to_word_characters() {
+        let pattern = |from| replacement_pattern(&rule(from, "")).unwrap();
+        assert_eq!(pattern(" dot com ").as_str(), r"\bdot com\b");
+        assert_eq!(pattern(".net").as_str(), r"\.net\b");
+        assert_eq!(pattern("c++").as_str(), r"\bc\+\+");
+        assert_eq!(
+            apply_replacements(&[rule("c++", "C++")], "I write c++, and c++x".to_string()),
+            "I write C++, and C++x"
+        );
+    }
+
+    #[test]
+    fn literal_replacements_do_not_expand_groups() {
+        assert_eq!(
+            apply_replacements(&[rule("price", "$1 each")], "price".to_string()),
+            "$1 each"
+        );
+    }
+
+    #[test]
+    fn regex_rules_use_groups_and_skip_invalid_patterns() {
+        let rules = [
+            regex_rule(r"(\d+) percent", "${1}%"),
+            regex_rule("(unclosed", "never"),
+        ];
+        assert_eq!(
+            apply_replacements(&rules, "50 percent (unclosed".to_string()),
+            "50% (unclosed"
+        );
+        assert!(replacement_pattern(&rules[1]).is_none());
+    }
+
+    #[test]
+    fn blank_phrases_never_match() {
+        assert!(replacement_pattern(&rule("  ", "x")).is_none());
+    }
+}
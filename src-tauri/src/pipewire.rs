@@ -0,0 +1,414 @@
+use std::{
+    io::Read,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use serde_json::Value;
+
+use crate::{
+    audio_buffer::{AudioChunk, BufferPool},
+    capture::{CaptureApplication, CaptureDevice, CaptureFormat, CaptureSource},
+    x11,
+};
+
+/// Speech models want 16 kHz mono; PipeWire resamples to it.
+pub const FORMAT: CaptureFormat = CaptureFormat {
+    sample_rate: 16_000,
+    channels: 1,
+};
+/// Bytes read from `pw-record` per pipe read.
+const READ_LEN: usize = 4096;
+/// 100 ms per chunk, and the chunks that may wait for the consumer.
+const CHUNK_LEN: usize = 1600;
+const POOL_SLOTS: usize = 16;
+const MISSING_TOOLS: &str =
+    "PipeWire capture needs pw-dump and pw-record (pipewire-utils or pipewire-bin).";
+
+/// Whether the PipeWire command-line tools are installed. Without them
+/// system audio can't be captured and the microphone goes through cpal.
+pub fn available() -> bool {
+    x11::on_path("pw-dump") && x11::on_path("pw-record")
+}
+
+/// One node from `pw-dump`, reduced to the properties capture cares about.
+struct Node {
+    name: String,
+    description: Option<String>,
+    media_class: String,
+    pid: Option<u32>,
+    application: Option<String>,
+    serial: Option<u64>,
+}
+
+/// The default sink and source names from the `default` metadata object.
+#[derive(Default)]
+struct Defaults {
+    sink: Option<String>,
+    source: Option<String>,
+}
+
+/// The PipeWire graph as `pw-dump` reports it.
+fn dump() -> Result<(Vec<Node>, Defaults), String> {
+    if !available() {
+        return Err(MISSING_TOOLS.to_string());
+    }
+    let output = Command::new("pw-dump")
+        .output()
+        .map_err(|err| format!("Unable to run pw-dump; is PipeWire installed? {err}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("pw-dump failed: {stderr}"));
+    }
+    let objects: Vec<Value> = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("Unexpected pw-dump output: {err}"))?;
+
+    let mut nodes = Vec::new();
+    let mut defaults = Defaults::default();
+    for object in &objects {
+        match object["type"].as_str() {
+            Some("PipeWire:Interface:Node") => {
+                let props = &object["info"]["props"];
+                let text = |key: &str| props[key].as_str().map(str::to_string);
+                let (Some(name), Some(media_class)) = (text("node.name"), text("media.class"))
+                else {
+                    continue;
+                };
+                nodes.push(Node {
+                    name,
+                    description: text("node.description").or_else(|| text("node.nick")),
+                    media_class,
+                    // Older PipeWire releases report the pid as a string.
+                    pid: props["application.process.id"]
+                        .as_u64()
+                        .or_else(|| props["application.process.id"].as_str()?.parse().ok())
+                        .and_then(|pid| u32::try_from(pid).ok()),
+                    application: text("application.name"),
+                    serial: props["object.serial"].as_u64(),
+                });
+            }
+            Some("PipeWire:Interface:Metadata")
+                if object["props"]["metadata.name"] == "default" =>
+            {
+                let entries = object["metadata"].as_array().into_iter().flatten();
+                for entry in entries {
+                    let name = entry["value"]["name"].as_str().map(str::to_string);
+                    match entry["key"].as_str() {
+                        Some("default.audio.sink") => defaults.sink = name,
+                        Some("default.audio.source") => defaults.source = name,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((nodes, defaults))
+}
+
+/// The nodes whose media class starts with `class`, as devices.
+fn devices(nodes: Vec<Node>, class: &str, default: Option<&str>) -> Vec<CaptureDevice> {
+    nodes
+        .into_iter()
+        .filter(|node| node.media_class.starts_with(class))
+        .map(|node| CaptureDevice {
+            is_default: default == Some(node.name.as_str()),
+            name: node.description.unwrap_or_else(|| node.name.clone()),
+            id: node.name,
+        })
+        .collect()
+}
+
+/// Output devices, captured through their monitor ports.
+pub fn list_devices() -> Result<Vec<CaptureDevice>, String> {
+    let (nodes, defaults) = dump()?;
+    Ok(devices(nodes, "Audio/Sink", defaults.sink.as_deref()))
+}
+
+/// Input devices (microphones and virtual sources), identified by node name.
+pub fn list_inputs() -> Result<Vec<CaptureDevice>, String> {
+    let (nodes, defaults) = dump()?;
+    Ok(devices(nodes, "Audio/Source", defaults.source.as_deref()))
+}
+
+pub fn probe() -> Result<String, String> {
+    let devices = list_devices()?;
+    Ok(format!("PipeWire, {} output device(s).", devices.len()))
+}
+
+/// Apps with a playback stream open right now.
+pub fn list_applications() -> Result<Vec<CaptureApplication>, String> {
+    let (nodes, _) = dump()?;
+    let mut apps: Vec<CaptureApplication> = Vec::new();
+    for node in nodes {
+        if node.media_class != "Stream/Output/Audio" {
+            continue;
+        }
+        let Some(pid) = node.pid else {
+            continue;
+        };
+        if apps.iter().any(|app| app.pid == pid) {
+            continue;
+        }
+        apps.push(CaptureApplication {
+            pid,
+            name: node.application.or(node.description).unwrap_or(node.name),
+        });
+    }
+    Ok(apps)
+}
+
+pub fn supports_applications() -> bool {
+    available()
+}
+
+/// `pw-record` target and properties for `source`. A sink is recorded from
+/// its monitor; an app is recorded by linking to its own playback stream,
+/// so nothing else on the device is mixed in.
+fn target(source: &CaptureSource) -> Result<(Option<String>, &'static str), String> {
+    match source {
+        CaptureSource::SystemAudio { device_id } => {
+            Ok((device_id.clone(), "{ stream.capture.sink = true }"))
+        }
+        CaptureSource::Application { pid } => {
+            let (nodes, _) = dump()?;
+            let stream = nodes
+                .into_iter()
+                .find(|node| node.media_class == "Stream/Output/Audio" && node.pid == Some(*pid))
+                .ok_or(format!("Process {pid} is not playing audio."))?;
+            let target = stream
+                .serial
+                .map(|serial| serial.to_string())
+                .unwrap_or(stream.name);
+            Ok((Some(target), "{ }"))
+        }
+    }
+}
+
+/// A `pw-record` child writing raw samples to a pipe, and the thread that
+/// decodes them straight into pooled chunks for the consumer.
+pub struct Recording {
+    child: Child,
+    _reader: thread::JoinHandle<()>,
+}
+
+impl Recording {
+    /// Records `target` (the default source when `None`) with the extra
+    /// stream `properties`. Chunks that don't fit in `chunks` are dropped
+    /// and counted in `overruns`, as the cpal callback does.
+    fn start(
+        target: Option<&str>,
+        properties: &str,
+        chunks: mpsc::SyncSender<AudioChunk>,
+        overruns: Arc<AtomicU64>,
+    ) -> Result<Self, String> {
+        let mut command = Command::new("pw-record");
+        command
+            .args(["--raw", "--format", "f32"])
+            .args(["--rate", &FORMAT.sample_rate.to_string()])
+            .args(["--channels", &FORMAT.channels.to_string()])
+            .args(["--media-category", "Capture", "-P", properties]);
+        if let Some(target) = target {
+            command.args(["--target", target]);
+        }
+        let mut child = command
+            .arg("-")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Unable to run pw-record; is PipeWire installed? {err}"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or("pw-record has no output pipe.".to_string())?;
+        let reader = thread::Builder::new()
+            .name("telepathy-pipewire".to_string())
+            .spawn(move || decode(stdout, chunks, overruns))
+            .map_err(|err| format!("Unable to start PipeWire reader: {err}"))?;
+        Ok(Self {
+            child,
+            _reader: reader,
+        })
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Reads native-endian `f32` samples from `pipe` into pool slots until it
+/// closes or the consumer hangs up. A sample split across reads is carried
+/// over at the front of the read buffer, so nothing is allocated per read.
+fn decode(mut pipe: impl Read, chunks: mpsc::SyncSender<AudioChunk>, overruns: Arc<AtomicU64>) {
+    let mut pool = BufferPool::new(POOL_SLOTS, CHUNK_LEN, FORMAT.sample_rate, FORMAT.channels);
+    let mut buffer = [0u8; READ_LEN];
+    let mut carried = 0;
+    let mut dropped = 0;
+    let mut hung_up = false;
+    while !hung_up {
+        let filled = match pipe.read(&mut buffer[carried..]) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => carried + read,
+        };
+        let whole = filled / 4 * 4;
+        let (samples, _) = buffer[..whole].as_chunks::<4>();
+        pool.write_converted(samples, f32::from_ne_bytes, |chunk| {
+            match chunks.try_send(chunk) {
+                Ok(()) => {}
+                Err(mpsc::TrySendError::Full(_)) => dropped += 1,
+                Err(mpsc::TrySendError::Disconnected(_)) => hung_up = true,
+            }
+        });
+        overruns.store(pool.overruns() + dropped, Ordering::Relaxed);
+        buffer.copy_within(whole..filled, 0);
+        carried = filled - whole;
+    }
+}
+
+/// Records the microphone `preferred` by node name, or the default source
+/// when it is unset or not connected. Returns the recording and the name of
+/// the device it opened.
+pub fn open_input(
+    preferred: Option<&str>,
+    chunks: mpsc::SyncSender<AudioChunk>,
+    overruns: Arc<AtomicU64>,
+) -> Result<(Recording, String), String> {
+    let inputs = list_inputs()?;
+    let device = preferred
+        .and_then(|name| inputs.iter().find(|input| input.id == name))
+        .or_else(|| {
+            if let Some(name) = preferred {
+                tracing::warn!(%name, "input device not found; using the default");
+            }
+            inputs.iter().find(|input| input.is_default)
+        })
+        .ok_or("No microphone is available.".to_string())?;
+    let recording = Recording::start(Some(&device.id), "{ }", chunks, overruns)?;
+    Ok((recording, device.name.clone()))
+}
+
+/// A system-audio or per-app capture for `capture`, polled with `read`.
+pub struct Stream {
+    recording: Recording,
+    chunks: mpsc::Receiver<AudioChunk>,
+    overruns: Arc<AtomicU64>,
+}
+
+impl Stream {
+    pub fn open(source: &CaptureSource) -> Result<Self, String> {
+        let (target, properties) = target(source)?;
+        let (sender, chunks) = mpsc::sync_channel(POOL_SLOTS);
+        let overruns = Arc::new(AtomicU64::new(0));
+        let recording =
+            Recording::start(target.as_deref(), properties, sender, Arc::clone(&overruns))?;
+        Ok(Self {
+            recording,
+            chunks,
+            overruns,
+        })
+    }
+
+    pub fn format(&self) -> CaptureFormat {
+        FORMAT
+    }
+
+    /// Chunks dropped because `read` fell behind.
+    pub fn overruns(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    pub fn read(&mut self, mut sink: impl FnMut(AudioChunk)) -> Result<(), String> {
+        loop {
+            match self.chunks.try_recv() {
+                Ok(chunk) => sink(chunk),
+                Err(mpsc::TryRecvError::Empty) => return Ok(()),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    return Err(match self.recording.child.wait() {
+                        Ok(status) => format!("pw-record exited ({status})."),
+                        Err(_) => "pw-record exited.".to_string(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out its bytes a few at a time, splitting samples across reads.
+    struct Trickle {
+        bytes: Vec<u8>,
+        step: usize,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.step.min(buffer.len()).min(self.bytes.len());
+            buffer[..len].copy_from_slice(&self.bytes[..len]);
+            self.bytes.drain(..len);
+            Ok(len)
+        }
+    }
+
+    fn node(name: &str, media_class: &str) -> Node {
+        Node {
+            name: name.to_string(),
+            description: None,
+            media_class: media_class.to_string(),
+            pid: None,
+            application: None,
+            serial: None,
+        }
+    }
+
+    #[test]
+    fn decode_reassembles_samples_split_across_reads() {
+        let samples: Vec<f32> = (0..100).map(|index| index as f32 / 100.0).collect();
+        let bytes = samples
+            .iter()
+            .flat_map(|sample| sample.to_ne_bytes())
+            .collect();
+        let (sender, chunks) = mpsc::sync_channel(POOL_SLOTS);
+        let overruns = Arc::new(AtomicU64::new(0));
+        // 14 reads, so the unread chunks fit in the pool's slots.
+        decode(Trickle { bytes, step: 30 }, sender, Arc::clone(&overruns));
+        let decoded: Vec<f32> = chunks.try_iter().flat_map(|chunk| chunk.to_vec()).collect();
+        assert_eq!(decoded, samples);
+        assert_eq!(overruns.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn decode_counts_chunks_the_consumer_had_no_room_for() {
+        let bytes = vec![0; 4 * 64];
+        let (sender, chunks) = mpsc::sync_channel(1);
+        let overruns = Arc::new(AtomicU64::new(0));
+        decode(Trickle { bytes, step: 16 }, sender, Arc::clone(&overruns));
+        assert_eq!(chunks.try_iter().count(), 1);
+        assert_eq!(overruns.load(Ordering::Relaxed), 15);
+    }
+
+    #[test]
+    fn devices_filters_by_class_and_marks_the_default() {
+        let nodes = vec![
+            node("alsa_input.usb", "Audio/Source"),
+            node("alsa_output.hdmi", "Audio/Sink"),
+            node("echo-cancel-source", "Audio/Source/Virtual"),
+            node("firefox", "Stream/Output/Audio"),
+        ];
+        let inputs = devices(nodes, "Audio/Source", Some("echo-cancel-source"));
+        let ids: Vec<_> = inputs.iter().map(|input| input.id.as_str()).collect();
+        assert_eq!(ids, ["alsa_input.usb", "echo-cancel-source"]);
+        assert!(!inputs[0].is_default && inputs[1].is_default);
+        assert_eq!(inputs[0].name, "alsa_input.usb");
+    }
+}
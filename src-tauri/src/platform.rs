@@ -0,0 +1,165 @@
+use tauri::AppHandle;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use tauri::Manager;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::accessibility::AccessibilityMonitor;
+use crate::capture;
+
+/// Whether one feature works in the current OS session, with the reason
+/// when it doesn't (or which mechanism it uses when it does).
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Support {
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Support {
+    fn yes(detail: &str) -> Self {
+        Self {
+            available: true,
+            detail: Some(detail.to_string()),
+        }
+    }
+
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    fn no(detail: &str) -> Self {
+        Self {
+            available: false,
+            detail: Some(detail.to_string()),
+        }
+    }
+
+    fn from_probe(probe: Result<String, String>) -> Self {
+        let available = probe.is_ok();
+        Self {
+            available,
+            detail: Some(probe.unwrap_or_else(|err| err)),
+        }
+    }
+}
+
+/// What works on this OS and session, so the frontend can hide or adapt
+/// features instead of offering options that silently fail. Unlike
+/// `get_build_info`, this is about the platform, not the compiled features.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformCapabilities {
+    /// `macos`, `windows`, or `linux`.
+    pub os: &'static str,
+    /// `x11` or `wayland` on Linux.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<&'static str>,
+    /// Sending the paste keystroke to the frontmost app.
+    pub auto_paste: Support,
+    /// Writing text at the caret without the clipboard.
+    pub caret_insertion: Support,
+    pub global_shortcuts: Support,
+    pub system_audio_capture: Support,
+    pub overlay_always_on_top: Support,
+    /// Hiding the overlay from screenshots and screen sharing.
+    pub screen_capture_exclusion: Support,
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn system_audio_capture() -> Support {
+    Support::from_probe(capture::probe())
+}
+
+#[cfg(target_os = "macos")]
+fn detect(app: &AppHandle) -> PlatformCapabilities {
+    let accessibility = app.state::<AccessibilityMonitor>().status();
+    PlatformCapabilities {
+        os: "macos",
+        session: None,
+        auto_paste: Support {
+            available: accessibility.granted,
+            detail: accessibility.detail,
+        },
+        caret_insertion: Support {
+            available: accessibility.granted,
+            detail: Some("AXUIElement selected text or value.".to_string()),
+        },
+        global_shortcuts: Support::yes("Carbon hotkeys and the optional event tap."),
+        system_audio_capture: Support::no(
+            "Not implemented on macOS yet: there is no ScreenCaptureKit source, so the capture \
+             commands list no devices and refuse to start.",
+        ),
+        overlay_always_on_top: Support::yes("Floating panel level."),
+        screen_capture_exclusion: Support::yes("NSWindowSharingNone."),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect(app: &AppHandle) -> PlatformCapabilities {
+    let accessibility = app.state::<AccessibilityMonitor>().status();
+    PlatformCapabilities {
+        os: "windows",
+        session: None,
+        auto_paste: Support {
+            available: accessibility.granted,
+            detail: accessibility.detail.or(Some("SendInput.".to_string())),
+        },
+        caret_insertion: Support::yes("UI Automation ValuePattern and TextPattern2."),
+        global_shortcuts: Support::yes("RegisterHotKey."),
+        system_audio_capture: system_audio_capture(),
+        overlay_always_on_top: Support::yes("Topmost window."),
+        screen_capture_exclusion: Support::yes("WDA_EXCLUDEFROMCAPTURE on Windows 10 2004+."),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect(_app: &AppHandle) -> PlatformCapabilities {
+    use crate::x11::{self, Session};
+
+    let no_screen_exclusion =
+        Support::no("Linux compositors offer no way to exclude a window from capture.");
+    let caret_insertion = Support::no("Text is inserted with a clipboard paste.");
+    match x11::session() {
+        Session::Wayland => PlatformCapabilities {
+            os: "linux",
+            session: Some("wayland"),
+            auto_paste: Support::from_probe(crate::wayland::probe_paste()),
+            caret_insertion,
+            global_shortcuts: Support::from_probe(crate::wayland::probe_shortcuts()),
+            system_audio_capture: system_audio_capture(),
+            overlay_always_on_top: Support::no(
+                "Wayland compositors decide window stacking; the overlay may be covered.",
+            ),
+            screen_capture_exclusion: no_screen_exclusion,
+        },
+        session => PlatformCapabilities {
+            os: "linux",
+            session: (session == Session::X11).then_some("x11"),
+            auto_paste: Support::from_probe(x11::probe().map(str::to_string)),
+            caret_insertion,
+            global_shortcuts: Support::yes("X11 key grabs."),
+            system_audio_capture: system_audio_capture(),
+            overlay_always_on_top: Support::yes("_NET_WM_STATE_ABOVE."),
+            screen_capture_exclusion: no_screen_exclusion,
+        },
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn detect(_app: &AppHandle) -> PlatformCapabilities {
+    let unsupported = || Support::no("Not supported on this platform.");
+    PlatformCapabilities {
+        os: std::env::consts::OS,
+        session: None,
+        auto_paste: unsupported(),
+        caret_insertion: unsupported(),
+        global_shortcuts: unsupported(),
+        system_audio_capture: system_audio_capture(),
+        overlay_always_on_top: unsupported(),
+        screen_capture_exclusion: unsupported(),
+    }
+}
+
+/// Probes spawn processes and talk to D-Bus, so this runs on the blocking pool.
+#[tauri::command]
+pub async fn get_platform_capabilities(app: AppHandle) -> Result<PlatformCapabilities, String> {
+    crate::run_blocking(move || Ok(detect(&app))).await
+}
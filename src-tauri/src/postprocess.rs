@@ -0,0 +1,134 @@
+use tauri::{AppHandle, Emitter};
+
+use crate::llm;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPreset {
+    pub name: String,
+    /// System prompt; the transcript is sent as the user message.
+    pub prompt: String,
+}
+
+impl PromptPreset {
+    fn new(name: &str, prompt: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PostprocessSettings {
+    /// Preset every dictation is rewritten with; off when unset.
+    pub preset: Option<String>,
+    pub presets: Vec<PromptPreset>,
+    /// OpenAI chat model, used with the OpenAI API key stored for
+    /// transcription.
+    pub model: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for PostprocessSettings {
+    fn default() -> Self {
+        Self {
+            preset: None,
+            presets: vec![
+                PromptPreset::new(
+                    "punctuation",
+                    "Fix the punctuation, capitalization, and obvious transcription errors in the user's dictated text. Do not change the wording otherwise. Reply with the corrected text only.",
+                ),
+                PromptPreset::new(
+                    "fillers",
+                    "Remove filler words (um, uh, like, you know), false starts, and repeated words from the user's dictated text, keeping everything else as spoken. Reply with the cleaned text only.",
+                ),
+                PromptPreset::new(
+                    "email",
+                    "Rewrite the user's dictated text as a concise, friendly email body with a greeting and sign-off, keeping every point they made. Reply with the email only.",
+                ),
+            ],
+            model: "gpt-4o-mini".to_string(),
+            timeout_secs: 20,
+        }
+    }
+}
+
+/// Payload of `telepathy://transcript-processed`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessedTranscript<'a> {
+    raw: &'a str,
+    processed: &'a str,
+    preset: &'a str,
+}
+
+/// The preset that applies: `requested` when given, where an empty name
+/// turns post-processing off for this text, else the configured one.
+fn resolve<'a>(
+    settings: &'a PostprocessSettings,
+    requested: Option<&str>,
+) -> Result<Option<&'a PromptPreset>, String> {
+    let Some(name) = requested
+        .or(settings.preset.as_deref())
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(None);
+    };
+    settings
+        .presets
+        .iter()
+        .find(|preset| preset.name == name)
+        .map(Some)
+        .ok_or_else(|| format!("Unknown post-processing preset \"{name}\"."))
+}
+
+/// Rewrites `text` with the preset that applies and emits
+/// `telepathy://transcript-processed` with both versions. Any failure
+/// leaves the text as it was, so a dictation is never lost to the LLM.
+pub async fn rewrite(
+    app: &AppHandle,
+    settings: &PostprocessSettings,
+    requested: Option<&str>,
+    text: String,
+) -> String {
+    if text.trim().is_empty() {
+        return text;
+    }
+    let preset = match resolve(settings, requested) {
+        Ok(Some(preset)) => preset,
+        Ok(None) => return text,
+        Err(err) => {
+            tracing::warn!(%err, "transcript not post-processed");
+            return text;
+        }
+    };
+    match llm::complete(
+        app,
+        &settings.model,
+        settings.timeout_secs,
+        &preset.prompt,
+        &text,
+    )
+    .await
+    {
+        Ok(processed) if !processed.is_empty() => {
+            tracing::info!(preset = %preset.name, "transcript post-processed");
+            let _ = app.emit(
+                "telepathy://transcript-processed",
+                ProcessedTranscript {
+                    raw: &text,
+                    processed: &processed,
+                    preset: &preset.name,
+                },
+            );
+            processed
+        }
+        Ok(_) => text,
+        Err(err) => {
+            tracing::warn!(preset = %preset.name, %err, "transcript not post-processed");
+            text
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::{sync::Mutex, time::Duration};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    idle::Dormancy,
+    model_warmup::{ModelState, ModelVariant},
+    settings::SettingsStore,
+};
+
+/// Power source changes have no cross-platform notification; a slow poll
+/// is cheap and catches them within a dictation or two.
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PowerSettings {
+    /// On battery or in low-power mode, ask the transcription server for
+    /// its battery model (see `TELEPATHY_AUTOAVSR_BATTERY_CONFIG`).
+    pub battery_model: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    /// Desktops without a battery, or a source the OS didn't report.
+    #[default]
+    Unknown,
+}
+
+/// Payload of `telepathy://power-state` and `get_power_state`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    pub source: PowerSource,
+    pub battery_percent: Option<u8>,
+    /// macOS Low Power Mode, Windows battery saver, or the Linux
+    /// `low-power` platform profile.
+    pub low_power: bool,
+}
+
+impl PowerState {
+    fn constrained(&self) -> bool {
+        self.source == PowerSource::Battery || self.low_power
+    }
+}
+
+/// `pmset -g batt` for the source and charge; NSProcessInfo for Low Power
+/// Mode.
+#[cfg(target_os = "macos")]
+fn probe() -> PowerState {
+    use objc2_foundation::NSProcessInfo;
+
+    let low_power = NSProcessInfo::processInfo().isLowPowerModeEnabled();
+    let Ok(output) = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+    else {
+        return PowerState {
+            low_power,
+            ..PowerState::default()
+        };
+    };
+    let report = String::from_utf8_lossy(&output.stdout);
+    let source = if report.contains("'AC Power'") {
+        PowerSource::Ac
+    } else if report.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else {
+        PowerSource::Unknown
+    };
+    // e.g. " -InternalBattery-0 (id=1234)	85%; discharging; 4:12 remaining"
+    let battery_percent = report
+        .split(['\t', ' '])
+        .find_map(|field| field.strip_suffix("%;")?.parse().ok());
+    PowerState {
+        source,
+        battery_percent,
+        low_power,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn probe() -> PowerState {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    /// `BatteryFlag` bit for "no system battery".
+    const NO_BATTERY: u8 = 128;
+    /// `BatteryLifePercent` when the charge is unknown.
+    const UNKNOWN_PERCENT: u8 = 255;
+
+    let mut status = SYSTEM_POWER_STATUS {
+        ACLineStatus: 0,
+        BatteryFlag: 0,
+        BatteryLifePercent: 0,
+        SystemStatusFlag: 0,
+        BatteryLifeTime: 0,
+        BatteryFullLifeTime: 0,
+    };
+    // SAFETY: `status` is a valid SYSTEM_POWER_STATUS to fill in.
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return PowerState::default();
+    }
+    let has_battery = status.BatteryFlag & NO_BATTERY == 0;
+    let source = match status.ACLineStatus {
+        1 => PowerSource::Ac,
+        0 if has_battery => PowerSource::Battery,
+        _ => PowerSource::Unknown,
+    };
+    PowerState {
+        source,
+        battery_percent: (has_battery && status.BatteryLifePercent != UNKNOWN_PERCENT)
+            .then_some(status.BatteryLifePercent),
+        low_power: status.SystemStatusFlag == 1,
+    }
+}
+
+/// `/sys/class/power_supply` for the source and charge, and the ACPI
+/// platform profile for low-power mode.
+#[cfg(target_os = "linux")]
+fn probe() -> PowerState {
+    use std::fs;
+
+    let read = |path: std::path::PathBuf| {
+        fs::read_to_string(path)
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut mains_online = None;
+    let mut battery_percent = None;
+    for supply in fs::read_dir("/sys/class/power_supply")
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let path = supply.path();
+        match read(path.join("type")).as_str() {
+            "Mains" => {
+                mains_online =
+                    Some(mains_online.unwrap_or(false) || read(path.join("online")) == "1")
+            }
+            // Peripheral batteries (mice, headsets) report scope "Device".
+            "Battery" if read(path.join("scope")) != "Device" => {
+                battery_percent = battery_percent.or(read(path.join("capacity")).parse().ok())
+            }
+            _ => {}
+        }
+    }
+    let source = match (mains_online, battery_percent) {
+        (Some(true), _) => PowerSource::Ac,
+        (_, Some(_)) => PowerSource::Battery,
+        _ => PowerSource::Unknown,
+    };
+    PowerState {
+        source,
+        battery_percent,
+        low_power: read("/sys/firmware/acpi/platform_profile".into()) == "low-power",
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn probe() -> PowerState {
+    PowerState::default()
+}
+
+/// The last power state seen by the poll.
+#[derive(Default)]
+pub struct Power(Mutex<PowerState>);
+
+impl Power {
+    pub fn state(&self) -> PowerState {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    /// Stores `state`, returning whether it changed.
+    fn update(&self, state: PowerState) -> bool {
+        let mut current = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let changed = *current != state;
+        *current = state;
+        changed
+    }
+}
+
+/// The model the transcription server should have loaded for the current
+/// power state and settings.
+pub fn preferred_variant(app: &AppHandle) -> ModelVariant {
+    let enabled = app.state::<SettingsStore>().snapshot().power.battery_model;
+    if enabled && app.state::<Power>().state().constrained() {
+        ModelVariant::Battery
+    } else {
+        ModelVariant::Default
+    }
+}
+
+async fn watch(app: AppHandle) {
+    loop {
+        if Dormancy::active(&app) {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        if let Ok(state) = crate::run_blocking(|| Ok(probe())).await {
+            if app.state::<Power>().update(state.clone()) {
+                tracing::info!(?state, "power state changed");
+                let _ = app.emit("telepathy://power-state", &state);
+            }
+        }
+        // Also catches the setting being turned on or off.
+        ModelState::match_variant(&app, preferred_variant(&app));
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Reads the power state once so the first model load already uses the
+/// right variant, then polls for changes, emitting `telepathy://power-state`.
+pub fn init(app: &AppHandle) {
+    app.state::<Power>().update(probe());
+    tauri::async_runtime::spawn(watch(app.clone()));
+}
+
+#[tauri::command]
+pub fn get_power_state(power: State<'_, Power>) -> PowerState {
+    power.state()
+}
@@ -0,0 +1,190 @@
+use std::{sync::Mutex, time::Duration};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{idle::Dormancy, settings::SettingsStore};
+
+/// None of the platforms notify apps when Focus changes, so it is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuietModeSettings {
+    /// Silence sounds and notifications while the system's Focus, Do Not
+    /// Disturb, or Focus Assist is on.
+    pub follow_system: bool,
+}
+
+impl Default for QuietModeSettings {
+    fn default() -> Self {
+        Self {
+            follow_system: true,
+        }
+    }
+}
+
+/// Payload of `telepathy://quiet-mode` and `get_quiet_mode`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietState {
+    /// The system asks apps to stay quiet right now.
+    pub active: bool,
+    /// What made it quiet, e.g. `focus`, `quietHours`, or `presentation`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'static str>,
+}
+
+impl QuietState {
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    fn quiet(reason: &'static str) -> Self {
+        Self {
+            active: true,
+            reason: Some(reason),
+        }
+    }
+}
+
+/// Reads `~/Library/DoNotDisturb/DB/Assertions.json`, where macOS records
+/// Focus modes turned on by hand. Scheduled Focus isn't listed there, and
+/// newer releases may deny access; both read as not quiet.
+#[cfg(target_os = "macos")]
+fn probe() -> QuietState {
+    let Some(home) = std::env::var_os("HOME") else {
+        return QuietState::default();
+    };
+    let path = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    let Some(assertions) = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+    else {
+        return QuietState::default();
+    };
+    let active = assertions["data"].as_array().is_some_and(|data| {
+        data.iter().any(|entry| {
+            entry["storeAssertionRecords"]
+                .as_array()
+                .is_some_and(|records| !records.is_empty())
+        })
+    });
+    if active {
+        QuietState::quiet("focus")
+    } else {
+        QuietState::default()
+    }
+}
+
+/// The shell's notification state, which reflects Focus Assist's quiet
+/// hours as well as its automatic rules (presentations, full-screen apps).
+#[cfg(target_os = "windows")]
+fn probe() -> QuietState {
+    use windows_sys::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+        QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+
+    let mut state = 0;
+    // SAFETY: `state` is a valid out pointer for the call.
+    if unsafe { SHQueryUserNotificationState(&mut state) } < 0 {
+        return QuietState::default();
+    }
+    match state {
+        QUNS_QUIET_TIME => QuietState::quiet("quietHours"),
+        QUNS_PRESENTATION_MODE => QuietState::quiet("presentation"),
+        QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN => QuietState::quiet("fullScreen"),
+        _ => QuietState::default(),
+    }
+}
+
+/// The notification server's `Inhibited` property (KDE and others), or
+/// GNOME's "Do Not Disturb", which turns off banners.
+#[cfg(target_os = "linux")]
+fn probe() -> QuietState {
+    use std::sync::OnceLock;
+
+    use zbus::blocking::{Connection, Proxy};
+
+    static BUS: OnceLock<Option<Connection>> = OnceLock::new();
+    let inhibited = BUS
+        .get_or_init(|| Connection::session().ok())
+        .as_ref()
+        .and_then(|conn| {
+            Proxy::new(
+                conn,
+                "org.freedesktop.Notifications",
+                "/org/freedesktop/Notifications",
+                "org.freedesktop.Notifications",
+            )
+            .ok()?
+            .get_property::<bool>("Inhibited")
+            .ok()
+        });
+    let active = inhibited.unwrap_or_else(|| {
+        std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "false")
+    });
+    QuietState {
+        active,
+        reason: active.then_some("focus"),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn probe() -> QuietState {
+    QuietState::default()
+}
+
+/// The system's quiet state as last polled.
+#[derive(Default)]
+pub struct QuietMode(Mutex<QuietState>);
+
+impl QuietMode {
+    pub fn state(&self) -> QuietState {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    fn update(&self, state: QuietState) -> bool {
+        let mut current = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let changed = *current != state;
+        *current = state;
+        changed
+    }
+
+    /// Whether sounds and notifications should be held back now.
+    pub fn silenced(app: &AppHandle) -> bool {
+        app.state::<SettingsStore>()
+            .snapshot()
+            .quiet_mode
+            .follow_system
+            && app.state::<QuietMode>().state().active
+    }
+}
+
+async fn watch(app: AppHandle) {
+    loop {
+        if Dormancy::active(&app) {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        if let Ok(state) = crate::run_blocking(|| Ok(probe())).await {
+            if app.state::<QuietMode>().update(state.clone()) {
+                tracing::info!(?state, "quiet mode changed");
+                let _ = app.emit("telepathy://quiet-mode", &state);
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Polls the system's Focus state, emitting `telepathy://quiet-mode` when
+/// it changes.
+pub fn init(app: &AppHandle) {
+    tauri::async_runtime::spawn(watch(app.clone()));
+}
+
+/// For the overlay's quiet-mode indicator.
+#[tauri::command]
+pub fn get_quiet_mode(quiet: State<'_, QuietMode>) -> QuietState {
+    quiet.state()
+}
@@ -0,0 +1,322 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use flacenc::{component::BitRepr, error::Verify};
+use tauri::{AppHandle, Listener, Manager};
+
+use crate::{
+    audio::{RecordedAudio, Recorder},
+    history::{History, Revision},
+    idle::Dormancy,
+    pipeline, postprocess,
+    settings::SettingsStore,
+    storage::Storage,
+    transcription::{
+        local,
+        providers::{self, ProviderKind, Transcript},
+    },
+    voice_commands,
+};
+
+const RECORDINGS_DIR: &str = "recordings";
+const EXTENSION: &str = "flac";
+/// Recordings are saved as 16-bit FLAC: lossless for speech at about half
+/// the size of WAV.
+const BITS_PER_SAMPLE: usize = 16;
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingSettings {
+    /// Save each native recording next to its history entry, so it can be
+    /// transcribed again later.
+    pub enabled: bool,
+    /// Recordings older than this are deleted; 0 keeps them regardless of
+    /// age.
+    pub max_days: u32,
+    /// The oldest recordings are deleted once together they take more than
+    /// this; 0 sets no limit.
+    pub max_mb: u64,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_days: 30,
+            max_mb: 500,
+        }
+    }
+}
+
+fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Storage::data_dir(app)
+        .map(|dir| dir.join(RECORDINGS_DIR))
+        .ok_or("No data directory for recordings.".to_string())
+}
+
+fn recording_path(app: &AppHandle, id: i64) -> Result<PathBuf, String> {
+    Ok(recordings_dir(app)?.join(format!("{id}.{EXTENSION}")))
+}
+
+fn encode(audio: &RecordedAudio) -> Result<Vec<u8>, String> {
+    let scale = f32::from(i16::MAX);
+    let samples: Vec<i32> = audio
+        .samples
+        .iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * scale) as i32)
+        .collect();
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, err)| format!("Invalid FLAC encoder settings: {err:?}"))?;
+    let source = flacenc::source::MemSource::from_samples(
+        &samples,
+        usize::from(audio.channels.max(1)),
+        BITS_PER_SAMPLE,
+        audio.sample_rate as usize,
+    );
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|err| format!("Unable to encode the recording: {err:?}"))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|err| format!("Unable to encode the recording: {err:?}"))?;
+    Ok(sink.as_slice().to_vec())
+}
+
+fn decode(bytes: Vec<u8>) -> Result<RecordedAudio, String> {
+    let mut reader = claxon::FlacReader::new(Cursor::new(bytes))
+        .map_err(|err| format!("Unable to read the recording: {err}"))?;
+    let info = reader.streaminfo();
+    let scale = (1u32 << (info.bits_per_sample - 1)) as f32;
+    let mut samples = reader
+        .samples()
+        .map(|sample| sample.map(|sample| sample as f32 / scale))
+        .collect::<Result<Vec<f32>, _>>()
+        .map_err(|err| format!("Unable to read the recording: {err}"))?;
+    // The last block is padded out to the full block size.
+    if let Some(frames) = info.samples {
+        samples.truncate(frames as usize * info.channels as usize);
+    }
+    Ok(RecordedAudio {
+        sample_rate: info.sample_rate,
+        channels: info.channels as u16,
+        samples,
+    })
+}
+
+fn write(app: &AppHandle, id: i64, audio: &RecordedAudio) -> Result<(), String> {
+    let path = recording_path(app, id)?;
+    let dir = recordings_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let bytes = encode(audio)?;
+    fs::write(&path, &bytes).map_err(|err| format!("Unable to write {}: {err}", path.display()))?;
+    app.state::<History>().set_recording(id, true)?;
+    tracing::info!(id, bytes = bytes.len(), "recording saved");
+    Ok(())
+}
+
+/// Saves the native recording of the dictation just recorded as history
+/// entry `id`, off the calling thread. Dictations captured elsewhere have
+/// nothing to save.
+pub fn save(app: &AppHandle, settings: &RecordingSettings, id: i64) {
+    let Some(audio) = app.state::<Recorder>().take_kept() else {
+        return;
+    };
+    if !settings.enabled {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = write(&app, id, &audio) {
+            tracing::warn!(%err, "recording not saved");
+        }
+    });
+}
+
+/// Deletes entry `id`'s recording, if it has one.
+pub fn remove(app: &AppHandle, id: i64) {
+    if let Ok(path) = recording_path(app, id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+pub fn remove_all(app: &AppHandle) {
+    if let Ok(dir) = recordings_dir(app) {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Saved recordings by entry id, with their size and age, newest first.
+fn saved(app: &AppHandle) -> Result<Vec<(i64, u64, SystemTime)>, String> {
+    let dir = recordings_dir(app)?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut saved: Vec<(i64, u64, SystemTime)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != EXTENSION {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.parse().ok()?;
+            let metadata = entry.metadata().ok()?;
+            Some((id, metadata.len(), metadata.modified().ok()?))
+        })
+        .collect();
+    saved.sort_by_key(|&(_, _, modified)| std::cmp::Reverse(modified));
+    Ok(saved)
+}
+
+/// Deletes recordings past the age limit, then the oldest until the rest
+/// fit the size limit. Returns how many were deleted.
+fn enforce(app: &AppHandle, settings: &RecordingSettings) -> Result<usize, String> {
+    let max_age = Duration::from_secs(u64::from(settings.max_days) * 24 * 60 * 60);
+    let max_bytes = settings.max_mb.saturating_mul(1024 * 1024);
+    let now = SystemTime::now();
+    let history = app.state::<History>();
+    let mut kept_bytes = 0;
+    let mut removed = 0;
+    for (id, bytes, modified) in saved(app)? {
+        let expired =
+            settings.max_days > 0 && now.duration_since(modified).is_ok_and(|age| age > max_age);
+        let over_size = settings.max_mb > 0 && kept_bytes + bytes > max_bytes;
+        if !expired && !over_size {
+            kept_bytes += bytes;
+            continue;
+        }
+        remove(app, id);
+        if let Err(err) = history.set_recording(id, false) {
+            tracing::warn!(%err, id, "history entry not updated");
+        }
+        removed += 1;
+    }
+    if removed > 0 {
+        tracing::info!(removed, kept_bytes, "old recordings deleted");
+    }
+    Ok(removed)
+}
+
+fn cleanup(app: &AppHandle) {
+    let settings = app.state::<SettingsStore>().snapshot().recordings;
+    if let Err(err) = enforce(app, &settings) {
+        tracing::warn!(%err, "recording cleanup failed");
+    }
+}
+
+/// Applies the retention limits now, whenever they change, and hourly.
+/// Recordings already saved stay under their limits after saving is turned
+/// off.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen("telepathy://settings-changed", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn_blocking(move || cleanup(&handle));
+    });
+    let handle = app.clone();
+    thread::spawn(move || loop {
+        if !Dormancy::active(&handle) {
+            cleanup(&handle);
+        }
+        thread::sleep(CLEANUP_INTERVAL);
+    });
+}
+
+fn read(app: &AppHandle, id: i64) -> Result<RecordedAudio, String> {
+    let path = recording_path(app, id)?;
+    let bytes = fs::read(&path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => "This dictation has no saved recording.".to_string(),
+        _ => format!("Unable to read {}: {err}", path.display()),
+    })?;
+    decode(bytes)
+}
+
+/// The saved recording of history entry `id`, to pass to
+/// `transcribe_local` or `transcribe_with_provider` for another attempt.
+#[tauri::command]
+pub async fn load_recording(app: AppHandle, id: i64) -> Result<RecordedAudio, String> {
+    crate::run_blocking(move || read(&app, id)).await
+}
+
+/// What `retranscribe` runs a saved recording through.
+#[derive(serde::Deserialize)]
+#[serde(tag = "engine", rename_all = "camelCase")]
+pub enum Transcriber {
+    /// A cloud provider, selected or not; `model` replaces the configured
+    /// one.
+    Cloud {
+        provider: ProviderKind,
+        model: Option<String>,
+    },
+    /// A downloaded whisper.cpp model, loaded in place of the current one.
+    Local { model: String },
+}
+
+/// Transcribes history entry `id`'s saved recording again with
+/// `transcriber`, runs the result through replacements, voice commands, and
+/// post-processing like a dictation, and stores it as a revision of the
+/// entry. Nothing is pasted.
+#[tauri::command]
+pub async fn retranscribe(
+    app: AppHandle,
+    id: i64,
+    transcriber: Transcriber,
+) -> Result<Revision, String> {
+    let handle = app.clone();
+    let audio = crate::run_blocking(move || {
+        if !handle.state::<History>().exists(id)? {
+            return Err(format!("History entry {id} does not exist."));
+        }
+        read(&handle, id)
+    })
+    .await?;
+    let settings = app.state::<SettingsStore>().snapshot();
+    let (raw, provider, model) = match transcriber {
+        Transcriber::Cloud { provider, model } => {
+            let model = model.or(match provider {
+                ProviderKind::OpenAi => Some(settings.provider.openai_model.clone()),
+                ProviderKind::Deepgram => Some(settings.provider.deepgram_model.clone()),
+                ProviderKind::AssemblyAi => None,
+            });
+            match providers::transcribe_with(&app, &audio, provider, model.clone()).await? {
+                Transcript::Cloud(transcript) => (transcript.text, provider.name(), model),
+                Transcript::Local(transcript) => {
+                    (transcript.text, "whisper.cpp", Some(transcript.model))
+                }
+            }
+        }
+        Transcriber::Local { model } => {
+            let handle = app.clone();
+            let transcript =
+                crate::run_blocking(move || local::transcribe(&handle, &audio, &model)).await?;
+            (transcript.text, "whisper.cpp", Some(transcript.model))
+        }
+    };
+    let commands = voice_commands::interpret(
+        &settings.voice_commands,
+        &pipeline::process(&settings, None, &raw),
+    );
+    let text = postprocess::rewrite(&app, &settings.postprocess, None, commands.text).await;
+    tracing::info!(
+        id,
+        provider,
+        chars = text.chars().count(),
+        "dictation transcribed again"
+    );
+    crate::run_blocking(move || {
+        app.state::<History>().add_revision(
+            id,
+            &text,
+            (raw.trim() != text).then_some(raw.as_str()),
+            provider,
+            model.as_deref(),
+        )
+    })
+    .await
+}
@@ -0,0 +1,193 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[cfg(target_os = "macos")]
+use crate::screen_ocr as backend;
+use crate::{
+    focus,
+    jobs::{self, JobPriority},
+    settings::SettingsStore,
+};
+
+const SCREEN_CONTEXT_EVENT: &str = "telepathy://screen-context";
+
+#[cfg(not(target_os = "macos"))]
+mod backend {
+    pub fn has_permission() -> bool {
+        false
+    }
+
+    pub fn request_permission() -> bool {
+        false
+    }
+
+    pub fn window_text(_pid: u32) -> Result<String, String> {
+        Err("Screen context is only available on macOS.".to_string())
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScreenContextSettings {
+    /// Reads the focused window when a dictation starts. Off unless the
+    /// user opts in, and still needs Screen Recording permission.
+    pub enabled: bool,
+    /// Bundle ids that are never read, e.g. password managers.
+    pub excluded_apps: Vec<String>,
+    /// Recognized text beyond this many characters is dropped.
+    pub max_chars: usize,
+}
+
+impl Default for ScreenContextSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            excluded_apps: [
+                "com.1password.1password",
+                "com.agilebits.onepassword7",
+                "com.apple.keychainaccess",
+                "com.apple.Passwords",
+                "com.bitwarden.desktop",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+            max_chars: 4000,
+        }
+    }
+}
+
+/// Text read from the focused window, for the rewrite stage's prompt.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenContext {
+    pub app: Option<String>,
+    pub text: String,
+}
+
+/// Payload of `telepathy://screen-context`, emitted around every read so
+/// the overlay can show that the screen is being looked at.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScreenContextStatus {
+    /// `capturing`, `captured`, `skipped`, or `failed`.
+    state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    app: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+fn emit_status(app: &AppHandle, state: &'static str, name: Option<String>, detail: Option<String>) {
+    let _ = app.emit(
+        SCREEN_CONTEXT_EVENT,
+        ScreenContextStatus {
+            state,
+            app: name,
+            detail,
+        },
+    );
+}
+
+/// The context read for the current dictation. Each new dictation bumps the
+/// generation, so a slow read from an earlier one is discarded.
+#[derive(Default)]
+pub struct ScreenContexts {
+    generation: AtomicU64,
+    latest: Mutex<Option<ScreenContext>>,
+}
+
+impl ScreenContexts {
+    fn reset(&self) -> u64 {
+        *self.latest.lock().unwrap_or_else(|err| err.into_inner()) = None;
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn store(&self, generation: u64, context: ScreenContext) -> bool {
+        let mut latest = self.latest.lock().unwrap_or_else(|err| err.into_inner());
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return false;
+        }
+        *latest = Some(context);
+        true
+    }
+
+    pub fn take(&self) -> Option<ScreenContext> {
+        self.latest
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+    }
+}
+
+/// Reads the focused window in the background when a dictation is armed,
+/// so the text is ready by the time the transcript is. Does nothing unless
+/// enabled in settings and Screen Recording is already granted; it never
+/// prompts on its own.
+pub fn capture_for_dictation(app: &AppHandle) {
+    let contexts = app.state::<ScreenContexts>();
+    let generation = contexts.reset();
+    let settings = app.state::<SettingsStore>().snapshot().screen_context;
+    if !settings.enabled || !backend::has_permission() {
+        return;
+    }
+    let target = focus::paste_target(app).ok().and_then(|target| target.app);
+    let name = target.as_ref().and_then(|target| target.name.clone());
+    let Some(pid) = target.as_ref().and_then(|target| target.pid) else {
+        emit_status(app, "skipped", name, Some("No focused app.".to_string()));
+        return;
+    };
+    let id = target.as_ref().and_then(|target| target.id.as_deref());
+    if id.is_some_and(|id| settings.excluded_apps.iter().any(|excluded| excluded == id)) {
+        emit_status(
+            app,
+            "skipped",
+            name,
+            Some("This app is excluded.".to_string()),
+        );
+        return;
+    }
+
+    emit_status(app, "capturing", name.clone(), None);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let read = jobs::run(&app, JobPriority::Live, "screenContext", move |_| {
+            backend::window_text(pid)
+        })
+        .await;
+        match read {
+            Ok(text) => {
+                let text: String = text.chars().take(settings.max_chars).collect();
+                let chars = text.chars().count();
+                let context = ScreenContext {
+                    app: name.clone(),
+                    text,
+                };
+                if app.state::<ScreenContexts>().store(generation, context) {
+                    emit_status(&app, "captured", name, Some(format!("{chars} characters")));
+                }
+            }
+            Err(err) => {
+                tracing::warn!(%err, "screen context read failed");
+                emit_status(&app, "failed", name, Some(err));
+            }
+        }
+    });
+}
+
+/// Asks for Screen Recording permission. Called when the user turns the
+/// setting on, never from a dictation.
+#[tauri::command]
+pub async fn request_screen_context_permission() -> Result<bool, String> {
+    crate::run_blocking(|| Ok(backend::has_permission() || backend::request_permission())).await
+}
+
+/// Hands the current dictation's screen text to the rewrite stage once;
+/// later calls return nothing until the next dictation reads again.
+#[tauri::command]
+pub fn take_screen_context(contexts: State<'_, ScreenContexts>) -> Option<ScreenContext> {
+    contexts.take()
+}
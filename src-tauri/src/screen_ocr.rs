@@ -0,0 +1,138 @@
+use std::{sync::mpsc, time::Duration};
+
+use block2::RcBlock;
+use objc2::{rc::Retained, AllocAnyThread};
+use objc2_core_graphics::{CGImage, CGPreflightScreenCaptureAccess, CGRequestScreenCaptureAccess};
+use objc2_foundation::{NSArray, NSDictionary, NSError};
+use objc2_screen_capture_kit::{
+    SCContentFilter, SCScreenshotManager, SCShareableContent, SCStreamConfiguration, SCWindow,
+};
+use objc2_vision::{VNImageRequestHandler, VNRecognizeTextRequest, VNRequest};
+
+/// Listing windows, the screenshot, and OCR together.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pixels per point requested for the screenshot; OCR misses small text at 1x.
+const CAPTURE_SCALE: f64 = 2.0;
+
+type Sender = mpsc::SyncSender<Result<String, String>>;
+
+fn describe(error: *mut NSError, fallback: &str) -> String {
+    // SAFETY: completion handlers pass a valid error or null.
+    match unsafe { error.as_ref() } {
+        Some(error) => format!("{fallback}: {}", error.localizedDescription()),
+        None => format!("{fallback}."),
+    }
+}
+
+/// Screen Recording permission, without prompting.
+pub fn has_permission() -> bool {
+    CGPreflightScreenCaptureAccess()
+}
+
+/// Shows the system Screen Recording prompt the first time; afterwards the
+/// user has to change it in System Settings.
+pub fn request_permission() -> bool {
+    CGRequestScreenCaptureAccess()
+}
+
+/// The front normal-level window of `pid`. ScreenCaptureKit lists windows
+/// front to back.
+fn front_window(content: &SCShareableContent, pid: u32) -> Option<Retained<SCWindow>> {
+    // SAFETY: plain property reads on objects ScreenCaptureKit handed us.
+    unsafe {
+        content.windows().into_iter().find(|window| {
+            window.isOnScreen()
+                && window.windowLayer() == 0
+                && window
+                    .owningApplication()
+                    .is_some_and(|app| u32::try_from(app.processID()).ok() == Some(pid))
+        })
+    }
+}
+
+fn recognize_text(image: &CGImage) -> Result<String, String> {
+    let request = VNRecognizeTextRequest::new();
+    let base: &VNRequest = &request;
+    // SAFETY: an empty options dictionary is valid for any image.
+    let handler = unsafe {
+        VNImageRequestHandler::initWithCGImage_options(
+            VNImageRequestHandler::alloc(),
+            image,
+            &NSDictionary::new(),
+        )
+    };
+    handler
+        .performRequests_error(&NSArray::from_slice(&[base]))
+        .map_err(|err| format!("Text recognition failed: {}", err.localizedDescription()))?;
+    let lines: Vec<String> = request
+        .results()
+        .into_iter()
+        .flatten()
+        .filter_map(|observation| {
+            let candidate = observation.topCandidates(1).firstObject()?;
+            Some(candidate.string().to_string())
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// Screenshots `window` alone, so overlapping windows never leak into the
+/// text, and sends its recognized text on `sender`.
+fn screenshot(window: &SCWindow, sender: Sender) {
+    // SAFETY: the filter and configuration are fully set up before the
+    // call, and the handler only touches the image it is given.
+    unsafe {
+        let filter =
+            SCContentFilter::initWithDesktopIndependentWindow(SCContentFilter::alloc(), window);
+        let frame = window.frame();
+        let config = SCStreamConfiguration::new();
+        config.setWidth((frame.size.width * CAPTURE_SCALE) as usize);
+        config.setHeight((frame.size.height * CAPTURE_SCALE) as usize);
+        config.setShowsCursor(false);
+        let on_image = RcBlock::new(move |image: *mut CGImage, error: *mut NSError| {
+            let result = match image.as_ref() {
+                Some(image) => recognize_text(image),
+                None => Err(describe(error, "Unable to capture the window")),
+            };
+            let _ = sender.try_send(result);
+        });
+        SCScreenshotManager::captureImageWithFilter_configuration_completionHandler(
+            &filter,
+            &config,
+            Some(&on_image),
+        );
+    }
+}
+
+/// Text in the front window of `pid`, read with ScreenCaptureKit and
+/// Vision. Blocks until the capture finishes; needs macOS 14.
+pub fn window_text(pid: u32) -> Result<String, String> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let on_content = RcBlock::new(
+        move |content: *mut SCShareableContent, error: *mut NSError| {
+            // SAFETY: the handler gets a valid content object or an error.
+            let Some(content) = (unsafe { content.as_ref() }) else {
+                let _ = sender.try_send(Err(describe(error, "Unable to list windows")));
+                return;
+            };
+            match front_window(content, pid) {
+                Some(window) => screenshot(&window, sender.clone()),
+                None => {
+                    let _ =
+                        sender.try_send(Err("The focused app has no visible window.".to_string()));
+                }
+            }
+        },
+    );
+    // SAFETY: the handler is retained by ScreenCaptureKit until it runs.
+    unsafe {
+        SCShareableContent::getShareableContentExcludingDesktopWindows_onScreenWindowsOnly_completionHandler(
+            true,
+            true,
+            &on_content,
+        );
+    }
+    receiver
+        .recv_timeout(CAPTURE_TIMEOUT)
+        .map_err(|_| "Timed out reading the screen.".to_string())?
+}
@@ -0,0 +1,256 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use objc2::{define_class, rc::Retained, runtime::AnyObject, ClassType, Message};
+use objc2_foundation::{NSInteger, NSObject, NSScriptCommand, NSString, NSURL};
+use tauri::{AppHandle, Listener, Manager};
+
+use crate::{
+    destinations::LastTranscript,
+    dictation::{
+        DictationEvent, DictationMachine, DictationPhase, DictationTransition, STATE_EVENT,
+    },
+    file_transcription,
+    history::History,
+    triggers::{self, Chord, TriggerAction, TriggerSource},
+};
+
+/// `errAEEventFailed`, what scripts see for a command that didn't work.
+const COMMAND_FAILED: NSInteger = -10000;
+/// How long `dictate` waits for the dictation to end.
+const DICTATE_TIMEOUT: Duration = Duration::from_secs(600);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static NEXT_COMMAND: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Commands waiting on a dictation or a transcription. Cocoa scripting
+    /// runs on the main thread, so they are only touched there.
+    static SUSPENDED: RefCell<HashMap<u64, Retained<NSScriptCommand>>> = RefCell::default();
+}
+
+fn text_object(text: &str) -> Retained<AnyObject> {
+    Retained::into_super(Retained::into_super(NSString::from_str(text)))
+}
+
+fn fail(command: &NSScriptCommand, message: &str) {
+    command.setScriptErrorNumber(COMMAND_FAILED);
+    command.setScriptErrorString(Some(&NSString::from_str(message)));
+}
+
+/// Parks `command` until [`resume`] is called with its id.
+fn suspend(command: &NSScriptCommand) -> u64 {
+    let id = NEXT_COMMAND.fetch_add(1, Ordering::Relaxed);
+    command.suspendExecution();
+    SUSPENDED.with_borrow_mut(|suspended| suspended.insert(id, command.retain()));
+    id
+}
+
+/// Answers a suspended command from any thread.
+fn resume(app: &AppHandle, id: u64, result: Result<String, String>) {
+    let _ = app.run_on_main_thread(move || {
+        let Some(command) = SUSPENDED.with_borrow_mut(|suspended| suspended.remove(&id)) else {
+            return;
+        };
+        match result {
+            Ok(text) => {
+                let text = text_object(&text);
+                // SAFETY: every command that suspends returns text.
+                unsafe { command.resumeExecutionWithResult(Some(&text)) };
+            }
+            Err(err) => {
+                fail(&command, &err);
+                // SAFETY: no result is a valid result for a failed command.
+                unsafe { command.resumeExecutionWithResult(None) };
+            }
+        }
+    });
+}
+
+/// The transcript of the dictation ending with `transition`.
+fn outcome(app: &AppHandle, transition: DictationTransition) -> Result<String, String> {
+    match transition.event {
+        DictationEvent::Finished => Ok(app.state::<LastTranscript>().get().unwrap_or_default()),
+        DictationEvent::Cancel => Err("The dictation was cancelled.".to_string()),
+        DictationEvent::Reset => Err("The dictation stopped responding.".to_string()),
+        _ => Err(transition
+            .error
+            .unwrap_or("The dictation failed.".to_string())),
+    }
+}
+
+/// Starts a dictation unless one is being recorded, then waits on another
+/// thread for it to end.
+fn dictate(app: &AppHandle, id: u64) {
+    let (sender, receiver) = mpsc::channel();
+    let listener = app.listen(STATE_EVENT, move |event| {
+        if let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) {
+            if transition.to == DictationPhase::Idle {
+                let _ = sender.send(transition);
+            }
+        }
+    });
+    if !app.state::<DictationMachine>().phase().recording() {
+        triggers::start(app, TriggerSource::Script);
+    }
+    if app.state::<DictationMachine>().phase() == DictationPhase::Idle {
+        app.unlisten(listener);
+        resume(
+            app,
+            id,
+            Err("A dictation could not be started.".to_string()),
+        );
+        return;
+    }
+    let app = app.clone();
+    thread::spawn(move || {
+        let result = match receiver.recv_timeout(DICTATE_TIMEOUT) {
+            Ok(transition) => outcome(&app, transition),
+            Err(_) => Err("Timed out waiting for the dictation to end.".to_string()),
+        };
+        app.unlisten(listener);
+        resume(&app, id, result);
+    });
+}
+
+/// `last transcript`: this session's, or the newest in history after a
+/// restart.
+fn last_transcript(app: &AppHandle, command: &NSScriptCommand) -> Option<Retained<AnyObject>> {
+    let text = match app.state::<LastTranscript>().get() {
+        Some(text) => Ok(Some(text)),
+        None => app.state::<History>().latest_text(),
+    };
+    match text {
+        Ok(text) => Some(text_object(&text.unwrap_or_default())),
+        Err(err) => {
+            fail(command, &err);
+            None
+        }
+    }
+}
+
+/// `transcribe file`, answered once the transcription ends.
+fn transcribe_file(app: &AppHandle, command: &NSScriptCommand) {
+    let path = command
+        .directParameter()
+        .and_then(|file| file.downcast::<NSURL>().ok())
+        .and_then(|url| url.path())
+        .map(|path| PathBuf::from(path.to_string()));
+    let Some(path) = path else {
+        fail(command, "Expected an audio file.");
+        return;
+    };
+    let id = suspend(command);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = file_transcription::run(&app, path)
+            .await
+            .map(|transcript| transcript.text);
+        resume(&app, id, result);
+    });
+}
+
+define_class!(
+    // SAFETY: NSScriptCommand has no subclassing requirements, and the
+    // class does not implement Drop.
+    #[unsafe(super(NSScriptCommand, NSObject))]
+    #[name = "TPStartDictationCommand"]
+    struct StartDictationCommand;
+
+    impl StartDictationCommand {
+        #[unsafe(method_id(performDefaultImplementation))]
+        fn perform(&self) -> Option<Retained<AnyObject>> {
+            if let Some(app) = APP.get() {
+                triggers::start(app, TriggerSource::Script);
+            }
+            None
+        }
+    }
+);
+
+define_class!(
+    // SAFETY: as above.
+    #[unsafe(super(NSScriptCommand, NSObject))]
+    #[name = "TPStopDictationCommand"]
+    struct StopDictationCommand;
+
+    impl StopDictationCommand {
+        #[unsafe(method_id(performDefaultImplementation))]
+        fn perform(&self) -> Option<Retained<AnyObject>> {
+            if let Some(app) = APP.get() {
+                triggers::dispatch(app, TriggerAction::Stop, TriggerSource::Script, Chord::Main);
+            }
+            None
+        }
+    }
+);
+
+define_class!(
+    // SAFETY: as above.
+    #[unsafe(super(NSScriptCommand, NSObject))]
+    #[name = "TPDictateCommand"]
+    struct DictateCommand;
+
+    impl DictateCommand {
+        #[unsafe(method_id(performDefaultImplementation))]
+        fn perform(&self) -> Option<Retained<AnyObject>> {
+            if let Some(app) = APP.get() {
+                dictate(app, suspend(self));
+            }
+            None
+        }
+    }
+);
+
+define_class!(
+    // SAFETY: as above.
+    #[unsafe(super(NSScriptCommand, NSObject))]
+    #[name = "TPLastTranscriptCommand"]
+    struct LastTranscriptCommand;
+
+    impl LastTranscriptCommand {
+        #[unsafe(method_id(performDefaultImplementation))]
+        fn perform(&self) -> Option<Retained<AnyObject>> {
+            APP.get().and_then(|app| last_transcript(app, self))
+        }
+    }
+);
+
+define_class!(
+    // SAFETY: as above.
+    #[unsafe(super(NSScriptCommand, NSObject))]
+    #[name = "TPTranscribeFileCommand"]
+    struct TranscribeFileCommand;
+
+    impl TranscribeFileCommand {
+        #[unsafe(method_id(performDefaultImplementation))]
+        fn perform(&self) -> Option<Retained<AnyObject>> {
+            if let Some(app) = APP.get() {
+                transcribe_file(app, self);
+            }
+            None
+        }
+    }
+);
+
+/// Registers the command classes named in `Telepathy.sdef`, which Cocoa
+/// scripting looks up by name when a script or Shortcut sends a command.
+pub fn init(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+    let _ = (
+        StartDictationCommand::class(),
+        StopDictationCommand::class(),
+        DictateCommand::class(),
+        LastTranscriptCommand::class(),
+        TranscribeFileCommand::class(),
+    );
+}
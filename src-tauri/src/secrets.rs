@@ -0,0 +1,70 @@
+const KEYRING_SERVICE: &str = "com.evanhu.telepathy";
+
+/// Secrets Telepathy knows how to store. Values live in the OS credential
+/// store and are never handed back to the webview.
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretKey {
+    ChatWebhookUrl,
+    OpenAiApiKey,
+    DeepgramApiKey,
+    AssemblyAiApiKey,
+    /// Bearer token for the local HTTP API; only `reset_http_api_token`
+    /// hands it out.
+    HttpApiToken,
+}
+
+impl SecretKey {
+    fn account(self) -> &'static str {
+        match self {
+            SecretKey::ChatWebhookUrl => "chat-webhook-url",
+            SecretKey::OpenAiApiKey => "openai-api-key",
+            SecretKey::DeepgramApiKey => "deepgram-api-key",
+            SecretKey::AssemblyAiApiKey => "assemblyai-api-key",
+            SecretKey::HttpApiToken => "http-api-token",
+        }
+    }
+}
+
+fn entry(key: SecretKey) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, key.account())
+        .map_err(|err| format!("Credential store unavailable: {err}"))
+}
+
+pub fn read_secret(key: SecretKey) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(format!("Unable to read secret: {err}")),
+    }
+}
+
+pub fn write_secret(key: SecretKey, value: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(value.trim())
+        .map_err(|err| format!("Unable to store secret: {err}"))
+}
+
+pub fn remove_secret(key: SecretKey) -> Result<(), String> {
+    match entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(format!("Unable to delete secret: {err}")),
+    }
+}
+
+// Credential store calls can block on IPC (Secret Service, Keychain prompts).
+
+#[tauri::command]
+pub async fn store_secret(key: SecretKey, value: String) -> Result<(), String> {
+    crate::run_blocking(move || write_secret(key, &value)).await
+}
+
+#[tauri::command]
+pub async fn delete_secret(key: SecretKey) -> Result<(), String> {
+    crate::run_blocking(move || remove_secret(key)).await
+}
+
+#[tauri::command]
+pub async fn has_secret(key: SecretKey) -> Result<bool, String> {
+    crate::run_blocking(move || Ok(read_secret(key)?.is_some())).await
+}
@@ -0,0 +1,209 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    audio::AudioSettings,
+    costs::CostSettings,
+    crash_reports::{self, CrashReportSettings},
+    destinations::{self, DestinationKind, DestinationSettings},
+    file_transcription::FileTranscriptionSettings,
+    history::HistorySettings,
+    http_api::{HttpApi, HttpApiSettings},
+    idle::IdleSettings,
+    injection::PasteSettings,
+    jobs::{JobPool, JobSettings},
+    launch::{self, LaunchSettings},
+    locale::LocaleSettings,
+    logging::{LogSettings, Logging},
+    model_warmup::ModelSettings,
+    notifications::NotificationSettings,
+    overlay::{self, OverlaySettings},
+    postprocess::PostprocessSettings,
+    power::PowerSettings,
+    quiet_mode::QuietModeSettings,
+    recordings::RecordingSettings,
+    screen_context::ScreenContextSettings,
+    sounds::{HapticSettings, SoundSettings},
+    storage::Storage,
+    transcript_stream::TranscriptStreamSettings,
+    transcription::{local::LocalTranscriptionSettings, providers::ProviderSettings},
+    translation::TranslationSettings,
+    triggers::{InputTriggers, TriggerSettings},
+    updater::UpdateSettings,
+    usage::UsageSettings,
+    vad::VadSettings,
+    vocabulary::{Replacement, VocabularySettings},
+    voice_commands::VoiceCommandSettings,
+    webhooks::WebhookSettings,
+};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub destinations: DestinationSettings,
+    pub profiles: Vec<Profile>,
+    pub active_profile: Option<String>,
+    pub triggers: TriggerSettings,
+    pub transcript_stream: TranscriptStreamSettings,
+    pub vocabulary: VocabularySettings,
+    pub model: ModelSettings,
+    pub jobs: JobSettings,
+    pub notifications: NotificationSettings,
+    pub screen_context: ScreenContextSettings,
+    pub sounds: SoundSettings,
+    pub haptics: HapticSettings,
+    pub logging: LogSettings,
+    pub crash_reports: CrashReportSettings,
+    pub updates: UpdateSettings,
+    pub power: PowerSettings,
+    pub quiet_mode: QuietModeSettings,
+    pub locale: LocaleSettings,
+    pub idle: IdleSettings,
+    pub audio: AudioSettings,
+    pub local_transcription: LocalTranscriptionSettings,
+    pub paste: PasteSettings,
+    pub provider: ProviderSettings,
+    pub history: HistorySettings,
+    pub overlay: OverlaySettings,
+    pub translation: TranslationSettings,
+    pub postprocess: PostprocessSettings,
+    pub voice_commands: VoiceCommandSettings,
+    pub vad: VadSettings,
+    pub recordings: RecordingSettings,
+    pub file_transcription: FileTranscriptionSettings,
+    pub launch: LaunchSettings,
+    pub http_api: HttpApiSettings,
+    pub webhooks: WebhookSettings,
+    pub usage: UsageSettings,
+    pub costs: CostSettings,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub destination: DestinationKind,
+    /// Applied after the global replacements while this profile is in use.
+    #[serde(default)]
+    pub replacements: Vec<Replacement>,
+}
+
+impl Settings {
+    /// Reads `settings.json` from `config_dir`. A missing or unreadable file
+    /// falls back to defaults rather than blocking startup; the next
+    /// successful update rewrites it.
+    pub fn read(config_dir: Option<&Path>) -> Self {
+        config_dir
+            .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    /// Looks up `name`, or the active profile when no name is given.
+    pub fn profile(&self, name: Option<&str>) -> Option<&Profile> {
+        let name = name.or(self.active_profile.as_deref())?;
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+}
+
+/// Settings persisted as JSON in the config dir (see [`Storage`]) and shared
+/// as managed state.
+pub struct SettingsStore {
+    app: AppHandle,
+    path: Option<PathBuf>,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let dir = Storage::config_dir(app);
+        let settings = Settings::read(dir.as_deref());
+        let path = dir.map(|dir| dir.join(SETTINGS_FILE));
+        Self {
+            app: app.clone(),
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn snapshot(&self) -> Settings {
+        self.settings
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Writes `settings` and emits `telepathy://settings-changed` with them,
+    /// so the webview and listeners see edits made from either side.
+    pub fn replace(&self, settings: Settings) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("App config directory is unavailable.".to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Unable to create settings directory: {err}"))?;
+        }
+        let raw = serde_json::to_string_pretty(&settings)
+            .map_err(|err| format!("Unable to serialize settings: {err}"))?;
+        fs::write(path, raw).map_err(|err| format!("Unable to write settings: {err}"))?;
+        *self.settings.lock().unwrap_or_else(|err| err.into_inner()) = settings.clone();
+        let _ = self.app.emit("telepathy://settings-changed", &settings);
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn get_settings(store: State<'_, SettingsStore>) -> Settings {
+    store.snapshot()
+}
+
+/// Rebinding listeners joins their threads, so this runs on the blocking pool.
+/// Settings that can't work (see [`destinations::validate`]) are refused
+/// unsaved; otherwise they are saved first and every subsystem is applied even when an
+/// earlier one fails; the failures are returned together afterwards.
+#[tauri::command]
+pub async fn update_settings(app: AppHandle, settings: Settings) -> Result<Settings, String> {
+    crate::run_blocking(move || {
+        destinations::validate(&settings)?;
+        let store = app.state::<SettingsStore>();
+        store.replace(settings)?;
+        let settings = store.snapshot();
+        let mut errors = Vec::new();
+        if let Err(err) = app
+            .state::<InputTriggers>()
+            .reconfigure(&app, &settings.triggers)
+        {
+            errors.push(err);
+        }
+        if let Err(err) = app.state::<HttpApi>().reconfigure(&app, &settings.http_api) {
+            errors.push(err);
+        }
+        JobPool::configure(&app, &settings.jobs);
+        if let Err(err) = Logging::configure(&app, &settings.logging) {
+            errors.push(err);
+        }
+        crash_reports::configure(&settings.crash_reports);
+        if let Err(err) = overlay::apply(&app, &settings.overlay) {
+            tracing::warn!(%err, "overlay not moved");
+        }
+        if let Err(err) = launch::apply(&app, &settings.launch) {
+            tracing::warn!(%err, "background agent mode not applied");
+        }
+        if !errors.is_empty() {
+            let err = errors.join(" ");
+            tracing::warn!(%err, "settings saved but not fully applied");
+            return Err(err);
+        }
+        Ok(settings)
+    })
+    .await
+}
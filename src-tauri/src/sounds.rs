@@ -0,0 +1,213 @@
+#[cfg(not(target_os = "macos"))]
+use std::{
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+use tauri::{AppHandle, Listener, Manager};
+
+use crate::{
+    benchmark::LatencyBenchmark,
+    dictation::{DictationEvent, DictationPhase, DictationTransition, STATE_EVENT},
+    quiet_mode::QuietMode,
+    settings::SettingsStore,
+};
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    /// 0.0 to 1.0, relative to the system volume.
+    pub volume: f32,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.4,
+        }
+    }
+}
+
+/// Trackpad haptics, felt only while a finger rests on a Force Touch
+/// trackpad. Meant as a quiet alternative to sounds.
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HapticSettings {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cue {
+    RecordStart,
+    RecordStop,
+    PasteComplete,
+    Error,
+}
+
+impl Cue {
+    fn for_transition(transition: &DictationTransition) -> Option<Self> {
+        match (transition.event, transition.from) {
+            (DictationEvent::RecorderStarted, _) => Some(Self::RecordStart),
+            (DictationEvent::Stop, DictationPhase::Recording | DictationPhase::Paused) => {
+                Some(Self::RecordStop)
+            }
+            (DictationEvent::Finished, DictationPhase::Injecting) => Some(Self::PasteComplete),
+            (DictationEvent::Fail, _) => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+/// System sounds through NSSound, which plays on the current default output.
+#[cfg(target_os = "macos")]
+mod backend {
+    use objc2_app_kit::NSSound;
+    use objc2_foundation::NSString;
+
+    use super::Cue;
+
+    pub fn play(cue: Cue, volume: f32) {
+        let name = match cue {
+            Cue::RecordStart => "Tink",
+            Cue::RecordStop => "Pop",
+            Cue::PasteComplete => "Glass",
+            Cue::Error => "Basso",
+        };
+        if let Some(sound) = NSSound::soundNamed(&NSString::from_str(name)) {
+            // A cue still playing from a quick previous dictation restarts.
+            sound.stop();
+            sound.setVolume(volume);
+            sound.play();
+        }
+    }
+}
+
+/// Recording start, stop, and failure as NSHapticFeedbackManager taps; a
+/// finished paste has no haptic.
+#[cfg(target_os = "macos")]
+fn tap(cue: Cue) {
+    use objc2_app_kit::{
+        NSHapticFeedbackManager, NSHapticFeedbackPattern, NSHapticFeedbackPerformanceTime,
+        NSHapticFeedbackPerformer,
+    };
+
+    let pattern = match cue {
+        Cue::RecordStart => NSHapticFeedbackPattern::Alignment,
+        Cue::RecordStop => NSHapticFeedbackPattern::LevelChange,
+        Cue::Error => NSHapticFeedbackPattern::Generic,
+        Cue::PasteComplete => return,
+    };
+    NSHapticFeedbackManager::defaultPerformer()
+        .performFeedbackPattern_performanceTime(pattern, NSHapticFeedbackPerformanceTime::Now);
+}
+
+#[cfg(not(target_os = "macos"))]
+fn tap(_cue: Cue) {}
+
+/// Short synthesized tones through rodio. The output stream is opened per
+/// cue so a change of default device is followed.
+#[cfg(not(target_os = "macos"))]
+mod backend {
+    use std::time::Duration;
+
+    use rodio::{source::SineWave, OutputStream, Sink, Source};
+
+    use super::Cue;
+
+    const TONE: Duration = Duration::from_millis(70);
+    const FADE: Duration = Duration::from_millis(10);
+    /// Sine tones at full scale are harsh next to system sounds.
+    const TONE_GAIN: f32 = 0.3;
+
+    /// Blocks until the cue has played.
+    pub fn play(cue: Cue, volume: f32) {
+        let tones: &[f32] = match cue {
+            Cue::RecordStart => &[660.0, 880.0],
+            Cue::RecordStop => &[880.0, 660.0],
+            Cue::PasteComplete => &[1320.0],
+            Cue::Error => &[392.0, 262.0],
+        };
+        let Ok((_stream, handle)) = OutputStream::try_default() else {
+            return;
+        };
+        let Ok(sink) = Sink::try_new(&handle) else {
+            return;
+        };
+        sink.set_volume(volume);
+        for &frequency in tones {
+            sink.append(
+                SineWave::new(frequency)
+                    .take_duration(TONE)
+                    .fade_in(FADE)
+                    .amplify(TONE_GAIN),
+            );
+        }
+        sink.sleep_until_end();
+    }
+}
+
+/// Plays cues without blocking the caller. rodio playback blocks, so it
+/// runs on a thread started with the first cue.
+#[derive(Default)]
+pub struct Player {
+    #[cfg(not(target_os = "macos"))]
+    queue: Mutex<Option<mpsc::Sender<(Cue, f32)>>>,
+}
+
+impl Player {
+    #[cfg(target_os = "macos")]
+    fn play(&self, cue: Cue, volume: f32) {
+        backend::play(cue, volume);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn play(&self, cue: Cue, volume: f32) {
+        let mut queue = self.queue.lock().unwrap_or_else(|err| err.into_inner());
+        if queue.is_none() {
+            let (sender, cues) = mpsc::channel::<(Cue, f32)>();
+            let spawned = thread::Builder::new()
+                .name("telepathy-sounds".to_string())
+                .spawn(move || {
+                    for (cue, volume) in cues {
+                        backend::play(cue, volume);
+                    }
+                });
+            if spawned.is_err() {
+                return;
+            }
+            *queue = Some(sender);
+        }
+        if let Some(sender) = queue.as_ref() {
+            let _ = sender.send((cue, volume));
+        }
+    }
+}
+
+/// Plays feedback cues (sounds, and haptics where enabled) from the
+/// dictation state machine, so they work even when the webview is hidden or
+/// busy. Benchmark runs stay silent.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(STATE_EVENT, move |event| {
+        let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) else {
+            return;
+        };
+        let Some(cue) = Cue::for_transition(&transition) else {
+            return;
+        };
+        if handle.state::<LatencyBenchmark>().is_running() {
+            return;
+        }
+        let settings = handle.state::<SettingsStore>().snapshot();
+        if settings.haptics.enabled {
+            tap(cue);
+        }
+        if settings.sounds.enabled && !QuietMode::silenced(&handle) {
+            handle
+                .state::<Player>()
+                .play(cue, settings.sounds.volume.clamp(0.0, 1.0));
+        }
+    });
+}
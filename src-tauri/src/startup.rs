@@ -0,0 +1,124 @@
+use std::{sync::Mutex, time::Instant};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemTiming {
+    pub name: &'static str,
+    pub ms: f64,
+    /// True for work done after the window was allowed to show.
+    pub deferred: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Payload of `telepathy://ready`: how long each startup step took, so
+/// regressions in time-to-interactive show up in bug reports.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupReport {
+    /// Process start until `setup()` returned and the event loop took over.
+    pub interactive_ms: f64,
+    /// Process start until deferred initialization finished.
+    pub ready_ms: f64,
+    pub subsystems: Vec<SubsystemTiming>,
+}
+
+pub struct Startup {
+    started: Instant,
+    interactive_ms: Mutex<Option<f64>>,
+    timings: Mutex<Vec<SubsystemTiming>>,
+    report: Mutex<Option<StartupReport>>,
+}
+
+fn ms_since(since: Instant) -> f64 {
+    since.elapsed().as_secs_f64() * 1000.0
+}
+
+impl Startup {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            interactive_ms: Mutex::new(None),
+            timings: Mutex::new(Vec::new()),
+            report: Mutex::new(None),
+        }
+    }
+
+    fn record(&self, name: &'static str, deferred: bool, since: Instant, error: Option<String>) {
+        self.timings
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(SubsystemTiming {
+                name,
+                ms: ms_since(since),
+                deferred,
+                error,
+            });
+    }
+
+    /// Times a step that must finish before the window is usable.
+    pub fn measure<T>(&self, name: &'static str, step: impl FnOnce() -> T) -> T {
+        let since = Instant::now();
+        let value = step();
+        self.record(name, false, since, None);
+        value
+    }
+
+    pub fn mark_interactive(&self) {
+        *self
+            .interactive_ms
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(ms_since(self.started));
+    }
+
+    /// Runs `steps` on a background thread once setup has returned, then
+    /// emits `telepathy://ready` with the full report.
+    pub fn defer(app: &AppHandle, steps: Vec<(&'static str, DeferredStep)>) {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let startup = app.state::<Startup>();
+            for (name, step) in steps {
+                let since = Instant::now();
+                let error = step(&app).err();
+                if let Some(err) = &error {
+                    tracing::warn!(step = name, %err, "deferred startup step failed");
+                }
+                startup.record(name, true, since, error);
+            }
+            let report = StartupReport {
+                interactive_ms: startup
+                    .interactive_ms
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .unwrap_or_default(),
+                ready_ms: ms_since(startup.started),
+                subsystems: startup
+                    .timings
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .clone(),
+            };
+            tracing::info!(
+                interactive_ms = report.interactive_ms,
+                ready_ms = report.ready_ms,
+                "startup finished"
+            );
+            *startup.report.lock().unwrap_or_else(|err| err.into_inner()) = Some(report.clone());
+            let _ = app.emit("telepathy://ready", report);
+        });
+    }
+}
+
+pub type DeferredStep = Box<dyn FnOnce(&AppHandle) -> Result<(), String> + Send>;
+
+/// The startup report, or `None` while deferred initialization is running.
+#[tauri::command]
+pub fn get_startup_report(startup: State<'_, Startup>) -> Option<StartupReport> {
+    startup
+        .report
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager, State};
+
+/// The bundle identifier in `tauri.conf.json`, which names the app's
+/// directories.
+const IDENTIFIER: &str = "com.evanhu.telepathy";
+
+/// Placed next to the executable (or passed as `--portable`) to keep all
+/// app state beside it instead of under AppData.
+#[cfg(target_os = "windows")]
+const PORTABLE_MARKER: &str = "portable";
+#[cfg(target_os = "windows")]
+const PORTABLE_FLAG: &str = "--portable";
+#[cfg(target_os = "windows")]
+const PORTABLE_DIR: &str = "TelepathyData";
+
+/// Where settings, local data, and logs live, decided once at launch.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Storage {
+    pub portable: bool,
+    pub config_dir: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub log_dir: Option<PathBuf>,
+}
+
+/// `<exe dir>/TelepathyData` when portable mode is requested, for users who
+/// run the app from a USB stick or a locked-down profile.
+#[cfg(target_os = "windows")]
+fn portable_root() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let requested = exe_dir.join(PORTABLE_MARKER).is_file()
+        || std::env::args().skip(1).any(|arg| arg == PORTABLE_FLAG);
+    requested.then(|| exe_dir.join(PORTABLE_DIR))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn portable_root() -> Option<PathBuf> {
+    None
+}
+
+impl Storage {
+    fn portable() -> Option<Self> {
+        portable_root().map(|root| Self {
+            portable: true,
+            config_dir: Some(root.join("config")),
+            data_dir: Some(root.join("data")),
+            log_dir: Some(root.join("logs")),
+        })
+    }
+
+    pub fn detect(app: &AppHandle) -> Self {
+        Self::portable().unwrap_or_else(|| Self {
+            portable: false,
+            config_dir: app.path().app_config_dir().ok(),
+            data_dir: app.path().app_data_dir().ok(),
+            log_dir: app.path().app_log_dir().ok(),
+        })
+    }
+
+    /// The same directories without a running app, resolved the way Tauri
+    /// resolves them, for `telepathy transcribe`.
+    pub fn standalone() -> Self {
+        Self::portable().unwrap_or_else(|| Self {
+            portable: false,
+            config_dir: dirs::config_dir().map(|dir| dir.join(IDENTIFIER)),
+            data_dir: dirs::data_dir().map(|dir| dir.join(IDENTIFIER)),
+            log_dir: if cfg!(target_os = "macos") {
+                dirs::home_dir().map(|dir| dir.join("Library/Logs").join(IDENTIFIER))
+            } else {
+                dirs::data_local_dir().map(|dir| dir.join(IDENTIFIER).join("logs"))
+            },
+        })
+    }
+
+    pub fn config_dir(app: &AppHandle) -> Option<PathBuf> {
+        app.state::<Storage>().config_dir.clone()
+    }
+
+    pub fn data_dir(app: &AppHandle) -> Option<PathBuf> {
+        app.state::<Storage>().data_dir.clone()
+    }
+
+    pub fn log_dir(app: &AppHandle) -> Option<PathBuf> {
+        app.state::<Storage>().log_dir.clone()
+    }
+}
+
+#[tauri::command]
+pub fn get_storage_paths(storage: State<'_, Storage>) -> Storage {
+    storage.inner().clone()
+}
@@ -0,0 +1,312 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    audio::Recorder,
+    capture::AudioCapture,
+    dictation::{DictationEvent, DictationMachine},
+    settings::SettingsStore,
+    triggers::InputTriggers,
+};
+
+/// Payload of `telepathy://system-event`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SystemEvent {
+    Sleep,
+    Wake,
+    Lock,
+    Unlock,
+}
+
+/// Ends a recording before the machine sleeps or the screen locks. The
+/// stop goes through the state machine, so the webview releases the mic
+/// and transcribes what it already has; later phases finish on their own.
+fn suspend(app: &AppHandle) {
+    let machine = app.state::<DictationMachine>();
+    if machine.phase().recording() {
+        let _ = machine.handle(app, DictationEvent::Stop, None);
+    }
+    let recorder = app.state::<Recorder>();
+    if recorder.is_running() {
+        if let Err(err) = recorder.stop() {
+            tracing::warn!(%err, "recording did not stop cleanly");
+        }
+    }
+    let capture = app.state::<AudioCapture>();
+    if capture.is_running() {
+        if let Err(err) = capture.stop() {
+            tracing::warn!(%err, "audio capture did not stop cleanly");
+        }
+    }
+}
+
+/// Event taps and MIDI connections don't reliably survive sleep, so every
+/// trigger is rebuilt on wake and unlock.
+fn resume(app: &AppHandle) {
+    let settings = app.state::<SettingsStore>().snapshot();
+    if let Err(err) = app.state::<InputTriggers>().rearm(app, &settings.triggers) {
+        tracing::warn!(%err, "triggers not fully re-armed");
+    }
+}
+
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "windows", target_os = "linux")),
+    allow(dead_code)
+)]
+fn handle(app: &AppHandle, event: SystemEvent) {
+    tracing::info!(?event, "system event");
+    let _ = app.emit("telepathy://system-event", event);
+    let app = app.clone();
+    // Re-arming joins listener threads; keep it off the notifying thread.
+    tauri::async_runtime::spawn_blocking(move || match event {
+        SystemEvent::Sleep | SystemEvent::Lock => suspend(&app),
+        SystemEvent::Wake | SystemEvent::Unlock => resume(&app),
+    });
+}
+
+/// NSWorkspace sleep/wake notifications and the distributed screen-lock
+/// notifications. Both are delivered on the main thread.
+#[cfg(target_os = "macos")]
+mod backend {
+    use std::ptr::NonNull;
+
+    use block2::RcBlock;
+    use objc2_app_kit::{
+        NSWorkspace, NSWorkspaceDidWakeNotification, NSWorkspaceWillSleepNotification,
+    };
+    use objc2_foundation::{
+        NSDistributedNotificationCenter, NSNotification, NSNotificationCenter, NSString,
+    };
+    use tauri::AppHandle;
+
+    use super::{handle, SystemEvent};
+
+    fn observe(
+        center: &NSNotificationCenter,
+        name: &NSString,
+        app: &AppHandle,
+        event: SystemEvent,
+    ) {
+        let app = app.clone();
+        let block = RcBlock::new(move |_: NonNull<NSNotification>| handle(&app, event));
+        // SAFETY: no sender filter or queue, and the block only captures an
+        // AppHandle, which is Send. The center keeps the block for the life
+        // of the app, so the returned token can be dropped.
+        let _ = unsafe {
+            center.addObserverForName_object_queue_usingBlock(Some(name), None, None, &block)
+        };
+    }
+
+    pub fn subscribe(app: &AppHandle) -> Result<(), String> {
+        let handle = app.clone();
+        app.run_on_main_thread(move || {
+            let workspace = NSWorkspace::sharedWorkspace().notificationCenter();
+            // SAFETY: AppKit's notification name constants are immutable.
+            let (sleep, wake) = unsafe {
+                (
+                    NSWorkspaceWillSleepNotification,
+                    NSWorkspaceDidWakeNotification,
+                )
+            };
+            observe(&workspace, sleep, &handle, SystemEvent::Sleep);
+            observe(&workspace, wake, &handle, SystemEvent::Wake);
+            let distributed = NSDistributedNotificationCenter::defaultCenter();
+            observe(
+                &distributed,
+                &NSString::from_str("com.apple.screenIsLocked"),
+                &handle,
+                SystemEvent::Lock,
+            );
+            observe(
+                &distributed,
+                &NSString::from_str("com.apple.screenIsUnlocked"),
+                &handle,
+                SystemEvent::Unlock,
+            );
+        })
+        .map_err(|err| format!("Unable to watch for sleep and lock: {err}"))
+    }
+}
+
+/// A hidden top-level window (message-only windows miss power broadcasts)
+/// receiving WM_POWERBROADCAST and session lock changes.
+#[cfg(target_os = "windows")]
+mod backend {
+    use std::sync::OnceLock;
+
+    use tauri::AppHandle;
+    use windows_sys::{
+        w,
+        Win32::{
+            Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+            System::{
+                LibraryLoader::GetModuleHandleW,
+                RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION},
+            },
+            UI::WindowsAndMessaging::{
+                CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+                TranslateMessage, MSG, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, WM_POWERBROADCAST,
+                WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+            },
+        },
+    };
+
+    use super::{handle, SystemEvent};
+
+    /// The window procedure has no user data of its own.
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        let event = match (message, wparam as u32) {
+            (WM_POWERBROADCAST, PBT_APMSUSPEND) => Some(SystemEvent::Sleep),
+            (WM_POWERBROADCAST, PBT_APMRESUMEAUTOMATIC) => Some(SystemEvent::Wake),
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => Some(SystemEvent::Lock),
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => Some(SystemEvent::Unlock),
+            _ => None,
+        };
+        if let (Some(event), Some(app)) = (event, APP.get()) {
+            handle(app, event);
+        }
+        // SAFETY: forwarding the arguments this procedure was called with.
+        unsafe { DefWindowProcW(hwnd, message, wparam, lparam) }
+    }
+
+    fn run() -> Result<(), String> {
+        // SAFETY: the class and window are created and used on this thread
+        // only, and the message loop runs for the life of the app.
+        unsafe {
+            let instance = GetModuleHandleW(std::ptr::null());
+            let class_name = w!("TelepathySystemEvents");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance,
+                lpszClassName: class_name,
+                ..std::mem::zeroed()
+            };
+            if RegisterClassW(&class) == 0 {
+                return Err("Unable to register the system event window.".to_string());
+            }
+            let hwnd = CreateWindowExW(
+                0,
+                class_name,
+                class_name,
+                0,
+                0,
+                0,
+                0,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null(),
+            );
+            if hwnd.is_null() {
+                return Err("Unable to create the system event window.".to_string());
+            }
+            WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION);
+            let mut message: MSG = std::mem::zeroed();
+            while GetMessageW(&mut message, hwnd, 0, 0) > 0 {
+                TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn subscribe(app: &AppHandle) -> Result<(), String> {
+        let _ = APP.set(app.clone());
+        std::thread::Builder::new()
+            .name("telepathy-system-events".to_string())
+            .spawn(|| {
+                if let Err(err) = run() {
+                    tracing::warn!(%err, "system events unavailable");
+                }
+            })
+            .map(|_| ())
+            .map_err(|err| format!("Unable to watch for sleep and lock: {err}"))
+    }
+}
+
+/// logind's PrepareForSleep on the system bus, and the screensaver's
+/// ActiveChanged (GNOME and freedesktop names) on the session bus.
+#[cfg(target_os = "linux")]
+mod backend {
+    use tauri::AppHandle;
+    use zbus::{
+        blocking::{Connection, MessageIterator},
+        message::Type,
+        MatchRule,
+    };
+
+    use super::{handle, SystemEvent};
+
+    fn bus_error(err: zbus::Error) -> String {
+        format!("Unable to watch for sleep and lock: {err}")
+    }
+
+    /// Forwards a boolean signal on its own thread; `true` maps to `on`.
+    fn watch(
+        conn: &Connection,
+        rule: MatchRule<'static>,
+        app: &AppHandle,
+        on: SystemEvent,
+        off: SystemEvent,
+    ) -> Result<(), String> {
+        let signals = MessageIterator::for_match_rule(rule, conn, None).map_err(bus_error)?;
+        let app = app.clone();
+        std::thread::Builder::new()
+            .name("telepathy-system-events".to_string())
+            .spawn(move || {
+                for message in signals.flatten() {
+                    if let Ok(active) = message.body().deserialize::<bool>() {
+                        handle(&app, if active { on } else { off });
+                    }
+                }
+            })
+            .map(|_| ())
+            .map_err(|err| format!("Unable to watch for sleep and lock: {err}"))
+    }
+
+    pub fn subscribe(app: &AppHandle) -> Result<(), String> {
+        let system = Connection::system().map_err(bus_error)?;
+        let sleep = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface("org.freedesktop.login1.Manager")
+            .and_then(|builder| builder.member("PrepareForSleep"))
+            .map_err(bus_error)?
+            .build();
+        watch(&system, sleep, app, SystemEvent::Sleep, SystemEvent::Wake)?;
+
+        // Not every desktop runs a screensaver service; sleep handling
+        // still works without one.
+        let Ok(session) = Connection::session() else {
+            return Ok(());
+        };
+        let lock = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .member("ActiveChanged")
+            .map_err(bus_error)?
+            .build();
+        watch(&session, lock, app, SystemEvent::Lock, SystemEvent::Unlock)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod backend {
+    use tauri::AppHandle;
+
+    pub fn subscribe(_app: &AppHandle) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Stops recording before sleep or lock and re-arms triggers afterwards,
+/// emitting `telepathy://system-event` for each change.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    backend::subscribe(app)
+}
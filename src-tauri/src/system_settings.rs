@@ -0,0 +1,248 @@
+#[cfg(target_os = "linux")]
+use std::process::Command;
+use std::sync::OnceLock;
+
+use tauri::AppHandle;
+
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingsPanel {
+    Camera,
+    Microphone,
+    Accessibility,
+}
+
+/// One way to reach a settings panel.
+enum Link {
+    Url(&'static str),
+    /// A settings app launched with one argument, for desktops without a
+    /// URL scheme.
+    Command(&'static str, &'static str),
+}
+
+/// The OS (or desktop) release the links are chosen for, detected once.
+/// `None` means unknown, in which case every known form is tried in turn.
+/// Each build only constructs the variants for its own OS.
+#[derive(Clone, Copy)]
+enum Platform {
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    MacOs(Option<u32>),
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    Windows,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    Gnome(Option<u32>),
+    /// Plasma major version, which also names the settings binary.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    Kde(Option<u32>),
+    #[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+    Other,
+}
+
+static PLATFORM: OnceLock<Platform> = OnceLock::new();
+
+#[cfg(target_os = "macos")]
+fn detect() -> Platform {
+    let version = objc2_foundation::NSProcessInfo::processInfo().operatingSystemVersion();
+    Platform::MacOs(
+        u32::try_from(version.majorVersion)
+            .ok()
+            .filter(|&major| major > 0),
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn detect() -> Platform {
+    Platform::Windows
+}
+
+/// Major version from `gnome-shell --version` ("GNOME Shell 46.0").
+#[cfg(target_os = "linux")]
+fn gnome_version() -> Option<u32> {
+    let output = Command::new("gnome-shell").arg("--version").output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .last()?
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(target_os = "linux")]
+fn detect() -> Platform {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let desktops: Vec<&str> = desktop.split(':').collect();
+    if desktops.contains(&"GNOME") {
+        Platform::Gnome(gnome_version())
+    } else if desktops.contains(&"KDE") {
+        let version = std::env::var("KDE_SESSION_VERSION").ok();
+        Platform::Kde(version.and_then(|version| version.parse().ok()))
+    } else {
+        Platform::Other
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn detect() -> Platform {
+    Platform::Other
+}
+
+fn platform() -> Platform {
+    *PLATFORM.get_or_init(detect)
+}
+
+/// Detects the release up front so the first click doesn't wait on it.
+pub fn init() {
+    platform();
+}
+
+const MAC_CAMERA_MODERN: &str =
+    "x-apple.systempreferences:com.apple.settings.PrivacySecurity.extension?Privacy_Camera";
+const MAC_CAMERA_LEGACY: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Camera";
+const MAC_MICROPHONE_MODERN: &str =
+    "x-apple.systempreferences:com.apple.settings.PrivacySecurity.extension?Privacy_Microphone";
+const MAC_MICROPHONE_LEGACY: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone";
+const MAC_ACCESSIBILITY_MODERN: &str =
+    "x-apple.systempreferences:com.apple.settings.PrivacySecurity.extension?Privacy_Accessibility";
+const MAC_ACCESSIBILITY_LEGACY: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+const MAC_SECURITY: &str = "x-apple.systempreferences:com.apple.preference.security";
+/// Ventura replaced System Preferences with System Settings and its
+/// extension-style pane identifiers.
+const MAC_SETTINGS_APP: u32 = 13;
+/// GNOME 46 renamed the Universal Access panel to Accessibility.
+const GNOME_ACCESSIBILITY_PANEL: u32 = 46;
+
+/// Links for `panel` on `platform`, most specific first.
+fn links(panel: SettingsPanel, platform: Platform) -> Vec<Link> {
+    use Link::{Command, Url};
+    use SettingsPanel::{Accessibility, Camera, Microphone};
+
+    match (platform, panel) {
+        (Platform::MacOs(Some(major)), Camera) if major >= MAC_SETTINGS_APP => {
+            vec![Url(MAC_CAMERA_MODERN)]
+        }
+        (Platform::MacOs(Some(_)), Camera) => vec![Url(MAC_CAMERA_LEGACY)],
+        (Platform::MacOs(Some(major)), Microphone) if major >= MAC_SETTINGS_APP => {
+            vec![Url(MAC_MICROPHONE_MODERN)]
+        }
+        (Platform::MacOs(Some(_)), Microphone) => vec![Url(MAC_MICROPHONE_LEGACY)],
+        (Platform::MacOs(Some(major)), Accessibility) if major >= MAC_SETTINGS_APP => {
+            vec![Url(MAC_ACCESSIBILITY_MODERN)]
+        }
+        (Platform::MacOs(Some(_)), Accessibility) => vec![Url(MAC_ACCESSIBILITY_LEGACY)],
+        (Platform::MacOs(None), Camera) => vec![
+            Url(MAC_CAMERA_LEGACY),
+            Url(MAC_CAMERA_MODERN),
+            Url(MAC_SECURITY),
+        ],
+        (Platform::MacOs(None), Microphone) => vec![
+            Url(MAC_MICROPHONE_LEGACY),
+            Url(MAC_MICROPHONE_MODERN),
+            Url(MAC_SECURITY),
+        ],
+        (Platform::MacOs(None), Accessibility) => vec![
+            Url(MAC_ACCESSIBILITY_LEGACY),
+            Url(MAC_ACCESSIBILITY_MODERN),
+            Url(MAC_SECURITY),
+        ],
+        (Platform::Windows, Camera) => vec![Url("ms-settings:privacy-webcam")],
+        (Platform::Windows, Microphone) => vec![Url("ms-settings:privacy-microphone")],
+        // Windows has no accessibility grant for synthesized input.
+        (Platform::Windows, Accessibility) => Vec::new(),
+        (Platform::Gnome(_), Camera) => vec![Command("gnome-control-center", "camera")],
+        (Platform::Gnome(_), Microphone) => vec![Command("gnome-control-center", "microphone")],
+        (Platform::Gnome(Some(major)), Accessibility) if major >= GNOME_ACCESSIBILITY_PANEL => {
+            vec![Command("gnome-control-center", "accessibility")]
+        }
+        (Platform::Gnome(Some(_)), Accessibility) => {
+            vec![Command("gnome-control-center", "universal-access")]
+        }
+        (Platform::Gnome(None), Accessibility) => vec![
+            Command("gnome-control-center", "accessibility"),
+            Command("gnome-control-center", "universal-access"),
+        ],
+        // Plasma has no camera or microphone privacy panel.
+        (Platform::Kde(_), Camera | Microphone) => Vec::new(),
+        (Platform::Kde(Some(5)), Accessibility) => vec![Command("systemsettings5", "kcm_access")],
+        (Platform::Kde(Some(_)), Accessibility) => vec![Command("systemsettings", "kcm_access")],
+        (Platform::Kde(None), Accessibility) => vec![
+            Command("systemsettings", "kcm_access"),
+            Command("systemsettings5", "kcm_access"),
+        ],
+        (Platform::Other, Camera) => vec![Command("gnome-control-center", "camera")],
+        (Platform::Other, Microphone) => vec![Command("gnome-control-center", "microphone")],
+        (Platform::Other, Accessibility) => vec![
+            Command("gnome-control-center", "accessibility"),
+            Command("gnome-control-center", "universal-access"),
+            Command("systemsettings", "kcm_access"),
+        ],
+    }
+}
+
+/// Hands `url` to LaunchServices directly; `openURL:` reports failure as
+/// `false` when no app claims the URL or the pane identifier is unknown.
+#[cfg(target_os = "macos")]
+fn open_url(_app: &AppHandle, url: &str) -> Result<(), String> {
+    use objc2_app_kit::NSWorkspace;
+    use objc2_foundation::{NSString, NSURL};
+
+    let ns_url = NSURL::URLWithString(&NSString::from_str(url))
+        .ok_or_else(|| format!("{url} is not a valid URL."))?;
+    if NSWorkspace::sharedWorkspace().openURL(&ns_url) {
+        Ok(())
+    } else {
+        Err(format!("No application accepted {url}."))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn open_url(app: &AppHandle, url: &str) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|err| format!("Unable to open {url}: {err}"))
+}
+
+#[cfg(target_os = "linux")]
+fn launch(program: &str, arg: &str) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .arg(arg)
+        .spawn()
+        .map_err(|err| format!("Unable to run {program}: {err}"))?;
+    // Reap the settings app whenever the user closes it.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn launch(program: &str, _arg: &str) -> Result<(), String> {
+    Err(format!("{program} is not available on this platform."))
+}
+
+pub fn open_panel(app: &AppHandle, panel: SettingsPanel) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for link in links(panel, platform()) {
+        let opened = match link {
+            Link::Url(url) => open_url(app, url),
+            Link::Command(program, arg) => launch(program, arg),
+        };
+        match opened {
+            Ok(()) => return Ok(()),
+            Err(err) => errors.push(err),
+        }
+    }
+    Err(if errors.is_empty() {
+        "This settings panel has no deep link on this platform.".to_string()
+    } else {
+        format!("Unable to open System Settings. {}", errors.join(" "))
+    })
+}
+
+#[tauri::command]
+pub async fn open_system_settings(app: AppHandle, panel: SettingsPanel) -> Result<(), String> {
+    crate::run_blocking(move || open_panel(&app, panel)).await
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::destinations::DestinationKind;
+
+/// Mirrors transcripts as JSON lines into a file or named pipe so scripts can
+/// `tail -f` (or `cat` a FIFO) without talking to the app, and to stdout for
+/// scripts that launch it themselves.
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TranscriptStreamSettings {
+    pub enabled: bool,
+    pub path: Option<PathBuf>,
+    /// Also print each line to the app's standard output.
+    pub stdout: bool,
+    /// Also write in-progress partial transcripts from streaming providers.
+    pub include_partials: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptLine<'a> {
+    kind: &'static str,
+    text: &'a str,
+    timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<DestinationKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delivered: Option<bool>,
+}
+
+#[cfg(unix)]
+fn open_for_append(path: &Path) -> io::Result<std::fs::File> {
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+    let is_fifo = std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_fifo());
+    // O_NONBLOCK makes opening a FIFO without a reader fail fast (ENXIO)
+    // instead of hanging the dictation until someone starts reading.
+    OpenOptions::new()
+        .append(true)
+        .create(!is_fifo)
+        .custom_flags(if is_fifo { libc::O_NONBLOCK } else { 0 })
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn open_for_append(path: &Path) -> io::Result<std::fs::File> {
+    OpenOptions::new().append(true).create(true).open(path)
+}
+
+fn encode(line: &TranscriptLine) -> Result<String, String> {
+    let mut raw = serde_json::to_string(line)
+        .map_err(|err| format!("Unable to serialize transcript line: {err}"))?;
+    raw.push('\n');
+    Ok(raw)
+}
+
+fn write_line(settings: &TranscriptStreamSettings, line: &TranscriptLine) -> Result<(), String> {
+    if !settings.enabled || (settings.path.is_none() && !settings.stdout) {
+        return Ok(());
+    }
+    let raw = encode(line)?;
+    if settings.stdout {
+        let mut stdout = io::stdout().lock();
+        stdout
+            .write_all(raw.as_bytes())
+            .and_then(|()| stdout.flush())
+            .map_err(|err| format!("Unable to write transcript stream to stdout: {err}"))?;
+    }
+    let Some(path) = settings.path.as_deref() else {
+        return Ok(());
+    };
+    let mut file = match open_for_append(path) {
+        Ok(file) => file,
+        // A FIFO with no reader attached: nobody is listening, so drop the line.
+        #[cfg(unix)]
+        Err(err) if err.raw_os_error() == Some(libc::ENXIO) => return Ok(()),
+        Err(err) => {
+            return Err(format!(
+                "Unable to open transcript stream {}: {err}",
+                path.display()
+            ))
+        }
+    };
+    // A single write keeps each line atomic for pipe readers (lines stay
+    // well under PIPE_BUF in practice).
+    file.write_all(raw.as_bytes())
+        .map_err(|err| format!("Unable to write transcript stream: {err}"))
+}
+
+pub fn write_final(
+    settings: &TranscriptStreamSettings,
+    text: &str,
+    provider: Option<&str>,
+    destination: DestinationKind,
+    delivered: bool,
+) -> Result<(), String> {
+    write_line(
+        settings,
+        &TranscriptLine {
+            kind: "final",
+            text,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            provider,
+            destination: Some(destination),
+            delivered: Some(delivered),
+        },
+    )
+}
+
+/// Writes an in-progress transcript when `include_partials` is on.
+pub fn write_partial(
+    settings: &TranscriptStreamSettings,
+    text: &str,
+    provider: Option<&str>,
+) -> Result<(), String> {
+    if !settings.include_partials {
+        return Ok(());
+    }
+    write_line(
+        settings,
+        &TranscriptLine {
+            kind: "partial",
+            text,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            provider,
+            destination: None,
+            delivered: None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_lines_carry_the_delivery() {
+        let raw = encode(&TranscriptLine {
+            kind: "final",
+            text: "hello \"world\"",
+            timestamp: "2026-01-02T09:30:00+00:00".to_string(),
+            provider: Some("Deepgram"),
+            destination: Some(DestinationKind::Paste),
+            delivered: Some(true),
+        })
+        .expect("line encodes");
+        assert!(raw.ends_with('\n'));
+        assert_eq!(raw.matches('\n').count(), 1);
+        let line: serde_json::Value = serde_json::from_str(&raw).expect("valid JSON");
+        assert_eq!(line["kind"], "final");
+        assert_eq!(line["text"], "hello \"world\"");
+        assert_eq!(line["provider"], "Deepgram");
+        assert_eq!(line["delivered"], true);
+    }
+
+    #[test]
+    fn partial_lines_leave_out_delivery_fields() {
+        let raw = encode(&TranscriptLine {
+            kind: "partial",
+            text: "line one\nline two",
+            timestamp: String::new(),
+            provider: None,
+            destination: None,
+            delivered: None,
+        })
+        .expect("line encodes");
+        // Newlines in the text stay escaped, one line per transcript.
+        assert_eq!(raw.matches('\n').count(), 1);
+        let line: serde_json::Value = serde_json::from_str(&raw).expect("valid JSON");
+        assert!(line.get("destination").is_none());
+        assert!(line.get("delivered").is_none());
+        assert!(line.get("provider").is_none());
+    }
+}
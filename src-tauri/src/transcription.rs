@@ -0,0 +1,4 @@
+pub mod local;
+pub mod models;
+pub mod providers;
+pub mod streaming;
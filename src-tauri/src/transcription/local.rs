@@ -0,0 +1,345 @@
+#[cfg(feature = "local-whisper")]
+use std::sync::Mutex;
+use std::{path::Path, sync::Arc, time::Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::models;
+use crate::{
+    audio::{RecordedAudio, Recorder},
+    dictation::DictationMachine,
+    event_throttle::{EventThrottle, DEFAULT_MAX_HZ},
+    locale,
+    settings::SettingsStore,
+};
+
+const PROGRESS_EVENT: &str = "telepathy://local-transcription";
+/// Whisper only takes 16 kHz mono.
+const SAMPLE_RATE: u32 = 16_000;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalTranscriptionSettings {
+    /// One of the names `list_local_models` reports. Changing it swaps the
+    /// model on the next transcription; `set_local_model` swaps it now.
+    pub model: String,
+    /// Decoder threads; 0 lets whisper.cpp choose.
+    pub threads: u16,
+    /// Run the encoder through CoreML on the Neural Engine, in builds with
+    /// the `coreml` feature. The model's CoreML encoder is downloaded on
+    /// first use; until it is ready, or if that fails, whisper.cpp uses
+    /// Metal or the CPU.
+    pub coreml: bool,
+}
+
+impl Default for LocalTranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            model: "base.en".to_string(),
+            threads: 0,
+            coreml: false,
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "local-whisper"), allow(dead_code))]
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Stage {
+    Loading,
+    Transcribing,
+    Done,
+}
+
+/// Payload of `telepathy://local-transcription`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalProgress {
+    stage: Stage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percent: Option<i32>,
+}
+
+/// Where loading and decoding progress goes: `telepathy://local-transcription`
+/// in the app, nowhere from the command line.
+type OnProgress = Arc<dyn Fn(Stage, Option<i32>) + Send + Sync>;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTranscript {
+    pub text: String,
+    pub model: String,
+    pub language: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// How whisper.cpp decodes one recording.
+#[derive(Clone, Default)]
+#[cfg_attr(not(feature = "local-whisper"), allow(dead_code))]
+pub struct Decoding {
+    /// ISO 639-1 code, or `None` to detect it.
+    pub language: Option<String>,
+    /// Decoder threads; 0 lets whisper.cpp choose.
+    pub threads: u16,
+    /// The vocabulary's words, as the initial prompt.
+    pub prompt: Option<String>,
+}
+
+/// The loaded whisper.cpp model, kept between dictations.
+#[derive(Default)]
+pub struct LocalWhisper {
+    #[cfg(feature = "local-whisper")]
+    context: Mutex<Option<(String, whisper_rs::WhisperContext)>>,
+}
+
+impl LocalWhisper {
+    /// Frees the loaded model; the next transcription loads it again.
+    pub fn release(&self) {
+        #[cfg(feature = "local-whisper")]
+        if self
+            .context
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+            .is_some()
+        {
+            tracing::info!("local whisper model released");
+        }
+    }
+
+    /// Frees the loaded model if it is `name`, so its file can be replaced or
+    /// removed. Returns whether it was loaded.
+    pub fn release_model(&self, name: &str) -> bool {
+        #[cfg(feature = "local-whisper")]
+        {
+            let mut context = self.context.lock().unwrap_or_else(|err| err.into_inner());
+            if context.as_ref().is_some_and(|(loaded, _)| loaded == name) {
+                *context = None;
+                tracing::info!(model = name, "local whisper model released");
+                return true;
+            }
+        }
+        let _ = name;
+        false
+    }
+
+    /// Loads `name` in place of the current model ahead of the next
+    /// transcription. A no-op in builds without local transcription.
+    pub fn preload(&self, app: &AppHandle, name: &str) -> Result<(), String> {
+        #[cfg(feature = "local-whisper")]
+        {
+            let path = models::model_path(app, name)?;
+            #[cfg(feature = "coreml")]
+            models::prepare_encoder(app, name);
+            let mut context = self.context.lock().unwrap_or_else(|err| err.into_inner());
+            load(&mut context, name, &path, &reporter(app))?;
+        }
+        let _ = (app, name);
+        Ok(())
+    }
+
+    /// Transcribes `audio` with the model file at `path`, loaded as `name`
+    /// in place of whichever model was loaded, as `decoding` says. Blocks
+    /// until done. Needs nothing from the running app, so `telepathy
+    /// transcribe` uses it directly.
+    pub fn transcribe(
+        &self,
+        audio: &RecordedAudio,
+        name: &str,
+        path: &Path,
+        decoding: Decoding,
+    ) -> Result<LocalTranscript, String> {
+        transcribe_model(self, audio, name, path, decoding, Arc::new(|_, _| {}))
+    }
+}
+
+fn emit_progress(app: &AppHandle, stage: Stage, percent: Option<i32>) {
+    EventThrottle::emit(
+        app,
+        PROGRESS_EVENT,
+        DEFAULT_MAX_HZ,
+        LocalProgress { stage, percent },
+    );
+}
+
+fn reporter(app: &AppHandle) -> OnProgress {
+    let app = app.clone();
+    Arc::new(move |stage, percent| emit_progress(&app, stage, percent))
+}
+
+/// Loads `name` into `cached` unless it is already there.
+#[cfg(feature = "local-whisper")]
+fn load(
+    cached: &mut Option<(String, whisper_rs::WhisperContext)>,
+    name: &str,
+    path: &Path,
+    on_progress: &OnProgress,
+) -> Result<(), String> {
+    use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+    if cached.as_ref().is_some_and(|(loaded, _)| loaded == name) {
+        return Ok(());
+    }
+    // Drop the old model first so two never sit in memory together.
+    *cached = None;
+    on_progress(Stage::Loading, None);
+    let path = path
+        .to_str()
+        .ok_or("The model path is not valid UTF-8.".to_string())?;
+    let context = WhisperContext::new_with_params(path, WhisperContextParameters::default())
+        .map_err(|err| format!("Unable to load the {name} model: {err}"))?;
+    tracing::info!(model = name, "local whisper model loaded");
+    *cached = Some((name.to_string(), context));
+    Ok(())
+}
+
+#[cfg(feature = "local-whisper")]
+fn run(
+    whisper: &LocalWhisper,
+    name: &str,
+    path: &Path,
+    samples: &[f32],
+    decoding: &Decoding,
+    on_progress: OnProgress,
+) -> Result<String, String> {
+    use whisper_rs::{FullParams, SamplingStrategy};
+
+    let mut cached = whisper
+        .context
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    load(&mut cached, name, path, &on_progress)?;
+    let Some((_, context)) = cached.as_ref() else {
+        return Err("The local model is not loaded.".to_string());
+    };
+    let mut state = context
+        .create_state()
+        .map_err(|err| format!("Unable to start local transcription: {err}"))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(decoding.language.as_deref());
+    if decoding.threads > 0 {
+        params.set_n_threads(decoding.threads.into());
+    }
+    if let Some(prompt) = &decoding.prompt {
+        params.set_initial_prompt(prompt);
+    }
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    params.set_progress_callback_safe(move |percent: i32| {
+        on_progress(Stage::Transcribing, Some(percent));
+    });
+    state
+        .full(params, samples)
+        .map_err(|err| format!("Local transcription failed: {err}"))?;
+    let text: String = state
+        .as_iter()
+        .filter_map(|segment| segment.to_str_lossy().ok().map(|text| text.into_owned()))
+        .collect();
+    Ok(text.trim().to_string())
+}
+
+#[cfg(not(feature = "local-whisper"))]
+fn run(
+    _whisper: &LocalWhisper,
+    _name: &str,
+    _path: &Path,
+    _samples: &[f32],
+    _decoding: &Decoding,
+    _on_progress: OnProgress,
+) -> Result<String, String> {
+    Err(
+        "This build does not include local transcription (the `local-whisper` feature)."
+            .to_string(),
+    )
+}
+
+/// Transcribes `audio` with the whisper.cpp model `model`, which replaces
+/// whichever model was loaded. Blocks until done.
+pub fn transcribe(
+    app: &AppHandle,
+    audio: &RecordedAudio,
+    model: &str,
+) -> Result<LocalTranscript, String> {
+    let settings = app.state::<SettingsStore>().snapshot();
+    let path = models::model_path(app, model)?;
+    #[cfg(feature = "coreml")]
+    models::prepare_encoder(app, model);
+    let decoding = Decoding {
+        language: locale::dictation_language(app)
+            .and_then(|tag| tag.split(['-', '_']).next().map(str::to_lowercase)),
+        threads: settings.local_transcription.threads,
+        prompt: settings.vocabulary.prompt(),
+    };
+    let transcript = transcribe_model(
+        &app.state::<LocalWhisper>(),
+        audio,
+        model,
+        &path,
+        decoding,
+        reporter(app),
+    )?;
+    let _ = app.emit(
+        PROGRESS_EVENT,
+        LocalProgress {
+            stage: Stage::Done,
+            percent: Some(100),
+        },
+    );
+    Ok(transcript)
+}
+
+fn transcribe_model(
+    whisper: &LocalWhisper,
+    audio: &RecordedAudio,
+    name: &str,
+    path: &Path,
+    mut decoding: Decoding,
+    on_progress: OnProgress,
+) -> Result<LocalTranscript, String> {
+    if !path.is_file() {
+        return Err(format!("The {name} model is not downloaded yet."));
+    }
+    // English-only models can't be asked for anything else.
+    if name.ends_with(".en") {
+        decoding.language = Some("en".to_string());
+    }
+    let started = Instant::now();
+    let samples = audio.to_mono(SAMPLE_RATE);
+    let text = run(whisper, name, path, &samples, &decoding, on_progress)
+        .inspect_err(|err| tracing::warn!(%err, "local transcription failed"))?;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    tracing::info!(model = name, elapsed_ms, "local transcription finished");
+    Ok(LocalTranscript {
+        text,
+        model: name.to_string(),
+        language: decoding.language,
+        elapsed_ms,
+    })
+}
+
+/// Transcribes `audio`, or the last native recording when none is given,
+/// with the selected whisper.cpp model. Runs fully offline and emits
+/// `telepathy://local-transcription` as it loads and decodes.
+#[tauri::command]
+pub async fn transcribe_local(
+    app: AppHandle,
+    audio: Option<RecordedAudio>,
+) -> Result<LocalTranscript, String> {
+    let span = app.state::<DictationMachine>().span();
+    crate::run_blocking(move || {
+        let _span = span.entered();
+        let audio = audio
+            .or_else(|| app.state::<Recorder>().take())
+            .ok_or("There is no recording to transcribe.".to_string())?;
+        let model = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .local_transcription
+            .model;
+        transcribe(&app, &audio, &model)
+    })
+    .await
+}
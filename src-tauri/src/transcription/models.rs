@@ -0,0 +1,503 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use super::local::LocalWhisper;
+use crate::{
+    event_throttle::{EventThrottle, DEFAULT_MAX_HZ},
+    settings::SettingsStore,
+    storage::Storage,
+};
+
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+/// Hugging Face's file listing, which carries each model's SHA-256.
+const MODEL_TREE_URL: &str = "https://huggingface.co/api/models/ggerganov/whisper.cpp/tree/main";
+const DOWNLOAD_EVENT: &str = "telepathy://local-model-download";
+/// Under the app data dir.
+const MODELS_DIR: &str = "whisper-models";
+/// Under the models dir: CoreML encoders moved aside while
+/// `localTranscription.coreml` is off, since whisper.cpp uses any it finds.
+#[cfg(feature = "coreml")]
+const PARKED_ENCODERS_DIR: &str = "coreml-off";
+
+/// whisper.cpp's ggml models offered for download, with their approximate
+/// size. `.en` models are English-only and a little more accurate for it;
+/// `-q5_*` and `-q8_0` are quantized, smaller and faster at a small cost in
+/// accuracy.
+const MODELS: &[(&str, u32)] = &[
+    ("tiny.en", 75),
+    ("tiny", 75),
+    ("tiny.en-q5_1", 31),
+    ("tiny-q5_1", 31),
+    ("tiny-q8_0", 42),
+    ("base.en", 142),
+    ("base", 142),
+    ("base.en-q5_1", 57),
+    ("base-q5_1", 57),
+    ("base-q8_0", 78),
+    ("small.en", 466),
+    ("small", 466),
+    ("small.en-q5_1", 181),
+    ("small-q5_1", 181),
+    ("small-q8_0", 252),
+    ("medium.en", 1500),
+    ("medium", 1500),
+    ("medium.en-q5_0", 514),
+    ("medium-q5_0", 514),
+    ("medium-q8_0", 785),
+    ("large-v3-turbo", 1600),
+    ("large-v3-turbo-q5_0", 547),
+    ("large-v3-turbo-q8_0", 834),
+];
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModel {
+    pub name: &'static str,
+    pub approx_size_mb: u32,
+    pub english_only: bool,
+    /// Quantization type such as `q5_1`; `None` for full precision.
+    pub quantization: Option<&'static str>,
+    pub downloaded: bool,
+    pub selected: bool,
+    /// The published file differs from the downloaded one. Only known for
+    /// models downloaded with a checksum and when the listing was reachable.
+    pub update_available: bool,
+}
+
+/// Payload of `telepathy://local-model-download`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadProgress<'a> {
+    name: &'a str,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// A file in the Hugging Face listing; only LFS files carry `lfs`.
+#[derive(serde::Deserialize)]
+struct TreeEntry {
+    path: String,
+    lfs: Option<LfsObject>,
+}
+
+#[derive(Clone, serde::Deserialize)]
+struct LfsObject {
+    /// SHA-256 of the file contents.
+    oid: String,
+    size: u64,
+}
+
+/// Downloads in flight and the published checksums, fetched once and kept
+/// until a refresh is asked for.
+#[derive(Default)]
+pub struct ModelManager {
+    /// Models being downloaded, so a second request doesn't race the first.
+    downloading: Mutex<Vec<String>>,
+    /// CoreML encoders fetched this run, or being fetched, so a failed
+    /// fetch isn't retried on every transcription.
+    #[cfg(feature = "coreml")]
+    encoders: Mutex<std::collections::HashSet<String>>,
+    published: tokio::sync::Mutex<Option<HashMap<String, LfsObject>>>,
+}
+
+impl ModelManager {
+    async fn published(&self, refresh: bool) -> Result<HashMap<String, LfsObject>, String> {
+        let mut published = self.published.lock().await;
+        if let Some(cached) = published.as_ref().filter(|_| !refresh) {
+            return Ok(cached.clone());
+        }
+        let entries: Vec<TreeEntry> = reqwest::get(MODEL_TREE_URL)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| format!("Unable to fetch the model list: {err}"))?
+            .json()
+            .await
+            .map_err(|err| format!("Unable to read the model list: {err}"))?;
+        let fresh: HashMap<String, LfsObject> = entries
+            .into_iter()
+            .filter_map(|entry| Some((entry.path, entry.lfs?)))
+            .collect();
+        *published = Some(fresh.clone());
+        Ok(fresh)
+    }
+}
+
+fn known(name: &str) -> Result<(&'static str, u32), String> {
+    MODELS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .copied()
+        .ok_or(format!("Unknown local model: {name}"))
+}
+
+fn file_name(name: &str) -> String {
+    format!("ggml-{name}.bin")
+}
+
+fn models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Storage::data_dir(app)
+        .map(|dir| dir.join(MODELS_DIR))
+        .ok_or("No data directory for local models.".to_string())
+}
+
+pub fn model_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let (name, _) = known(name)?;
+    Ok(models_dir(app)?.join(file_name(name)))
+}
+
+/// Where `name` is downloaded to under the app data dir `data_dir`, for
+/// callers without a running app.
+pub fn model_file(data_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let (name, _) = known(name)?;
+    Ok(data_dir.join(MODELS_DIR).join(file_name(name)))
+}
+
+/// Where the verified SHA-256 of a downloaded model is kept, so checking for
+/// updates doesn't rehash gigabytes.
+fn checksum_path(path: &Path) -> PathBuf {
+    path.with_extension("bin.sha256")
+}
+
+fn quantization(name: &str) -> Option<&str> {
+    name.rsplit_once('-')
+        .map(|(_, suffix)| suffix)
+        .filter(|suffix| suffix.starts_with('q') && suffix.contains('_'))
+}
+
+/// The CoreML encoder whisper.cpp looks for beside `name`'s model file.
+/// Quantized models share their full-precision model's encoder.
+#[cfg_attr(not(feature = "coreml"), allow(dead_code))]
+fn encoder_name(name: &str) -> String {
+    let base = quantization(name).map_or(name, |suffix| &name[..name.len() - suffix.len() - 1]);
+    format!("ggml-{base}-encoder.mlmodelc")
+}
+
+/// Models that can be downloaded and which of them are on disk. Update
+/// checks use the published checksums, fetched on first use or when
+/// `refresh` is set; offline, the list is still returned without them.
+#[tauri::command]
+pub async fn list_local_models(
+    app: AppHandle,
+    refresh: Option<bool>,
+) -> Result<Vec<LocalModel>, String> {
+    let published = app
+        .state::<ModelManager>()
+        .published(refresh.unwrap_or(false))
+        .await
+        .inspect_err(|err| tracing::warn!(%err, "model updates not checked"))
+        .unwrap_or_default();
+    let selected = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .local_transcription
+        .model;
+    let dir = models_dir(&app)?;
+    Ok(MODELS
+        .iter()
+        .map(|&(name, approx_size_mb)| {
+            let path = dir.join(file_name(name));
+            let local_checksum = fs::read_to_string(checksum_path(&path)).ok();
+            let update_available = match (&local_checksum, published.get(&file_name(name))) {
+                (Some(local), Some(remote)) => local.trim() != remote.oid,
+                _ => false,
+            };
+            LocalModel {
+                name,
+                approx_size_mb,
+                english_only: name
+                    .split('-')
+                    .next()
+                    .is_some_and(|base| base.ends_with(".en")),
+                quantization: quantization(name),
+                downloaded: path.is_file(),
+                selected: name == selected,
+                update_available,
+            }
+        })
+        .collect())
+}
+
+/// Downloads a model into the data directory, or its newer version over
+/// the old one, emitting `telepathy://local-model-download`. The file is
+/// checked against its published SHA-256 and renamed into place only once
+/// it matches. A model that was in use is swapped for the new file.
+#[tauri::command]
+pub async fn download_local_model(app: AppHandle, name: String) -> Result<(), String> {
+    let path = model_path(&app, &name)?;
+    {
+        let manager = app.state::<ModelManager>();
+        let mut downloading = manager
+            .downloading
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if downloading.contains(&name) {
+            return Err(format!("The {name} model is already downloading."));
+        }
+        downloading.push(name.clone());
+    }
+    let result = download(&app, &name, &path).await;
+    app.state::<ModelManager>()
+        .downloading
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .retain(|pending| *pending != name);
+    result?;
+    if app.state::<LocalWhisper>().release_model(&name) {
+        let selected = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .local_transcription
+            .model;
+        if selected == name {
+            preload(app, name).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn download(app: &AppHandle, name: &str, path: &Path) -> Result<(), String> {
+    let published = app.state::<ModelManager>().published(true).await?;
+    let expected = published
+        .get(&file_name(name))
+        .cloned()
+        .ok_or(format!("The {name} model is no longer published."))?;
+    let url = format!("{MODEL_BASE_URL}/{}", file_name(name));
+    tracing::info!(model = name, "downloading local model");
+    let mut response = reqwest::get(&url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Unable to download the {name} model: {err}"))?;
+    let total = response.content_length().or(Some(expected.size));
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    }
+    let partial = path.with_extension("bin.part");
+    let mut file = fs::File::create(&partial)
+        .map_err(|err| format!("Unable to create {}: {err}", partial.display()))?;
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0u64;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| format!("The {name} model download failed: {err}"))?
+    {
+        file.write_all(&chunk)
+            .map_err(|err| format!("Unable to write {}: {err}", partial.display()))?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+        EventThrottle::emit(
+            app,
+            DOWNLOAD_EVENT,
+            DEFAULT_MAX_HZ,
+            DownloadProgress {
+                name,
+                downloaded,
+                total,
+            },
+        );
+    }
+    drop(file);
+    let checksum = format!("{:x}", hasher.finalize());
+    if checksum != expected.oid {
+        let _ = fs::remove_file(&partial);
+        return Err(format!(
+            "The {name} model download is corrupt (checksum mismatch); try again."
+        ));
+    }
+    fs::rename(&partial, path)
+        .map_err(|err| format!("Unable to move {} into place: {err}", path.display()))?;
+    if let Err(err) = fs::write(checksum_path(path), &checksum) {
+        tracing::warn!(%err, "model checksum not saved");
+    }
+    tracing::info!(model = name, bytes = downloaded, "local model downloaded");
+    Ok(())
+}
+
+fn remove(app: &AppHandle, name: &str) -> Result<(), String> {
+    let path = model_path(app, name)?;
+    app.state::<LocalWhisper>().release_model(name);
+    fs::remove_file(&path).map_err(|err| format!("Unable to remove {}: {err}", path.display()))?;
+    let _ = fs::remove_file(checksum_path(&path));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_local_model(app: AppHandle, name: String) -> Result<(), String> {
+    remove(&app, &name)
+}
+
+/// Deletes every downloaded model except the selected one, returning the
+/// names removed.
+#[tauri::command]
+pub async fn delete_unused_local_models(app: AppHandle) -> Result<Vec<String>, String> {
+    crate::run_blocking(move || {
+        let selected = app
+            .state::<SettingsStore>()
+            .snapshot()
+            .local_transcription
+            .model;
+        let dir = models_dir(&app)?;
+        let mut removed = Vec::new();
+        for &(name, _) in MODELS {
+            if name != selected && dir.join(file_name(name)).is_file() {
+                remove(&app, name)?;
+                removed.push(name.to_string());
+            }
+        }
+        tracing::info!(count = removed.len(), "unused local models deleted");
+        Ok(removed)
+    })
+    .await
+}
+
+async fn preload(app: AppHandle, name: String) -> Result<(), String> {
+    crate::run_blocking(move || app.state::<LocalWhisper>().preload(&app, &name)).await
+}
+
+/// Makes `name` the local model and loads it in place of the current one,
+/// so the next dictation uses it without a restart or a load delay.
+#[tauri::command]
+pub async fn set_local_model(app: AppHandle, name: String) -> Result<(), String> {
+    let path = model_path(&app, &name)?;
+    if !path.is_file() {
+        return Err(format!("The {name} model is not downloaded yet."));
+    }
+    let handle = app.clone();
+    let selected = name.clone();
+    crate::run_blocking(move || {
+        let store = handle.state::<SettingsStore>();
+        let mut settings = store.snapshot();
+        settings.local_transcription.model = selected;
+        store.replace(settings)
+    })
+    .await?;
+    tracing::info!(model = %name, "local model selected");
+    preload(app, name).await
+}
+
+/// Puts `name`'s CoreML encoder where whisper.cpp looks for it while
+/// `localTranscription.coreml` is on, and moves it aside while off. A
+/// missing encoder is fetched in the background; until it arrives, or if
+/// the fetch fails, whisper.cpp falls back to Metal or the CPU.
+#[cfg(feature = "coreml")]
+pub fn prepare_encoder(app: &AppHandle, name: &str) {
+    let enabled = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .local_transcription
+        .coreml;
+    let Ok(dir) = models_dir(app) else {
+        return;
+    };
+    let encoder = encoder_name(name);
+    let active = dir.join(&encoder);
+    let parked = dir.join(PARKED_ENCODERS_DIR).join(&encoder);
+    let moved = if enabled && !active.is_dir() && parked.is_dir() {
+        fs::rename(&parked, &active)
+    } else if !enabled && active.is_dir() {
+        let _ = fs::remove_dir_all(&parked);
+        fs::create_dir_all(dir.join(PARKED_ENCODERS_DIR))
+            .and_then(|()| fs::rename(&active, &parked))
+    } else {
+        Ok(())
+    };
+    if let Err(err) = moved {
+        tracing::warn!(%err, encoder, "CoreML encoder not moved");
+    }
+    if !enabled || active.is_dir() || parked.is_dir() {
+        return;
+    }
+    let first = app
+        .state::<ModelManager>()
+        .encoders
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .insert(encoder.clone());
+    if first {
+        tauri::async_runtime::spawn(async move {
+            match fetch_encoder(dir, encoder.clone()).await {
+                Ok(()) => tracing::info!(encoder, "CoreML encoder ready"),
+                Err(err) => {
+                    tracing::warn!(%err, encoder, "CoreML unavailable, using Metal or the CPU")
+                }
+            }
+        });
+    }
+}
+
+/// Downloads the published `encoder` archive into `dir` and unpacks it in
+/// place. Nothing appears under the final name until it is complete.
+#[cfg(feature = "coreml")]
+async fn fetch_encoder(dir: PathBuf, encoder: String) -> Result<(), String> {
+    let url = format!("{MODEL_BASE_URL}/{encoder}.zip");
+    tracing::info!(encoder, "downloading CoreML encoder");
+    let mut response = reqwest::get(&url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Unable to download {encoder}: {err}"))?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let archive = dir.join(format!("{encoder}.zip.part"));
+    let mut file = fs::File::create(&archive)
+        .map_err(|err| format!("Unable to create {}: {err}", archive.display()))?;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| format!("The {encoder} download failed: {err}"))?
+    {
+        file.write_all(&chunk)
+            .map_err(|err| format!("Unable to write {}: {err}", archive.display()))?;
+    }
+    drop(file);
+    crate::run_blocking(move || {
+        let staging = dir.join(format!("{encoder}.part"));
+        let _ = fs::remove_dir_all(&staging);
+        let unpacked = fs::File::open(&archive)
+            .map_err(|err| err.to_string())
+            .and_then(|file| zip::ZipArchive::new(file).map_err(|err| err.to_string()))
+            .and_then(|mut zip| zip.extract(&staging).map_err(|err| err.to_string()))
+            .and_then(|()| {
+                fs::rename(staging.join(&encoder), dir.join(&encoder))
+                    .map_err(|err| err.to_string())
+            })
+            .map_err(|err| format!("Unable to unpack {encoder}: {err}"));
+        let _ = fs::remove_dir_all(&staging);
+        let _ = fs::remove_file(&archive);
+        unpacked
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantization_reads_the_suffix() {
+        assert_eq!(quantization("base.en-q5_1"), Some("q5_1"));
+        assert_eq!(quantization("large-v3-turbo-q8_0"), Some("q8_0"));
+        assert_eq!(quantization("large-v3-turbo"), None);
+        assert_eq!(quantization("base.en"), None);
+    }
+
+    #[test]
+    fn quantized_models_share_the_full_precision_encoder() {
+        assert_eq!(encoder_name("base.en"), "ggml-base.en-encoder.mlmodelc");
+        assert_eq!(
+            encoder_name("base.en-q5_1"),
+            "ggml-base.en-encoder.mlmodelc"
+        );
+        assert_eq!(
+            encoder_name("large-v3-turbo-q5_0"),
+            "ggml-large-v3-turbo-encoder.mlmodelc"
+        );
+    }
+}
@@ -0,0 +1,596 @@
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+use tracing::Instrument;
+
+use crate::{
+    audio::{RecordedAudio, Recorder},
+    costs::{self, Metered},
+    dictation::DictationMachine,
+    locale,
+    secrets::{self, SecretKey},
+    settings::SettingsStore,
+    vocabulary::VocabularySettings,
+};
+
+use super::local::{self, LocalTranscript};
+
+/// Uploads are resampled to this; every provider accepts 16 kHz mono WAV.
+const SAMPLE_RATE: u32 = 16_000;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// AssemblyAI transcribes asynchronously; how often to ask if it's done.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MULTIPART_BOUNDARY: &str = "telepathy-upload-boundary";
+
+/// Cloud transcription services. The lip-reading server stays the default
+/// when none is selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderKind {
+    OpenAi,
+    Deepgram,
+    AssemblyAi,
+}
+
+impl ProviderKind {
+    pub const ALL: [ProviderKind; 3] = [Self::OpenAi, Self::Deepgram, Self::AssemblyAi];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OpenAi => "OpenAI Whisper",
+            Self::Deepgram => "Deepgram",
+            Self::AssemblyAi => "AssemblyAI",
+        }
+    }
+
+    pub fn secret(self) -> SecretKey {
+        match self {
+            Self::OpenAi => SecretKey::OpenAiApiKey,
+            Self::Deepgram => SecretKey::DeepgramApiKey,
+            Self::AssemblyAi => SecretKey::AssemblyAiApiKey,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderSettings {
+    /// Selected with `set_provider`; `None` keeps the local lip-reading
+    /// server.
+    pub provider: Option<ProviderKind>,
+    pub openai_model: String,
+    pub deepgram_model: String,
+    /// Extra attempts after a network error, rate limit, or server error.
+    pub max_retries: u32,
+    pub timeout_secs: u64,
+    /// Stream audio to the provider while recording and show partial
+    /// results. Deepgram only, and needs `audio.nativeCapture`.
+    pub streaming: bool,
+}
+
+impl Default for ProviderSettings {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            openai_model: "whisper-1".to_string(),
+            deepgram_model: "nova-2".to_string(),
+            max_retries: 2,
+            timeout_secs: 60,
+            streaming: false,
+        }
+    }
+}
+
+impl ProviderSettings {
+    /// These settings with `model` in place of `kind`'s configured one.
+    /// AssemblyAI has no model to choose.
+    pub fn with_model(mut self, kind: ProviderKind, model: Option<String>) -> Self {
+        match (kind, model) {
+            (ProviderKind::OpenAi, Some(model)) => self.openai_model = model,
+            (ProviderKind::Deepgram, Some(model)) => self.deepgram_model = model,
+            _ => {}
+        }
+        self
+    }
+}
+
+/// Why a provider request failed, normalized across APIs. Only transient
+/// failures are retried.
+enum Failure {
+    Transient(String),
+    Fatal(String),
+}
+
+impl Failure {
+    fn message(self) -> String {
+        match self {
+            Failure::Transient(message) | Failure::Fatal(message) => message,
+        }
+    }
+}
+
+fn request_failed(name: &str, err: reqwest::Error) -> Failure {
+    if err.is_timeout() {
+        Failure::Transient(format!("{name} did not respond in time."))
+    } else if err.is_connect() {
+        Failure::Transient(format!("Unable to reach {name}: {err}"))
+    } else {
+        Failure::Fatal(format!("{name} request failed: {err}"))
+    }
+}
+
+/// Maps a non-success response to the same messages for every provider.
+async fn response_failure(name: &str, response: reqwest::Response) -> Failure {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let detail = body.trim();
+    match status.as_u16() {
+        401 | 403 => Failure::Fatal(format!("{name} rejected the API key.")),
+        402 => Failure::Fatal(format!("{name} reports the account is out of credit.")),
+        408 | 429 => Failure::Transient(format!("{name} is rate limiting requests.")),
+        500..=599 => Failure::Transient(format!("{name} had a server error ({status}).")),
+        _ => Failure::Fatal(format!("{name} responded with {status}: {detail}")),
+    }
+}
+
+async fn json(name: &str, response: reqwest::Response) -> Result<serde_json::Value, Failure> {
+    if !response.status().is_success() {
+        return Err(response_failure(name, response).await);
+    }
+    response
+        .json()
+        .await
+        .map_err(|err| Failure::Fatal(format!("{name} sent an unreadable response: {err}")))
+}
+
+fn missing(name: &str, field: &str) -> Failure {
+    Failure::Fatal(format!("{name}'s response has no {field}."))
+}
+
+/// What a provider needs for one upload.
+struct Upload<'a> {
+    client: &'a reqwest::Client,
+    api_key: &'a str,
+    wav: &'a [u8],
+    /// ISO 639-1 code, or `None` to let the provider detect it.
+    language: Option<&'a str>,
+    settings: &'a ProviderSettings,
+    /// Custom words to bias recognition toward.
+    vocabulary: &'a VocabularySettings,
+}
+
+/// A cloud speech-to-text API. Implementations make one attempt; retries
+/// and key handling live in [`transcribe`].
+trait TranscriptionProvider {
+    const KIND: ProviderKind;
+
+    async fn transcribe(&self, upload: &Upload<'_>) -> Result<String, Failure>;
+}
+
+struct OpenAi;
+
+impl TranscriptionProvider for OpenAi {
+    const KIND: ProviderKind = ProviderKind::OpenAi;
+
+    async fn transcribe(&self, upload: &Upload<'_>) -> Result<String, Failure> {
+        let name = Self::KIND.name();
+        let prompt = upload.vocabulary.prompt();
+        let mut fields = vec![("model", upload.settings.openai_model.as_str())];
+        if let Some(language) = upload.language {
+            fields.push(("language", language));
+        }
+        if let Some(prompt) = &prompt {
+            fields.push(("prompt", prompt));
+        }
+        let response = upload
+            .client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .bearer_auth(upload.api_key)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"),
+            )
+            .body(multipart(&fields, upload.wav))
+            .send()
+            .await
+            .map_err(|err| request_failed(name, err))?;
+        let body = json(name, response).await?;
+        body["text"]
+            .as_str()
+            .map(|text| text.trim().to_string())
+            .ok_or_else(|| missing(name, "text"))
+    }
+}
+
+/// A `multipart/form-data` body with text `fields` and the audio as `file`.
+/// Hand-built since it is the only multipart upload in the app.
+fn multipart(fields: &[(&str, &str)], wav: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(wav.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(wav);
+    body.extend_from_slice(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+struct Deepgram;
+
+impl TranscriptionProvider for Deepgram {
+    const KIND: ProviderKind = ProviderKind::Deepgram;
+
+    async fn transcribe(&self, upload: &Upload<'_>) -> Result<String, Failure> {
+        let name = Self::KIND.name();
+        let mut query = vec![
+            ("model", upload.settings.deepgram_model.as_str()),
+            ("smart_format", "true"),
+        ];
+        match upload.language {
+            Some(language) => query.push(("language", language)),
+            None => query.push(("detect_language", "true")),
+        }
+        let parameter = keyword_parameter(&upload.settings.deepgram_model);
+        query.extend(upload.vocabulary.terms().map(|term| (parameter, term)));
+        let response = upload
+            .client
+            .post("https://api.deepgram.com/v1/listen")
+            .query(&query)
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Token {}", upload.api_key),
+            )
+            .header(reqwest::header::CONTENT_TYPE, "audio/wav")
+            .body(upload.wav.to_vec())
+            .send()
+            .await
+            .map_err(|err| request_failed(name, err))?;
+        let body = json(name, response).await?;
+        body["results"]["channels"][0]["alternatives"][0]["transcript"]
+            .as_str()
+            .map(|text| text.trim().to_string())
+            .ok_or_else(|| missing(name, "transcript"))
+    }
+}
+
+/// Nova-3 takes custom words as `keyterm`; earlier models as `keywords`.
+pub(crate) fn keyword_parameter(model: &str) -> &'static str {
+    if model.starts_with("nova-3") {
+        "keyterm"
+    } else {
+        "keywords"
+    }
+}
+
+struct AssemblyAi;
+
+impl TranscriptionProvider for AssemblyAi {
+    const KIND: ProviderKind = ProviderKind::AssemblyAi;
+
+    /// Uploads the audio, queues a transcript for it, and polls until it
+    /// finishes; AssemblyAI has no synchronous endpoint.
+    async fn transcribe(&self, upload: &Upload<'_>) -> Result<String, Failure> {
+        let name = Self::KIND.name();
+        let response = upload
+            .client
+            .post("https://api.assemblyai.com/v2/upload")
+            .header(reqwest::header::AUTHORIZATION, upload.api_key)
+            .body(upload.wav.to_vec())
+            .send()
+            .await
+            .map_err(|err| request_failed(name, err))?;
+        let uploaded = json(name, response).await?;
+        let audio_url = uploaded["upload_url"]
+            .as_str()
+            .ok_or_else(|| missing(name, "upload URL"))?;
+
+        let mut request = serde_json::json!({ "audio_url": audio_url });
+        match upload.language {
+            Some(language) => request["language_code"] = language.into(),
+            None => request["language_detection"] = true.into(),
+        }
+        let word_boost: Vec<&str> = upload.vocabulary.terms().collect();
+        if !word_boost.is_empty() {
+            request["word_boost"] = word_boost.into();
+        }
+        let response = upload
+            .client
+            .post("https://api.assemblyai.com/v2/transcript")
+            .header(reqwest::header::AUTHORIZATION, upload.api_key)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|err| request_failed(name, err))?;
+        let queued = json(name, response).await?;
+        let id = queued["id"]
+            .as_str()
+            .ok_or_else(|| missing(name, "transcript id"))?;
+
+        let started = Instant::now();
+        let timeout = Duration::from_secs(upload.settings.timeout_secs);
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let response = upload
+                .client
+                .get(format!("https://api.assemblyai.com/v2/transcript/{id}"))
+                .header(reqwest::header::AUTHORIZATION, upload.api_key)
+                .send()
+                .await
+                .map_err(|err| request_failed(name, err))?;
+            let transcript = json(name, response).await?;
+            match transcript["status"].as_str() {
+                Some("completed") => {
+                    return Ok(transcript["text"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string())
+                }
+                Some("error") => {
+                    let error = transcript["error"].as_str().unwrap_or("unknown error");
+                    return Err(Failure::Fatal(format!("{name} failed: {error}")));
+                }
+                _ if started.elapsed() > timeout => {
+                    return Err(Failure::Transient(format!(
+                        "{name} did not finish in time."
+                    )));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// One attempt plus up to `max_retries` more with exponential backoff for
+/// transient failures.
+async fn with_retries<P: TranscriptionProvider>(
+    provider: P,
+    upload: &Upload<'_>,
+) -> Result<(String, u32), String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match provider.transcribe(upload).await {
+            Ok(text) => return Ok((text, attempt)),
+            Err(Failure::Transient(message)) if attempt <= upload.settings.max_retries => {
+                let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                tracing::warn!(provider = ?P::KIND, attempt, %message, "retrying transcription");
+                tokio::time::sleep(delay).await;
+            }
+            Err(failure) => return Err(failure.message()),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudTranscript {
+    pub text: String,
+    pub provider: ProviderKind,
+    pub language: Option<String>,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+}
+
+/// What `transcribe` returns: the provider's transcript, or the local
+/// model's when the monthly budget blocks cloud transcription.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase", tag = "transcriber")]
+pub enum Transcript {
+    Cloud(CloudTranscript),
+    Local(LocalTranscript),
+}
+
+/// Transcribes `audio` with the selected provider. The API key is read from
+/// the credential store here, so it never passes through the webview.
+pub async fn transcribe(app: &AppHandle, audio: &RecordedAudio) -> Result<Transcript, String> {
+    let kind = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .provider
+        .provider
+        .ok_or("No transcription provider is selected.".to_string())?;
+    transcribe_with(app, audio, kind, None).await
+}
+
+/// Transcribes `audio` with `kind`, selected or not, and `model` in place
+/// of the configured one. AssemblyAI has no model to choose. Once the
+/// monthly budget blocks cloud transcription, the selected local model
+/// transcribes it instead; otherwise the audio sent is metered.
+pub async fn transcribe_with(
+    app: &AppHandle,
+    audio: &RecordedAudio,
+    kind: ProviderKind,
+    model: Option<String>,
+) -> Result<Transcript, String> {
+    let snapshot = app.state::<SettingsStore>().snapshot();
+    if !costs::cloud_allowed(app).await {
+        tracing::info!(
+            provider = kind.name(),
+            "cloud budget used up, transcribing locally"
+        );
+        let (handle, audio) = (app.clone(), audio.clone());
+        let model = snapshot.local_transcription.model;
+        return crate::run_blocking(move || local::transcribe(&handle, &audio, &model))
+            .await
+            .map(Transcript::Local);
+    }
+    let settings = snapshot.provider.with_model(kind, model);
+    let language = locale::dictation_language(app)
+        .and_then(|tag| tag.split(['-', '_']).next().map(str::to_lowercase));
+    let transcript = request(audio, kind, &settings, &snapshot.vocabulary, language).await?;
+    let metered = Metered {
+        audio_ms: audio.duration_ms(),
+        ..Metered::default()
+    };
+    costs::record(app, kind, metered).await;
+    Ok(Transcript::Cloud(transcript))
+}
+
+/// Transcribes `audio` with `kind` as `settings` configure it, in
+/// `language` (ISO 639-1) or a detected one, biased toward `vocabulary`'s
+/// words. Needs nothing from the running app, so `telepathy transcribe`
+/// uses it directly.
+pub async fn request(
+    audio: &RecordedAudio,
+    kind: ProviderKind,
+    settings: &ProviderSettings,
+    vocabulary: &VocabularySettings,
+    language: Option<String>,
+) -> Result<CloudTranscript, String> {
+    let api_key = crate::run_blocking(move || secrets::read_secret(kind.secret()))
+        .await?
+        .ok_or_else(|| format!("No API key is stored for {}.", kind.name()))?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(settings.timeout_secs))
+        .build()
+        .map_err(|err| format!("Unable to create an HTTP client: {err}"))?;
+    let wav = audio.to_wav(SAMPLE_RATE);
+    let upload = Upload {
+        client: &client,
+        api_key: &api_key,
+        wav: &wav,
+        language: language.as_deref(),
+        settings,
+        vocabulary,
+    };
+
+    let started = Instant::now();
+    let result = match kind {
+        ProviderKind::OpenAi => with_retries(OpenAi, &upload).await,
+        ProviderKind::Deepgram => with_retries(Deepgram, &upload).await,
+        ProviderKind::AssemblyAi => with_retries(AssemblyAi, &upload).await,
+    };
+    let (text, attempts) = result
+        .inspect_err(|err| tracing::warn!(provider = ?kind, %err, "cloud transcription failed"))?;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    tracing::info!(
+        provider = ?kind,
+        attempts,
+        elapsed_ms,
+        audio_ms = audio.duration_ms(),
+        "cloud transcription finished"
+    );
+    Ok(CloudTranscript {
+        text,
+        provider: kind,
+        language,
+        attempts,
+        elapsed_ms,
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub id: ProviderKind,
+    pub name: &'static str,
+    /// Whether an API key is in the credential store.
+    pub configured: bool,
+    pub selected: bool,
+}
+
+#[tauri::command]
+pub async fn list_providers(app: AppHandle) -> Result<Vec<ProviderInfo>, String> {
+    let selected = app.state::<SettingsStore>().snapshot().provider.provider;
+    crate::run_blocking(move || {
+        ProviderKind::ALL
+            .into_iter()
+            .map(|kind| {
+                Ok(ProviderInfo {
+                    id: kind,
+                    name: kind.name(),
+                    configured: secrets::read_secret(kind.secret())?.is_some(),
+                    selected: selected == Some(kind),
+                })
+            })
+            .collect()
+    })
+    .await
+}
+
+/// A stored API key as the webview sees it: only enough to recognize it.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyHint {
+    pub provider: ProviderKind,
+    /// The last four characters, e.g. `••••a1b2`.
+    pub masked: String,
+}
+
+fn mask(key: &str) -> String {
+    let hidden = key.chars().count().saturating_sub(4);
+    let tail: String = key.chars().skip(hidden).collect();
+    format!("••••{tail}")
+}
+
+/// Saves `provider`'s API key in the macOS Keychain, Windows Credential
+/// Manager, or the Secret Service (libsecret) on Linux.
+#[tauri::command]
+pub async fn store_api_key(provider: ProviderKind, key: String) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err(format!("The {} API key is empty.", provider.name()));
+    }
+    crate::run_blocking(move || secrets::write_secret(provider.secret(), &key)).await?;
+    tracing::info!(?provider, "API key stored");
+    Ok(())
+}
+
+/// Whether `provider` has a stored key, masked. The key itself never leaves
+/// Rust.
+#[tauri::command]
+pub async fn get_api_key(provider: ProviderKind) -> Result<Option<ApiKeyHint>, String> {
+    let key = crate::run_blocking(move || secrets::read_secret(provider.secret())).await?;
+    Ok(key.map(|key| ApiKeyHint {
+        provider,
+        masked: mask(&key),
+    }))
+}
+
+#[tauri::command]
+pub async fn delete_api_key(provider: ProviderKind) -> Result<(), String> {
+    crate::run_blocking(move || secrets::remove_secret(provider.secret())).await?;
+    tracing::info!(?provider, "API key deleted");
+    Ok(())
+}
+
+/// Selects the cloud provider, or the local lip-reading server with `None`.
+#[tauri::command]
+pub async fn set_provider(
+    app: AppHandle,
+    provider: Option<ProviderKind>,
+) -> Result<Option<ProviderKind>, String> {
+    crate::run_blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let mut settings = store.snapshot();
+        settings.provider.provider = provider;
+        store.replace(settings)?;
+        tracing::info!(?provider, "transcription provider changed");
+        Ok(provider)
+    })
+    .await
+}
+
+/// Transcribes `audio`, or the last native recording when none is given,
+/// with the selected cloud provider, or locally once the budget blocks it.
+#[tauri::command]
+pub async fn transcribe_with_provider(
+    app: AppHandle,
+    audio: Option<RecordedAudio>,
+) -> Result<Transcript, String> {
+    let audio = audio
+        .or_else(|| app.state::<Recorder>().take())
+        .ok_or("There is no recording to transcribe.".to_string())?;
+    let span = app.state::<DictationMachine>().span();
+    transcribe(&app, &audio).instrument(span).await
+}
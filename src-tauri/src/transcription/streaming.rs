@@ -0,0 +1,257 @@
+use futures_util::{SinkExt, StreamExt};
+use tauri::{AppHandle, Emitter, Listener, Manager, Url};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+use super::providers::{keyword_parameter, ProviderKind};
+use crate::{
+    analytics::Analytics,
+    audio::{RecordedAudio, Recorder},
+    costs::{self, Metered},
+    dictation::{DictationEvent, DictationTransition, STATE_EVENT},
+    event_throttle::{EventThrottle, DEFAULT_MAX_HZ},
+    locale, secrets,
+    settings::SettingsStore,
+    transcript_stream,
+    vocabulary::VocabularySettings,
+};
+
+const PARTIAL_EVENT: &str = "telepathy://partial-transcript";
+/// Deepgram's live endpoint takes raw `linear16` at the rate it is told.
+const SAMPLE_RATE: u32 = 16_000;
+
+/// Payload of `telepathy://partial-transcript`. `text` is everything heard
+/// so far in the session; `done` marks the last event once the stream has
+/// closed.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PartialTranscript {
+    session: u64,
+    text: String,
+    done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Finalized segments plus the segment still being revised.
+#[derive(Default)]
+struct Heard {
+    finals: Vec<String>,
+    interim: String,
+}
+
+impl Heard {
+    fn text(&self) -> String {
+        self.finals
+            .iter()
+            .map(String::as_str)
+            .chain((!self.interim.is_empty()).then_some(self.interim.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Deepgram's live endpoint for `model`, in `language` or its default,
+/// biased toward `vocabulary`'s words.
+fn listen_url(
+    model: &str,
+    language: Option<&str>,
+    vocabulary: &VocabularySettings,
+) -> Result<String, String> {
+    let sample_rate = SAMPLE_RATE.to_string();
+    let mut query = vec![
+        ("model", model),
+        ("encoding", "linear16"),
+        ("sample_rate", &sample_rate),
+        ("channels", "1"),
+        ("interim_results", "true"),
+        ("smart_format", "true"),
+    ];
+    if let Some(language) = language {
+        query.push(("language", language));
+    }
+    let parameter = keyword_parameter(model);
+    query.extend(vocabulary.terms().map(|term| (parameter, term)));
+    Url::parse_with_params("wss://api.deepgram.com/v1/listen", &query)
+        .map(String::from)
+        .map_err(|err| format!("Invalid Deepgram streaming URL: {err}"))
+}
+
+/// Streams one recording to Deepgram and emits the transcript as it grows.
+/// Returns the final text once the recording ends and Deepgram has flushed.
+/// The audio sent is metered, even when the stream fails partway.
+async fn stream(
+    app: &AppHandle,
+    session: u64,
+    mut chunks: UnboundedReceiver<RecordedAudio>,
+) -> Result<String, String> {
+    let kind = ProviderKind::Deepgram;
+    let name = kind.name();
+    if !costs::cloud_allowed(app).await {
+        return Err(costs::blocked_error(app));
+    }
+    let settings = app.state::<SettingsStore>().snapshot();
+    let api_key = crate::run_blocking(move || secrets::read_secret(kind.secret()))
+        .await?
+        .ok_or_else(|| format!("No API key is stored for {name}."))?;
+    let language = locale::dictation_language(app)
+        .and_then(|tag| tag.split(['-', '_']).next().map(str::to_lowercase));
+
+    let mut request = listen_url(
+        &settings.provider.deepgram_model,
+        language.as_deref(),
+        &settings.vocabulary,
+    )?
+    .into_client_request()
+    .map_err(|err| format!("Invalid {name} streaming request: {err}"))?;
+    let auth = format!("Token {api_key}")
+        .parse()
+        .map_err(|_| format!("The {name} API key is not a valid header value."))?;
+    request.headers_mut().insert("Authorization", auth);
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|err| format!("Unable to open a {name} stream: {err}"))?;
+    tracing::info!(session, "streaming transcription connected");
+    let (mut sink, mut source) = socket.split();
+
+    let mut sent_ms = 0;
+    let send = async {
+        while let Some(chunk) = chunks.recv().await {
+            sent_ms += chunk.duration_ms();
+            sink.send(Message::binary(chunk.to_pcm16(SAMPLE_RATE)))
+                .await
+                .map_err(|err| format!("{name} stream failed: {err}"))?;
+        }
+        // The recording ended: ask Deepgram to flush and close.
+        sink.send(Message::text(r#"{"type":"CloseStream"}"#))
+            .await
+            .map_err(|err| format!("{name} stream failed: {err}"))
+    };
+
+    let receive = async {
+        let mut heard = Heard::default();
+        while let Some(message) = source.next().await {
+            let message = message.map_err(|err| format!("{name} stream failed: {err}"))?;
+            let Message::Text(raw) = message else {
+                continue;
+            };
+            let Ok(result) = serde_json::from_str::<serde_json::Value>(raw.as_str()) else {
+                continue;
+            };
+            if result["type"] != "Results" {
+                continue;
+            }
+            let transcript = result["channel"]["alternatives"][0]["transcript"]
+                .as_str()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            if result["is_final"].as_bool().unwrap_or(false) {
+                heard.interim.clear();
+                if !transcript.is_empty() {
+                    heard.finals.push(transcript);
+                }
+            } else {
+                heard.interim = transcript;
+            }
+            let text = heard.text();
+            if text.is_empty() {
+                continue;
+            }
+            app.state::<Analytics>().first_partial(Some(name));
+            if let Err(err) =
+                transcript_stream::write_partial(&settings.transcript_stream, &text, Some(name))
+            {
+                tracing::warn!(%err, "partial transcript not streamed");
+            }
+            EventThrottle::emit(
+                app,
+                PARTIAL_EVENT,
+                DEFAULT_MAX_HZ,
+                PartialTranscript {
+                    session,
+                    text,
+                    done: false,
+                    error: None,
+                },
+            );
+        }
+        Ok(heard.finals.join(" "))
+    };
+
+    let result = tokio::try_join!(send, receive);
+    let metered = Metered {
+        audio_ms: sent_ms,
+        ..Metered::default()
+    };
+    costs::record(app, kind, metered).await;
+    let ((), text) = result?;
+    Ok(text)
+}
+
+/// Opens a live transcription stream for each hotkey dictation when
+/// `provider.streaming` is on, Deepgram is the selected provider, and the
+/// native recorder is capturing. The overlay follows
+/// `telepathy://partial-transcript`.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(STATE_EVENT, move |event| {
+        let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) else {
+            return;
+        };
+        if transition.event != DictationEvent::Arm {
+            return;
+        }
+        let settings = handle.state::<SettingsStore>().snapshot();
+        if !settings.provider.streaming
+            || settings.provider.provider != Some(ProviderKind::Deepgram)
+            || !settings.audio.native_capture
+        {
+            return;
+        }
+        // Subscribe before the recorder opens so no audio is missed.
+        let chunks = handle.state::<Recorder>().subscribe();
+        let handle = handle.clone();
+        let session = transition.session;
+        tauri::async_runtime::spawn(async move {
+            let result = stream(&handle, session, chunks).await;
+            if let Err(err) = &result {
+                tracing::warn!(%err, "streaming transcription failed");
+            }
+            let (text, error) = match result {
+                Ok(text) => (text, None),
+                Err(err) => (String::new(), Some(err)),
+            };
+            let _ = handle.emit(
+                PARTIAL_EVENT,
+                PartialTranscript {
+                    session,
+                    text,
+                    done: true,
+                    error,
+                },
+            );
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_url_encodes_vocabulary_words() {
+        let vocabulary = VocabularySettings {
+            words: vec!["Tauri".to_string(), "C++ & Rust".to_string()],
+            ..VocabularySettings::default()
+        };
+        let url = listen_url("nova-2", Some("de"), &vocabulary).unwrap();
+        assert!(url.starts_with("wss://api.deepgram.com/v1/listen?model=nova-2&"));
+        assert!(url.contains("&language=de&"));
+        assert!(url.ends_with("&keywords=Tauri&keywords=C%2B%2B+%26+Rust"));
+
+        let url = listen_url("nova-3", None, &vocabulary).unwrap();
+        assert!(!url.contains("language="));
+        assert!(url.contains("&keyterm=Tauri&"));
+    }
+}
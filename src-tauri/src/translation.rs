@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{llm, settings::SettingsStore};
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TranslationSettings {
+    /// Language the translate hotkey's transcripts come out in: a BCP 47
+    /// tag like `de` or `pt-BR`, or a name like `Japanese`.
+    pub target: String,
+    /// OpenAI chat model that does the translation, with the OpenAI API key
+    /// stored for transcription.
+    pub model: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for TranslationSettings {
+    fn default() -> Self {
+        Self {
+            target: "en".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            timeout_secs: 20,
+        }
+    }
+}
+
+/// Set when the dictation in progress was started by the translate hotkey.
+#[derive(Default)]
+pub struct PendingTranslation(AtomicBool);
+
+impl PendingTranslation {
+    pub fn set(&self, pending: bool) {
+        self.0.store(pending, Ordering::Release);
+    }
+
+    pub fn take(&self) -> bool {
+        self.0.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Translates `text` into `settings.target`. Returns the text unchanged when
+/// it is blank.
+pub async fn translate(
+    app: &AppHandle,
+    text: &str,
+    settings: &TranslationSettings,
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Ok(text.to_string());
+    }
+    let instructions = format!(
+        "Translate the user's dictated text into {}. Reply with the translation only, keeping the meaning, tone, and formatting. If it is already in that language, return it unchanged.",
+        settings.target
+    );
+    llm::complete(
+        app,
+        &settings.model,
+        settings.timeout_secs,
+        &instructions,
+        text,
+    )
+    .await
+}
+
+/// Sets the language the translate hotkey translates into and saves it.
+#[tauri::command]
+pub async fn set_translation_target(app: AppHandle, target: String) -> Result<String, String> {
+    let target = target.trim().to_string();
+    if target.is_empty() {
+        return Err("Choose a language to translate into.".to_string());
+    }
+    crate::run_blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let mut settings = store.snapshot();
+        settings.translation.target = target.clone();
+        store.replace(settings)?;
+        tracing::info!(%target, "translation target changed");
+        Ok(target)
+    })
+    .await
+}
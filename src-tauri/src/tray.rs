@@ -0,0 +1,184 @@
+use tauri::{
+    image::Image,
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    tray::{TrayIcon, TrayIconBuilder},
+    AppHandle, Listener, Manager, Wry,
+};
+
+use crate::{
+    destinations::{self, LastTranscript},
+    dictation::{DictationMachine, DictationPhase, DictationTransition, STATE_EVENT},
+    panels,
+    triggers::{self, InputTriggers, TriggerSource},
+};
+
+const TOGGLE: &str = "toggle";
+const LAST_TRANSCRIPT: &str = "lastTranscript";
+const OPEN_SETTINGS: &str = "openSettings";
+const PAUSE_HOTKEY: &str = "pauseHotkey";
+const QUIT: &str = "quit";
+const TOOLTIP: &str = "Telepathy";
+/// `#E5484D`, drawn over the app icon while recording.
+const RECORDING_DOT: [u8; 4] = [0xE5, 0x48, 0x4D, 0xFF];
+
+/// The tray (menu bar on macOS) icon and the menu items it updates.
+pub struct Tray {
+    icon: TrayIcon,
+    toggle: MenuItem<Wry>,
+    last_transcript: MenuItem<Wry>,
+    pause: CheckMenuItem<Wry>,
+    idle_icon: Image<'static>,
+    recording_icon: Image<'static>,
+}
+
+/// The app icon with a red dot in its lower-right quarter.
+fn with_recording_dot(icon: &Image<'_>) -> Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    let radius = f64::from(width.min(height)) / 4.0;
+    let center = (
+        f64::from(width) - radius - 1.0,
+        f64::from(height) - radius - 1.0,
+    );
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (f64::from(x) + 0.5 - center.0, f64::from(y) + 0.5 - center.1);
+            if dx * dx + dy * dy <= radius * radius {
+                let offset = ((y * width + x) * 4) as usize;
+                rgba[offset..offset + 4].copy_from_slice(&RECORDING_DOT);
+            }
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
+
+fn last_transcript_label(text: Option<&str>) -> (String, bool) {
+    match text {
+        Some(text) => {
+            let title = destinations::title_from(text);
+            let ellipsis = if title.len() < text.len() { "…" } else { "" };
+            (
+                format!("Paste Again: \u{201c}{title}{ellipsis}\u{201d}"),
+                true,
+            )
+        }
+        None => ("No Transcript Yet".to_string(), false),
+    }
+}
+
+impl Tray {
+    /// Follows the dictation: the dot and "Stop Dictation" while recording,
+    /// and the newest transcript once it has been delivered.
+    fn refresh(&self, app: &AppHandle, phase: DictationPhase) {
+        let (icon, tooltip, toggle) = if phase == DictationPhase::Paused {
+            (&self.recording_icon, "Telepathy: Paused", "Stop Dictation")
+        } else if phase.recording() {
+            (
+                &self.recording_icon,
+                "Telepathy: Recording",
+                "Stop Dictation",
+            )
+        } else {
+            (&self.idle_icon, TOOLTIP, "Start Dictation")
+        };
+        let _ = self.icon.set_icon(Some(icon.clone()));
+        let _ = self.icon.set_tooltip(Some(tooltip));
+        let _ = self.toggle.set_text(toggle);
+        let (label, enabled) =
+            last_transcript_label(app.state::<LastTranscript>().get().as_deref());
+        let _ = self.last_transcript.set_text(label);
+        let _ = self.last_transcript.set_enabled(enabled);
+        let _ = self
+            .pause
+            .set_checked(app.state::<InputTriggers>().paused());
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        TOGGLE => triggers::toggle(app, TriggerSource::Tray),
+        LAST_TRANSCRIPT => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = destinations::paste_last(&app).await;
+            });
+        }
+        OPEN_SETTINGS => {
+            if let Err(err) = panels::open_settings(app, None) {
+                tracing::warn!(%err, "settings window not opened");
+            }
+        }
+        PAUSE_HOTKEY => {
+            let triggers = app.state::<InputTriggers>();
+            triggers.set_paused(app, !triggers.paused());
+        }
+        QUIT => app.exit(0),
+        _ => {}
+    }
+}
+
+/// Adds the tray icon and keeps it in step with the dictation state, the
+/// last transcript, and the hotkey pause.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let idle_icon = app
+        .default_window_icon()
+        .ok_or("The app has no icon for the tray.".to_string())?
+        .clone()
+        .to_owned();
+    let recording_icon = with_recording_dot(&idle_icon);
+
+    let build = || -> tauri::Result<Tray> {
+        let toggle = MenuItem::with_id(app, TOGGLE, "Start Dictation", true, None::<&str>)?;
+        let (label, enabled) = last_transcript_label(None);
+        let last_transcript =
+            MenuItem::with_id(app, LAST_TRANSCRIPT, label, enabled, None::<&str>)?;
+        let settings = MenuItem::with_id(app, OPEN_SETTINGS, "Open Settings", true, None::<&str>)?;
+        let pause =
+            CheckMenuItem::with_id(app, PAUSE_HOTKEY, "Pause Hotkey", true, false, None::<&str>)?;
+        let quit = MenuItem::with_id(app, QUIT, "Quit Telepathy", true, None::<&str>)?;
+        let menu = Menu::with_items(
+            app,
+            &[
+                &toggle,
+                &last_transcript,
+                &PredefinedMenuItem::separator(app)?,
+                &settings,
+                &pause,
+                &PredefinedMenuItem::separator(app)?,
+                &quit,
+            ],
+        )?;
+        let icon = TrayIconBuilder::with_id("main")
+            .icon(idle_icon.clone())
+            .tooltip(TOOLTIP)
+            .menu(&menu)
+            .show_menu_on_left_click(true)
+            .on_menu_event(on_menu_event)
+            .build(app)?;
+        Ok(Tray {
+            icon,
+            toggle,
+            last_transcript,
+            pause,
+            idle_icon: idle_icon.clone(),
+            recording_icon,
+        })
+    };
+    let tray = build().map_err(|err| format!("Unable to create the tray icon: {err}"))?;
+    app.manage(tray);
+
+    let handle = app.clone();
+    // Transitions are emitted with the dictation machine locked, so the phase
+    // comes from the payload rather than from `DictationMachine::phase`.
+    app.listen(STATE_EVENT, move |event| {
+        if let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) {
+            handle.state::<Tray>().refresh(&handle, transition.to);
+        }
+    });
+    let handle = app.clone();
+    app.listen("telepathy://hotkey-paused", move |_| {
+        let phase = handle.state::<DictationMachine>().phase();
+        handle.state::<Tray>().refresh(&handle, phase);
+    });
+    Ok(())
+}
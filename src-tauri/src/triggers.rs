@@ -0,0 +1,1099 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
+
+#[cfg(target_os = "macos")]
+use crate::event_tap::EventTap;
+use crate::{
+    analytics::Analytics,
+    destinations::{self, PendingDestination},
+    dictation::{DictationEvent, DictationMachine, DictationPhase, STATE_EVENT},
+    idle::Dormancy,
+    model_warmup::ModelState,
+    screen_context,
+    settings::SettingsStore,
+    translation::PendingTranslation,
+    HOLD_TO_RECORD_SHORTCUT,
+};
+
+#[cfg(feature = "midi-trigger")]
+mod midi;
+#[cfg(feature = "osc-trigger")]
+mod osc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerAction {
+    Start,
+    Stop,
+    Cancel,
+}
+
+#[derive(Clone, Copy)]
+pub enum TriggerSource {
+    Hotkey,
+    Tray,
+    #[cfg(feature = "osc-trigger")]
+    Osc,
+    #[cfg(feature = "midi-trigger")]
+    Midi,
+    /// Silence after speech ended a hands-free dictation.
+    Vad,
+    /// Escape pressed during a recording.
+    Escape,
+    /// A `telepathy://` link opened by another app.
+    DeepLink,
+    /// An AppleScript command, including the Run AppleScript action in
+    /// Shortcuts.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    Script,
+    /// A request to the local HTTP API.
+    #[cfg_attr(not(feature = "http-api"), allow(dead_code))]
+    HttpApi,
+}
+
+/// Which accelerator a hold-to-record gesture came from; each routes the
+/// dictation differently.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Chord {
+    /// The hold-to-record hotkey.
+    Main,
+    /// The hotkey with Alt added, for the modifier destination.
+    Modifier,
+    /// `translateHotkey`, which translates the transcript before delivery.
+    Translate,
+}
+
+impl Chord {
+    pub fn main_or_modifier(with_modifier: bool) -> Self {
+        if with_modifier {
+            Self::Modifier
+        } else {
+            Self::Main
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HotkeyEventPayload {
+    state: &'static str,
+    shortcut: String,
+    chord: Chord,
+    source: &'static str,
+    /// Whether the dictation state machine took the event; a press while
+    /// a dictation is already in flight is rejected.
+    accepted: bool,
+}
+
+/// The hold-to-record accelerator, plus the same chord with Alt added,
+/// which routes the dictation to the modifier destination.
+#[derive(Clone)]
+struct Hotkey {
+    accelerator: String,
+    shortcut: Shortcut,
+    /// Absent when the accelerator already uses Alt.
+    alt: Option<(String, Shortcut)>,
+}
+
+impl Hotkey {
+    /// Accepts the global-shortcut plugin's accelerator syntax, e.g.
+    /// `CommandOrControl+Shift+Space`. A modifier is required so the key
+    /// can still be typed normally.
+    fn parse(accelerator: &str) -> Result<Self, String> {
+        let accelerator = accelerator.trim();
+        let shortcut: Shortcut = accelerator
+            .parse()
+            .map_err(|err| format!("Invalid hotkey \"{accelerator}\": {err}"))?;
+        if shortcut.mods.is_empty() {
+            return Err(format!(
+                "The hotkey \"{accelerator}\" needs at least one modifier."
+            ));
+        }
+        let alt = match accelerator.rsplit_once('+') {
+            Some((mods, key)) if !shortcut.mods.contains(Modifiers::ALT) => Some((
+                format!("{mods}+Alt+{key}"),
+                Shortcut::new(Some(shortcut.mods | Modifiers::ALT), shortcut.key),
+            )),
+            _ => None,
+        };
+        Ok(Self {
+            accelerator: accelerator.to_string(),
+            shortcut,
+            alt,
+        })
+    }
+
+    fn shortcuts(&self) -> Vec<Shortcut> {
+        std::iter::once(self.shortcut)
+            .chain(self.alt.as_ref().map(|(_, shortcut)| *shortcut))
+            .collect()
+    }
+
+    fn label(&self, chord: Chord) -> String {
+        match &self.alt {
+            Some((alt, _)) if chord == Chord::Modifier => alt.clone(),
+            _ => self.accelerator.clone(),
+        }
+    }
+}
+
+impl Default for Hotkey {
+    fn default() -> Self {
+        Self::parse(HOLD_TO_RECORD_SHORTCUT).expect("default hotkey is valid")
+    }
+}
+
+/// Returned by `get_hotkey` and `set_hotkey`.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyInfo {
+    pub accelerator: String,
+    pub alt_accelerator: Option<String>,
+    pub default_accelerator: &'static str,
+}
+
+impl From<Hotkey> for HotkeyInfo {
+    fn from(hotkey: Hotkey) -> Self {
+        Self {
+            accelerator: hotkey.accelerator,
+            alt_accelerator: hotkey.alt.map(|(alt, _)| alt),
+            default_accelerator: HOLD_TO_RECORD_SHORTCUT,
+        }
+    }
+}
+
+/// Feeds a start/stop/cancel request into the dictation state machine and
+/// reports whether it was accepted.
+fn apply(app: &AppHandle, action: TriggerAction) -> bool {
+    let analytics = app.state::<Analytics>();
+    let event = match action {
+        TriggerAction::Start => DictationEvent::Arm,
+        TriggerAction::Stop => DictationEvent::Stop,
+        TriggerAction::Cancel => DictationEvent::Cancel,
+    };
+    // A start while a dictation is already running is simply ignored.
+    let outcome = app.state::<DictationMachine>().handle(app, event, None);
+    if outcome == Ok(DictationPhase::Arming) {
+        Dormancy::wake(app);
+        ModelState::ensure_loaded(app);
+        screen_context::capture_for_dictation(app);
+    }
+    match action {
+        TriggerAction::Start => analytics.hotkey_pressed(),
+        TriggerAction::Stop => analytics.hotkey_released(),
+        TriggerAction::Cancel => {}
+    }
+    outcome.is_ok()
+}
+
+fn emit_hotkey(
+    app: &AppHandle,
+    state: &'static str,
+    source: TriggerSource,
+    chord: Chord,
+    accepted: bool,
+) {
+    let source = match source {
+        TriggerSource::Hotkey => "hotkey",
+        TriggerSource::Tray => "tray",
+        #[cfg(feature = "osc-trigger")]
+        TriggerSource::Osc => "osc",
+        #[cfg(feature = "midi-trigger")]
+        TriggerSource::Midi => "midi",
+        TriggerSource::Vad => "vad",
+        TriggerSource::Escape => "escape",
+        TriggerSource::DeepLink => "deepLink",
+        TriggerSource::Script => "script",
+        TriggerSource::HttpApi => "httpApi",
+    };
+    tracing::debug!(state, source, accepted, "dictation trigger");
+    let triggers = app.state::<InputTriggers>();
+    let shortcut = match triggers.translate_hotkey() {
+        Some(translate) if chord == Chord::Translate => translate.accelerator,
+        _ => triggers.hotkey().label(chord),
+    };
+    let _ = app.emit(
+        "telepathy://hotkey",
+        HotkeyEventPayload {
+            state,
+            shortcut,
+            chord,
+            source,
+            accepted,
+        },
+    );
+}
+
+/// Feeds a start/stop/cancel request into the dictation state machine and
+/// mirrors it as a `telepathy://hotkey` event, so every input source drives
+/// the same recording flow. `chord` picks which accelerator the event
+/// reports.
+pub fn dispatch(app: &AppHandle, action: TriggerAction, source: TriggerSource, chord: Chord) {
+    let accepted = apply(app, action);
+    // Entered after applying, so a start is logged under its new session.
+    let _span = app.state::<DictationMachine>().span().entered();
+    let state = match action {
+        TriggerAction::Start => "pressed",
+        TriggerAction::Stop => "released",
+        TriggerAction::Cancel => "cancelled",
+    };
+    emit_hotkey(app, state, source, chord, accepted);
+}
+
+/// Starts a plain dictation, without the destination or translation a
+/// modifier chord would pick, for one-shot sources like the tray menu.
+pub fn start(app: &AppHandle, source: TriggerSource) {
+    app.state::<PendingDestination>().set(None);
+    app.state::<PendingTranslation>().set(false);
+    dispatch(app, TriggerAction::Start, source, Chord::Main);
+}
+
+/// Runs `action` for sources without chords, like OSC, MIDI, and deep
+/// links. Starts go through `start`, so a destination or translation left
+/// pending by a cancelled chord dictation doesn't carry over.
+pub fn trigger(app: &AppHandle, action: TriggerAction, source: TriggerSource) {
+    match action {
+        TriggerAction::Start => start(app, source),
+        action => dispatch(app, action, source, Chord::Main),
+    }
+}
+
+/// Starts a dictation, or stops the one being recorded, for one-click
+/// sources like the tray menu.
+pub fn toggle(app: &AppHandle, source: TriggerSource) {
+    if app.state::<DictationMachine>().phase().recording() {
+        dispatch(app, TriggerAction::Stop, source, Chord::Main);
+    } else {
+        start(app, source);
+    }
+}
+
+/// Pauses the dictation being recorded, or resumes a paused one.
+pub fn toggle_pause(app: &AppHandle) {
+    let machine = app.state::<DictationMachine>();
+    let event = match machine.phase() {
+        DictationPhase::Recording => DictationEvent::Pause,
+        DictationPhase::Paused => DictationEvent::Resume,
+        _ => return,
+    };
+    let _ = machine.handle(app, event, None);
+}
+
+/// Where the hold-to-record key is in a gesture, for telling taps from
+/// holds.
+#[derive(Default)]
+struct Gesture {
+    /// When the press that armed the current dictation went down.
+    pressed_at: Option<Instant>,
+    /// A tap started the current dictation, so the next press stops it.
+    toggled: bool,
+    /// The press that stopped a toggled dictation; its release is ignored.
+    ending_toggle: bool,
+}
+
+/// Shared by the global-shortcut plugin, the event tap, and the Wayland
+/// portal. Recording starts on every press; a release within
+/// `tapThresholdMs` leaves it running as a toggle (`tapToggle`) that the next
+/// press stops, while a longer hold stops on release (`holdStart`,
+/// `holdEnd`). Holding Alt routes the dictation to the configured modifier
+/// destination; the translate chord translates it first.
+pub fn hold_to_record(app: &AppHandle, pressed: bool, chord: Chord) {
+    let triggers = app.state::<InputTriggers>();
+    // Releases still go through so a hold in progress can end.
+    if pressed && triggers.paused() {
+        return;
+    }
+    let mut gesture = triggers
+        .gesture
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    if pressed {
+        // Key repeat while held.
+        if gesture.pressed_at.is_some() || gesture.ending_toggle {
+            return;
+        }
+        if std::mem::take(&mut gesture.toggled) {
+            // The dictation may have been cancelled or reset since the tap.
+            let phase = app.state::<DictationMachine>().phase();
+            if phase.recording() {
+                gesture.ending_toggle = true;
+                drop(gesture);
+                let accepted = apply(app, TriggerAction::Stop);
+                emit_hotkey(app, "tapToggle", TriggerSource::Hotkey, chord, accepted);
+                return;
+            }
+        }
+        let destination = (chord == Chord::Modifier).then(|| {
+            app.state::<SettingsStore>()
+                .snapshot()
+                .destinations
+                .modifier_destination
+        });
+        app.state::<PendingDestination>().set(destination);
+        app.state::<PendingTranslation>()
+            .set(chord == Chord::Translate);
+        let accepted = apply(app, TriggerAction::Start);
+        gesture.pressed_at = accepted.then(Instant::now);
+        drop(gesture);
+        emit_hotkey(app, "holdStart", TriggerSource::Hotkey, chord, accepted);
+        return;
+    }
+
+    if std::mem::take(&mut gesture.ending_toggle) {
+        return;
+    }
+    let threshold = Duration::from_millis(
+        app.state::<SettingsStore>()
+            .snapshot()
+            .triggers
+            .tap_threshold_ms,
+    );
+    let held = gesture.pressed_at.take().map(|at| at.elapsed());
+    if held.is_some_and(|held| held < threshold) {
+        gesture.toggled = true;
+        drop(gesture);
+        emit_hotkey(app, "tapToggle", TriggerSource::Hotkey, chord, true);
+        return;
+    }
+    drop(gesture);
+    let accepted = apply(app, TriggerAction::Stop);
+    emit_hotkey(app, "holdEnd", TriggerSource::Hotkey, chord, accepted);
+}
+
+/// Handler for the global-shortcut plugin.
+pub fn on_global_shortcut(app: &AppHandle, shortcut: &Shortcut, pressed: bool) {
+    let triggers = app.state::<InputTriggers>();
+    if triggers.pause_id() == Some(shortcut.id()) {
+        if pressed && !triggers.paused() {
+            toggle_pause(app);
+        }
+        return;
+    }
+    if triggers.cancel_id() == Some(shortcut.id()) {
+        if pressed {
+            dispatch(
+                app,
+                TriggerAction::Cancel,
+                TriggerSource::Escape,
+                Chord::Main,
+            );
+        }
+        return;
+    }
+    if triggers.repaste_id() == Some(shortcut.id()) {
+        // On release, so the chord's modifiers aren't held under the paste
+        // keystroke.
+        if !pressed && !triggers.paused() {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = destinations::paste_last(&app).await;
+            });
+        }
+        return;
+    }
+    let chord = if triggers
+        .translate_hotkey()
+        .is_some_and(|translate| translate.shortcut.id() == shortcut.id())
+    {
+        Chord::Translate
+    } else {
+        let hotkey = triggers.hotkey();
+        Chord::main_or_modifier(hotkey.alt.is_some_and(|(_, alt)| alt.id() == shortcut.id()))
+    };
+    hold_to_record(app, pressed, chord);
+}
+
+/// Follows the dictation so Escape is registered only while recording.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(STATE_EVENT, move |_| {
+        let handle = handle.clone();
+        // Shortcuts can't be changed from inside the shortcut handler that
+        // may have caused this transition.
+        tauri::async_runtime::spawn_blocking(move || {
+            handle.state::<InputTriggers>().sync_cancel(&handle);
+        });
+    });
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OscSettings {
+    pub enabled: bool,
+    /// UDP port bound on the loopback interface.
+    pub port: u16,
+    /// A non-zero first argument (or none) starts, zero stops, so a
+    /// momentary button mapped to this address behaves like hold-to-record.
+    pub start_address: String,
+    pub stop_address: String,
+    pub cancel_address: String,
+}
+
+impl Default for OscSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9000,
+            start_address: "/telepathy/start".to_string(),
+            stop_address: "/telepathy/stop".to_string(),
+            cancel_address: "/telepathy/cancel".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MidiSettings {
+    pub enabled: bool,
+    /// Case-insensitive substring of the input port name; first port when unset.
+    pub device: Option<String>,
+    /// 1-16; any channel when unset.
+    pub channel: Option<u8>,
+    /// Note-on starts recording and the matching note-off stops it.
+    pub record_note: u8,
+    pub cancel_note: Option<u8>,
+}
+
+impl Default for MidiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            device: None,
+            channel: None,
+            record_note: 60,
+            cancel_note: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TriggerSettings {
+    /// Hold-to-record accelerator; `HOLD_TO_RECORD_SHORTCUT` when unset.
+    pub hotkey: Option<String>,
+    /// Presses released sooner than this toggle recording on and off
+    /// instead of acting as push-to-talk. 0 makes every press a hold.
+    pub tap_threshold_ms: u64,
+    /// Pastes the last transcript again, e.g. `CommandOrControl+Alt+V`.
+    /// Off when unset.
+    pub repaste_hotkey: Option<String>,
+    /// Records like the hold-to-record hotkey, then translates the
+    /// transcript into `translation.target` before delivering it. Off when
+    /// unset.
+    pub translate_hotkey: Option<String>,
+    /// Pauses and resumes the dictation being recorded, e.g.
+    /// `CommandOrControl+Alt+P`. Off when unset.
+    pub pause_hotkey: Option<String>,
+    /// Escape cancels a dictation while it is being recorded, discarding
+    /// the audio. Escape is only taken from other apps during a recording.
+    pub escape_cancels: bool,
+    /// macOS: read the hold-to-record chord from a CGEventTap that swallows
+    /// it. Falls back to the global shortcut when the tap is not permitted.
+    /// The tap only knows the default chord, so a custom hotkey disables it.
+    pub event_tap: bool,
+    pub osc: OscSettings,
+    pub midi: MidiSettings,
+}
+
+impl Default for TriggerSettings {
+    fn default() -> Self {
+        Self {
+            hotkey: None,
+            tap_threshold_ms: 300,
+            repaste_hotkey: None,
+            translate_hotkey: None,
+            pause_hotkey: None,
+            escape_cancels: true,
+            event_tap: false,
+            osc: OscSettings::default(),
+            midi: MidiSettings::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Listeners {
+    settings: Option<TriggerSettings>,
+    #[cfg(feature = "osc-trigger")]
+    osc: Option<osc::OscListener>,
+    #[cfg(feature = "midi-trigger")]
+    midi: Option<midir::MidiInputConnection<()>>,
+    #[cfg(target_os = "macos")]
+    event_tap: Option<EventTap>,
+    #[cfg(target_os = "linux")]
+    portal_shortcuts: Option<crate::wayland::PortalShortcuts>,
+}
+
+impl Listeners {
+    #[cfg(feature = "osc-trigger")]
+    fn reconfigure_osc(
+        &mut self,
+        app: &AppHandle,
+        previous: &OscSettings,
+        settings: &OscSettings,
+    ) -> Result<(), String> {
+        if previous != settings || self.osc.is_none() {
+            self.osc = None;
+            if settings.enabled {
+                self.osc = Some(osc::OscListener::spawn(app.clone(), settings.clone())?);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "osc-trigger"))]
+    fn reconfigure_osc(
+        &mut self,
+        _app: &AppHandle,
+        _previous: &OscSettings,
+        settings: &OscSettings,
+    ) -> Result<(), String> {
+        if settings.enabled {
+            return Err("This build does not include OSC triggers.".to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "midi-trigger")]
+    fn reconfigure_midi(
+        &mut self,
+        app: &AppHandle,
+        previous: &MidiSettings,
+        settings: &MidiSettings,
+    ) -> Result<(), String> {
+        if previous != settings || self.midi.is_none() {
+            self.midi = None;
+            if settings.enabled {
+                self.midi = Some(midi::connect(app.clone(), settings.clone())?);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "midi-trigger"))]
+    fn reconfigure_midi(
+        &mut self,
+        _app: &AppHandle,
+        _previous: &MidiSettings,
+        settings: &MidiSettings,
+    ) -> Result<(), String> {
+        if settings.enabled {
+            return Err("This build does not include MIDI triggers.".to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn event_tap_active(&self) -> bool {
+        self.event_tap.is_some()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn event_tap_active(&self) -> bool {
+        false
+    }
+
+    #[cfg(target_os = "macos")]
+    fn reconfigure_event_tap(&mut self, app: &AppHandle, enabled: bool) -> Result<(), String> {
+        if !enabled {
+            self.event_tap = None;
+        } else if self.event_tap.is_none() {
+            self.event_tap = Some(EventTap::spawn(app.clone())?);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn reconfigure_event_tap(&mut self, _app: &AppHandle, enabled: bool) -> Result<(), String> {
+        if enabled {
+            return Err("Event tap hotkeys are only available on macOS.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Drops every listener except the portal binding, which the compositor
+    /// keeps on its own, so the next reconfigure starts them afresh.
+    fn release(&mut self) {
+        self.settings = None;
+        #[cfg(feature = "osc-trigger")]
+        {
+            self.osc = None;
+        }
+        #[cfg(feature = "midi-trigger")]
+        {
+            self.midi = None;
+        }
+        #[cfg(target_os = "macos")]
+        {
+            self.event_tap = None;
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn portal_shortcuts_active(&self) -> bool {
+        self.portal_shortcuts.is_some()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn portal_shortcuts_active(&self) -> bool {
+        false
+    }
+
+    /// Wayland compositors don't deliver keys to unfocused X11-style grabs,
+    /// so the hotkey is bound through the GlobalShortcuts portal instead.
+    /// Bound once; the compositor keeps the binding for the app's lifetime.
+    #[cfg(target_os = "linux")]
+    fn ensure_portal_shortcuts(&mut self, app: &AppHandle) -> Result<(), String> {
+        if self.portal_shortcuts.is_none() && crate::x11::session() == crate::x11::Session::Wayland
+        {
+            self.portal_shortcuts = Some(crate::wayland::PortalShortcuts::spawn(app.clone())?);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn ensure_portal_shortcuts(&mut self, _app: &AppHandle) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Keeps exactly one hotkey path live: the plugin's shortcuts are released
+/// while the event tap or the portal owns the chord, and restored when it
+/// goes away.
+fn sync_global_shortcuts(
+    app: &AppHandle,
+    hotkey: &Hotkey,
+    native_active: bool,
+) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    for shortcut in hotkey.shortcuts() {
+        let registered = shortcuts.is_registered(shortcut);
+        let result = if native_active && registered {
+            shortcuts.unregister(shortcut)
+        } else if !native_active && !registered {
+            shortcuts.register(shortcut)
+        } else {
+            Ok(())
+        };
+        result.map_err(|err| format!("Unable to update global shortcut {shortcut}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Hotkey event tap and OSC/MIDI listeners, rebuilt whenever their settings
+/// change, and the hold-to-record accelerator.
+#[derive(Default)]
+pub struct InputTriggers {
+    listeners: Mutex<Listeners>,
+    /// Kept apart from the listeners so the shortcut handler never waits
+    /// on a reconfigure.
+    hotkey: Mutex<Hotkey>,
+    gesture: Mutex<Gesture>,
+    /// The registered re-paste shortcut.
+    repaste: Mutex<Option<Shortcut>>,
+    /// The registered translate chord; its Alt variant is not used.
+    translate: Mutex<Option<Hotkey>>,
+    /// The registered pause chord.
+    pause: Mutex<Option<Shortcut>>,
+    /// Escape, registered only while a dictation is being recorded.
+    cancel: Mutex<Option<Shortcut>>,
+    /// Hotkey presses are ignored while set; the chords stay registered.
+    paused: AtomicBool,
+}
+
+impl InputTriggers {
+    fn hotkey(&self) -> Hotkey {
+        self.hotkey
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// The hold-to-record key is down and will stop the dictation itself.
+    pub fn holding(&self) -> bool {
+        self.gesture
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .pressed_at
+            .is_some()
+    }
+
+    /// Pauses or resumes the hotkeys and announces it on
+    /// `telepathy://hotkey-paused`.
+    pub fn set_paused(&self, app: &AppHandle, paused: bool) {
+        if self.paused.swap(paused, Ordering::AcqRel) != paused {
+            tracing::info!(paused, "hotkey pause changed");
+            let _ = app.emit("telepathy://hotkey-paused", paused);
+        }
+    }
+
+    fn repaste_id(&self) -> Option<u32> {
+        self.repaste
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .map(|shortcut| shortcut.id())
+    }
+
+    fn translate_hotkey(&self) -> Option<Hotkey> {
+        self.translate
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Registers the translate chord for `accelerator`, releasing the
+    /// previous one. It must not collide with the hold-to-record chords.
+    fn sync_translate(&self, app: &AppHandle, accelerator: Option<&str>) -> Result<(), String> {
+        let next = accelerator.map(Hotkey::parse).transpose()?;
+        if next
+            .as_ref()
+            .is_some_and(|next| self.hotkey().shortcuts().contains(&next.shortcut))
+        {
+            return Err("The translate hotkey is already the recording hotkey.".to_string());
+        }
+        let mut translate = self.translate.lock().unwrap_or_else(|err| err.into_inner());
+        let (current, wanted) = (
+            translate.as_ref().map(|hotkey| hotkey.shortcut),
+            next.as_ref().map(|hotkey| hotkey.shortcut),
+        );
+        if current == wanted
+            && wanted.is_none_or(|wanted| app.global_shortcut().is_registered(wanted))
+        {
+            return Ok(());
+        }
+        if let Some(previous) = translate.take() {
+            let _ = app.global_shortcut().unregister(previous.shortcut);
+        }
+        if let Some(next) = next {
+            app.global_shortcut()
+                .register(next.shortcut)
+                .map_err(|err| {
+                    format!(
+                        "Unable to register translate hotkey {}: {err}",
+                        next.accelerator
+                    )
+                })?;
+            *translate = Some(next);
+        }
+        Ok(())
+    }
+
+    fn pause_id(&self) -> Option<u32> {
+        self.pause
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .map(|shortcut| shortcut.id())
+    }
+
+    /// Registers the pause chord for `accelerator`, releasing the previous
+    /// one. It must not collide with the other chords.
+    fn sync_pause(&self, app: &AppHandle, accelerator: Option<&str>) -> Result<(), String> {
+        let next = accelerator
+            .map(|accelerator| Hotkey::parse(accelerator).map(|hotkey| hotkey.shortcut))
+            .transpose()?;
+        let taken = next.is_some_and(|next| {
+            self.hotkey().shortcuts().contains(&next)
+                || self
+                    .translate_hotkey()
+                    .is_some_and(|translate| translate.shortcut == next)
+                || self.repaste_id() == Some(next.id())
+        });
+        if taken {
+            return Err("The pause hotkey is already used by another hotkey.".to_string());
+        }
+        let mut pause = self.pause.lock().unwrap_or_else(|err| err.into_inner());
+        if *pause == next && next.is_none_or(|next| app.global_shortcut().is_registered(next)) {
+            return Ok(());
+        }
+        if let Some(previous) = pause.take() {
+            let _ = app.global_shortcut().unregister(previous);
+        }
+        if let Some(next) = next {
+            app.global_shortcut()
+                .register(next)
+                .map_err(|err| format!("Unable to register pause hotkey {next}: {err}"))?;
+            *pause = Some(next);
+        }
+        Ok(())
+    }
+
+    fn cancel_id(&self) -> Option<u32> {
+        self.cancel
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .map(|shortcut| shortcut.id())
+    }
+
+    /// Holds Escape while a dictation is arming or recording and hands it
+    /// back to other apps otherwise.
+    fn sync_cancel(&self, app: &AppHandle) {
+        let wanted = app.state::<DictationMachine>().phase().recording()
+            && !self.paused()
+            && app
+                .state::<SettingsStore>()
+                .snapshot()
+                .triggers
+                .escape_cancels;
+        let mut cancel = self.cancel.lock().unwrap_or_else(|err| err.into_inner());
+        if cancel.is_some() == wanted {
+            return;
+        }
+        if let Some(previous) = cancel.take() {
+            let _ = app.global_shortcut().unregister(previous);
+            return;
+        }
+        let escape = Shortcut::new(None, Code::Escape);
+        match app.global_shortcut().register(escape) {
+            Ok(()) => *cancel = Some(escape),
+            Err(err) => tracing::warn!(%err, "escape to cancel unavailable"),
+        }
+    }
+
+    /// Registers the re-paste shortcut for `accelerator`, releasing the
+    /// previous one. It must not collide with the hold-to-record chords.
+    fn sync_repaste(&self, app: &AppHandle, accelerator: Option<&str>) -> Result<(), String> {
+        let next = accelerator
+            .map(|accelerator| Hotkey::parse(accelerator).map(|hotkey| hotkey.shortcut))
+            .transpose()?;
+        if next.is_some_and(|next| self.hotkey().shortcuts().contains(&next)) {
+            return Err("The re-paste hotkey is already the recording hotkey.".to_string());
+        }
+        if next.is_some_and(|next| {
+            self.translate_hotkey()
+                .is_some_and(|translate| translate.shortcut == next)
+        }) {
+            return Err("The re-paste hotkey is already the translate hotkey.".to_string());
+        }
+        let mut repaste = self.repaste.lock().unwrap_or_else(|err| err.into_inner());
+        if *repaste == next && next.is_none_or(|next| app.global_shortcut().is_registered(next)) {
+            return Ok(());
+        }
+        if let Some(previous) = repaste.take() {
+            let _ = app.global_shortcut().unregister(previous);
+        }
+        if let Some(next) = next {
+            app.global_shortcut()
+                .register(next)
+                .map_err(|err| format!("Unable to register re-paste hotkey {next}: {err}"))?;
+            *repaste = Some(next);
+        }
+        Ok(())
+    }
+
+    /// Moves the global shortcuts from the current hotkey to `hotkey`,
+    /// restoring the old ones if the new chord can't be registered (most
+    /// often because another app holds it).
+    fn swap_hotkey(
+        &self,
+        app: &AppHandle,
+        hotkey: Hotkey,
+        native_active: bool,
+    ) -> Result<(), String> {
+        let previous = self.hotkey();
+        if !native_active {
+            let shortcuts = app.global_shortcut();
+            let _ = shortcuts.unregister_multiple(previous.shortcuts());
+            if let Err(err) = shortcuts.register_multiple(hotkey.shortcuts()) {
+                let _ = shortcuts.unregister_multiple(hotkey.shortcuts());
+                let _ = shortcuts.register_multiple(previous.shortcuts());
+                return Err(format!("Unable to register {}: {err}", hotkey.accelerator));
+            }
+        }
+        tracing::info!(from = %previous.accelerator, to = %hotkey.accelerator, "hotkey changed");
+        *self.hotkey.lock().unwrap_or_else(|err| err.into_inner()) = hotkey;
+        Ok(())
+    }
+
+    /// Registers the configured hotkey at launch, before the deferred
+    /// reconfigure brings up the other listeners. An invalid or taken
+    /// accelerator falls back to the default.
+    pub fn register_hotkey(&self, app: &AppHandle, accelerator: Option<&str>) {
+        if let Some(accelerator) = accelerator {
+            match Hotkey::parse(accelerator) {
+                Ok(hotkey) => *self.hotkey.lock().unwrap_or_else(|err| err.into_inner()) = hotkey,
+                Err(err) => tracing::warn!(%err, "configured hotkey ignored"),
+            }
+        }
+        let mut hotkey = self.hotkey();
+        if let Err(err) = app.global_shortcut().register_multiple(hotkey.shortcuts()) {
+            tracing::warn!(accelerator = %hotkey.accelerator, %err, "hotkey unavailable; using the default");
+            hotkey = Hotkey::default();
+            if let Err(err) = app.global_shortcut().register_multiple(hotkey.shortcuts()) {
+                tracing::warn!(%err, "default hotkey unavailable");
+            }
+            *self.hotkey.lock().unwrap_or_else(|err| err.into_inner()) = hotkey;
+        }
+    }
+
+    /// Starts, stops, or rebinds listeners to match `settings`. Listener
+    /// errors are returned but never prevent the other listeners from running.
+    pub fn reconfigure(&self, app: &AppHandle, settings: &TriggerSettings) -> Result<(), String> {
+        let mut listeners = self.listeners.lock().unwrap_or_else(|err| err.into_inner());
+        let previous = listeners
+            .settings
+            .replace(settings.clone())
+            .unwrap_or_default();
+        let mut errors = Vec::new();
+        let hotkey = match settings.hotkey.as_deref().map(Hotkey::parse) {
+            Some(Ok(hotkey)) => hotkey,
+            Some(Err(err)) => {
+                errors.push(err);
+                self.hotkey()
+            }
+            None => Hotkey::default(),
+        };
+        let custom_hotkey = hotkey.accelerator != HOLD_TO_RECORD_SHORTCUT;
+
+        if let Err(err) = listeners.reconfigure_osc(app, &previous.osc, &settings.osc) {
+            errors.push(err);
+        }
+        if let Err(err) = listeners.reconfigure_midi(app, &previous.midi, &settings.midi) {
+            errors.push(err);
+        }
+        if settings.event_tap && custom_hotkey {
+            errors.push("The event tap only supports the default hotkey.".to_string());
+        }
+        if let Err(err) = listeners.reconfigure_event_tap(app, settings.event_tap && !custom_hotkey)
+        {
+            errors.push(err);
+        }
+        if let Err(err) = listeners.ensure_portal_shortcuts(app) {
+            errors.push(err);
+        }
+        let native_active = listeners.event_tap_active() || listeners.portal_shortcuts_active();
+        if hotkey.accelerator != self.hotkey().accelerator {
+            if let Err(err) = self.swap_hotkey(app, hotkey, native_active) {
+                errors.push(err);
+            }
+        }
+        if let Err(err) = sync_global_shortcuts(app, &self.hotkey(), native_active) {
+            errors.push(err);
+        }
+        if let Err(err) = self.sync_translate(app, settings.translate_hotkey.as_deref()) {
+            errors.push(err);
+        }
+        if let Err(err) = self.sync_repaste(app, settings.repaste_hotkey.as_deref()) {
+            errors.push(err);
+        }
+        if let Err(err) = self.sync_pause(app, settings.pause_hotkey.as_deref()) {
+            errors.push(err);
+        }
+
+        if errors.is_empty() {
+            tracing::info!(native_active, "input triggers configured");
+            Ok(())
+        } else {
+            tracing::warn!(native_active, errors = %errors.join(" "), "input triggers partly configured");
+            Err(errors.join(" "))
+        }
+    }
+
+    /// Rebuilds every listener and re-registers the global shortcuts, for
+    /// after sleep or a locked session, when taps and MIDI ports may have
+    /// gone stale.
+    pub fn rearm(&self, app: &AppHandle, settings: &TriggerSettings) -> Result<(), String> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .release();
+        let _ = app
+            .global_shortcut()
+            .unregister_multiple(self.hotkey().shortcuts());
+        self.reconfigure(app, settings)
+    }
+}
+
+#[tauri::command]
+pub fn get_hotkey(triggers: State<'_, InputTriggers>) -> HotkeyInfo {
+    triggers.hotkey().into()
+}
+
+/// Validates and registers `accelerator` in place of the current hotkey,
+/// then saves it. The old hotkey stays if the new one can't be registered.
+#[tauri::command]
+pub async fn set_hotkey(app: AppHandle, accelerator: String) -> Result<HotkeyInfo, String> {
+    crate::run_blocking(move || {
+        let hotkey = Hotkey::parse(&accelerator)?;
+        let store = app.state::<SettingsStore>();
+        let mut settings = store.snapshot();
+        settings.triggers.hotkey = Some(hotkey.accelerator.clone());
+        let triggers = app.state::<InputTriggers>();
+        {
+            let listeners = triggers
+                .listeners
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            let native_active = listeners.event_tap_active() || listeners.portal_shortcuts_active();
+            triggers.swap_hotkey(&app, hotkey, native_active)?;
+        }
+        store.replace(settings.clone())?;
+        // The event tap can't follow a custom chord; this turns it off.
+        if let Err(err) = triggers.reconfigure(&app, &settings.triggers) {
+            tracing::warn!(%err, "triggers not fully reconfigured after hotkey change");
+        }
+        Ok(triggers.hotkey().into())
+    })
+    .await
+}
+
+/// Ignores the recording, translate, and re-paste hotkeys until resumed, without giving
+/// up their registrations.
+#[tauri::command]
+pub fn set_hotkey_paused(app: AppHandle, triggers: State<'_, InputTriggers>, paused: bool) -> bool {
+    triggers.set_paused(&app, paused);
+    paused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkey_adds_an_alt_chord() {
+        let hotkey = Hotkey::parse(" CommandOrControl+Shift+Space ").unwrap();
+        assert_eq!(hotkey.accelerator, "CommandOrControl+Shift+Space");
+        let (alt, shortcut) = hotkey.alt.as_ref().unwrap();
+        assert_eq!(alt, "CommandOrControl+Shift+Alt+Space");
+        assert!(shortcut.mods.contains(Modifiers::ALT | Modifiers::SHIFT));
+        assert_eq!(shortcut.key, hotkey.shortcut.key);
+        assert_eq!(hotkey.shortcuts().len(), 2);
+        assert_eq!(hotkey.label(Chord::Main), "CommandOrControl+Shift+Space");
+        assert_eq!(
+            hotkey.label(Chord::Modifier),
+            "CommandOrControl+Shift+Alt+Space"
+        );
+        assert_eq!(
+            hotkey.label(Chord::Translate),
+            "CommandOrControl+Shift+Space"
+        );
+    }
+
+    #[test]
+    fn hotkey_with_alt_has_no_alt_chord() {
+        let hotkey = Hotkey::parse("Ctrl+Alt+KeyK").unwrap();
+        assert!(hotkey.alt.is_none());
+        assert_eq!(hotkey.shortcuts().len(), 1);
+        assert_eq!(hotkey.label(Chord::Modifier), "Ctrl+Alt+KeyK");
+    }
+
+    #[test]
+    fn hotkey_needs_a_modifier_and_a_known_key() {
+        let err = Hotkey::parse("Space").err().unwrap();
+        assert!(err.contains("needs at least one modifier"), "{err}");
+        let err = Hotkey::parse("Ctrl+NoSuchKey").err().unwrap();
+        assert!(err.starts_with("Invalid hotkey"), "{err}");
+        assert!(Hotkey::parse("").is_err());
+    }
+}
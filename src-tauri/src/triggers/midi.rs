@@ -0,0 +1,117 @@
+use tauri::AppHandle;
+
+use super::{trigger, MidiSettings, TriggerAction, TriggerSource};
+
+const CLIENT_NAME: &str = "Telepathy";
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+
+fn action(settings: &MidiSettings, message: &[u8]) -> Option<TriggerAction> {
+    let [status, note, velocity, ..] = *message else {
+        return None;
+    };
+    let channel = (status & 0x0F) + 1;
+    if settings.channel.is_some_and(|wanted| wanted != channel) {
+        return None;
+    }
+    // Note-on with zero velocity is the running-status form of note-off.
+    let note_on = status & 0xF0 == NOTE_ON && velocity > 0;
+    let note_off = status & 0xF0 == NOTE_OFF || (status & 0xF0 == NOTE_ON && velocity == 0);
+    if note == settings.record_note {
+        if note_on {
+            return Some(TriggerAction::Start);
+        }
+        if note_off {
+            return Some(TriggerAction::Stop);
+        }
+    }
+    (note_on && settings.cancel_note == Some(note)).then_some(TriggerAction::Cancel)
+}
+
+pub fn connect(
+    app: AppHandle,
+    settings: MidiSettings,
+) -> Result<midir::MidiInputConnection<()>, String> {
+    let input =
+        midir::MidiInput::new(CLIENT_NAME).map_err(|err| format!("MIDI is unavailable: {err}"))?;
+    let wanted = settings.device.as_deref().map(str::to_lowercase);
+    let port = input
+        .ports()
+        .into_iter()
+        .find(|port| match &wanted {
+            Some(wanted) => input
+                .port_name(port)
+                .is_ok_and(|name| name.to_lowercase().contains(wanted)),
+            None => true,
+        })
+        .ok_or_else(|| match &settings.device {
+            Some(device) => format!("No MIDI input matches \"{device}\"."),
+            None => "No MIDI input devices found.".to_string(),
+        })?;
+    input
+        .connect(
+            &port,
+            "telepathy-trigger",
+            move |_timestamp, message, _| {
+                if let Some(action) = action(&settings, message) {
+                    trigger(&app, action, TriggerSource::Midi);
+                }
+            },
+            (),
+        )
+        .map_err(|err| format!("Unable to open MIDI input: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> MidiSettings {
+        MidiSettings {
+            cancel_note: Some(62),
+            ..MidiSettings::default()
+        }
+    }
+
+    #[test]
+    fn record_note_starts_and_stops() {
+        let settings = settings();
+        assert_eq!(
+            action(&settings, &[0x90, 60, 100]),
+            Some(TriggerAction::Start)
+        );
+        assert_eq!(
+            action(&settings, &[0x80, 60, 64]),
+            Some(TriggerAction::Stop)
+        );
+        // Note-on with zero velocity is a note-off.
+        assert_eq!(action(&settings, &[0x90, 60, 0]), Some(TriggerAction::Stop));
+        assert_eq!(action(&settings, &[0x90, 61, 100]), None);
+    }
+
+    #[test]
+    fn cancel_note_only_acts_on_note_on() {
+        let settings = settings();
+        assert_eq!(
+            action(&settings, &[0x90, 62, 100]),
+            Some(TriggerAction::Cancel)
+        );
+        assert_eq!(action(&settings, &[0x80, 62, 0]), None);
+    }
+
+    #[test]
+    fn channel_filter_and_short_messages() {
+        let settings = MidiSettings {
+            channel: Some(2),
+            ..settings()
+        };
+        assert_eq!(action(&settings, &[0x90, 60, 100]), None);
+        assert_eq!(
+            action(&settings, &[0x91, 60, 100]),
+            Some(TriggerAction::Start)
+        );
+        assert_eq!(action(&settings, &[0x91, 60]), None);
+        // Control changes are ignored.
+        assert_eq!(action(&settings, &[0xB1, 60, 127]), None);
+    }
+}
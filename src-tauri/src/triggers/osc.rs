@@ -0,0 +1,135 @@
+use std::{
+    net::{Ipv4Addr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use rosc::{OscPacket, OscType};
+use tauri::AppHandle;
+
+use super::{trigger, OscSettings, TriggerAction, TriggerSource};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_PACKET_BYTES: usize = 1536;
+
+fn action(settings: &OscSettings, addr: &str, args: &[OscType]) -> Option<TriggerAction> {
+    let pressed = match args.first() {
+        Some(OscType::Int(value)) => *value != 0,
+        Some(OscType::Long(value)) => *value != 0,
+        Some(OscType::Float(value)) => *value > 0.0,
+        Some(OscType::Double(value)) => *value > 0.0,
+        Some(OscType::Bool(value)) => *value,
+        _ => true,
+    };
+    if addr == settings.start_address {
+        Some(if pressed {
+            TriggerAction::Start
+        } else {
+            TriggerAction::Stop
+        })
+    } else if addr == settings.stop_address {
+        Some(TriggerAction::Stop)
+    } else if addr == settings.cancel_address {
+        Some(TriggerAction::Cancel)
+    } else {
+        None
+    }
+}
+
+fn handle_packet(app: &AppHandle, settings: &OscSettings, packet: OscPacket) {
+    match packet {
+        OscPacket::Message(message) => {
+            if let Some(action) = action(settings, &message.addr, &message.args) {
+                trigger(app, action, TriggerSource::Osc);
+            }
+        }
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                handle_packet(app, settings, packet);
+            }
+        }
+    }
+}
+
+pub struct OscListener {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl OscListener {
+    pub fn spawn(app: AppHandle, settings: OscSettings) -> Result<Self, String> {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, settings.port))
+            .map_err(|err| format!("Unable to bind OSC port {}: {err}", settings.port))?;
+        socket
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| format!("Unable to configure OSC socket: {err}"))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let thread = thread::Builder::new()
+            .name("telepathy-osc".to_string())
+            .spawn(move || {
+                let mut buf = [0u8; MAX_PACKET_BYTES];
+                while !stop_flag.load(Ordering::Relaxed) {
+                    // Timeouts just re-check the stop flag; malformed packets are ignored.
+                    let Ok(len) = socket.recv(&mut buf) else {
+                        continue;
+                    };
+                    if let Ok((_, packet)) = rosc::decoder::decode_udp(&buf[..len]) {
+                        handle_packet(&app, &settings, packet);
+                    }
+                }
+            })
+            .map_err(|err| format!("Unable to start OSC listener: {err}"))?;
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for OscListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Join so the port is released before a replacement listener binds it.
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_address_follows_the_button_state() {
+        let settings = OscSettings::default();
+        let start = |args: &[OscType]| action(&settings, "/telepathy/start", args);
+        assert_eq!(start(&[]), Some(TriggerAction::Start));
+        assert_eq!(start(&[OscType::Int(1)]), Some(TriggerAction::Start));
+        assert_eq!(start(&[OscType::Float(0.5)]), Some(TriggerAction::Start));
+        assert_eq!(start(&[OscType::Bool(true)]), Some(TriggerAction::Start));
+        assert_eq!(start(&[OscType::Int(0)]), Some(TriggerAction::Stop));
+        assert_eq!(start(&[OscType::Double(0.0)]), Some(TriggerAction::Stop));
+        assert_eq!(start(&[OscType::Bool(false)]), Some(TriggerAction::Stop));
+    }
+
+    #[test]
+    fn stop_and_cancel_ignore_arguments() {
+        let settings = OscSettings::default();
+        let zero = [OscType::Int(0)];
+        assert_eq!(
+            action(&settings, "/telepathy/stop", &zero),
+            Some(TriggerAction::Stop)
+        );
+        assert_eq!(
+            action(&settings, "/telepathy/cancel", &[]),
+            Some(TriggerAction::Cancel)
+        );
+        assert_eq!(action(&settings, "/telepathy/other", &[]), None);
+    }
+}
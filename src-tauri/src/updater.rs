@@ -0,0 +1,225 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Listener, Manager, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::{
+    dictation::{DictationMachine, DictationPhase, DictationTransition, STATE_EVENT},
+    event_throttle::{EventThrottle, DEFAULT_MAX_HZ},
+    settings::SettingsStore,
+};
+
+const STABLE_ENDPOINT: &str =
+    "https://github.com/evanhu1/telepathy/releases/latest/download/latest.json";
+/// Beta builds publish their manifest under a fixed `beta` release tag.
+const BETA_ENDPOINT: &str =
+    "https://github.com/evanhu1/telepathy/releases/download/beta/latest.json";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    fn endpoint(self) -> &'static str {
+        match self {
+            Self::Stable => STABLE_ENDPOINT,
+            Self::Beta => BETA_ENDPOINT,
+        }
+    }
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdateSettings {
+    pub channel: UpdateChannel,
+}
+
+/// An available update, as reported to the webview.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+/// Payload of `telepathy://update-progress`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// Payload of `telepathy://update-ready`, emitted once a download is
+/// staged. `waiting` means a dictation is running and the restart happens
+/// when it ends.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateReady {
+    version: String,
+    waiting: bool,
+}
+
+/// An update that has been downloaded but not installed yet.
+struct Staged {
+    update: Update,
+    bytes: Vec<u8>,
+}
+
+/// The last update found by `check_for_updates`, and a downloaded one
+/// waiting for the current dictation to end.
+#[derive(Default)]
+pub struct Updates {
+    available: Mutex<Option<Update>>,
+    staged: Mutex<Option<Staged>>,
+}
+
+/// Release builds set the updater public key through the Tauri config;
+/// builds without one can't verify updates and don't look for them.
+fn has_pubkey(app: &AppHandle) -> bool {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .is_some_and(|pubkey| !pubkey.trim().is_empty())
+}
+
+async fn check(app: &AppHandle, channel: UpdateChannel) -> Result<Option<Update>, String> {
+    if !has_pubkey(app) {
+        return Err("This build is not signed for updates.".to_string());
+    }
+    let endpoint =
+        Url::parse(channel.endpoint()).map_err(|err| format!("Invalid update endpoint: {err}"))?;
+    app.updater_builder()
+        .endpoints(vec![endpoint])
+        .and_then(|builder| builder.build())
+        .map_err(|err| format!("Unable to set up the updater: {err}"))?
+        .check()
+        .await
+        .map_err(|err| format!("Update check failed: {err}"))
+}
+
+/// Installs `staged` and relaunches. On Windows the installer takes over
+/// and the app exits inside `install`.
+fn apply(app: &AppHandle, staged: Staged) -> Result<(), String> {
+    tracing::info!(version = %staged.update.version, "installing update");
+    staged
+        .update
+        .install(&staged.bytes)
+        .map_err(|err| format!("Unable to install the update: {err}"))?;
+    app.restart()
+}
+
+/// Applies a staged update unless a dictation is running, so an update
+/// never cuts off a recording or a paste. Doesn't return once applied.
+fn apply_when_idle(app: &AppHandle) -> Result<(), String> {
+    let updates = app.state::<Updates>();
+    let mut staged = updates.staged.lock().unwrap_or_else(|err| err.into_inner());
+    if app.state::<DictationMachine>().phase() != DictationPhase::Idle {
+        return Ok(());
+    }
+    match staged.take() {
+        Some(staged) => apply(app, staged),
+        None => Ok(()),
+    }
+}
+
+/// Watches for the end of a dictation to apply an update staged while it
+/// was running.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(STATE_EVENT, move |event| {
+        let Ok(transition) = serde_json::from_str::<DictationTransition>(event.payload()) else {
+            return;
+        };
+        if transition.to != DictationPhase::Idle {
+            return;
+        }
+        let handle = handle.clone();
+        // The machine emits under its lock, so the phase is read off this thread.
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(err) = apply_when_idle(&handle) {
+                tracing::warn!(%err, "staged update failed to install");
+            }
+        });
+    });
+}
+
+/// Looks for a newer release on the channel in settings. The result is
+/// kept for `install_update`.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let channel = app.state::<SettingsStore>().snapshot().updates.channel;
+    let update = check(&app, channel).await?;
+    let info = update.as_ref().map(|update| UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        channel,
+        notes: update.body.clone(),
+        date: update.date.map(|date| date.to_string()),
+    });
+    let available = info.as_ref().map(|info| info.version.as_str());
+    tracing::info!(?channel, ?available, "checked for updates");
+    *app.state::<Updates>()
+        .available
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = update;
+    Ok(info)
+}
+
+/// Downloads the update found by the last check, emitting
+/// `telepathy://update-progress`, then restarts into it. While a dictation
+/// is running the restart waits for it to finish.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    let available = app
+        .state::<Updates>()
+        .available
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .take();
+    let update = match available {
+        Some(update) => update,
+        None => {
+            let channel = app.state::<SettingsStore>().snapshot().updates.channel;
+            check(&app, channel)
+                .await?
+                .ok_or("No update is available.".to_string())?
+        }
+    };
+
+    let mut downloaded = 0u64;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                EventThrottle::emit(
+                    &app,
+                    "telepathy://update-progress",
+                    DEFAULT_MAX_HZ,
+                    UpdateProgress { downloaded, total },
+                );
+            },
+            || {},
+        )
+        .await
+        .map_err(|err| format!("Unable to download the update: {err}"))?;
+
+    let version = update.version.clone();
+    *app.state::<Updates>()
+        .staged
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = Some(Staged { update, bytes });
+    let waiting = app.state::<DictationMachine>().phase() != DictationPhase::Idle;
+    let _ = app.emit("telepathy://update-ready", UpdateReady { version, waiting });
+    crate::run_blocking(move || apply_when_idle(&app)).await
+}
@@ -0,0 +1,401 @@
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Manager};
+
+use crate::{history::History, settings::SettingsStore};
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UsageSettings {
+    /// How fast the user types, which `get_usage_stats` compares dictating
+    /// with to estimate the time saved.
+    pub typing_words_per_minute: u32,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            typing_words_per_minute: 40,
+        }
+    }
+}
+
+/// What one finished dictation adds to its day's totals. No text is kept.
+#[derive(Default)]
+pub struct UsageSample {
+    pub words: u64,
+    /// Hotkey press to release.
+    pub recording_ms: Option<u64>,
+    /// Hotkey press to the recorder running.
+    pub record_start_ms: Option<u64>,
+    /// Hotkey release to the final transcript.
+    pub transcript_ms: Option<u64>,
+    /// Final transcript to delivery; only for delivered dictations.
+    pub paste_ms: Option<u64>,
+}
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageRange {
+    /// Today and the six days before.
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl UsageRange {
+    fn days(self) -> Option<u64> {
+        match self {
+            Self::Week => Some(7),
+            Self::Month => Some(30),
+            Self::Year => Some(365),
+            Self::All => None,
+        }
+    }
+}
+
+/// The daily aggregates table, in the history database.
+pub(crate) fn prepare(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS daily_usage (
+             day TEXT PRIMARY KEY,
+             dictations INTEGER NOT NULL DEFAULT 0,
+             words INTEGER NOT NULL DEFAULT 0,
+             recording_ms INTEGER NOT NULL DEFAULT 0,
+             record_start_ms INTEGER NOT NULL DEFAULT 0,
+             record_start_count INTEGER NOT NULL DEFAULT 0,
+             transcript_ms INTEGER NOT NULL DEFAULT 0,
+             transcript_count INTEGER NOT NULL DEFAULT 0,
+             paste_ms INTEGER NOT NULL DEFAULT 0,
+             paste_count INTEGER NOT NULL DEFAULT 0
+         );",
+    )
+}
+
+/// One day's totals, as stored. Latencies are summed with the number of
+/// dictations that measured them.
+#[derive(Default)]
+struct DayTotals {
+    dictations: u64,
+    words: u64,
+    recording_ms: u64,
+    record_start: (u64, u64),
+    transcript: (u64, u64),
+    paste: (u64, u64),
+}
+
+impl DayTotals {
+    fn add(&mut self, sample: &UsageSample) {
+        let measured = |ms: Option<u64>| (ms.unwrap_or(0), u64::from(ms.is_some()));
+        self.dictations += 1;
+        self.words += sample.words;
+        self.recording_ms += sample.recording_ms.unwrap_or(0);
+        add(&mut self.record_start, measured(sample.record_start_ms));
+        add(&mut self.transcript, measured(sample.transcript_ms));
+        add(&mut self.paste, measured(sample.paste_ms));
+    }
+}
+
+/// Latencies are averaged over the dictations that measured them.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// `YYYY-MM-DD`, local time.
+    pub day: String,
+    pub dictations: u64,
+    pub words: u64,
+    pub recording_ms: u64,
+    pub record_start_ms: Option<f64>,
+    pub transcript_ms: Option<f64>,
+    pub paste_ms: Option<f64>,
+    /// Typing the words minus recording, transcribing, and pasting them;
+    /// never negative.
+    pub time_saved_ms: u64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    pub range: UsageRange,
+    /// Days with at least one dictation, oldest first.
+    pub days: Vec<DailyUsage>,
+    pub dictations: u64,
+    pub words: u64,
+    /// Over the days with dictations.
+    pub words_per_day: Option<f64>,
+    pub record_start_ms: Option<f64>,
+    pub transcript_ms: Option<f64>,
+    pub paste_ms: Option<f64>,
+    pub time_saved_ms: u64,
+    pub typing_words_per_minute: u32,
+}
+
+fn average((total, count): (u64, u64)) -> Option<f64> {
+    (count > 0).then(|| total as f64 / count as f64)
+}
+
+fn add((total, count): &mut (u64, u64), (more, more_count): (u64, u64)) {
+    *total += more;
+    *count += more_count;
+}
+
+/// Adds `sample` to `day`'s row.
+fn record(conn: &Connection, day: &str, sample: &UsageSample) -> rusqlite::Result<()> {
+    let mut row = DayTotals::default();
+    row.add(sample);
+    conn.execute(
+        "INSERT INTO daily_usage
+             (day, dictations, words, recording_ms, record_start_ms, record_start_count, transcript_ms, transcript_count, paste_ms, paste_count)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT (day) DO UPDATE SET
+             dictations = dictations + excluded.dictations,
+             words = words + excluded.words,
+             recording_ms = recording_ms + excluded.recording_ms,
+             record_start_ms = record_start_ms + excluded.record_start_ms,
+             record_start_count = record_start_count + excluded.record_start_count,
+             transcript_ms = transcript_ms + excluded.transcript_ms,
+             transcript_count = transcript_count + excluded.transcript_count,
+             paste_ms = paste_ms + excluded.paste_ms,
+             paste_count = paste_count + excluded.paste_count",
+        params![
+            day,
+            row.dictations,
+            row.words,
+            row.recording_ms,
+            row.record_start.0,
+            row.record_start.1,
+            row.transcript.0,
+            row.transcript.1,
+            row.paste.0,
+            row.paste.1,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Days from `since` (`YYYY-MM-DD`) on, or all of them, oldest first.
+fn days(conn: &Connection, since: Option<&str>) -> rusqlite::Result<Vec<(String, DayTotals)>> {
+    let mut statement = conn.prepare(
+        "SELECT day, dictations, words, recording_ms, record_start_ms, record_start_count, transcript_ms, transcript_count, paste_ms, paste_count
+         FROM daily_usage
+         WHERE ?1 IS NULL OR day >= ?1
+         ORDER BY day",
+    )?;
+    let rows = statement.query_map(params![since], |row| {
+        Ok((
+            row.get(0)?,
+            DayTotals {
+                dictations: row.get(1)?,
+                words: row.get(2)?,
+                recording_ms: row.get(3)?,
+                record_start: (row.get(4)?, row.get(5)?),
+                transcript: (row.get(6)?, row.get(7)?),
+                paste: (row.get(8)?, row.get(9)?),
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+impl History {
+    /// Adds a finished dictation to today's totals.
+    pub fn record_usage(&self, sample: &UsageSample) -> Result<(), String> {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        self.with(|conn| record(conn, &today, sample))
+    }
+}
+
+fn stats(
+    range: UsageRange,
+    rows: Vec<(String, DayTotals)>,
+    typing_words_per_minute: u32,
+) -> UsageStats {
+    let typing_ms = |words: u64| words * 60_000 / u64::from(typing_words_per_minute.max(1));
+    let mut stats = UsageStats {
+        range,
+        days: Vec::with_capacity(rows.len()),
+        dictations: 0,
+        words: 0,
+        words_per_day: None,
+        record_start_ms: None,
+        transcript_ms: None,
+        paste_ms: None,
+        time_saved_ms: 0,
+        typing_words_per_minute,
+    };
+    let (mut record_start, mut transcript, mut paste) = ((0, 0), (0, 0), (0, 0));
+    for (day, row) in rows {
+        let dictating_ms = row.recording_ms + row.transcript.0 + row.paste.0;
+        let time_saved_ms = typing_ms(row.words).saturating_sub(dictating_ms);
+        stats.dictations += row.dictations;
+        stats.words += row.words;
+        stats.time_saved_ms += time_saved_ms;
+        add(&mut record_start, row.record_start);
+        add(&mut transcript, row.transcript);
+        add(&mut paste, row.paste);
+        stats.days.push(DailyUsage {
+            day,
+            dictations: row.dictations,
+            words: row.words,
+            recording_ms: row.recording_ms,
+            record_start_ms: average(row.record_start),
+            transcript_ms: average(row.transcript),
+            paste_ms: average(row.paste),
+            time_saved_ms,
+        });
+    }
+    stats.words_per_day = average((stats.words, stats.days.len() as u64));
+    stats.record_start_ms = average(record_start);
+    stats.transcript_ms = average(transcript);
+    stats.paste_ms = average(paste);
+    stats
+}
+
+/// Words dictated per day, average latencies, and the estimated time saved
+/// against typing at `usage.typingWordsPerMinute`, over `range` ending
+/// today. Days without dictations are left out.
+#[tauri::command]
+pub async fn get_usage_stats(app: AppHandle, range: UsageRange) -> Result<UsageStats, String> {
+    let typing_words_per_minute = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .usage
+        .typing_words_per_minute;
+    crate::run_blocking(move || {
+        let since = range.days().map(|days| {
+            (chrono::Local::now().date_naive() - chrono::Days::new(days - 1))
+                .format("%Y-%m-%d")
+                .to_string()
+        });
+        let rows = app
+            .state::<History>()
+            .with(|conn| days(conn, since.as_deref()))?;
+        Ok(stats(range, rows, typing_words_per_minute))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(samples: &[UsageSample]) -> DayTotals {
+        let mut totals = DayTotals::default();
+        for sample in samples {
+            totals.add(sample);
+        }
+        totals
+    }
+
+    #[test]
+    fn day_totals_count_only_measured_latencies() {
+        let totals = day(&[
+            UsageSample {
+                words: 10,
+                recording_ms: Some(4_000),
+                transcript_ms: Some(500),
+                paste_ms: Some(100),
+                ..UsageSample::default()
+            },
+            UsageSample {
+                words: 5,
+                recording_ms: Some(2_000),
+                transcript_ms: Some(300),
+                ..UsageSample::default()
+            },
+        ]);
+        assert_eq!(totals.dictations, 2);
+        assert_eq!(totals.words, 15);
+        assert_eq!(totals.recording_ms, 6_000);
+        assert_eq!(totals.record_start, (0, 0));
+        assert_eq!(totals.transcript, (800, 2));
+        assert_eq!(totals.paste, (100, 1));
+    }
+
+    #[test]
+    fn stats_estimates_time_saved_against_typing() {
+        // 60 words at 40 wpm take 90 s to type; dictating took 20 s.
+        let totals = day(&[UsageSample {
+            words: 60,
+            recording_ms: Some(18_000),
+            transcript_ms: Some(1_500),
+            paste_ms: Some(500),
+            ..UsageSample::default()
+        }]);
+        let stats = stats(
+            UsageRange::Week,
+            vec![("2026-01-02".to_string(), totals)],
+            40,
+        );
+        assert_eq!(stats.dictations, 1);
+        assert_eq!(stats.words_per_day, Some(60.0));
+        assert_eq!(stats.transcript_ms, Some(1_500.0));
+        assert_eq!(stats.record_start_ms, None);
+        assert_eq!(stats.time_saved_ms, 70_000);
+        assert_eq!(stats.days[0].day, "2026-01-02");
+    }
+
+    #[test]
+    fn stats_never_reports_negative_time_saved() {
+        let totals = day(&[UsageSample {
+            words: 1,
+            recording_ms: Some(10_000),
+            ..UsageSample::default()
+        }]);
+        let stats = stats(
+            UsageRange::All,
+            vec![("2026-01-02".to_string(), totals)],
+            40,
+        );
+        assert_eq!(stats.time_saved_ms, 0);
+    }
+
+    #[test]
+    fn stats_of_no_days_has_no_averages() {
+        let stats = stats(UsageRange::Month, Vec::new(), 0);
+        assert_eq!(stats.words_per_day, None);
+        assert_eq!(stats.paste_ms, None);
+        assert_eq!(stats.time_saved_ms, 0);
+    }
+
+    #[test]
+    fn daily_rows_accumulate_in_the_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        prepare(&conn).unwrap();
+        let sample = UsageSample {
+            words: 10,
+            recording_ms: Some(4_000),
+            transcript_ms: Some(500),
+            ..UsageSample::default()
+        };
+        record(&conn, "2026-01-01", &sample).unwrap();
+        record(&conn, "2026-01-02", &sample).unwrap();
+        record(
+            &conn,
+            "2026-01-02",
+            &UsageSample {
+                words: 5,
+                paste_ms: Some(100),
+                ..UsageSample::default()
+            },
+        )
+        .unwrap();
+
+        let rows = days(&conn, Some("2026-01-02")).unwrap();
+        assert_eq!(rows.len(), 1);
+        let (day, totals) = &rows[0];
+        assert_eq!(day, "2026-01-02");
+        assert_eq!((totals.dictations, totals.words), (2, 15));
+        assert_eq!(totals.recording_ms, 4_000);
+        assert_eq!(totals.transcript, (500, 1));
+        assert_eq!(totals.paste, (100, 1));
+        assert_eq!(totals.record_start, (0, 0));
+
+        let all: Vec<_> = days(&conn, None)
+            .unwrap()
+            .into_iter()
+            .map(|(day, _)| day)
+            .collect();
+        assert_eq!(all, ["2026-01-01", "2026-01-02"]);
+    }
+}
@@ -0,0 +1,170 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::triggers::{self, Chord, InputTriggers, TriggerAction, TriggerSource};
+
+/// Loud audio must last this long to count as speech, so clicks and bumps
+/// don't.
+const MIN_SPEECH_MS: u64 = 60;
+/// Quiet this long ends a stretch of speech; shorter gaps are pauses
+/// between words.
+const HANGOVER_MS: u64 = 300;
+/// Kept after the last speech when trimming, so word endings aren't clipped.
+const TRIM_PADDING_MS: u64 = 250;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VadSettings {
+    pub enabled: bool,
+    /// Stops a dictation that isn't being held after this much silence
+    /// following speech; 0 never stops.
+    pub auto_stop_ms: u64,
+    /// Cuts the silence after the last speech from native recordings before
+    /// they are transcribed.
+    pub trim_silence: bool,
+    /// Level, in dBFS, above which audio counts as speech. Raise it in noisy
+    /// rooms.
+    pub threshold_db: f32,
+}
+
+impl Default for VadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_stop_ms: 2000,
+            trim_silence: true,
+            threshold_db: -40.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum VadState {
+    SpeechStarted,
+    SpeechEnded,
+}
+
+/// Payload of `telepathy://vad`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VadEvent {
+    state: VadState,
+    /// Position in the recording.
+    offset_ms: u64,
+}
+
+/// Energy-based speech detection over a recording's chunks, run on the
+/// recorder thread.
+pub struct VoiceDetector {
+    app: AppHandle,
+    settings: VadSettings,
+    threshold: f32,
+    speaking: bool,
+    /// Loud audio since the last quiet chunk.
+    loud_ms: u64,
+    /// Quiet audio since the last loud chunk.
+    quiet_ms: u64,
+    heard_speech: bool,
+    /// Samples recorded up to the end of the last loud chunk.
+    speech_end: usize,
+    recorded: usize,
+    recorded_ms: u64,
+    stopping: bool,
+}
+
+impl VoiceDetector {
+    pub fn new(app: AppHandle, settings: VadSettings) -> Self {
+        let threshold = 10f32.powf(settings.threshold_db / 20.0);
+        Self {
+            app,
+            settings,
+            threshold,
+            speaking: false,
+            loud_ms: 0,
+            quiet_ms: 0,
+            heard_speech: false,
+            speech_end: 0,
+            recorded: 0,
+            recorded_ms: 0,
+            stopping: false,
+        }
+    }
+
+    fn emit(&self, state: VadState) {
+        let _ = self.app.emit(
+            "telepathy://vad",
+            VadEvent {
+                state,
+                offset_ms: self.recorded_ms,
+            },
+        );
+    }
+
+    /// Takes the next chunk of interleaved samples and its RMS level.
+    pub fn feed(&mut self, samples: &[f32], rms: f32, sample_rate: u32, channels: u16) {
+        let frames = samples.len() / usize::from(channels.max(1));
+        let duration_ms = frames as u64 * 1000 / u64::from(sample_rate.max(1));
+        self.recorded += samples.len();
+        self.recorded_ms += duration_ms;
+        if !self.settings.enabled {
+            return;
+        }
+
+        if rms >= self.threshold {
+            self.loud_ms += duration_ms;
+            self.quiet_ms = 0;
+            self.speech_end = self.recorded;
+            if !self.speaking && self.loud_ms >= MIN_SPEECH_MS {
+                self.speaking = true;
+                self.heard_speech = true;
+                self.emit(VadState::SpeechStarted);
+            }
+            return;
+        }
+        self.loud_ms = 0;
+        self.quiet_ms += duration_ms;
+        if self.speaking && self.quiet_ms >= HANGOVER_MS {
+            self.speaking = false;
+            self.emit(VadState::SpeechEnded);
+        }
+        let auto_stop = self.settings.auto_stop_ms;
+        if auto_stop > 0 && self.heard_speech && self.quiet_ms >= auto_stop && !self.stopping {
+            self.stop_dictation();
+        }
+    }
+
+    /// A held hotkey stops on release, so only hands-free dictations stop
+    /// on silence.
+    fn stop_dictation(&mut self) {
+        if self.app.state::<InputTriggers>().holding() {
+            return;
+        }
+        self.stopping = true;
+        tracing::info!(
+            silence_ms = self.quiet_ms,
+            "stopping dictation after silence"
+        );
+        let app = self.app.clone();
+        // Stopping joins the recorder thread this runs on.
+        tauri::async_runtime::spawn_blocking(move || {
+            triggers::dispatch(&app, TriggerAction::Stop, TriggerSource::Vad, Chord::Main);
+        });
+    }
+
+    /// Drops the silence after the last speech from `recording`, keeping a
+    /// little padding. Recordings with no speech at all are left alone.
+    pub fn trim(&self, recording: &mut Vec<f32>, sample_rate: u32, channels: u16) {
+        if !self.settings.enabled || !self.settings.trim_silence || !self.heard_speech {
+            return;
+        }
+        let channels = usize::from(channels.max(1));
+        let padding = (TRIM_PADDING_MS * u64::from(sample_rate) / 1000) as usize * channels;
+        let keep = (self.speech_end + padding).div_ceil(channels) * channels;
+        if keep < recording.len() {
+            let trimmed_ms =
+                ((recording.len() - keep) / channels) as u64 * 1000 / u64::from(sample_rate.max(1));
+            tracing::debug!(trimmed_ms, "trailing silence trimmed");
+            recording.truncate(keep);
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use quick_xml::events::Event;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    jobs::{self, JobContext, JobPriority},
+    settings::{Settings, SettingsStore},
+};
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VocabularySettings {
+    /// Custom words and phrases to bias recognition toward.
+    pub words: Vec<String>,
+    pub replacements: Vec<Replacement>,
+}
+
+/// Whisper reads at most 224 tokens of prompt; this many characters stays
+/// inside that for ordinary words.
+const PROMPT_MAX_CHARS: usize = 600;
+
+impl VocabularySettings {
+    /// The custom words, trimmed, leaving out blanks and any with a NUL,
+    /// which whisper.cpp can't take.
+    pub fn terms(&self) -> impl Iterator<Item = &str> {
+        self.words
+            .iter()
+            .map(|word| word.trim())
+            .filter(|word| !word.is_empty() && !word.contains('\0'))
+    }
+
+    /// The custom words as a Whisper prompt (OpenAI's or whisper.cpp's),
+    /// which biases spelling toward them. Words past `PROMPT_MAX_CHARS` are
+    /// left out; `None` without words.
+    pub fn prompt(&self) -> Option<String> {
+        let mut prompt = String::new();
+        for term in self.terms() {
+            let separator = if prompt.is_empty() { "" } else { ", " };
+            if prompt.len() + separator.len() + term.len() > PROMPT_MAX_CHARS {
+                break;
+            }
+            prompt.push_str(separator);
+            prompt.push_str(term);
+        }
+        (!prompt.is_empty()).then_some(prompt)
+    }
+}
+
+/// Rewrites the spoken form `from` into the written form `to`.
+#[derive(Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replacement {
+    pub from: String,
+    pub to: String,
+    /// `from` is a regular expression, matched as written, and `to` may use
+    /// its groups (`$1`, `${name}`). Otherwise `from` is a literal phrase
+    /// matched case-insensitively on whole words.
+    #[serde(default)]
+    pub regex: bool,
+}
+
+impl Replacement {
+    /// Rejects rules that could never match, so a typo surfaces when the
+    /// rule is saved instead of silently doing nothing.
+    fn validate(&self) -> Result<(), String> {
+        if self.from.trim().is_empty() {
+            return Err("A replacement needs text to replace.".to_string());
+        }
+        if self.regex {
+            regex::Regex::new(&self.from)
+                .map_err(|err| format!("Invalid pattern \"{}\": {err}", self.from))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DictionaryFormat {
+    /// Picks a format from the file extension and contents.
+    #[default]
+    Auto,
+    /// Dragon "Export words" XML (`<Word><WrittenForm>…<SpokenForm>…`).
+    DragonXml,
+    /// Dragon text word list: `written` or `written\spoken` per line.
+    DragonText,
+    /// One word per line, or `spoken => written` / `spoken<TAB>written` pairs.
+    PlainText,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedEntry {
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub words_added: Vec<String>,
+    pub replacements_added: Vec<Replacement>,
+    /// Entries already present in the dictionaries (or repeated in the file).
+    pub duplicates: usize,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+enum Entry {
+    Word(String),
+    Replacement(Replacement),
+}
+
+/// Decodes text honouring a UTF-8/UTF-16 BOM; Dragon exports UTF-16LE.
+fn decode_text(bytes: &[u8]) -> Result<String, String> {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).map_err(|err| format!("Invalid UTF-16 text: {err}"))
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|err| format!("Invalid UTF-8 text: {err}"))
+        }
+        _ => Ok(String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+fn entry_from_forms(written: &str, spoken: Option<&str>) -> Option<Entry> {
+    let written = written.trim();
+    if written.is_empty() {
+        return None;
+    }
+    match spoken.map(str::trim).filter(|spoken| !spoken.is_empty()) {
+        Some(spoken) if !spoken.eq_ignore_ascii_case(written) => {
+            Some(Entry::Replacement(Replacement {
+                from: spoken.to_string(),
+                to: written.to_string(),
+                regex: false,
+            }))
+        }
+        _ => Some(Entry::Word(written.to_string())),
+    }
+}
+
+fn parse_dragon_xml(text: &str, skipped: &mut Vec<SkippedEntry>) -> Result<Vec<Entry>, String> {
+    let mut reader = quick_xml::Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut entries = Vec::new();
+    let mut written: Option<String> = None;
+    let mut spoken: Option<String> = None;
+    let mut field: Option<Vec<u8>> = None;
+    let mut word_index = 0;
+
+    loop {
+        match reader.read_event().map_err(|err| {
+            format!(
+                "Invalid Dragon XML at byte {}: {err}",
+                reader.buffer_position()
+            )
+        })? {
+            Event::Start(tag) => match tag.name().as_ref() {
+                b"Word" => {
+                    word_index += 1;
+                    written = None;
+                    spoken = None;
+                }
+                name => field = Some(name.to_vec()),
+            },
+            Event::Text(value) => {
+                let value = value
+                    .unescape()
+                    .map_err(|err| format!("Invalid Dragon XML text: {err}"))?
+                    .into_owned();
+                match field.as_deref() {
+                    Some(b"WrittenForm") => written = Some(value),
+                    Some(b"SpokenForm") => spoken = Some(value),
+                    _ => {}
+                }
+            }
+            Event::End(tag) => {
+                if tag.name().as_ref() == b"Word" {
+                    match written
+                        .as_deref()
+                        .and_then(|w| entry_from_forms(w, spoken.as_deref()))
+                    {
+                        Some(entry) => entries.push(entry),
+                        None => skipped.push(SkippedEntry {
+                            line: word_index,
+                            reason: "Word has no written form.".to_string(),
+                        }),
+                    }
+                }
+                field = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_lines(
+    text: &str,
+    format: DictionaryFormat,
+    skipped: &mut Vec<SkippedEntry>,
+) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry = match format {
+            DictionaryFormat::DragonText => match line.split_once('\\') {
+                Some((written, spoken)) => entry_from_forms(written, Some(spoken)),
+                None => entry_from_forms(line, None),
+            },
+            _ => {
+                let pair = line
+                    .split_once("=>")
+                    .or_else(|| line.split_once("->"))
+                    .or_else(|| line.split_once('\t'));
+                match pair {
+                    Some((spoken, written)) => entry_from_forms(written, Some(spoken)),
+                    None => entry_from_forms(line, None),
+                }
+            }
+        };
+        match entry {
+            Some(entry) => entries.push(entry),
+            None => skipped.push(SkippedEntry {
+                line: index + 1,
+                reason: "Missing written form.".to_string(),
+            }),
+        }
+    }
+    entries
+}
+
+fn detect_format(path: &std::path::Path, text: &str) -> DictionaryFormat {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    if extension.as_deref() == Some("xml") || text.trim_start().starts_with("<?xml") {
+        DictionaryFormat::DragonXml
+    } else if text.lines().any(|line| line.contains('\\')) {
+        DictionaryFormat::DragonText
+    } else {
+        DictionaryFormat::PlainText
+    }
+}
+
+fn import(
+    context: &JobContext,
+    store: &SettingsStore,
+    path: PathBuf,
+    format: Option<DictionaryFormat>,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    let bytes =
+        fs::read(&path).map_err(|err| format!("Unable to read {}: {err}", path.display()))?;
+    let text = decode_text(&bytes)?;
+    let format = match format.unwrap_or_default() {
+        DictionaryFormat::Auto => detect_format(&path, &text),
+        format => format,
+    };
+
+    let mut report = ImportReport {
+        dry_run: dry_run.unwrap_or(false),
+        ..ImportReport::default()
+    };
+    let entries = match format {
+        DictionaryFormat::DragonXml => parse_dragon_xml(&text, &mut report.skipped)?,
+        _ => parse_lines(&text, format, &mut report.skipped),
+    };
+    // Large dictionaries parse for a while; let a pending dictation go first.
+    context.yield_to_live();
+
+    let mut settings = store.snapshot();
+    let vocabulary = &mut settings.vocabulary;
+    let mut known_words: HashSet<String> = vocabulary
+        .words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let mut known_replacements: HashSet<String> = vocabulary
+        .replacements
+        .iter()
+        .map(|rule| rule.from.to_lowercase())
+        .collect();
+
+    for entry in entries {
+        match entry {
+            Entry::Word(word) if known_words.insert(word.to_lowercase()) => {
+                report.words_added.push(word)
+            }
+            Entry::Replacement(rule) if known_replacements.insert(rule.from.to_lowercase()) => {
+                report.replacements_added.push(rule)
+            }
+            _ => report.duplicates += 1,
+        }
+    }
+
+    let changed = !report.words_added.is_empty() || !report.replacements_added.is_empty();
+    if !report.dry_run && changed {
+        vocabulary.words.extend(report.words_added.iter().cloned());
+        vocabulary
+            .replacements
+            .extend(report.replacements_added.iter().cloned());
+        store.replace(settings)?;
+    }
+    Ok(report)
+}
+
+/// Imports a word list into the custom vocabulary and replacement
+/// dictionaries. With `dry_run` the report is computed but nothing is saved.
+#[tauri::command]
+pub async fn import_dictionary(
+    app: AppHandle,
+    path: PathBuf,
+    format: Option<DictionaryFormat>,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    let handle = app.clone();
+    jobs::run(
+        &app,
+        JobPriority::Background,
+        "importDictionary",
+        move |context| {
+            import(
+                context,
+                &handle.state::<SettingsStore>(),
+                path,
+                format,
+                dry_run,
+            )
+        },
+    )
+    .await
+}
+
+/// The rule set `profile` names, or the global one when it is `None`.
+fn rule_set<'a>(
+    settings: &'a mut Settings,
+    profile: Option<&str>,
+) -> Result<&'a mut Vec<Replacement>, String> {
+    match profile {
+        None => Ok(&mut settings.vocabulary.replacements),
+        Some(name) => settings
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+            .map(|profile| &mut profile.replacements)
+            .ok_or_else(|| format!("Unknown profile \"{name}\".")),
+    }
+}
+
+/// Applies `edit` to a rule set, saves, and returns the set afterwards.
+async fn edit_rules(
+    app: AppHandle,
+    profile: Option<String>,
+    edit: impl FnOnce(&mut Vec<Replacement>) -> Result<(), String> + Send + 'static,
+) -> Result<Vec<Replacement>, String> {
+    crate::run_blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let mut settings = store.snapshot();
+        let rules = rule_set(&mut settings, profile.as_deref())?;
+        edit(rules)?;
+        let rules = rules.clone();
+        store.replace(settings)?;
+        Ok(rules)
+    })
+    .await
+}
+
+fn check_index(rules: &[Replacement], index: usize) -> Result<(), String> {
+    if index < rules.len() {
+        Ok(())
+    } else {
+        Err(format!("No replacement at index {index}."))
+    }
+}
+
+/// The replacement rules of `profile`, or the global ones applied to every
+/// transcript when no profile is given.
+#[tauri::command]
+pub async fn list_replacements(
+    app: AppHandle,
+    profile: Option<String>,
+) -> Result<Vec<Replacement>, String> {
+    let mut settings = app.state::<SettingsStore>().snapshot();
+    rule_set(&mut settings, profile.as_deref()).map(|rules| rules.clone())
+}
+
+/// Appends `rule` to the global rules or to `profile`'s.
+#[tauri::command]
+pub async fn add_replacement(
+    app: AppHandle,
+    rule: Replacement,
+    profile: Option<String>,
+) -> Result<Vec<Replacement>, String> {
+    rule.validate()?;
+    edit_rules(app, profile, move |rules| {
+        rules.push(rule);
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_replacement(
+    app: AppHandle,
+    index: usize,
+    rule: Replacement,
+    profile: Option<String>,
+) -> Result<Vec<Replacement>, String> {
+    rule.validate()?;
+    edit_rules(app, profile, move |rules| {
+        check_index(rules, index)?;
+        rules[index] = rule;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_replacement(
+    app: AppHandle,
+    index: usize,
+    profile: Option<String>,
+) -> Result<Vec<Replacement>, String> {
+    edit_rules(app, profile, move |rules| {
+        check_index(rules, index)?;
+        rules.remove(index);
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary(words: &[&str]) -> VocabularySettings {
+        VocabularySettings {
+            words: words.iter().map(|word| word.to_string()).collect(),
+            ..VocabularySettings::default()
+        }
+    }
+
+    /// Entries as `word` or `spoken -> written`, to compare.
+    fn describe(entries: &[Entry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Word(word) => word.clone(),
+                Entry::Replacement(rule) => format!("{} -> {}", rule.from, rule.to),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decode_text_honours_byte_order_marks() {
+        assert_eq!(decode_text(b"\xEF\xBB\xBFcaf\xC3\xA9").unwrap(), "café");
+        assert_eq!(decode_text(b"\xFF\xFEh\0i\0").unwrap(), "hi");
+        assert_eq!(decode_text(b"\xFE\xFF\0h\0i").unwrap(), "hi");
+        assert_eq!(decode_text(b"plain").unwrap(), "plain");
+        assert!(decode_text(b"\xFF\xFE\x00\xD8").is_err());
+    }
+
+    #[test]
+    fn dragon_xml_reads_words_and_spoken_forms() {
+        let xml = r#"<?xml version="1.0"?>
+            <Words>
+              <Word><WrittenForm>Tauri</WrittenForm></Word>
+              <Word><WrittenForm>R&amp;D</WrittenForm><SpokenForm>R and D</SpokenForm></Word>
+              <Word><WrittenForm>Rust</WrittenForm><SpokenForm>rust</SpokenForm></Word>
+              <Word><SpokenForm>nothing written</SpokenForm></Word>
+            </Words>"#;
+        let mut skipped = Vec::new();
+        let entries = parse_dragon_xml(xml, &mut skipped).unwrap();
+        assert_eq!(describe(&entries), ["Tauri", "R and D -> R&D", "Rust"]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].line, 4);
+    }
+
+    #[test]
+    fn dragon_xml_reports_malformed_files() {
+        let mut skipped = Vec::new();
+        assert!(parse_dragon_xml("<Words><Word></Words>", &mut skipped).is_err());
+    }
+
+    #[test]
+    fn plain_lines_take_words_and_pairs() {
+        let text = "# comment\nTauri\n\nmy email => me@example.com\ntele path -> Telepathy\nnp\tnumpy\nbroken =>\n";
+        let mut skipped = Vec::new();
+        let entries = parse_lines(text, DictionaryFormat::PlainText, &mut skipped);
+        assert_eq!(
+            describe(&entries),
+            [
+                "Tauri",
+                "my email -> me@example.com",
+                "tele path -> Telepathy",
+                "np -> numpy",
+            ]
+        );
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].line, 7);
+    }
+
+    #[test]
+    fn dragon_text_lines_put_the_written_form_first() {
+        let mut skipped = Vec::new();
+        let entries = parse_lines(
+            "Telepathy\\tele path\nGitHub\ngithub\\GitHub\n",
+            DictionaryFormat::DragonText,
+            &mut skipped,
+        );
+        assert_eq!(
+            describe(&entries),
+            ["tele path -> Telepathy", "GitHub", "github"]
+        );
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn detect_format_uses_extension_then_contents() {
+        let path = std::path::Path::new;
+        assert!(matches!(
+            detect_format(path("words.xml"), "Tauri"),
+            DictionaryFormat::DragonXml
+        ));
+        assert!(matches!(
+            detect_format(path("words.txt"), "<?xml version=\"1.0\"?>"),
+            DictionaryFormat::DragonXml
+        ));
+        assert!(matches!(
+            detect_format(path("words.txt"), "Telepathy\\tele path"),
+            DictionaryFormat::DragonText
+        ));
+        assert!(matches!(
+            detect_format(path("words.txt"), "a => b"),
+            DictionaryFormat::PlainText
+        ));
+    }
+
+    #[test]
+    fn terms_skip_blanks_and_nuls() {
+        let vocabulary = vocabulary(&[" Tauri ", "", "  ", "bad\0word", "whisper.cpp"]);
+        assert_eq!(
+            vocabulary.terms().collect::<Vec<_>>(),
+            ["Tauri", "whisper.cpp"]
+        );
+    }
+
+    #[test]
+    fn prompt_joins_words_and_stops_at_the_limit() {
+        assert_eq!(vocabulary(&[]).prompt(), None);
+        assert_eq!(
+            vocabulary(&["Tauri", "Deepgram"]).prompt().as_deref(),
+            Some("Tauri, Deepgram")
+        );
+
+        let long = "x".repeat(PROMPT_MAX_CHARS - 4);
+        let prompt = vocabulary(&[&long, "ab", "too long"]).prompt().unwrap();
+        assert_eq!(prompt, format!("{long}, ab"));
+        assert!(prompt.len() <= PROMPT_MAX_CHARS);
+    }
+}
@@ -0,0 +1,282 @@
+use regex::{Regex, RegexBuilder};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CommandAction {
+    NewLine,
+    NewParagraph,
+    Tab,
+    /// Drops the utterance before the command, back to the previous sentence
+    /// end or line break. Spoken first, it erases the previous dictation.
+    DeleteThat,
+    /// The next word in capitals.
+    AllCaps,
+    /// The next word with a capital first letter.
+    Capitalize,
+    /// The next word in lower case.
+    NoCaps,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandRule {
+    /// Matched case-insensitively as whole words, ignoring punctuation the
+    /// transcriber adds after it.
+    pub phrase: String,
+    pub action: CommandAction,
+}
+
+impl CommandRule {
+    fn new(phrase: &str, action: CommandAction) -> Self {
+        Self {
+            phrase: phrase.to_string(),
+            action,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceCommandSettings {
+    pub enabled: bool,
+    /// Spoken phrases and what they do. Replace them to dictate commands in
+    /// another language; several phrases may share an action.
+    pub rules: Vec<CommandRule>,
+}
+
+impl Default for VoiceCommandSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: vec![
+                CommandRule::new("new line", CommandAction::NewLine),
+                CommandRule::new("new paragraph", CommandAction::NewParagraph),
+                CommandRule::new("tab key", CommandAction::Tab),
+                CommandRule::new("delete that", CommandAction::DeleteThat),
+                CommandRule::new("scratch that", CommandAction::DeleteThat),
+                CommandRule::new("all caps", CommandAction::AllCaps),
+                CommandRule::new("capitalize", CommandAction::Capitalize),
+                CommandRule::new("no caps", CommandAction::NoCaps),
+            ],
+        }
+    }
+}
+
+/// A transcript with its commands carried out.
+pub struct Interpreted {
+    pub text: String,
+    /// "Delete that" came before any text, so it applies to whatever the
+    /// previous dictation inserted.
+    pub erase_previous: bool,
+}
+
+fn normalize(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// One alternation over every phrase, longest first so "new paragraph"
+/// isn't cut short by a shorter rule sharing its start. It takes the
+/// whitespace before a command and the punctuation after it, which the
+/// transcriber adds around spoken commands.
+fn commands_pattern(rules: &[CommandRule]) -> Option<Regex> {
+    let mut phrases: Vec<String> = rules
+        .iter()
+        .map(|rule| normalize(&rule.phrase))
+        .filter(|phrase| !phrase.is_empty())
+        .collect();
+    if phrases.is_empty() {
+        return None;
+    }
+    phrases.sort_by_key(|phrase| std::cmp::Reverse(phrase.len()));
+    let alternatives: Vec<String> = phrases
+        .iter()
+        .map(|phrase| {
+            phrase
+                .split(' ')
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(r"\s+")
+        })
+        .collect();
+    let pattern = format!(r"\s*\b(?P<phrase>{})\b[.,;:!?]*", alternatives.join("|"));
+    RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .inspect_err(|err| tracing::warn!(%err, "voice command rules not usable"))
+        .ok()
+}
+
+fn apply_case(action: CommandAction, word: &str) -> String {
+    match action {
+        CommandAction::AllCaps => word.to_uppercase(),
+        CommandAction::NoCaps => word.to_lowercase(),
+        CommandAction::Capitalize => {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        _ => word.to_string(),
+    }
+}
+
+/// Appends the text between two commands, casing its first word when a
+/// casing command is pending. Returns the casing still pending, which
+/// carries over an empty segment.
+fn push_segment(
+    output: &mut String,
+    segment: &str,
+    case: Option<CommandAction>,
+) -> Option<CommandAction> {
+    let segment = segment.trim();
+    if segment.is_empty() {
+        return case;
+    }
+    let joins_word = output.chars().last().is_some_and(|ch| !ch.is_whitespace());
+    if joins_word && !segment.starts_with(|ch: char| ch.is_ascii_punctuation()) {
+        output.push(' ');
+    }
+    match case {
+        Some(action) => {
+            let split = segment.find(char::is_whitespace).unwrap_or(segment.len());
+            output.push_str(&apply_case(action, &segment[..split]));
+            output.push_str(&segment[split..]);
+        }
+        None => output.push_str(segment),
+    }
+    None
+}
+
+/// Cuts `output` back to the end of the previous sentence or line.
+fn delete_utterance(output: &mut String) {
+    let body = output.trim_end_matches(|ch: char| {
+        ch.is_whitespace() || matches!(ch, '.' | ',' | ';' | ':' | '!' | '?')
+    });
+    let boundary = body
+        .char_indices()
+        .rev()
+        .find(|&(_, ch)| matches!(ch, '.' | '!' | '?' | '\n' | '\t'))
+        .map_or(0, |(index, ch)| index + ch.len_utf8());
+    output.truncate(boundary);
+}
+
+/// Scans `text` for the configured spoken commands and carries them out.
+pub fn interpret(settings: &VoiceCommandSettings, text: &str) -> Interpreted {
+    let pattern = settings
+        .enabled
+        .then(|| commands_pattern(&settings.rules))
+        .flatten();
+    let Some(pattern) = pattern else {
+        return Interpreted {
+            text: text.to_string(),
+            erase_previous: false,
+        };
+    };
+    let mut output = String::new();
+    let mut erase_previous = false;
+    let mut case = None;
+    let mut last = 0;
+    for captures in pattern.captures_iter(text) {
+        let (Some(whole), Some(phrase)) = (captures.get(0), captures.name("phrase")) else {
+            continue;
+        };
+        let phrase = normalize(phrase.as_str());
+        let Some(rule) = settings
+            .rules
+            .iter()
+            .find(|rule| normalize(&rule.phrase) == phrase)
+        else {
+            continue;
+        };
+        case = push_segment(&mut output, &text[last..whole.start()], case);
+        last = whole.end();
+        match rule.action {
+            CommandAction::NewLine => output.push('\n'),
+            CommandAction::NewParagraph => output.push_str("\n\n"),
+            CommandAction::Tab => output.push('\t'),
+            CommandAction::DeleteThat if output.is_empty() => erase_previous = true,
+            CommandAction::DeleteThat => delete_utterance(&mut output),
+            action => case = Some(action),
+        }
+        tracing::debug!(action = ?rule.action, "voice command");
+    }
+    push_segment(&mut output, &text[last..], case);
+    Interpreted {
+        text: output,
+        erase_previous,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interpret_default(text: &str) -> Interpreted {
+        interpret(&VoiceCommandSettings::default(), text)
+    }
+
+    #[test]
+    fn line_breaks_replace_their_phrases_and_punctuation() {
+        assert_eq!(
+            interpret_default("Hello new line world").text,
+            "Hello\nworld"
+        );
+        assert_eq!(
+            interpret_default("Dear Sam, New paragraph. Thanks.").text,
+            "Dear Sam,\n\nThanks."
+        );
+        assert_eq!(interpret_default("a tab key b").text, "a\tb");
+    }
+
+    #[test]
+    fn phrases_only_match_whole_words() {
+        let text = "renew lines and a newline";
+        assert_eq!(interpret_default(text).text, text);
+    }
+
+    #[test]
+    fn delete_that_drops_the_last_utterance() {
+        let interpreted = interpret_default("Buy milk. Buy eggs delete that call mom");
+        assert_eq!(interpreted.text, "Buy milk. call mom");
+        assert!(!interpreted.erase_previous);
+    }
+
+    #[test]
+    fn delete_that_first_erases_the_previous_dictation() {
+        let interpreted = interpret_default("Scratch that. Hello");
+        assert_eq!(interpreted.text, "Hello");
+        assert!(interpreted.erase_previous);
+    }
+
+    #[test]
+    fn casing_applies_to_the_next_word() {
+        assert_eq!(
+            interpret_default("all caps nasa launched capitalize monday no caps HELLO world").text,
+            "NASA launched Monday hello world"
+        );
+        assert_eq!(interpret_default("all caps new line hello").text, "\nHELLO");
+    }
+
+    #[test]
+    fn custom_rules_and_disabling() {
+        let settings = VoiceCommandSettings {
+            enabled: true,
+            rules: vec![CommandRule::new("nueva  línea", CommandAction::NewLine)],
+        };
+        assert_eq!(
+            interpret(&settings, "hola Nueva línea mundo").text,
+            "hola\nmundo"
+        );
+
+        let disabled = VoiceCommandSettings {
+            enabled: false,
+            ..VoiceCommandSettings::default()
+        };
+        assert_eq!(interpret(&disabled, "a new line b").text, "a new line b");
+    }
+}
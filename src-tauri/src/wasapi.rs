@@ -0,0 +1,306 @@
+use std::{mem::ManuallyDrop, sync::mpsc, time::Duration};
+
+use windows::{
+    core::{implement, Interface, Ref, HRESULT, HSTRING},
+    Win32::{
+        Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+        Media::Audio::{
+            eConsole, eRender, ActivateAudioInterfaceAsync, IActivateAudioInterfaceAsyncOperation,
+            IActivateAudioInterfaceCompletionHandler,
+            IActivateAudioInterfaceCompletionHandler_Impl, IAudioCaptureClient, IAudioClient,
+            IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator, AUDCLNT_BUFFERFLAGS_SILENT,
+            AUDCLNT_SHAREMODE_SHARED, AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM,
+            AUDCLNT_STREAMFLAGS_LOOPBACK, AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY,
+            AUDIOCLIENT_ACTIVATION_PARAMS, AUDIOCLIENT_ACTIVATION_PARAMS_0,
+            AUDIOCLIENT_ACTIVATION_TYPE_PROCESS_LOOPBACK, AUDIOCLIENT_PROCESS_LOOPBACK_PARAMS,
+            DEVICE_STATE_ACTIVE, PROCESS_LOOPBACK_MODE_INCLUDE_TARGET_PROCESS_TREE,
+            VIRTUAL_AUDIO_DEVICE_PROCESS_LOOPBACK, WAVEFORMATEX,
+        },
+        System::{
+            Com::{
+                CoCreateInstance, CoInitializeEx, CoTaskMemFree, StructuredStorage::PROPVARIANT,
+                BLOB, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ,
+            },
+            Variant::VT_BLOB,
+        },
+    },
+};
+
+use crate::{
+    audio_buffer::{AudioChunk, BufferPool},
+    capture::{CaptureApplication, CaptureDevice, CaptureFormat, CaptureSource},
+};
+
+/// Speech models want 16 kHz mono; the audio engine resamples to it.
+const FORMAT: CaptureFormat = CaptureFormat {
+    sample_rate: 16_000,
+    channels: 1,
+};
+/// `WAVE_FORMAT_IEEE_FLOAT`, from mmreg.h.
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+/// Shared-mode buffer length in 100 ns units (200 ms).
+const BUFFER_DURATION: i64 = 2_000_000;
+const ACTIVATION_TIMEOUT: Duration = Duration::from_secs(5);
+/// 100 ms per pooled chunk, and the chunks the consumer may hold at once.
+const CHUNK_LEN: usize = 1600;
+const POOL_SLOTS: usize = 8;
+
+fn wasapi_error(err: windows::core::Error) -> String {
+    format!("WASAPI call failed: {err}")
+}
+
+fn com_init() {
+    // SAFETY: initializing COM on the calling thread has no preconditions;
+    // S_FALSE (already initialized) is fine.
+    let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+}
+
+fn enumerator() -> Result<IMMDeviceEnumerator, String> {
+    // SAFETY: COM is initialized on this thread by the caller.
+    unsafe { CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) }.map_err(wasapi_error)
+}
+
+/// # Safety
+/// Must be called on a thread with COM initialized.
+unsafe fn device_id(device: &IMMDevice) -> windows::core::Result<String> {
+    unsafe {
+        let id = device.GetId()?;
+        let text = id.to_string().unwrap_or_default();
+        CoTaskMemFree(Some(id.0 as *const _));
+        Ok(text)
+    }
+}
+
+/// # Safety
+/// Must be called on a thread with COM initialized.
+unsafe fn device_name(device: &IMMDevice) -> windows::core::Result<String> {
+    unsafe {
+        let store = device.OpenPropertyStore(STGM_READ)?;
+        Ok(store.GetValue(&PKEY_Device_FriendlyName)?.to_string())
+    }
+}
+
+/// Output devices whose mix can be captured through loopback.
+pub fn list_devices() -> Result<Vec<CaptureDevice>, String> {
+    com_init();
+    let enumerator = enumerator()?;
+    // SAFETY: COM is initialized above; every interface is used on this thread.
+    unsafe {
+        let default_id = enumerator
+            .GetDefaultAudioEndpoint(eRender, eConsole)
+            .and_then(|device| device_id(&device))
+            .ok();
+        let devices = enumerator
+            .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+            .map_err(wasapi_error)?;
+        let count = devices.GetCount().map_err(wasapi_error)?;
+        let mut listed = Vec::new();
+        for index in 0..count {
+            let device = devices.Item(index).map_err(wasapi_error)?;
+            let id = device_id(&device).map_err(wasapi_error)?;
+            listed.push(CaptureDevice {
+                name: device_name(&device).unwrap_or_else(|_| id.clone()),
+                is_default: default_id.as_deref() == Some(id.as_str()),
+                id,
+            });
+        }
+        Ok(listed)
+    }
+}
+
+pub fn probe() -> Result<String, String> {
+    let devices = list_devices()?;
+    Ok(format!(
+        "WASAPI loopback, {} output device(s).",
+        devices.len()
+    ))
+}
+
+/// WASAPI only enumerates audio sessions per device, so apps aren't listed;
+/// callers pass a pid they already know (e.g. from the paste target).
+pub fn list_applications() -> Result<Vec<CaptureApplication>, String> {
+    Ok(Vec::new())
+}
+
+/// Per-process loopback arrived in Windows 10 build 20348; older builds
+/// fail activation, which `open` reports.
+pub fn supports_applications() -> bool {
+    true
+}
+
+#[implement(IActivateAudioInterfaceCompletionHandler)]
+struct Activation {
+    done: mpsc::SyncSender<()>,
+}
+
+impl IActivateAudioInterfaceCompletionHandler_Impl for Activation_Impl {
+    fn ActivateCompleted(
+        &self,
+        _operation: Ref<'_, IActivateAudioInterfaceAsyncOperation>,
+    ) -> windows::core::Result<()> {
+        let _ = self.done.try_send(());
+        Ok(())
+    }
+}
+
+fn device_client(device: Option<&str>) -> Result<IAudioClient, String> {
+    let enumerator = enumerator()?;
+    // SAFETY: COM is initialized by `Stream::open`.
+    unsafe {
+        let device = match device {
+            Some(id) => enumerator.GetDevice(&HSTRING::from(id)),
+            None => enumerator.GetDefaultAudioEndpoint(eRender, eConsole),
+        }
+        .map_err(|err| format!("Output device not found: {err}"))?;
+        device
+            .Activate::<IAudioClient>(CLSCTX_ALL, None)
+            .map_err(wasapi_error)
+    }
+}
+
+fn process_client(pid: u32) -> Result<IAudioClient, String> {
+    let mut params = AUDIOCLIENT_ACTIVATION_PARAMS {
+        ActivationType: AUDIOCLIENT_ACTIVATION_TYPE_PROCESS_LOOPBACK,
+        Anonymous: AUDIOCLIENT_ACTIVATION_PARAMS_0 {
+            ProcessLoopbackParams: AUDIOCLIENT_PROCESS_LOOPBACK_PARAMS {
+                TargetProcessId: pid,
+                ProcessLoopbackMode: PROCESS_LOOPBACK_MODE_INCLUDE_TARGET_PROCESS_TREE,
+            },
+        },
+    };
+    // The blob points at `params` on this stack frame, so the variant must
+    // never be cleared with PropVariantClear.
+    let mut variant = ManuallyDrop::new(PROPVARIANT::default());
+    // SAFETY: writes the VT_BLOB arm of a zeroed PROPVARIANT.
+    unsafe {
+        let inner = &mut variant.Anonymous.Anonymous;
+        inner.vt = VT_BLOB;
+        inner.Anonymous.blob = BLOB {
+            cbSize: size_of::<AUDIOCLIENT_ACTIVATION_PARAMS>() as u32,
+            pBlobData: (&mut params as *mut AUDIOCLIENT_ACTIVATION_PARAMS).cast(),
+        };
+    }
+    let (done, finished) = mpsc::sync_channel(1);
+    let handler: IActivateAudioInterfaceCompletionHandler = Activation { done }.into();
+    // SAFETY: `variant` and `params` outlive the wait below, which covers
+    // the whole asynchronous activation.
+    let operation = unsafe {
+        ActivateAudioInterfaceAsync(
+            VIRTUAL_AUDIO_DEVICE_PROCESS_LOOPBACK,
+            &IAudioClient::IID,
+            Some(&*variant as *const PROPVARIANT),
+            &handler,
+        )
+    }
+    .map_err(|err| format!("Per-application capture is unavailable: {err}"))?;
+    finished
+        .recv_timeout(ACTIVATION_TIMEOUT)
+        .map_err(|_| "Timed out starting per-application capture.".to_string())?;
+    let mut result = HRESULT(0);
+    let mut client = None;
+    // SAFETY: activation has completed, so the result is ready to read.
+    unsafe { operation.GetActivateResult(&mut result, &mut client) }.map_err(wasapi_error)?;
+    result
+        .ok()
+        .map_err(|err| format!("Per-application capture is unavailable: {err}"))?;
+    client
+        .ok_or("Per-application capture returned no audio client.".to_string())?
+        .cast::<IAudioClient>()
+        .map_err(wasapi_error)
+}
+
+/// A running loopback capture. COM objects are bound to the thread that
+/// opened the stream, so it is opened and read on the capture thread.
+pub struct Stream {
+    client: IAudioClient,
+    capture: IAudioCaptureClient,
+    pool: BufferPool,
+}
+
+impl Stream {
+    pub fn open(source: &CaptureSource) -> Result<Self, String> {
+        com_init();
+        let client = match source {
+            CaptureSource::SystemAudio { device_id } => device_client(device_id.as_deref())?,
+            CaptureSource::Application { pid } => process_client(*pid)?,
+        };
+        let block_align = FORMAT.channels * 4;
+        let format = WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_IEEE_FLOAT,
+            nChannels: FORMAT.channels,
+            nSamplesPerSec: FORMAT.sample_rate,
+            nAvgBytesPerSec: FORMAT.sample_rate * u32::from(block_align),
+            nBlockAlign: block_align,
+            wBitsPerSample: 32,
+            cbSize: 0,
+        };
+        // SAFETY: `format` is a complete WAVEFORMATEX that outlives the call.
+        unsafe {
+            client
+                .Initialize(
+                    AUDCLNT_SHAREMODE_SHARED,
+                    AUDCLNT_STREAMFLAGS_LOOPBACK
+                        | AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM
+                        | AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY,
+                    BUFFER_DURATION,
+                    0,
+                    &format,
+                    None,
+                )
+                .map_err(wasapi_error)?;
+            let capture = client
+                .GetService::<IAudioCaptureClient>()
+                .map_err(wasapi_error)?;
+            client.Start().map_err(wasapi_error)?;
+            Ok(Self {
+                client,
+                capture,
+                pool: BufferPool::new(POOL_SLOTS, CHUNK_LEN, FORMAT.sample_rate, FORMAT.channels),
+            })
+        }
+    }
+
+    pub fn format(&self) -> CaptureFormat {
+        FORMAT
+    }
+
+    /// Chunks dropped because the consumer still held every slot.
+    pub fn overruns(&self) -> u64 {
+        self.pool.overruns()
+    }
+
+    /// Copies every packet captured since the last call into pooled chunks
+    /// for `sink`.
+    pub fn read(&mut self, mut sink: impl FnMut(AudioChunk)) -> Result<(), String> {
+        // SAFETY: the buffer returned by GetBuffer holds `frames` frames in
+        // the format requested in `open` until ReleaseBuffer.
+        unsafe {
+            while self.capture.GetNextPacketSize().map_err(wasapi_error)? > 0 {
+                let mut data = std::ptr::null_mut();
+                let mut frames = 0u32;
+                let mut flags = 0u32;
+                self.capture
+                    .GetBuffer(&mut data, &mut frames, &mut flags, None, None)
+                    .map_err(wasapi_error)?;
+                let len = frames as usize * usize::from(FORMAT.channels);
+                if data.is_null() || flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 {
+                    // A zero-sized element type, so this allocates nothing.
+                    let silence = vec![(); len];
+                    self.pool.write_converted(&silence, |()| 0.0, &mut sink);
+                } else {
+                    let samples = std::slice::from_raw_parts(data.cast::<f32>(), len);
+                    self.pool.write(samples, &mut sink);
+                }
+                self.capture.ReleaseBuffer(frames).map_err(wasapi_error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        // SAFETY: the client was started in `open` on this thread.
+        let _ = unsafe { self.client.Stop() };
+    }
+}
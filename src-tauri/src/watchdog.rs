@@ -0,0 +1,123 @@
+use std::{thread, time::Duration};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::dictation::{DictationMachine, DictationPhase};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Camera/recorder start-up is normally well under a second.
+const ARMING_TIMEOUT: Duration = Duration::from_secs(10);
+/// The webview heartbeats about once a second while recording.
+const RECORDING_SILENCE_TIMEOUT: Duration = Duration::from_secs(5);
+const TRANSCRIBING_TIMEOUT: Duration = Duration::from_secs(90);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Payload of `telepathy://watchdog`, for logs and bug reports.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchdogReport {
+    phase: DictationPhase,
+    session: u64,
+    stuck_ms: u128,
+    reason: &'static str,
+}
+
+fn stuck_reason(
+    phase: DictationPhase,
+    in_phase: Duration,
+    since_heartbeat: Option<Duration>,
+) -> Option<&'static str> {
+    match phase {
+        // A pause lasts as long as the user needs.
+        DictationPhase::Idle | DictationPhase::Paused => None,
+        DictationPhase::Arming => (in_phase > ARMING_TIMEOUT).then_some("Recorder never started."),
+        DictationPhase::Recording => {
+            let silent = since_heartbeat.unwrap_or(in_phase);
+            (silent > RECORDING_SILENCE_TIMEOUT).then_some("Recorder stopped producing data.")
+        }
+        DictationPhase::Transcribing => {
+            (in_phase > TRANSCRIBING_TIMEOUT).then_some("Transcription timed out.")
+        }
+        DictationPhase::Processing | DictationPhase::Injecting => {
+            (in_phase > DELIVERY_TIMEOUT).then_some("Delivery never completed.")
+        }
+    }
+}
+
+/// Force-resets the dictation state machine when a phase outlives its
+/// budget, so the overlay can't stay wedged until restart.
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        let machine = app.state::<DictationMachine>();
+        let snapshot = machine.snapshot();
+        let Some(reason) =
+            stuck_reason(snapshot.phase, snapshot.in_phase, snapshot.since_heartbeat)
+        else {
+            continue;
+        };
+        if machine.force_reset(&app, snapshot.session, snapshot.phase, reason.to_string()) {
+            tracing::warn!(
+                session = snapshot.session,
+                phase = ?snapshot.phase,
+                stuck_ms = snapshot.in_phase.as_millis() as u64,
+                reason,
+                "watchdog reset a stuck dictation"
+            );
+            let _ = app.emit(
+                "telepathy://watchdog",
+                WatchdogReport {
+                    phase: snapshot.phase,
+                    session: snapshot.session,
+                    stuck_ms: snapshot.in_phase.as_millis(),
+                    reason,
+                },
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn idle_and_paused_are_never_stuck() {
+        let hour = Duration::from_secs(3600);
+        assert_eq!(stuck_reason(DictationPhase::Idle, hour, None), None);
+        assert_eq!(stuck_reason(DictationPhase::Paused, hour, Some(hour)), None);
+    }
+
+    #[test]
+    fn each_busy_phase_has_its_own_budget() {
+        for (phase, timeout) in [
+            (DictationPhase::Arming, ARMING_TIMEOUT),
+            (DictationPhase::Transcribing, TRANSCRIBING_TIMEOUT),
+            (DictationPhase::Processing, DELIVERY_TIMEOUT),
+            (DictationPhase::Injecting, DELIVERY_TIMEOUT),
+        ] {
+            assert_eq!(stuck_reason(phase, timeout, None), None, "{phase:?}");
+            assert!(
+                stuck_reason(phase, timeout + SECOND, None).is_some(),
+                "{phase:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn recording_is_judged_by_its_heartbeat() {
+        let long = RECORDING_SILENCE_TIMEOUT * 10;
+        let recording = DictationPhase::Recording;
+        assert_eq!(stuck_reason(recording, long, Some(SECOND)), None);
+        assert_eq!(
+            stuck_reason(recording, long, Some(RECORDING_SILENCE_TIMEOUT + SECOND)),
+            Some("Recorder stopped producing data.")
+        );
+        // Without a heartbeat yet, time in the phase counts as silence.
+        assert_eq!(stuck_reason(recording, SECOND, None), None);
+        assert!(stuck_reason(recording, long, None).is_some());
+    }
+}
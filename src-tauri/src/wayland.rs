@@ -0,0 +1,399 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+use tauri::AppHandle;
+use zbus::{
+    blocking::{Connection, Proxy},
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+};
+
+use crate::{
+    triggers::{self, Chord},
+    x11,
+};
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const REMOTE_DESKTOP: &str = "org.freedesktop.portal.RemoteDesktop";
+const GLOBAL_SHORTCUTS: &str = "org.freedesktop.portal.GlobalShortcuts";
+/// `KEYBOARD` in the RemoteDesktop device-type bitmask.
+const DEVICE_KEYBOARD: u32 = 1;
+/// Keep the grant for as long as the app runs, so the compositor asks once.
+const PERSIST_WHILE_RUNNING: u32 = 1;
+const XK_CONTROL_L: i32 = 0xffe3;
+const XK_V: i32 = 0x0076;
+const XK_RETURN: i32 = 0xff0d;
+const XK_TAB: i32 = 0xff09;
+const XK_BACKSPACE: i32 = 0xff08;
+/// `KEY_BACKSPACE` as a Linux input-event code, for ydotool.
+const YDOTOOL_BACKSPACE: [&str; 2] = ["14:1", "14:0"];
+/// ydotool arguments, as Linux input-event codes: `KEY_LEFTCTRL` and `KEY_V`.
+const YDOTOOL_PASTE: [&str; 5] = ["key", "29:1", "47:1", "47:0", "29:0"];
+
+const SHORTCUT_ID: &str = "hold-to-record";
+const ALT_SHORTCUT_ID: &str = "hold-to-record-alt";
+
+type Options<'a> = HashMap<&'a str, Value<'a>>;
+type Results = HashMap<String, OwnedValue>;
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+fn token() -> String {
+    format!("telepathy{}", NEXT_TOKEN.fetch_add(1, Ordering::Relaxed))
+}
+
+fn portal_error(err: zbus::Error) -> String {
+    format!("Desktop portal request failed: {err}")
+}
+
+/// Session-bus connection to xdg-desktop-portal.
+struct Portal {
+    conn: Connection,
+}
+
+impl Portal {
+    fn connect() -> Result<Self, String> {
+        Connection::session()
+            .map(|conn| Self { conn })
+            .map_err(|err| format!("Unable to reach the session bus: {err}"))
+    }
+
+    fn proxy(&self, interface: &'static str) -> Result<Proxy<'static>, String> {
+        Proxy::new(&self.conn, PORTAL_DESTINATION, PORTAL_PATH, interface).map_err(portal_error)
+    }
+
+    /// Interface version, or an error when the portal backend lacks it.
+    fn version(&self, interface: &'static str) -> Result<u32, String> {
+        self.proxy(interface)?
+            .get_property::<u32>("version")
+            .map_err(|_| format!("The desktop portal does not provide {interface}."))
+    }
+
+    /// Calls a portal method that answers through a `Request` object and
+    /// waits for its `Response`, which may sit behind a consent dialog.
+    fn request<B>(
+        &self,
+        interface: &'static str,
+        method: &'static str,
+        token: &str,
+        body: &B,
+    ) -> Result<Results, String>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        let sender = self
+            .conn
+            .unique_name()
+            .ok_or("Session bus connection has no name.".to_string())?
+            .trim_start_matches(':')
+            .replace('.', "_");
+        let path = format!("{PORTAL_PATH}/request/{sender}/{token}");
+        // Subscribe before calling so a fast response can't be missed.
+        let request = Proxy::new(
+            &self.conn,
+            PORTAL_DESTINATION,
+            path,
+            "org.freedesktop.portal.Request",
+        )
+        .map_err(portal_error)?;
+        let mut responses = request.receive_signal("Response").map_err(portal_error)?;
+        self.proxy(interface)?
+            .call_method(method, body)
+            .map_err(portal_error)?;
+        let message = responses
+            .next()
+            .ok_or(format!("The desktop portal dropped the {method} request."))?;
+        let (code, results): (u32, Results) = message.body().deserialize().map_err(portal_error)?;
+        match code {
+            0 => Ok(results),
+            1 => Err(format!(
+                "{method} was declined in the desktop portal dialog."
+            )),
+            _ => Err(format!("The desktop portal could not complete {method}.")),
+        }
+    }
+
+    fn create_session(&self, interface: &'static str) -> Result<OwnedObjectPath, String> {
+        let handle_token = token();
+        let session_token = token();
+        let options: Options = HashMap::from([
+            ("handle_token", Value::from(handle_token.as_str())),
+            ("session_handle_token", Value::from(session_token.as_str())),
+        ]);
+        let results = self.request(interface, "CreateSession", &handle_token, &(options,))?;
+        // Older portal versions send the handle as a string, newer as a path.
+        let handle = results.get("session_handle").and_then(|value| {
+            OwnedObjectPath::try_from(value.clone())
+                .ok()
+                .or_else(|| String::try_from(value.clone()).ok()?.try_into().ok())
+        });
+        handle.ok_or("The desktop portal returned no session.".to_string())
+    }
+}
+
+/// Keyboard access granted through the RemoteDesktop portal.
+pub struct RemoteDesktop {
+    portal: Portal,
+    session: OwnedObjectPath,
+}
+
+impl RemoteDesktop {
+    fn start() -> Result<Self, String> {
+        let portal = Portal::connect()?;
+        portal.version(REMOTE_DESKTOP)?;
+        let session = portal.create_session(REMOTE_DESKTOP)?;
+
+        let select_token = token();
+        let options: Options = HashMap::from([
+            ("handle_token", Value::from(select_token.as_str())),
+            ("types", Value::from(DEVICE_KEYBOARD)),
+            ("persist_mode", Value::from(PERSIST_WHILE_RUNNING)),
+        ]);
+        portal.request(
+            REMOTE_DESKTOP,
+            "SelectDevices",
+            &select_token,
+            &(ObjectPath::from(&session), options),
+        )?;
+
+        let start_token = token();
+        let options: Options = HashMap::from([("handle_token", Value::from(start_token.as_str()))]);
+        let results = portal.request(
+            REMOTE_DESKTOP,
+            "Start",
+            &start_token,
+            &(ObjectPath::from(&session), "", options),
+        )?;
+        let devices = results
+            .get("devices")
+            .and_then(|value| u32::try_from(value.clone()).ok())
+            .unwrap_or(0);
+        if devices & DEVICE_KEYBOARD == 0 {
+            return Err("Keyboard access was not granted in the desktop portal.".to_string());
+        }
+        Ok(Self { portal, session })
+    }
+
+    fn press_paste(&self) -> Result<(), String> {
+        self.send_keysyms(&[(XK_CONTROL_L, 1), (XK_V, 1), (XK_V, 0), (XK_CONTROL_L, 0)])
+    }
+
+    fn press_backspace(&self, count: usize) -> Result<(), String> {
+        let events: Vec<(i32, u32)> = (0..count)
+            .flat_map(|_| [(XK_BACKSPACE, 1), (XK_BACKSPACE, 0)])
+            .collect();
+        self.send_keysyms(&events)
+    }
+
+    /// Presses and releases each character's keysym; the compositor maps
+    /// keysyms missing from the layout itself.
+    fn type_text(&self, text: &str) -> Result<(), String> {
+        let events: Vec<(i32, u32)> = text
+            .chars()
+            .map(keysym_for)
+            .flat_map(|keysym| [(keysym, 1), (keysym, 0)])
+            .collect();
+        self.send_keysyms(&events)
+    }
+
+    fn send_keysyms(&self, events: &[(i32, u32)]) -> Result<(), String> {
+        let proxy = self.portal.proxy(REMOTE_DESKTOP)?;
+        for &(keysym, state) in events {
+            proxy
+                .call_method(
+                    "NotifyKeyboardKeysym",
+                    &(
+                        ObjectPath::from(&self.session),
+                        Options::new(),
+                        keysym,
+                        state,
+                    ),
+                )
+                .map_err(portal_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Latin-1 characters are their own keysyms; everything else uses the
+/// Unicode keysym range.
+fn keysym_for(ch: char) -> i32 {
+    match ch {
+        '\n' => XK_RETURN,
+        '\t' => XK_TAB,
+        ' '..='~' | '\u{a0}'..='\u{ff}' => ch as i32,
+        _ => 0x0100_0000 + ch as i32,
+    }
+}
+
+fn ydotool_paste() -> Result<(), String> {
+    x11::run_tool("ydotool", &YDOTOOL_PASTE)
+}
+
+/// Ctrl+V through wtype, which speaks the virtual-keyboard protocol that
+/// wlroots compositors (Sway, Hyprland, river) and KDE implement.
+fn wtype_paste() -> Result<(), String> {
+    x11::run_tool("wtype", &["-M", "ctrl", "-k", "v", "-m", "ctrl"])
+}
+
+/// Mutter does not implement the virtual-keyboard protocol, so wtype only
+/// errors there.
+fn wtype_usable() -> bool {
+    let gnome = std::env::var("XDG_CURRENT_DESKTOP")
+        .is_ok_and(|desktop| desktop.split(':').any(|name| name == "GNOME"));
+    !gnome && x11::on_path("wtype")
+}
+
+/// How paste keystrokes reach Wayland apps: the RemoteDesktop portal when
+/// the compositor offers it, otherwise wtype on compositors with the
+/// virtual-keyboard protocol, otherwise ydotool (which needs `ydotoold`).
+pub enum Keyboard {
+    Portal(Box<RemoteDesktop>),
+    Wtype,
+    Ydotool,
+}
+
+impl Keyboard {
+    pub fn connect() -> Result<Self, String> {
+        match RemoteDesktop::start() {
+            Ok(remote) => Ok(Self::Portal(Box::new(remote))),
+            Err(_) if wtype_usable() => Ok(Self::Wtype),
+            Err(_) if x11::on_path("ydotool") => Ok(Self::Ydotool),
+            Err(portal_err) => Err(format!(
+                "{portal_err} Install wtype or ydotool as a fallback on compositors without the RemoteDesktop portal."
+            )),
+        }
+    }
+
+    pub fn press_paste(&self) -> Result<(), String> {
+        match self {
+            Self::Portal(remote) => remote.press_paste(),
+            Self::Wtype => wtype_paste(),
+            Self::Ydotool => ydotool_paste(),
+        }
+    }
+
+    pub fn press_backspace(&self, count: usize) -> Result<(), String> {
+        match self {
+            Self::Portal(remote) => remote.press_backspace(count),
+            Self::Wtype => {
+                let args: Vec<&str> = (0..count).flat_map(|_| ["-k", "BackSpace"]).collect();
+                x11::run_tool("wtype", &args)
+            }
+            Self::Ydotool => {
+                let args: Vec<&str> = std::iter::once("key")
+                    .chain((0..count).flat_map(|_| YDOTOOL_BACKSPACE))
+                    .collect();
+                x11::run_tool("ydotool", &args)
+            }
+        }
+    }
+
+    pub fn type_text(&self, text: &str) -> Result<(), String> {
+        match self {
+            Self::Portal(remote) => remote.type_text(text),
+            Self::Wtype => x11::run_tool("wtype", &["--", text]),
+            Self::Ydotool => x11::run_tool("ydotool", &["type", "--", text]),
+        }
+    }
+}
+
+/// Which Wayland paste path is usable, without opening a consent dialog.
+pub fn probe_paste() -> Result<String, String> {
+    let portal = Portal::connect().and_then(|portal| portal.version(REMOTE_DESKTOP));
+    match portal {
+        Ok(version) => Ok(format!("RemoteDesktop portal v{version}")),
+        Err(_) if wtype_usable() => Ok("wtype".to_string()),
+        Err(_) if x11::on_path("ydotool") => Ok("ydotool".to_string()),
+        Err(err) => Err(format!("{err} Neither wtype nor ydotool is installed.")),
+    }
+}
+
+pub fn probe_shortcuts() -> Result<String, String> {
+    let version = Portal::connect()?.version(GLOBAL_SHORTCUTS)?;
+    Ok(format!("GlobalShortcuts portal v{version}"))
+}
+
+fn bind_shortcuts(portal: &Portal) -> Result<OwnedObjectPath, String> {
+    portal.version(GLOBAL_SHORTCUTS)?;
+    let session = portal.create_session(GLOBAL_SHORTCUTS)?;
+    let shortcut = |description: &'static str, trigger: &'static str| -> HashMap<&str, Value> {
+        HashMap::from([
+            ("description", Value::from(description)),
+            ("preferred_trigger", Value::from(trigger)),
+        ])
+    };
+    let shortcuts = vec![
+        (SHORTCUT_ID, shortcut("Hold to dictate", "CTRL+SHIFT+space")),
+        (
+            ALT_SHORTCUT_ID,
+            shortcut(
+                "Hold to dictate to the modifier destination",
+                "CTRL+ALT+SHIFT+space",
+            ),
+        ),
+    ];
+    let bind_token = token();
+    let options: Options = HashMap::from([("handle_token", Value::from(bind_token.as_str()))]);
+    portal.request(
+        GLOBAL_SHORTCUTS,
+        "BindShortcuts",
+        &bind_token,
+        &(ObjectPath::from(&session), shortcuts, "", options),
+    )?;
+    Ok(session)
+}
+
+/// Hold-to-record bound through the GlobalShortcuts portal, the only way a
+/// Wayland client sees key presses while another app is focused. The
+/// compositor owns the actual key combination and may let the user change it.
+pub struct PortalShortcuts {
+    _thread: thread::JoinHandle<()>,
+}
+
+impl PortalShortcuts {
+    /// Binds the shortcuts (the compositor may ask for consent) and then
+    /// listens for the rest of the app's lifetime.
+    pub fn spawn(app: AppHandle) -> Result<Self, String> {
+        let portal = Portal::connect()?;
+        let session = bind_shortcuts(&portal)?;
+        let signals = portal
+            .proxy(GLOBAL_SHORTCUTS)?
+            .receive_all_signals()
+            .map_err(portal_error)?;
+        let thread = thread::Builder::new()
+            .name("telepathy-portal-shortcuts".to_string())
+            .spawn(move || {
+                // Keeps the connection, and with it the session, alive.
+                let _portal = portal;
+                for message in signals {
+                    let header = message.header();
+                    let pressed = match header.member().map(|member| member.as_str()) {
+                        Some("Activated") => true,
+                        Some("Deactivated") => false,
+                        _ => continue,
+                    };
+                    let Ok((handle, id, _timestamp, _options)) =
+                        message
+                            .body()
+                            .deserialize::<(OwnedObjectPath, String, u64, Results)>()
+                    else {
+                        continue;
+                    };
+                    if handle != session {
+                        continue;
+                    }
+                    triggers::hold_to_record(
+                        &app,
+                        pressed,
+                        Chord::main_or_modifier(id == ALT_SHORTCUT_ID),
+                    );
+                }
+            })
+            .map_err(|err| format!("Unable to start portal shortcut listener: {err}"))?;
+        Ok(Self { _thread: thread })
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::destinations::DestinationKind;
+
+/// Deliveries kept for `list_webhook_deliveries`, newest first.
+const KEPT_DELIVERIES: usize = 100;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const FIRST_RETRY: Duration = Duration::from_secs(2);
+const MAX_RETRY: Duration = Duration::from_secs(120);
+
+/// URLs that receive every completed transcript as JSON, for automation
+/// services like Zapier, Make, or n8n.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebhookSettings {
+    pub urls: Vec<String>,
+    /// Tries per URL before a delivery is logged as failed; retries back off
+    /// exponentially from 2 s.
+    pub max_attempts: u32,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            max_attempts: 5,
+        }
+    }
+}
+
+/// What finished: a dictation, or an audio file from `transcribe_file`.
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TranscriptSource {
+    Dictation,
+    File,
+}
+
+/// The JSON body posted to each webhook.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptPayload {
+    pub event: &'static str,
+    pub source: TranscriptSource,
+    pub text: String,
+    /// The transcript as recognized, when post-processing changed it.
+    pub original_text: Option<String>,
+    /// Recording length, or the audio file's length.
+    pub duration_ms: Option<u64>,
+    /// Hotkey release to delivery.
+    pub latency_ms: Option<u64>,
+    /// RFC 3339, local time; when the hotkey was pressed.
+    pub started_at: Option<String>,
+    /// RFC 3339, local time.
+    pub completed_at: String,
+    /// The app the transcript was pasted into.
+    pub app: Option<String>,
+    pub provider: Option<String>,
+    pub destination: Option<DestinationKind>,
+    pub delivered: bool,
+    pub history_id: Option<i64>,
+    pub source_file: Option<String>,
+}
+
+impl TranscriptPayload {
+    pub fn new(source: TranscriptSource, text: String) -> Self {
+        Self {
+            event: "transcript.completed",
+            source,
+            text,
+            original_text: None,
+            duration_ms: None,
+            latency_ms: None,
+            started_at: None,
+            completed_at: chrono::Local::now().to_rfc3339(),
+            app: None,
+            provider: None,
+            destination: None,
+            delivered: true,
+            history_id: None,
+            source_file: None,
+        }
+    }
+
+    /// Fills `started_at` back from the recording length and latency.
+    pub fn with_timings(mut self, duration_ms: Option<u64>, latency_ms: Option<u64>) -> Self {
+        self.duration_ms = duration_ms;
+        self.latency_ms = latency_ms;
+        if let (Some(duration), Some(latency)) = (duration_ms, latency_ms) {
+            let elapsed = chrono::Duration::milliseconds((duration + latency) as i64);
+            self.started_at = Some((chrono::Local::now() - elapsed).to_rfc3339());
+        }
+        self
+    }
+}
+
+/// One webhook delivery, as logged and emitted on
+/// `telepathy://webhook-delivery`.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub url: String,
+    /// `completedAt` of the transcript it carried.
+    pub transcript_at: String,
+    pub delivered: bool,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the server answered.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub finished_at: String,
+}
+
+/// The delivery log, newest first.
+#[derive(Default)]
+pub struct Webhooks(Mutex<VecDeque<Delivery>>);
+
+impl Webhooks {
+    fn log(&self, delivery: Delivery) {
+        let mut log = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        log.push_front(delivery);
+        log.truncate(KEPT_DELIVERIES);
+    }
+}
+
+/// Posts until the server accepts, up to `max_attempts`. Client errors
+/// other than 408 and 429 are not retried; the request itself is wrong.
+async fn post(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &TranscriptPayload,
+    max_attempts: u32,
+) -> Delivery {
+    let mut delivery = Delivery {
+        url: url.to_string(),
+        transcript_at: payload.completed_at.clone(),
+        delivered: false,
+        attempts: 0,
+        status: None,
+        error: None,
+        finished_at: String::new(),
+    };
+    let mut backoff = FIRST_RETRY;
+    while delivery.attempts < max_attempts.max(1) {
+        if delivery.attempts > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_RETRY);
+        }
+        delivery.attempts += 1;
+        match client.post(url).json(payload).send().await {
+            Ok(response) => {
+                let status = response.status();
+                delivery.status = Some(status.as_u16());
+                if status.is_success() {
+                    delivery.delivered = true;
+                    delivery.error = None;
+                    break;
+                }
+                let body = response.text().await.unwrap_or_default();
+                delivery.error = Some(format!("Responded with {status}: {}", body.trim()));
+                let retryable = status.is_server_error()
+                    || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                if !retryable {
+                    break;
+                }
+            }
+            Err(err) => {
+                delivery.status = None;
+                delivery.error = Some(format!("Request failed: {err}"));
+            }
+        }
+    }
+    delivery.finished_at = chrono::Local::now().to_rfc3339();
+    delivery
+}
+
+/// Sends `payload` to every configured URL in the background. Webhooks are
+/// best-effort, like the transcript stream: they never hold up or fail the
+/// dictation.
+pub fn send(app: &AppHandle, settings: &WebhookSettings, payload: TranscriptPayload) {
+    let urls: Vec<String> = settings
+        .urls
+        .iter()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    if urls.is_empty() || payload.text.trim().is_empty() {
+        return;
+    }
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::warn!(%err, "webhook client not created");
+            return;
+        }
+    };
+    let max_attempts = settings.max_attempts;
+    for url in urls {
+        let (app, client, payload) = (app.clone(), client.clone(), payload.clone());
+        tauri::async_runtime::spawn(async move {
+            let delivery = post(&client, &url, &payload, max_attempts).await;
+            if delivery.delivered {
+                tracing::info!(attempts = delivery.attempts, "webhook delivered");
+            } else {
+                tracing::warn!(
+                    attempts = delivery.attempts,
+                    error = delivery.error.as_deref().unwrap_or_default(),
+                    "webhook delivery failed"
+                );
+            }
+            let _ = app.emit("telepathy://webhook-delivery", &delivery);
+            app.state::<Webhooks>().log(delivery);
+        });
+    }
+}
+
+/// Recent webhook deliveries, newest first, including failed ones with the
+/// last status and error.
+#[tauri::command]
+pub fn list_webhook_deliveries(webhooks: State<'_, Webhooks>) -> Vec<Delivery> {
+    webhooks
+        .0
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,333 @@
+use std::{ffi::c_void, mem::size_of};
+
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, ERROR_FILE_NOT_FOUND, ERROR_SUCCESS, HANDLE, POINT},
+    Graphics::Gdi::ClientToScreen,
+    Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY},
+    System::Registry::{
+        RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW, HKEY, REG_SZ, RRF_RT_REG_SZ,
+    },
+    System::StationsAndDesktops::{
+        CloseDesktop, GetUserObjectInformationW, OpenInputDesktop, DESKTOP_READOBJECTS, UOI_NAME,
+    },
+    System::Threading::{
+        GetCurrentProcess, OpenProcess, OpenProcessToken, QueryFullProcessImageNameW,
+        PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    },
+    UI::{
+        Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+            KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_RETURN, VK_V,
+        },
+        WindowsAndMessaging::{
+            GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId, GUITHREADINFO,
+        },
+    },
+};
+
+use crate::focus::Rect;
+
+mod uia;
+
+pub use uia::{caret_bounds, insert_text};
+
+/// Whether `process` runs with an elevated (administrator) token. `None`
+/// when the token can't be read, which for another process usually means
+/// it is elevated and we are not.
+fn is_elevated(process: HANDLE) -> Option<bool> {
+    let mut token: HANDLE = std::ptr::null_mut();
+    // SAFETY: `process` is a valid process handle and `token` is written only
+    // on success, after which it is closed below.
+    if unsafe { OpenProcessToken(process, TOKEN_QUERY, &mut token) } == 0 {
+        return None;
+    }
+    let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+    let mut returned = 0u32;
+    // SAFETY: the buffer is a TOKEN_ELEVATION of the size passed in.
+    let ok = unsafe {
+        GetTokenInformation(
+            token,
+            TokenElevation,
+            (&mut elevation as *mut TOKEN_ELEVATION).cast::<c_void>(),
+            size_of::<TOKEN_ELEVATION>() as u32,
+            &mut returned,
+        )
+    };
+    // SAFETY: `token` came from OpenProcessToken above.
+    unsafe { CloseHandle(token) };
+    (ok != 0).then_some(elevation.TokenIsElevated != 0)
+}
+
+pub fn current_process_elevated() -> bool {
+    // SAFETY: GetCurrentProcess returns a pseudo-handle that needs no closing.
+    is_elevated(unsafe { GetCurrentProcess() }).unwrap_or(false)
+}
+
+fn foreground_pid() -> Option<u32> {
+    // SAFETY: plain Win32 queries with no preconditions; a null window or a
+    // zero process id is handled below.
+    let pid = unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(window, &mut pid);
+        pid
+    };
+    (pid != 0).then_some(pid)
+}
+
+/// Process id and executable path of the foreground window's process.
+pub fn foreground_process() -> Option<(u32, String)> {
+    let pid = foreground_pid()?;
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    // SAFETY: the handle is checked for null and closed after use; `buffer`
+    // holds `len` UTF-16 units and `len` is updated to the written length.
+    let ok = unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return Some((pid, String::new()));
+        }
+        let ok =
+            QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buffer.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        ok
+    };
+    let image = if ok != 0 {
+        String::from_utf16_lossy(&buffer[..len as usize])
+    } else {
+        String::new()
+    };
+    Some((pid, image))
+}
+
+/// The foreground thread's system caret, in screen pixels. Classic Win32
+/// controls keep one; most newer toolkits only report theirs through UI
+/// Automation.
+pub fn system_caret() -> Option<Rect> {
+    // SAFETY: GUITHREADINFO is plain data, valid zeroed, and `cbSize` is set
+    // as the call requires; the caret window is checked for null before
+    // its client coordinates are converted.
+    unsafe {
+        let mut info: GUITHREADINFO = std::mem::zeroed();
+        info.cbSize = size_of::<GUITHREADINFO>() as u32;
+        if GetGUIThreadInfo(0, &mut info) == 0 || info.hwndCaret.is_null() {
+            return None;
+        }
+        let caret = info.rcCaret;
+        let mut origin = POINT {
+            x: caret.left,
+            y: caret.top,
+        };
+        if ClientToScreen(info.hwndCaret, &mut origin) == 0 {
+            return None;
+        }
+        Some(Rect {
+            x: f64::from(origin.x),
+            y: f64::from(origin.y),
+            width: f64::from(caret.right - caret.left),
+            height: f64::from(caret.bottom - caret.top),
+        })
+    }
+}
+
+/// UIPI silently drops input sent from a normal process to an elevated
+/// one, and `SendInput` reports success anyway, so this is checked first.
+pub fn foreground_blocks_input() -> bool {
+    if current_process_elevated() {
+        return false;
+    }
+    let Some(pid) = foreground_pid() else {
+        return false;
+    };
+    if pid == std::process::id() {
+        return false;
+    }
+    // SAFETY: the handle is checked for null and closed after use.
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return true;
+        }
+        let elevated = is_elevated(process).unwrap_or(true);
+        CloseHandle(process);
+        elevated
+    }
+}
+
+/// Whether the input desktop is something other than the user's normal
+/// one: the UAC secure desktop, the lock screen, or Ctrl+Alt+Del. No app on
+/// the default desktop can send input there.
+pub fn secure_desktop_active() -> bool {
+    // SAFETY: the desktop handle is checked for null and closed after use;
+    // `name` holds `size_of_val(&name)` bytes.
+    unsafe {
+        let desktop = OpenInputDesktop(0, 0, DESKTOP_READOBJECTS);
+        if desktop.is_null() {
+            // Access to the secure desktop is denied to normal processes.
+            return true;
+        }
+        let mut name = [0u16; 64];
+        let mut needed = 0u32;
+        let ok = GetUserObjectInformationW(
+            desktop,
+            UOI_NAME,
+            name.as_mut_ptr().cast::<c_void>(),
+            size_of_val(&name) as u32,
+            &mut needed,
+        );
+        CloseDesktop(desktop);
+        if ok == 0 {
+            return false;
+        }
+        let len = name
+            .iter()
+            .position(|&unit| unit == 0)
+            .unwrap_or(name.len());
+        !String::from_utf16_lossy(&name[..len]).eq_ignore_ascii_case("Default")
+    }
+}
+
+fn key(vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    keyboard_input(vk, 0, flags)
+}
+
+/// A key event carrying one UTF-16 unit, independent of the keyboard layout.
+fn unicode_key(unit: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    keyboard_input(0, unit, KEYEVENTF_UNICODE | flags)
+}
+
+fn keyboard_input(vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// Sends `inputs` as one atomic `SendInput` batch.
+fn send_inputs(inputs: &[INPUT], what: &str) -> Result<(), String> {
+    // SAFETY: `inputs` is a valid array of INPUT structs of the stated size.
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            size_of::<INPUT>() as i32,
+        )
+    };
+    if sent as usize == inputs.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{what} was blocked: {}",
+            std::io::Error::last_os_error()
+        ))
+    }
+}
+
+/// Sends Ctrl+V to the foreground window as one atomic `SendInput` batch.
+pub fn press_paste() -> Result<(), String> {
+    let inputs = [
+        key(VK_CONTROL, 0),
+        key(VK_V, 0),
+        key(VK_V, KEYEVENTF_KEYUP),
+        key(VK_CONTROL, KEYEVENTF_KEYUP),
+    ];
+    send_inputs(&inputs, "Paste keystroke")
+}
+
+/// Presses Backspace `count` times in the foreground window.
+pub fn press_backspace(count: usize) -> Result<(), String> {
+    let inputs: Vec<INPUT> = (0..count)
+        .flat_map(|_| [key(VK_BACK, 0), key(VK_BACK, KEYEVENTF_KEYUP)])
+        .collect();
+    send_inputs(&inputs, "Backspace keystroke")
+}
+
+/// Types `text` into the foreground window as Unicode key events. Newlines
+/// become Enter, which is what terminals and editors expect.
+pub fn type_text(text: &str) -> Result<(), String> {
+    let mut inputs = Vec::with_capacity(text.len() * 2);
+    for ch in text.chars() {
+        if ch == '\n' {
+            inputs.extend([key(VK_RETURN, 0), key(VK_RETURN, KEYEVENTF_KEYUP)]);
+            continue;
+        }
+        let mut units = [0u16; 2];
+        for &unit in ch.encode_utf16(&mut units).iter() {
+            inputs.extend([unicode_key(unit, 0), unicode_key(unit, KEYEVENTF_KEYUP)]);
+        }
+    }
+    send_inputs(&inputs, "Typed input")
+}
+
+/// A `REG_SZ` value, or `None` when the key or value is missing.
+pub fn registry_string(root: HKEY, key: &str, name: &str) -> Option<String> {
+    let key: Vec<u16> = key.encode_utf16().chain(Some(0)).collect();
+    let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    let mut buffer = [0u16; 256];
+    let mut size = (buffer.len() * size_of::<u16>()) as u32;
+    // SAFETY: both strings are NUL-terminated and `size` is the buffer's
+    // length in bytes; the value is written only on success.
+    let status = unsafe {
+        RegGetValueW(
+            root,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            buffer.as_mut_ptr().cast(),
+            &mut size,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    // `size` counts bytes including the terminator.
+    let len = (size as usize / size_of::<u16>()).saturating_sub(1);
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// Writes a `REG_SZ` value, creating `key` if it is missing.
+pub fn set_registry_string(root: HKEY, key: &str, name: &str, value: &str) -> Result<(), String> {
+    let key: Vec<u16> = key.encode_utf16().chain(Some(0)).collect();
+    let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = value.encode_utf16().chain(Some(0)).collect();
+    // SAFETY: all three strings are NUL-terminated and the data length is
+    // the value's length in bytes, terminator included.
+    let status = unsafe {
+        RegSetKeyValueW(
+            root,
+            key.as_ptr(),
+            name.as_ptr(),
+            REG_SZ,
+            value.as_ptr().cast(),
+            (value.len() * size_of::<u16>()) as u32,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return Err(format!("Registry write failed (error {status})."));
+    }
+    Ok(())
+}
+
+/// Deletes a value; one that is already gone counts as deleted.
+pub fn delete_registry_value(root: HKEY, key: &str, name: &str) -> Result<(), String> {
+    let key: Vec<u16> = key.encode_utf16().chain(Some(0)).collect();
+    let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    // SAFETY: both strings are NUL-terminated.
+    let status = unsafe { RegDeleteKeyValueW(root, key.as_ptr(), name.as_ptr()) };
+    if status != ERROR_SUCCESS && status != ERROR_FILE_NOT_FOUND {
+        return Err(format!("Registry delete failed (error {status})."));
+    }
+    Ok(())
+}
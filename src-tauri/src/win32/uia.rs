@@ -0,0 +1,174 @@
+use windows::{
+    core::{BOOL, BSTR},
+    Win32::{
+        System::{
+            Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED},
+            Ole::{
+                SafeArrayAccessData, SafeArrayDestroy, SafeArrayGetLBound, SafeArrayGetUBound,
+                SafeArrayUnaccessData,
+            },
+        },
+        UI::Accessibility::{
+            CUIAutomation, IUIAutomation, IUIAutomationTextPattern2, IUIAutomationTextRange,
+            IUIAutomationValuePattern, TextPatternRangeEndpoint_End,
+            TextPatternRangeEndpoint_Start, TextUnit_Character, UIA_TextPattern2Id,
+            UIA_ValuePatternId,
+        },
+    },
+};
+
+use crate::focus::Rect;
+
+fn uia_error(err: windows::core::Error) -> String {
+    format!("UI Automation call failed: {err}")
+}
+
+/// The first box `range` covers on screen, if it has one.
+///
+/// # Safety
+/// Must be called on a thread with COM initialized.
+unsafe fn first_rect(range: &IUIAutomationTextRange) -> windows::core::Result<Option<Rect>> {
+    unsafe {
+        let array = range.GetBoundingRectangles()?;
+        if array.is_null() {
+            return Ok(None);
+        }
+        // The array holds left, top, width, and height for each line.
+        let len = SafeArrayGetUBound(array, 1)? - SafeArrayGetLBound(array, 1)? + 1;
+        let mut rect = None;
+        let mut data = std::ptr::null_mut();
+        if len >= 4 && SafeArrayAccessData(array, &mut data).is_ok() {
+            let values = std::slice::from_raw_parts(data.cast::<f64>(), 4);
+            rect = Some(Rect {
+                x: values[0],
+                y: values[1],
+                width: values[2],
+                height: values[3],
+            });
+            let _ = SafeArrayUnaccessData(array);
+        }
+        let _ = SafeArrayDestroy(array);
+        Ok(rect.filter(|rect| rect.height > 0.0))
+    }
+}
+
+/// Screen box of the focused control's caret through TextPattern2, in
+/// pixels. `None` when the control doesn't expose its caret.
+pub fn caret_bounds() -> Result<Option<Rect>, String> {
+    // SAFETY: COM is initialized for this (blocking-pool) thread before any
+    // interface call, as in `insert_text`.
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER).map_err(uia_error)?;
+        let element = automation.GetFocusedElement().map_err(uia_error)?;
+        let Ok(pattern) =
+            element.GetCurrentPatternAs::<IUIAutomationTextPattern2>(UIA_TextPattern2Id)
+        else {
+            return Ok(None);
+        };
+        let mut active = BOOL::default();
+        let caret = pattern.GetCaretRange(&mut active).map_err(uia_error)?;
+        if let Some(rect) = first_rect(&caret).map_err(uia_error)? {
+            return Ok(Some(rect));
+        }
+        // An empty range usually has no box; measure the character after
+        // the caret and keep its leading edge.
+        caret
+            .MoveEndpointByUnit(TextPatternRangeEndpoint_End, TextUnit_Character, 1)
+            .map_err(uia_error)?;
+        Ok(first_rect(&caret)
+            .map_err(uia_error)?
+            .map(|rect| Rect { width: 0.0, ..rect }))
+    }
+}
+
+/// Text before and after `selection` within the whole document.
+///
+/// # Safety
+/// Must be called on a thread with COM initialized.
+unsafe fn split_around(
+    text: &IUIAutomationTextPattern2,
+    selection: &IUIAutomationTextRange,
+) -> windows::core::Result<(String, String)> {
+    unsafe {
+        let before = text.DocumentRange()?;
+        before.MoveEndpointByRange(
+            TextPatternRangeEndpoint_End,
+            selection,
+            TextPatternRangeEndpoint_Start,
+        )?;
+        let after = text.DocumentRange()?;
+        after.MoveEndpointByRange(
+            TextPatternRangeEndpoint_Start,
+            selection,
+            TextPatternRangeEndpoint_End,
+        )?;
+        Ok((
+            before.GetText(-1)?.to_string(),
+            after.GetText(-1)?.to_string(),
+        ))
+    }
+}
+
+/// Replaces the focused control's selection (or inserts at its caret) with
+/// `text` through UI Automation, leaving the clipboard alone.
+///
+/// Only plain value controls qualify: the control must expose ValuePattern
+/// for writing and TextPattern2 for locating the caret. Anything else, or a
+/// document whose text pattern disagrees with its value (rich editors), is
+/// refused so the caller can fall back to a clipboard paste.
+pub fn insert_text(text: &str) -> Result<(), String> {
+    // SAFETY: COM is initialized for this (blocking-pool) thread before any
+    // interface call; S_FALSE and RPC_E_CHANGED_MODE both leave it usable.
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER).map_err(uia_error)?;
+        let element = automation.GetFocusedElement().map_err(uia_error)?;
+        if element.CurrentIsPassword().map_err(uia_error)?.as_bool() {
+            return Err("The focused field is a password field.".to_string());
+        }
+        let value: IUIAutomationValuePattern = element
+            .GetCurrentPatternAs(UIA_ValuePatternId)
+            .map_err(|_| "The focused control has no editable value.".to_string())?;
+        if value.CurrentIsReadOnly().map_err(uia_error)?.as_bool() {
+            return Err("The focused control is read-only.".to_string());
+        }
+        let pattern: IUIAutomationTextPattern2 = element
+            .GetCurrentPatternAs(UIA_TextPattern2Id)
+            .map_err(|_| "The focused control does not report its caret.".to_string())?;
+
+        let selections = pattern.GetSelection().map_err(uia_error)?;
+        let selection = if selections.Length().map_err(uia_error)? > 0 {
+            selections.GetElement(0).map_err(uia_error)?
+        } else {
+            let mut active = BOOL::default();
+            pattern.GetCaretRange(&mut active).map_err(uia_error)?
+        };
+        let (before, after) = split_around(&pattern, &selection).map_err(uia_error)?;
+        let current = value.CurrentValue().map_err(uia_error)?.to_string();
+        if !current.starts_with(&before) || !current.ends_with(&after) {
+            return Err("The focused control's text does not match its value.".to_string());
+        }
+
+        let updated = format!("{before}{text}{after}");
+        value
+            .SetValue(&BSTR::from(updated.as_str()))
+            .map_err(uia_error)?;
+
+        // SetValue usually drops the caret at the end; put it after the
+        // inserted text. Best effort: the text is already in place.
+        let caret = before.encode_utf16().count() + text.encode_utf16().count();
+        if let Ok(range) = pattern.DocumentRange() {
+            let _ = range.MoveEndpointByRange(
+                TextPatternRangeEndpoint_End,
+                &range,
+                TextPatternRangeEndpoint_Start,
+            );
+            let _ = range.Move(TextUnit_Character, i32::try_from(caret).unwrap_or(i32::MAX));
+            let _ = range.Select();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::{
+        xproto::{ConnectionExt as _, Keycode, Keysym, Window, KEY_PRESS_EVENT, KEY_RELEASE_EVENT},
+        xtest::{self, ConnectionExt as _},
+    },
+    rust_connection::RustConnection,
+    CURRENT_TIME,
+};
+
+const XK_CONTROL_L: Keysym = 0xffe3;
+const XK_V: Keysym = 0x0076;
+const XK_BACKSPACE: Keysym = 0xff08;
+
+/// Which display server the app is talking to. Under XWayland `DISPLAY` is
+/// set too, but XTest input only reaches other X11 clients, so Wayland wins.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Session {
+    X11,
+    Wayland,
+    Unknown,
+}
+
+pub fn session() -> Session {
+    let set = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+    match std::env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") => return Session::Wayland,
+        Ok("x11") => return Session::X11,
+        _ => {}
+    }
+    if set("WAYLAND_DISPLAY") {
+        Session::Wayland
+    } else if set("DISPLAY") {
+        Session::X11
+    } else {
+        Session::Unknown
+    }
+}
+
+/// Whether `program` is an executable on `PATH`, for the command-line
+/// keystroke fallbacks on both X11 and Wayland and the PipeWire tools.
+pub fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// Runs a keystroke tool (xdotool, wtype, ydotool) to completion.
+pub fn run_tool(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Unable to run {program}: {err}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(format!("{program} failed: {stderr}"))
+    }
+}
+
+/// Ctrl+V through xdotool, for X servers without XTEST (some VNC and
+/// remote-desktop servers) where xdotool can still fall back to
+/// `XSendEvent`.
+pub fn xdotool_paste() -> Result<(), String> {
+    run_tool("xdotool", &["key", "--clearmodifiers", "ctrl+v"])
+}
+
+/// Backspace through xdotool, for the same X servers as [`xdotool_paste`].
+pub fn xdotool_backspace(count: usize) -> Result<(), String> {
+    let count = count.to_string();
+    run_tool(
+        "xdotool",
+        &["key", "--clearmodifiers", "--repeat", &count, "BackSpace"],
+    )
+}
+
+/// Types `text` through xdotool, which remaps a spare keycode for
+/// characters missing from the layout; XTest alone can only press keys the
+/// layout already has.
+pub fn xdotool_type(text: &str) -> Result<(), String> {
+    run_tool(
+        "xdotool",
+        &["type", "--clearmodifiers", "--delay", "0", "--", text],
+    )
+}
+
+/// A live X connection with the keycodes paste needs, kept across pastes
+/// like the macOS event source.
+pub struct KeystrokeSource {
+    conn: RustConnection,
+    root: Window,
+    control: Keycode,
+    v: Keycode,
+    backspace: Keycode,
+}
+
+fn keycode_for(conn: &RustConnection, keysym: Keysym) -> Result<Keycode, String> {
+    let setup = conn.setup();
+    let (min, max) = (setup.min_keycode, setup.max_keycode);
+    let mapping = conn
+        .get_keyboard_mapping(min, max - min + 1)
+        .map_err(|err| format!("X11 keyboard mapping request failed: {err}"))?
+        .reply()
+        .map_err(|err| format!("X11 keyboard mapping request failed: {err}"))?;
+    let per_keycode = usize::from(mapping.keysyms_per_keycode.max(1));
+    mapping
+        .keysyms
+        .chunks(per_keycode)
+        .position(|keysyms| keysyms.contains(&keysym))
+        .and_then(|index| u8::try_from(index).ok())
+        .map(|index| min + index)
+        .ok_or_else(|| format!("No X11 keycode produces keysym {keysym:#x}."))
+}
+
+impl KeystrokeSource {
+    pub fn new() -> Result<Self, String> {
+        let (conn, screen) =
+            x11rb::connect(None).map_err(|err| format!("Unable to connect to X11: {err}"))?;
+        let has_xtest = conn
+            .extension_information(xtest::X11_EXTENSION_NAME)
+            .map_err(|err| format!("Unable to query X11 extensions: {err}"))?
+            .is_some();
+        if !has_xtest {
+            return Err("The X server does not support the XTEST extension.".to_string());
+        }
+        let root = conn.setup().roots[screen].root;
+        let control = keycode_for(&conn, XK_CONTROL_L)?;
+        let v = keycode_for(&conn, XK_V)?;
+        let backspace = keycode_for(&conn, XK_BACKSPACE)?;
+        Ok(Self {
+            conn,
+            root,
+            control,
+            v,
+            backspace,
+        })
+    }
+
+    pub fn press_paste(&self) -> Result<(), String> {
+        self.send(&[
+            (KEY_PRESS_EVENT, self.control),
+            (KEY_PRESS_EVENT, self.v),
+            (KEY_RELEASE_EVENT, self.v),
+            (KEY_RELEASE_EVENT, self.control),
+        ])
+    }
+
+    pub fn press_backspace(&self, count: usize) -> Result<(), String> {
+        let events: Vec<(u8, Keycode)> = (0..count)
+            .flat_map(|_| {
+                [
+                    (KEY_PRESS_EVENT, self.backspace),
+                    (KEY_RELEASE_EVENT, self.backspace),
+                ]
+            })
+            .collect();
+        self.send(&events)
+    }
+
+    fn send(&self, events: &[(u8, Keycode)]) -> Result<(), String> {
+        for &(kind, keycode) in events {
+            self.conn
+                .xtest_fake_input(kind, keycode, CURRENT_TIME, self.root, 0, 0, 0)
+                .map_err(|err| format!("XTest keystroke failed: {err}"))?;
+        }
+        // A round trip makes sure the server processed the events (and
+        // surfaces a dead connection) before the clipboard can change again.
+        self.conn
+            .get_input_focus()
+            .map_err(|err| format!("XTest keystroke failed: {err}"))?
+            .reply()
+            .map_err(|err| format!("XTest keystroke failed: {err}"))?;
+        Ok(())
+    }
+}
+
+/// Which X11 paste path works here, for the capability report.
+pub fn probe() -> Result<&'static str, String> {
+    match session() {
+        Session::X11 => match KeystrokeSource::new() {
+            Ok(_) => Ok("XTEST."),
+            Err(_) if on_path("xdotool") => Ok("xdotool."),
+            Err(err) => Err(format!("{err} xdotool is not installed either.")),
+        },
+        Session::Wayland => Err("Wayland session; XTest cannot reach native apps.".to_string()),
+        Session::Unknown => Err("No X11 display.".to_string()),
+    }
+}
+
+/// Re-asserts `_NET_WM_STATE_ABOVE`, and `_NET_WM_STATE_STICKY` with
+/// `all_workspaces`, once the window is mapped. Several window managers
+/// ignore the hints GTK sets before mapping, which left the overlay behind
+/// other windows.
+pub fn apply_overlay_hints(
+    window: &tauri::WebviewWindow,
+    all_workspaces: bool,
+) -> Result<(), String> {
+    if session() != Session::X11 {
+        return Ok(());
+    }
+    window
+        .set_always_on_top(true)
+        .map_err(|err| format!("Unable to keep the overlay above other windows: {err}"))?;
+    window
+        .set_visible_on_all_workspaces(all_workspaces)
+        .map_err(|err| format!("Unable to show the overlay on all workspaces: {err}"))
+}
@@ -22,20 +22,84 @@ type ServerState = "checking" | "ready" | "loading" | "offline" | "error";
 
 type TranscribeResponse = {
   text?: string;
+  meta?: {
+    backend?: string;
+  };
 };
 
 type HealthResponse = {
   status?: string;
   ready?: boolean;
+  stage?: "idle" | "loading" | "ready" | "error" | "offline";
+};
+
+type DictationPhase =
+  | "idle"
+  | "arming"
+  | "recording"
+  | "paused"
+  | "transcribing"
+  | "processing"
+  | "injecting";
+
+type DictationEvent =
+  | "arm"
+  | "recorderStarted"
+  | "pause"
+  | "resume"
+  | "stop"
+  | "transcriptReady"
+  | "deliver"
+  | "finished"
+  | "cancel"
+  | "fail"
+  | "reset";
+
+type DictationTransition = {
+  from: DictationPhase;
+  to: DictationPhase;
+  event: DictationEvent;
+  session: number;
+  error?: string;
 };
 
 type HotkeyEventPayload = {
-  state?: "pressed" | "released";
+  state?: "holdStart" | "holdEnd" | "tapToggle" | "pressed" | "released" | "cancelled";
   shortcut?: string;
+  source?: "hotkey" | "tray" | "osc" | "midi";
+  accepted?: boolean;
+};
+
+type PartialTranscript = {
+  session: number;
+  text: string;
+  done: boolean;
+  error?: string;
 };
 
-type PasteResult = {
+type DeliveryResult = {
+  destination: "paste" | "obsidianDailyNote" | "appleNote" | "chatWebhook" | "email";
   pasted: boolean;
+  location?: string | null;
+  pasteBlocked?: "elevatedTarget" | "secureDesktop" | "noInputMethod" | "secureField";
+};
+
+type ScreenContextStatus = {
+  state: "capturing" | "captured" | "skipped" | "failed";
+  app?: string;
+  detail?: string;
+};
+
+type QuietState = {
+  active: boolean;
+  reason?: "focus" | "quietHours" | "presentation" | "fullScreen";
+};
+
+type LocaleInfo = {
+  uiLanguage?: string | null;
+  region?: string | null;
+  inputSource?: { id: string; name?: string | null; language?: string | null } | null;
+  language?: string | null;
 };
 
 type AccessibilityStatus = {
@@ -43,6 +107,14 @@ type AccessibilityStatus = {
   detail?: string | null;
 };
 
+function reportDictation(event: DictationEvent, error?: string) {
+  if (!isTauriRuntime()) {
+    return;
+  }
+  // The backend rejects events that don't fit its current phase; that's fine.
+  void invoke("dictation_event", { event, error: error ?? null }).catch(() => undefined);
+}
+
 function getUnavailableCameraMessage(): string {
   const protocol = window.location.protocol;
   const secureHint = window.isSecureContext
@@ -73,6 +145,33 @@ function isTauriRuntime(): boolean {
   return typeof window !== "undefined" && "__TAURI_INTERNALS__" in window;
 }
 
+function describeDelivery(delivery: DeliveryResult | null): string {
+  switch (delivery?.destination) {
+    case "obsidianDailyNote":
+      return "Appended to Obsidian daily note.";
+    case "appleNote":
+      return "Saved as Apple Note.";
+    case "chatWebhook":
+      return "Posted to chat webhook.";
+    case "email":
+      return "Opened email draft.";
+    default:
+      if (delivery?.pasteBlocked === "elevatedTarget") {
+        return "Copied to clipboard. Windows blocks pasting into apps running as administrator.";
+      }
+      if (delivery?.pasteBlocked === "secureDesktop") {
+        return "Copied to clipboard. Windows blocks pasting while a UAC prompt or the lock screen is up.";
+      }
+      if (delivery?.pasteBlocked === "secureField") {
+        return "Copied to clipboard. Not pasted because a password field has focus.";
+      }
+      if (delivery?.pasteBlocked === "noInputMethod") {
+        return "Copied to clipboard. This Wayland session offers no way to send the paste keystroke.";
+      }
+      return delivery?.pasted ? "Pasted into active app." : "Copied to clipboard.";
+  }
+}
+
 function statusTone(status: boolean): string {
   return status ? "text-emerald-200" : "text-amber-200";
 }
@@ -81,12 +180,16 @@ function App() {
   const streamRef = useRef<MediaStream | null>(null);
   const recorderRef = useRef<MediaRecorder | null>(null);
   const chunksRef = useRef<BlobPart[]>([]);
+  // Sent with each transcription as a language hint; follows input source switches.
+  const languageRef = useRef<string | null>(null);
   const stopPromiseRef = useRef<Promise<Blob> | null>(null);
   const hotkeyDownRef = useRef(false);
   const overlayModeRef = useRef(false);
   const hideTimerRef = useRef<number | null>(null);
   const [overlayState, setOverlayState] = useState<OverlayState>("idle");
   const [overlayDetail, setOverlayDetail] = useState("");
+  // Words from a streaming provider, shown while the dictation is in flight.
+  const [partialTranscript, setPartialTranscript] = useState("");
   const [serverState, setServerState] = useState<ServerState>("checking");
   const [cameraError, setCameraError] = useState<string | null>(null);
   const [cameraReady, setCameraReady] = useState(false);
@@ -98,6 +201,8 @@ function App() {
   const [onboardingError, setOnboardingError] = useState<string>("");
   const [lastTranscript, setLastTranscript] = useState<string>("");
   const [lastHotkey, setLastHotkey] = useState(HOTKEY_LABEL);
+  const [screenContext, setScreenContext] = useState<ScreenContextStatus | null>(null);
+  const [quiet, setQuiet] = useState<QuietState | null>(null);
 
   const setTimedIdle = useCallback((ms: number) => {
     if (hideTimerRef.current !== null) {
@@ -116,7 +221,19 @@ function App() {
     await invoke("set_overlay_passthrough", { ignoreCursorEvents: enabled });
   }, []);
 
-  const checkAccessibility = useCallback(async () => {
+  const applyAccessibilityStatus = useCallback((status: AccessibilityStatus) => {
+    setAccessibilityGranted(status.granted);
+    if (!status.granted) {
+      setAccessibilityDetail(
+        status.detail?.trim() ||
+          "Accessibility permission is required to auto-paste into other apps.",
+      );
+    } else {
+      setAccessibilityDetail("");
+    }
+  }, []);
+
+  const checkAccessibility = useCallback(async (refresh = false) => {
     if (!isTauriRuntime()) {
       setAccessibilityGranted(false);
       setAccessibilityDetail("Run with `npm run tauri dev`.");
@@ -125,23 +242,16 @@ function App() {
 
     setCheckingAccessibility(true);
     try {
-      const status = await invoke<AccessibilityStatus>("check_accessibility_permission");
-      setAccessibilityGranted(status.granted);
-      if (!status.granted) {
-        setAccessibilityDetail(
-          status.detail?.trim() ||
-            "Accessibility permission is required to auto-paste into other apps.",
-        );
-      } else {
-        setAccessibilityDetail("");
-      }
+      applyAccessibilityStatus(
+        await invoke<AccessibilityStatus>("check_accessibility_permission", { refresh }),
+      );
     } catch (err) {
       setAccessibilityGranted(false);
       setAccessibilityDetail(err instanceof Error ? err.message : "Unable to verify permission.");
     } finally {
       setCheckingAccessibility(false);
     }
-  }, []);
+  }, [applyAccessibilityStatus]);
 
   const requestCamera = useCallback(async () => {
     if (cameraReady) {
@@ -188,6 +298,7 @@ function App() {
 
   const showError = useCallback(
     (message: string) => {
+      reportDictation("fail", message);
       setOverlayState("error");
       setOverlayDetail(message);
       setTimedIdle(2600);
@@ -234,11 +345,18 @@ function App() {
       ? new MediaRecorder(stream, { mimeType })
       : new MediaRecorder(stream);
 
+    let lastHeartbeat = 0;
     stopPromiseRef.current = new Promise<Blob>((resolve, reject) => {
       recorder.ondataavailable = (event: BlobEvent) => {
         if (event.data.size > 0) {
           chunksRef.current.push(event.data);
         }
+        // Lets the backend watchdog tell a live recorder from a stalled one.
+        const now = Date.now();
+        if (isTauriRuntime() && now - lastHeartbeat >= 1000) {
+          lastHeartbeat = now;
+          void invoke("dictation_heartbeat").catch(() => undefined);
+        }
       };
       recorder.onerror = () => reject(new Error("Unable to record video clip."));
       recorder.onstop = () => {
@@ -293,6 +411,7 @@ function App() {
       streamRef.current?.getTracks().forEach((track) => track.stop());
       streamRef.current = stream;
       startRecorder();
+      reportDictation("recorderStarted");
       setLastTranscript("");
       setOverlayState("recording");
       setOverlayDetail("Recording. Release to transcribe.");
@@ -342,6 +461,7 @@ function App() {
           width: settings?.width ?? null,
           height: settings?.height ?? null,
           videoDataUrl,
+          language: languageRef.current,
         }),
       });
 
@@ -365,14 +485,16 @@ function App() {
       const text = (payload.text ?? "").trim() || "(No transcription returned)";
       setLastTranscript(text);
 
-      let pasted = false;
+      let delivery: DeliveryResult | null = null;
       if (isTauriRuntime()) {
-        const pasteResult = await invoke<PasteResult>("paste_text", { text });
-        pasted = pasteResult.pasted;
+        delivery = await invoke<DeliveryResult>("deliver_transcript", {
+          text,
+          provider: payload.meta?.backend ?? null,
+        });
       }
 
       setOverlayState("pasted");
-      setOverlayDetail(pasted ? "Pasted into active app." : "Copied to clipboard.");
+      setOverlayDetail(describeDelivery(delivery));
       setTimedIdle(OVERLAY_HIDE_MS);
     } catch (err) {
       // Ensure the camera is released even if encoding or network fails.
@@ -382,6 +504,20 @@ function App() {
     }
   }, [encodeBlob, overlayState, setTimedIdle, showError]);
 
+  const handleCancel = useCallback(() => {
+    const recorder = recorderRef.current;
+    recorderRef.current = null;
+    stopPromiseRef.current = null;
+    if (recorder && recorder.state !== "inactive") {
+      recorder.stop();
+    }
+    chunksRef.current = [];
+    streamRef.current?.getTracks().forEach((t) => t.stop());
+    streamRef.current = null;
+    setOverlayState("idle");
+    setOverlayDetail("");
+  }, []);
+
   useEffect(() => {
     let cancelled = false;
     let timerId: number | undefined;
@@ -397,6 +533,8 @@ function App() {
         if (!cancelled) {
           if (response.ok && data?.ready) {
             setServerState("ready");
+          } else if (data?.stage === "error") {
+            setServerState("error");
           } else if (response.status === 503 || data?.status === "loading") {
             setServerState("loading");
           } else {
@@ -432,23 +570,49 @@ function App() {
     let active = true;
     let unlisten: (() => void) | undefined;
     const bind = async () => {
-      unlisten = await listen<HotkeyEventPayload>("telepathy://hotkey", ({ payload }) => {
-        if (!active) {
-          return;
-        }
-        if (payload.shortcut) {
-          setLastHotkey(payload.shortcut);
-        }
-        if (payload.state === "pressed") {
-          if (!hotkeyDownRef.current) {
+      const unlistenHotkey = await listen<HotkeyEventPayload>(
+        "telepathy://hotkey",
+        ({ payload }) => {
+          if (active && payload.shortcut) {
+            setLastHotkey(payload.shortcut);
+          }
+        },
+      );
+      // Recording is driven by the backend's dictation state machine rather
+      // than raw key events, so every trigger source behaves the same.
+      const unlistenState = await listen<DictationTransition>(
+        "telepathy://state",
+        ({ payload }) => {
+          if (!active) {
+            return;
+          }
+          if (payload.event === "arm") {
             hotkeyDownRef.current = true;
+            setScreenContext(null);
+            setPartialTranscript("");
             void handleHotkeyPressed();
+          } else if (payload.event === "stop") {
+            hotkeyDownRef.current = false;
+            void handleHotkeyReleased();
+          } else if (payload.event === "cancel") {
+            hotkeyDownRef.current = false;
+            handleCancel();
+          } else if (payload.event === "reset") {
+            // The watchdog gave up on a stuck phase.
+            hotkeyDownRef.current = false;
+            handleCancel();
+            showError(payload.error ?? "Dictation was reset.");
           }
-        } else if (payload.state === "released") {
-          hotkeyDownRef.current = false;
-          void handleHotkeyReleased();
-        }
-      });
+        },
+      );
+      unlisten = () => {
+        unlistenHotkey();
+        unlistenState();
+      };
+      const hotkey = await invoke<{ accelerator: string }>("get_hotkey");
+      if (active) {
+        setLastHotkey(hotkey.accelerator);
+      }
     };
 
     void bind();
@@ -458,12 +622,191 @@ function App() {
         unlisten();
       }
     };
-  }, [handleHotkeyPressed, handleHotkeyReleased]);
+  }, [handleCancel, handleHotkeyPressed, handleHotkeyReleased, showError]);
 
   useEffect(() => {
     void checkAccessibility();
   }, [checkAccessibility]);
 
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let active = true;
+    let unlisten: (() => void) | undefined;
+    const bind = async () => {
+      // The backend warms the model at launch; flip to ready without waiting
+      // for the next health poll.
+      const unlistenReady = await listen<HealthResponse>("telepathy://model-ready", () => {
+        if (active) {
+          setServerState("ready");
+        }
+      });
+      // Pressing the hotkey after an idle unload reloads the model first.
+      const unlistenLoading = await listen<"startup" | "arm">(
+        "telepathy://model-loading",
+        ({ payload }) => {
+          if (active && payload === "arm") {
+            setServerState("loading");
+            setOverlayState("waiting");
+            setOverlayDetail("Waking up the model after inactivity...");
+          }
+        },
+      );
+      unlisten = () => {
+        unlistenReady();
+        unlistenLoading();
+      };
+    };
+
+    void bind();
+    return () => {
+      active = false;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let active = true;
+    let unlisten: (() => void) | undefined;
+    const bind = async () => {
+      unlisten = await listen<AccessibilityStatus>(
+        "telepathy://accessibility-changed",
+        ({ payload }) => {
+          if (active) {
+            applyAccessibilityStatus(payload);
+          }
+        },
+      );
+    };
+
+    void bind();
+    return () => {
+      active = false;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, [applyAccessibilityStatus]);
+
+  // The backend reports every screen read so the overlay can show it is
+  // looking at the focused window.
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let active = true;
+    let unlisten: (() => void) | undefined;
+    const bind = async () => {
+      unlisten = await listen<ScreenContextStatus>("telepathy://screen-context", ({ payload }) => {
+        if (active) {
+          setScreenContext(payload);
+        }
+      });
+    };
+
+    void bind();
+    return () => {
+      active = false;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // Sounds and notifications are held back while the system's Focus mode
+  // is on; the overlay shows it so silence doesn't look like a failure.
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let active = true;
+    let unlisten: (() => void) | undefined;
+    const bind = async () => {
+      unlisten = await listen<QuietState>("telepathy://quiet-mode", ({ payload }) => {
+        if (active) {
+          setQuiet(payload);
+        }
+      });
+      const current = await invoke<QuietState>("get_quiet_mode");
+      if (active) {
+        setQuiet(current);
+      }
+    };
+
+    void bind();
+    return () => {
+      active = false;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let active = true;
+    let unlisten: (() => void) | undefined;
+    const bind = async () => {
+      unlisten = await listen<LocaleInfo>("telepathy://input-source", ({ payload }) => {
+        if (active) {
+          languageRef.current = payload.language ?? null;
+        }
+      });
+      const current = await invoke<LocaleInfo>("get_system_locale_info");
+      if (active) {
+        languageRef.current = current.language ?? null;
+      }
+    };
+
+    void bind();
+    return () => {
+      active = false;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let active = true;
+    let unlisten: (() => void) | undefined;
+    const bind = async () => {
+      unlisten = await listen<PartialTranscript>(
+        "telepathy://partial-transcript",
+        ({ payload }) => {
+          if (active && payload.text) {
+            setPartialTranscript(payload.text);
+          }
+        },
+      );
+    };
+
+    void bind();
+    return () => {
+      active = false;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
   useEffect(() => {
     overlayModeRef.current = onboardingComplete;
   }, [onboardingComplete]);
@@ -562,7 +905,13 @@ function App() {
     }
   }, [overlayState]);
 
-  const metaLine = overlayState === "pasted" && lastTranscript ? lastTranscript : overlayDetail;
+  const inFlight = overlayState === "recording" || overlayState === "processing";
+  const metaLine =
+    overlayState === "pasted" && lastTranscript
+      ? lastTranscript
+      : inFlight && partialTranscript
+        ? partialTranscript
+        : overlayDetail;
   const cameraStatusLabel = cameraReady ? "Granted" : "Missing";
   const accessibilityStatusLabel = accessibilityGranted ? "Granted" : "Missing";
 
@@ -648,7 +997,7 @@ function App() {
                   <button
                     className="rounded-full border border-slate-400/50 px-3 py-1 text-xs font-semibold text-slate-200 hover:bg-slate-700/70 disabled:opacity-60"
                     onClick={() => {
-                      void checkAccessibility();
+                      void checkAccessibility(true);
                     }}
                     disabled={checkingAccessibility}
                   >
@@ -715,6 +1064,26 @@ function App() {
               </p>
               <p className="truncate text-sm font-medium">{metaLine}</p>
             </div>
+            {(screenContext?.state === "capturing" || screenContext?.state === "captured") && (
+              <span
+                className="ml-auto shrink-0 rounded-full border border-current px-2 py-0.5 text-[10px] font-semibold uppercase tracking-[0.15em]"
+                title={screenContext.app ? `Read ${screenContext.app}` : "Read the focused window"}
+              >
+                {screenContext.state === "capturing" ? "Reading screen" : "Screen"}
+              </span>
+            )}
+            {quiet?.active && (
+              <span
+                className={`${
+                  screenContext?.state === "capturing" || screenContext?.state === "captured"
+                    ? "ml-2"
+                    : "ml-auto"
+                } shrink-0 text-[10px] font-semibold uppercase tracking-[0.15em] opacity-60`}
+                title="Sounds and notifications are off while Focus is on"
+              >
+                Quiet
+              </span>
+            )}
           </div>
         </div>
       </div>
@@ -0,0 +1,74 @@
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { useCallback, useEffect, useState } from "react";
+
+type HistoryEntry = {
+  id: number;
+  text: string;
+  createdAt: string;
+  app: string | null;
+  delivered: boolean;
+  error: string | null;
+};
+
+type HistoryPage = {
+  entries: HistoryEntry[];
+  total: number;
+};
+
+// Standalone window opened by `open_history_window`: recent transcripts,
+// newest first. Refreshed whenever the window regains focus.
+function History() {
+  const [page, setPage] = useState<HistoryPage | null>(null);
+  const [error, setError] = useState("");
+
+  const refresh = useCallback(async () => {
+    try {
+      setPage(await invoke<HistoryPage>("list_history", { page: 0 }));
+      setError("");
+    } catch (err) {
+      setError(String(err));
+    }
+  }, []);
+
+  useEffect(() => {
+    void refresh();
+    let unlisten: (() => void) | undefined;
+    void getCurrentWindow()
+      .onFocusChanged(({ payload: focused }) => {
+        if (focused) {
+          void refresh();
+        }
+      })
+      .then((stop) => {
+        unlisten = stop;
+      });
+    return () => unlisten?.();
+  }, [refresh]);
+
+  return (
+    <div className="flex h-full w-full flex-col gap-3 overflow-y-auto bg-slate-950 p-4 text-slate-100">
+      <p className="text-xs font-semibold uppercase tracking-[0.18em] text-slate-300">
+        History{page ? ` · ${page.total}` : ""}
+      </p>
+      {error && <p className="text-xs text-rose-200">{error}</p>}
+      {page?.entries.map((entry) => (
+        <div
+          key={entry.id}
+          className="rounded-2xl border border-slate-500/30 bg-slate-800/80 p-3 text-sm"
+        >
+          <div className="flex items-center justify-between text-xs text-slate-300">
+            <span>{new Date(entry.createdAt).toLocaleString()}</span>
+            <span>{entry.app ?? ""}</span>
+          </div>
+          <p className="mt-1 whitespace-pre-wrap">{entry.text}</p>
+          {!entry.delivered && (
+            <p className="mt-1 text-xs text-amber-200">{entry.error ?? "Not delivered"}</p>
+          )}
+        </div>
+      ))}
+    </div>
+  );
+}
+
+export default History;
@@ -0,0 +1,32 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useRef } from "react";
+
+// Scratch window opened by `test_injection`: a single focused field that
+// reports every change back so the backend can verify what arrived.
+function InjectionTest() {
+  const fieldRef = useRef<HTMLTextAreaElement | null>(null);
+
+  useEffect(() => {
+    fieldRef.current?.focus();
+    void invoke("injection_test_ready").catch(() => undefined);
+  }, []);
+
+  return (
+    <div className="flex h-full w-full flex-col gap-2 bg-slate-950 p-4 text-slate-100">
+      <p className="text-xs font-semibold uppercase tracking-[0.18em] text-slate-300">
+        Injection test
+      </p>
+      <textarea
+        ref={fieldRef}
+        className="flex-1 resize-none rounded-xl border border-slate-500/40 bg-slate-800 p-2 text-sm outline-none"
+        onInput={(event) => {
+          void invoke("injection_test_input", { value: event.currentTarget.value }).catch(
+            () => undefined,
+          );
+        }}
+      />
+    </div>
+  );
+}
+
+export default InjectionTest;
@@ -0,0 +1,115 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+
+type LaunchAtLogin = "enabled" | "disabled" | "requiresApproval";
+
+type AppSettings = {
+  launch: { backgroundAgent: boolean };
+  triggers: { hotkey: string | null };
+  [key: string]: unknown;
+};
+
+function scrollToSection(section: string) {
+  document.querySelector(`[data-section="${section}"]`)?.scrollIntoView({ behavior: "smooth" });
+}
+
+// Standalone window opened by `open_settings_window`, so settings no longer
+// share the small always-on-top overlay. `#settings/<section>` and
+// `telepathy://open-section` scroll to a section.
+function Settings() {
+  const [settings, setSettings] = useState<AppSettings | null>(null);
+  const [launchAtLogin, setLaunchAtLogin] = useState<LaunchAtLogin | null>(null);
+  const [error, setError] = useState("");
+
+  useEffect(() => {
+    void invoke<AppSettings>("get_settings").then(setSettings, (err) => setError(String(err)));
+    void invoke<LaunchAtLogin>("get_launch_at_login").then(setLaunchAtLogin, () => undefined);
+    const unlisten: Array<() => void> = [];
+    void listen<AppSettings>("telepathy://settings-changed", ({ payload }) => setSettings(payload)).then(
+      (stop) => unlisten.push(stop),
+    );
+    void listen<string>("telepathy://open-section", ({ payload }) => scrollToSection(payload)).then(
+      (stop) => unlisten.push(stop),
+    );
+    return () => unlisten.forEach((stop) => stop());
+  }, []);
+
+  // Sections render once settings arrive.
+  const loaded = settings !== null;
+  useEffect(() => {
+    const section = window.location.hash.split("/")[1];
+    if (loaded && section) {
+      scrollToSection(section);
+    }
+  }, [loaded]);
+
+  const toggleLaunchAtLogin = async () => {
+    try {
+      setLaunchAtLogin(
+        await invoke<LaunchAtLogin>("set_launch_at_login", {
+          enabled: launchAtLogin === "disabled",
+        }),
+      );
+      setError("");
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const toggleBackgroundAgent = async () => {
+    if (!settings) {
+      return;
+    }
+    const next = {
+      ...settings,
+      launch: { ...settings.launch, backgroundAgent: !settings.launch.backgroundAgent },
+    };
+    try {
+      await invoke("update_settings", { settings: next });
+      setError("");
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="flex h-full w-full flex-col gap-3 overflow-y-auto bg-slate-950 p-4 text-sm text-slate-100">
+      <p className="text-xs font-semibold uppercase tracking-[0.18em] text-slate-300">Settings</p>
+      {error && <p className="text-xs text-rose-200">{error}</p>}
+      <div
+        data-section="hotkey"
+        className="flex items-center justify-between rounded-2xl border border-slate-500/30 bg-slate-800/80 p-3"
+      >
+        <span>Hotkey</span>
+        <span className="text-slate-300">{settings?.triggers.hotkey ?? "Default"}</span>
+      </div>
+      <label
+        data-section="launch"
+        className="flex items-center justify-between rounded-2xl border border-slate-500/30 bg-slate-800/80 p-3"
+      >
+        <span>Launch at login</span>
+        <input
+          type="checkbox"
+          disabled={launchAtLogin === null}
+          checked={launchAtLogin !== null && launchAtLogin !== "disabled"}
+          onChange={() => void toggleLaunchAtLogin()}
+        />
+      </label>
+      {launchAtLogin === "requiresApproval" && (
+        <p className="text-xs text-amber-200">Allow Telepathy under Login Items in System Settings.</p>
+      )}
+      <label className="flex items-center justify-between rounded-2xl border border-slate-500/30 bg-slate-800/80 p-3">
+        <span>Run in the background (tray only)</span>
+        <input
+          type="checkbox"
+          disabled={settings === null}
+          checked={settings?.launch.backgroundAgent ?? false}
+          onChange={() => void toggleBackgroundAgent()}
+        />
+      </label>
+    </div>
+  );
+}
+
+export default Settings;
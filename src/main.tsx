@@ -1,10 +1,22 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import History from "./History";
+import InjectionTest from "./InjectionTest";
+import Settings from "./Settings";
 import "./index.css";
 
+// Each window loads index.html with its own hash, optionally followed by a
+// section (`#settings/hotkey`); `main` has none.
+const views: Record<string, React.ComponentType> = {
+  "#injection-test": InjectionTest,
+  "#settings": Settings,
+  "#history": History,
+};
+const View = views[window.location.hash.split("/")[0]] ?? App;
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    <View />
   </React.StrictMode>,
 );